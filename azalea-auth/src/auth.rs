@@ -7,11 +7,15 @@ use serde_json::json;
 use std::{
     collections::HashMap,
     path::PathBuf,
-    time::{Instant, SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use thiserror::Error;
 use uuid::Uuid;
 
+/// A function that shows the user where to go and what code to enter to log
+/// into Microsoft. See [`AuthOpts::on_device_code`].
+pub type DeviceCodeCallback = dyn Fn(&DeviceCodeResponse) + Send + Sync;
+
 #[derive(Default)]
 pub struct AuthOpts {
     /// Whether we should check if the user actually owns the game. This will
@@ -24,6 +28,10 @@ pub struct AuthOpts {
     /// The directory to store the cache in. If this is not set, caching is not
     /// done.
     pub cache_file: Option<PathBuf>,
+    /// Called with the link and code that the user has to enter to log into
+    /// Microsoft, if we couldn't log in from the cache. If this is not set,
+    /// they're printed to the terminal.
+    pub on_device_code: Option<Box<DeviceCodeCallback>>,
 }
 
 #[derive(Debug, Error)]
@@ -79,6 +87,10 @@ pub async fn auth(email: &str, opts: AuthOpts) -> Result<AuthResult, AuthError>
         let client = reqwest::Client::new();
         let mut msa = if let Some(account) = cached_account {
             account.msa
+        } else if let Some(on_device_code) = &opts.on_device_code {
+            let res = get_ms_link_code(&client).await?;
+            on_device_code(&res);
+            get_ms_auth_token(&client, res).await?
         } else {
             interactive_get_ms_auth_token(&client, email).await?
         };
@@ -255,6 +267,36 @@ pub enum GetMicrosoftAuthTokenError {
     Http(#[from] reqwest::Error),
     #[error("Authentication timed out")]
     Timeout,
+    #[error("The user declined to log in")]
+    Declined,
+    /// Microsoft is getting too many requests from us. If it told us how long
+    /// to wait before trying again, that's in `retry_after`.
+    #[error("Rate limited by Microsoft")]
+    RateLimited { retry_after: Option<Duration> },
+}
+
+/// The response from polling the token endpoint while the user hasn't logged
+/// in yet.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DeviceCodePollResponse {
+    Success(AccessTokenResponse),
+    Error { error: String },
+}
+
+/// Return a [`GetMicrosoftAuthTokenError::RateLimited`] if the response has a
+/// 429 status.
+fn check_rate_limit(res: &reqwest::Response) -> Result<(), GetMicrosoftAuthTokenError> {
+    if res.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Ok(());
+    }
+    let retry_after = res
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .map(Duration::from_secs);
+    Err(GetMicrosoftAuthTokenError::RateLimited { retry_after })
 }
 
 /// Get the Microsoft link code that's shown to the user for logging into
@@ -283,7 +325,7 @@ pub enum GetMicrosoftAuthTokenError {
 pub async fn get_ms_link_code(
     client: &reqwest::Client,
 ) -> Result<DeviceCodeResponse, GetMicrosoftAuthTokenError> {
-    Ok(client
+    let res = client
         .post("https://login.live.com/oauth20_connect.srf")
         .form(&vec![
             ("scope", "service::user.auth.xboxlive.com::MBI_SSL"),
@@ -291,26 +333,30 @@ pub async fn get_ms_link_code(
             ("response_type", "device_code"),
         ])
         .send()
-        .await?
-        .json::<DeviceCodeResponse>()
-        .await?)
+        .await?;
+    check_rate_limit(&res)?;
+    Ok(res.json::<DeviceCodeResponse>().await?)
 }
 
 /// Wait until the user logged into Microsoft with the given code. You get the
 /// device code response needed for this function from [`get_ms_link_code`].
 ///
 /// You should pass the response from this to [`get_minecraft_token`].
+///
+/// This polls until the user logs in, so it can take a while. To cancel it,
+/// just drop the future.
 pub async fn get_ms_auth_token(
     client: &reqwest::Client,
     res: DeviceCodeResponse,
 ) -> Result<ExpiringValue<AccessTokenResponse>, GetMicrosoftAuthTokenError> {
-    let login_expires_at = Instant::now() + std::time::Duration::from_secs(res.expires_in);
+    let login_expires_at = Instant::now() + Duration::from_secs(res.expires_in);
+    let mut interval = Duration::from_secs(res.interval);
 
     while Instant::now() < login_expires_at {
-        tokio::time::sleep(std::time::Duration::from_secs(res.interval)).await;
+        tokio::time::sleep(interval).await;
 
         log::trace!("Polling to check if user has logged in...");
-        if let Ok(access_token_response) = client
+        let poll_res = client
             .post(format!(
                 "https://login.live.com/oauth20_token.srf?client_id={CLIENT_ID}"
             ))
@@ -320,20 +366,33 @@ pub async fn get_ms_auth_token(
                 ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
             ])
             .send()
-            .await?
-            .json::<AccessTokenResponse>()
-            .await
-        {
-            log::trace!("access_token_response: {:?}", access_token_response);
-            let expires_at = SystemTime::now()
-                + std::time::Duration::from_secs(access_token_response.expires_in);
-            return Ok(ExpiringValue {
-                data: access_token_response,
-                expires_at: expires_at
-                    .duration_since(UNIX_EPOCH)
-                    .expect("Time went backwards")
-                    .as_secs(),
-            });
+            .await?;
+        check_rate_limit(&poll_res)?;
+
+        match poll_res.json::<DeviceCodePollResponse>().await {
+            Ok(DeviceCodePollResponse::Success(access_token_response)) => {
+                log::trace!("access_token_response: {:?}", access_token_response);
+                let expires_at =
+                    SystemTime::now() + Duration::from_secs(access_token_response.expires_in);
+                return Ok(ExpiringValue {
+                    data: access_token_response,
+                    expires_at: expires_at
+                        .duration_since(UNIX_EPOCH)
+                        .expect("Time went backwards")
+                        .as_secs(),
+                });
+            }
+            Ok(DeviceCodePollResponse::Error { error }) => match error.as_str() {
+                "authorization_pending" => {}
+                // we're polling too fast, the spec says to add 5 seconds to the interval
+                "slow_down" => interval += Duration::from_secs(5),
+                "authorization_declined" | "access_denied" => {
+                    return Err(GetMicrosoftAuthTokenError::Declined)
+                }
+                "expired_token" => return Err(GetMicrosoftAuthTokenError::Timeout),
+                _ => log::warn!("Unknown error while polling for Microsoft auth token: {error}"),
+            },
+            Err(e) => log::trace!("Couldn't parse Microsoft auth token response: {e}"),
         }
     }
 
@@ -4,4 +4,10 @@ use crate::{exceptions::CommandSyntaxException, string_reader::StringReader};
 
 pub trait ArgumentType {
     fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException>;
+
+    /// Get suggestions for what the whole argument could be, given the part of
+    /// it that was typed so far.
+    fn list_suggestions(&self, _input: &str) -> Vec<String> {
+        Vec::new()
+    }
 }
@@ -0,0 +1,40 @@
+use std::{any::Any, rc::Rc};
+
+use crate::{exceptions::CommandSyntaxException, string_reader::StringReader};
+
+use super::ArgumentType;
+
+/// An argument type that parses the input itself, for arguments that don't
+/// fit any of the built-in types.
+///
+/// Use [`custom_argument`] to add one to a command.
+///
+/// [`custom_argument`]: crate::builder::required_argument_builder::custom_argument
+pub trait CustomArgumentType {
+    /// Parse the argument from the start of `input`, which is the rest of the
+    /// command. Returns the parsed value and how many bytes of the input were
+    /// used.
+    fn parse(&self, input: &str) -> Result<(Rc<dyn Any>, usize), CommandSyntaxException>;
+
+    /// Get suggestions for what the whole argument could be, given the part of
+    /// it that was typed so far.
+    fn list_suggestions(&self, _input: &str) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Wraps a [`CustomArgumentType`] so it can be used like any other argument
+/// type.
+pub struct Custom<T>(pub T);
+
+impl<T: CustomArgumentType> ArgumentType for Custom<T> {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        let (result, length) = self.0.parse(reader.remaining())?;
+        reader.cursor = (reader.cursor + length).min(reader.total_length());
+        Ok(result)
+    }
+
+    fn list_suggestions(&self, input: &str) -> Vec<String> {
+        self.0.list_suggestions(input)
+    }
+}
@@ -1,5 +1,6 @@
 mod argument_type;
 pub mod bool_argument_type;
+pub mod custom_argument_type;
 pub mod double_argument_type;
 pub mod float_argument_type;
 pub mod integer_argument_type;
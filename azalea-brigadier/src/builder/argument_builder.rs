@@ -2,6 +2,7 @@ use parking_lot::RwLock;
 
 use crate::{
     context::CommandContext,
+    exceptions::CommandSyntaxException,
     modifier::RedirectModifier,
     tree::{Command, CommandNode},
 };
@@ -111,6 +112,44 @@ impl<S> ArgumentBuilder<S> {
         self.forward(target, None, false)
     }
 
+    /// Redirect to the target node, but change the source of the command
+    /// with the modifier first. The modifier can fail, in which case the
+    /// command fails with that error.
+    ///
+    /// Modifiers run synchronously while the command is executed, so anything
+    /// that has to be looked up asynchronously (like the entities that a
+    /// selector matches) should be resolved before calling
+    /// [`CommandDispatcher::execute`]. Use [`Self::fork`] to redirect to more
+    /// than one source.
+    ///
+    /// [`CommandDispatcher::execute`]: crate::command_dispatcher::CommandDispatcher::execute
+    /// ```
+    /// # use azalea_brigadier::prelude::*;
+    /// # use azalea_brigadier::exceptions::BuiltInExceptions;
+    /// # use std::sync::Arc;
+    /// # struct CommandSource { opped: bool }
+    /// # let mut subject = CommandDispatcher::<CommandSource>::new();
+    /// subject.register(literal("sudo").redirect_modifier(
+    ///     subject.root.clone(),
+    ///     |ctx: &CommandContext<CommandSource>| {
+    ///         if ctx.source.opped {
+    ///             Ok(Arc::new(CommandSource { opped: true }))
+    ///         } else {
+    ///             Err(BuiltInExceptions::DispatcherUnknownCommand.create())
+    ///         }
+    ///     },
+    /// ));
+    /// ```
+    pub fn redirect_modifier<F>(self, target: Arc<RwLock<CommandNode<S>>>, modifier: F) -> Self
+    where
+        F: Fn(&CommandContext<S>) -> Result<Arc<S>, CommandSyntaxException> + Send + Sync + 'static,
+        S: 'static,
+    {
+        let modifier: Arc<RedirectModifier<S>> =
+            Arc::new(move |context: &CommandContext<S>| Ok(vec![modifier(context)?]));
+        self.forward(target, Some(modifier), false)
+    }
+
     pub fn fork(
         self,
         target: Arc<RwLock<CommandNode<S>>>,
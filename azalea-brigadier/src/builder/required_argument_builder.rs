@@ -1,6 +1,11 @@
 use super::argument_builder::{ArgumentBuilder, ArgumentBuilderType};
 use crate::{
-    arguments::ArgumentType, exceptions::CommandSyntaxException, string_reader::StringReader,
+    arguments::{
+        custom_argument_type::{Custom, CustomArgumentType},
+        ArgumentType,
+    },
+    exceptions::CommandSyntaxException,
+    string_reader::StringReader,
 };
 use std::{any::Any, fmt::Debug, rc::Rc, sync::Arc};
 
@@ -22,6 +27,12 @@ impl Argument {
     pub fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
         self.parser.parse(reader)
     }
+
+    /// Get suggestions for what this argument could be, given the part of it
+    /// that was typed so far.
+    pub fn list_suggestions(&self, input: &str) -> Vec<String> {
+        self.parser.list_suggestions(input)
+    }
 }
 
 impl From<Argument> for ArgumentBuilderType {
@@ -46,3 +57,30 @@ pub fn argument<S>(
 ) -> ArgumentBuilder<S> {
     ArgumentBuilder::new(Argument::new(name, Arc::new(parser)).into())
 }
+
+/// Shortcut for creating a new argument builder node with a
+/// [`CustomArgumentType`].
+///
+/// ```
+/// # use azalea_brigadier::prelude::*;
+/// # use azalea_brigadier::arguments::custom_argument_type::CustomArgumentType;
+/// # use azalea_brigadier::exceptions::CommandSyntaxException;
+/// # use std::{any::Any, rc::Rc};
+/// /// A word that's made of only the letter 'a'.
+/// struct Screaming;
+/// impl CustomArgumentType for Screaming {
+///     fn parse(&self, input: &str) -> Result<(Rc<dyn Any>, usize), CommandSyntaxException> {
+///         let length = input.chars().take_while(|&c| c == 'a').count();
+///         Ok((Rc::new(length), length))
+///     }
+/// }
+///
+/// # let mut subject = CommandDispatcher::<()>::new();
+/// subject.register(literal("scream").then(custom_argument("a", Screaming).executes(|_| 1)));
+/// ```
+pub fn custom_argument<S>(
+    name: &str,
+    parser: impl CustomArgumentType + Send + Sync + 'static,
+) -> ArgumentBuilder<S> {
+    argument(name, Custom(parser))
+}
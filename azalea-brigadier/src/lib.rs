@@ -21,7 +21,10 @@ pub mod prelude {
             long_argument_type::{get_long, long},
             string_argument_type::{get_string, greedy_string, string, word},
         },
-        builder::{literal_argument_builder::literal, required_argument_builder::argument},
+        builder::{
+            literal_argument_builder::literal,
+            required_argument_builder::{argument, custom_argument},
+        },
         command_dispatcher::CommandDispatcher,
         context::CommandContext,
     };
@@ -1,8 +1,11 @@
-use std::sync::Arc;
+use std::{any::Any, rc::Rc, sync::Arc};
 
 use azalea_brigadier::{
-    arguments::integer_argument_type::integer,
-    builder::{literal_argument_builder::literal, required_argument_builder::argument},
+    arguments::{custom_argument_type::CustomArgumentType, integer_argument_type::integer},
+    builder::{
+        literal_argument_builder::literal,
+        required_argument_builder::{argument, custom_argument},
+    },
     command_dispatcher::CommandDispatcher,
     context::CommandContext,
     exceptions::{BuiltInExceptions, CommandSyntaxException},
@@ -284,6 +287,131 @@ fn execute_redirected() {
     assert_eq!(CommandDispatcher::execute_parsed(parse).unwrap(), 2);
 }
 
+#[test]
+fn execute_redirected_with_modifier() {
+    let mut subject = CommandDispatcher::new();
+
+    subject.register(literal("actual").executes(|_| 42));
+    subject.register(
+        literal("redirected")
+            .redirect_modifier(subject.root.clone(), |_: &CommandContext<CommandSource>| {
+                Ok(Arc::new(CommandSource {}))
+            }),
+    );
+
+    assert_eq!(
+        subject
+            .execute("redirected actual", CommandSource {})
+            .unwrap(),
+        42
+    );
+}
+
+#[test]
+fn execute_redirected_with_failing_modifier() {
+    let mut subject = CommandDispatcher::new();
+
+    subject.register(literal("actual").executes(|_| 42));
+    subject.register(literal("redirected").redirect_modifier(
+        subject.root.clone(),
+        |_: &CommandContext<CommandSource>| {
+            Err(BuiltInExceptions::DispatcherParseException {
+                message: "no".to_string(),
+            }
+            .create())
+        },
+    ));
+
+    let result = subject.execute("redirected actual", CommandSource {});
+    assert_eq!(
+        result.unwrap_err().get_type(),
+        &BuiltInExceptions::DispatcherParseException {
+            message: "no".to_string()
+        }
+    );
+}
+
+/// A duration like `10m30s`, parsed into a number of seconds.
+struct Duration;
+impl CustomArgumentType for Duration {
+    fn parse(&self, input: &str) -> Result<(Rc<dyn Any>, usize), CommandSyntaxException> {
+        let length = input.find(' ').unwrap_or(input.len());
+        let mut seconds = 0;
+        let mut number = String::new();
+        for c in input[..length].chars() {
+            let multiplier = match c {
+                '0'..='9' => {
+                    number.push(c);
+                    continue;
+                }
+                'h' => 60 * 60,
+                'm' => 60,
+                's' => 1,
+                _ => {
+                    return Err(BuiltInExceptions::DispatcherParseException {
+                        message: format!("Unknown unit '{c}'"),
+                    }
+                    .create())
+                }
+            };
+            let amount = number
+                .parse::<u32>()
+                .map_err(|_| BuiltInExceptions::ReaderExpectedInt.create())?;
+            seconds += amount * multiplier;
+            number.clear();
+        }
+        if !number.is_empty() || length == 0 {
+            return Err(BuiltInExceptions::ReaderExpectedSymbol { symbol: 's' }.create());
+        }
+        Ok((Rc::new(seconds), length))
+    }
+
+    fn list_suggestions(&self, input: &str) -> Vec<String> {
+        if input.ends_with(|c: char| c.is_ascii_digit()) {
+            ["h", "m", "s"]
+                .iter()
+                .map(|unit| format!("{input}{unit}"))
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[test]
+fn execute_custom_argument() {
+    let mut subject = CommandDispatcher::new();
+    subject.register(
+        literal("wait").then(
+            custom_argument("duration", Duration)
+                .executes(|ctx: &CommandContext<CommandSource>| {
+                    *ctx.argument("duration")
+                        .unwrap()
+                        .downcast_ref::<u32>()
+                        .unwrap() as i32
+                })
+                .then(literal("then").executes(|_| 1)),
+        ),
+    );
+
+    assert_eq!(
+        subject.execute("wait 10m30s", CommandSource {}).unwrap(),
+        630
+    );
+    assert_eq!(
+        subject.execute("wait 1h then", CommandSource {}).unwrap(),
+        1
+    );
+    assert!(subject.execute("wait 10x", CommandSource {}).is_err());
+    assert!(subject.execute("wait 10", CommandSource {}).is_err());
+
+    let duration_node = subject.find_node(&["wait", "duration"]).unwrap();
+    assert_eq!(
+        duration_node.read().argument().list_suggestions("10"),
+        vec!["10h", "10m", "10s"]
+    );
+}
+
 #[test]
 fn execute_orphaned_subcommand() {
     let mut subject = CommandDispatcher::new();
@@ -40,6 +40,9 @@ pub struct Account {
     ///
     /// This is an `Arc<Mutex>` so it can be modified by [`Self::refresh`].
     pub access_token: Option<Arc<Mutex<String>>>,
+    /// The UUID of the account. For offline-mode accounts, this is the UUID
+    /// that offline-mode servers will give us.
+    ///
     /// Only required for online-mode accounts.
     pub uuid: Option<Uuid>,
 
@@ -78,7 +81,7 @@ impl Account {
         Self {
             username: username.to_string(),
             access_token: None,
-            uuid: None,
+            uuid: Some(azalea_crypto::offline_uuid(username)),
             account_opts: AccountOpts::Offline {
                 username: username.to_string(),
             },
@@ -91,6 +94,42 @@ impl Account {
     /// a key for the cache, but it's recommended to use the real email to
     /// avoid confusion.
     pub async fn microsoft(email: &str) -> Result<Self, azalea_auth::AuthError> {
+        Self::microsoft_with_opts(email, None).await
+    }
+
+    /// Like [`Self::microsoft`], but instead of printing the link and code to
+    /// log in with to the terminal, they're passed to `on_device_code` so you
+    /// can show them to the user however you want. This is only called if we
+    /// couldn't log in with the cached tokens.
+    ///
+    /// The tokens are cached and refreshed the same way as
+    /// [`Self::microsoft`]. To cancel the login while we're waiting for the
+    /// user, drop the future.
+    ///
+    /// ```no_run
+    /// # use azalea_client::Account;
+    /// # async fn example() -> Result<(), azalea_auth::AuthError> {
+    /// let account = Account::microsoft_device_code("example@example.com", |res| {
+    ///     println!(
+    ///         "Go to {} and enter the code {}",
+    ///         res.verification_uri, res.user_code
+    ///     );
+    /// })
+    /// .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn microsoft_device_code(
+        email: &str,
+        on_device_code: impl Fn(&azalea_auth::DeviceCodeResponse) + Send + Sync + 'static,
+    ) -> Result<Self, azalea_auth::AuthError> {
+        Self::microsoft_with_opts(email, Some(Box::new(on_device_code))).await
+    }
+
+    async fn microsoft_with_opts(
+        email: &str,
+        on_device_code: Option<Box<azalea_auth::DeviceCodeCallback>>,
+    ) -> Result<Self, azalea_auth::AuthError> {
         let minecraft_dir = get_mc_dir::minecraft_dir().unwrap_or_else(|| {
             panic!(
                 "No {} environment variable found",
@@ -101,6 +140,7 @@ impl Account {
             email,
             azalea_auth::AuthOpts {
                 cache_file: Some(minecraft_dir.join("azalea-auth.json")),
+                on_device_code,
                 ..Default::default()
             },
         )
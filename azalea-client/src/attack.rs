@@ -1,11 +1,15 @@
+use azalea_block::FluidState;
 use azalea_core::game_type::GameMode;
 use azalea_entity::{
+    effects::ActiveEffects,
+    indexing::EntityIdIndex,
     metadata::{ShiftKeyDown, Sprinting},
-    update_bounding_box, Attributes, Physics,
+    update_bounding_box, Attributes, EyeHeight, Jumping, Physics, Position, Vehicle,
 };
-use azalea_physics::PhysicsSet;
+use azalea_inventory::ItemSlot;
+use azalea_physics::{on_climbable, PhysicsSet};
 use azalea_protocol::packets::game::serverbound_interact_packet::{
-    self, ServerboundInteractPacket,
+    self, InteractionHand, ServerboundInteractPacket,
 };
 use azalea_world::MinecraftEntityId;
 use bevy_app::{App, FixedUpdate, Plugin, Update};
@@ -13,9 +17,10 @@ use bevy_ecs::prelude::*;
 use derive_more::{Deref, DerefMut};
 
 use crate::{
-    interact::SwingArmEvent,
-    local_player::{LocalGameMode, SendPacketEvent},
-    movement::walk_listener,
+    interact::{check_reach, OutOfReachEvent, Reach, ReachTarget, SwingArmEvent},
+    inventory::InventoryComponent,
+    local_player::{InstanceHolder, LocalGameMode, SendPacketEvent},
+    movement::{set_sprinting, walk_listener, PhysicsState},
     respawn::perform_respawn,
     Client,
 };
@@ -35,7 +40,9 @@ impl Plugin for AttackPlugin {
                 FixedUpdate,
                 (
                     increment_ticks_since_last_attack,
+                    reset_attack_strength_on_item_switch,
                     update_attack_strength_scale.after(PhysicsSet),
+                    attack_when_ready,
                 )
                     .chain(),
             );
@@ -43,7 +50,8 @@ impl Plugin for AttackPlugin {
 }
 
 impl Client {
-    /// Attack the entity with the given id.
+    /// Attack the entity with the given id. Nothing happens if it's farther
+    /// away than our [`Reach`].
     pub fn attack(&mut self, entity_id: MinecraftEntityId) {
         self.ecs.lock().send_event(AttackEvent {
             entity: self.entity,
@@ -51,36 +59,253 @@ impl Client {
         });
     }
 
+    /// Attack the entity with the given id as soon as our attack cooldown has
+    /// fully recharged, so the hit does as much damage as possible.
+    ///
+    /// If we're already fully charged then this attacks on the next tick.
+    /// Calling this again before the attack happens replaces the target.
+    pub fn attack_when_ready(&mut self, entity_id: MinecraftEntityId) {
+        self.ecs
+            .lock()
+            .entity_mut(self.entity)
+            .insert(PendingAttack {
+                target: entity_id,
+                timing: AttackTiming::Charged,
+            });
+    }
+
+    /// Attack the entity with the given id as a critical hit, which does 50%
+    /// more damage.
+    ///
+    /// Critical hits only happen while we're falling, so this keeps jumping
+    /// until our attack cooldown has recharged and we're on the way down.
+    /// Sprinting also prevents critical hits, so we stop sprinting before the
+    /// attack happens. If we can't get a critical hit where we are (like in
+    /// water or on a ladder), this attacks as soon as we're charged like
+    /// [`Self::attack_when_ready`].
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # use azalea_world::MinecraftEntityId;
+    /// # fn example(bot: &mut Client, target: MinecraftEntityId) {
+    /// bot.attack_crit(target);
+    /// // ...a few ticks later
+    /// if bot.last_attack_kind().is_critical() {
+    ///     println!("critical hit!");
+    /// }
+    /// # }
+    /// ```
+    pub fn attack_crit(&mut self, entity_id: MinecraftEntityId) {
+        self.ecs
+            .lock()
+            .entity_mut(self.entity)
+            .insert(PendingAttack {
+                target: entity_id,
+                timing: AttackTiming::Critical,
+            });
+    }
+
+    /// Attack the entity with the given id with a sweep attack, which also hits
+    /// the entities around it.
+    ///
+    /// Sweep attacks can only be done with a sword, so if we're not holding one
+    /// this is the same as [`Self::attack_when_ready`]. Otherwise, this waits
+    /// until our attack cooldown has recharged and we're on the ground, not
+    /// sprinting, and not moving faster than walking speed.
+    pub fn attack_sweep(&mut self, entity_id: MinecraftEntityId) {
+        self.ecs
+            .lock()
+            .entity_mut(self.entity)
+            .insert(PendingAttack {
+                target: entity_id,
+                timing: AttackTiming::Sweep,
+            });
+    }
+
     /// Whether the player has an attack cooldown.
     pub fn has_attack_cooldown(&self) -> bool {
         let ticks_since_last_attack = *self.component::<AttackStrengthScale>();
         ticks_since_last_attack < 1.0
     }
+
+    /// How charged our attack is, from 0 to 1. Attacks do full damage when
+    /// this is 1.
+    pub fn attack_strength_scale(&self) -> f32 {
+        *self.component::<AttackStrengthScale>()
+    }
+
+    /// What kind of hit our last attack was, as far as we can tell. This is
+    /// [`AttackKind::Normal`] if we haven't attacked anything yet.
+    ///
+    /// The server decides this itself, so it might disagree if it thinks we
+    /// were somewhere else.
+    pub fn last_attack_kind(&self) -> AttackKind {
+        *self.component::<LastAttackKind>()
+    }
+}
+
+/// The kinds of hits that a player's attack can be. These are worked out in
+/// the same way as vanilla, which is also what the server does.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttackKind {
+    /// A weak attack or one that isn't any of the others.
+    #[default]
+    Normal,
+    /// A charged attack while sprinting, which knocks the target back farther.
+    Knockback,
+    /// A charged attack while falling, which does 50% more damage.
+    Critical,
+    /// A charged attack with a sword while standing on the ground, which also
+    /// hits the entities around the target.
+    Sweep,
+}
+impl AttackKind {
+    pub fn is_critical(&self) -> bool {
+        *self == AttackKind::Critical
+    }
+}
+
+/// What kind of hit our attack would be right now.
+///
+/// `can_crit` is whether there's nothing stopping us from getting a critical
+/// hit where we are (see [`can_crit_at`]).
+pub fn get_attack_kind(
+    attack_strength_scale: f32,
+    sprinting: bool,
+    physics: &Physics,
+    attributes: &Attributes,
+    can_crit: bool,
+    held_item: &ItemSlot,
+) -> AttackKind {
+    // vanilla checks the attack strength scale before it's reset, so this is the
+    // same number
+    if attack_strength_scale <= 0.9 {
+        return AttackKind::Normal;
+    }
+    if sprinting {
+        return AttackKind::Knockback;
+    }
+    if can_crit && is_falling(physics) {
+        return AttackKind::Critical;
+    }
+    // vanilla compares the distance we walked last tick (which is 0.6 times how
+    // far we moved horizontally) to our movement speed
+    let walked = physics.delta.horizontal_distance_sqr().sqrt() * 0.6;
+    if physics.on_ground
+        && walked < attributes.speed.calculate()
+        && azalea_registry::tags::items::SWORDS.contains(&held_item.kind())
+    {
+        return AttackKind::Sweep;
+    }
+    AttackKind::Normal
+}
+
+/// Whether we're in the air and moving down, which is when attacks are
+/// critical hits.
+fn is_falling(physics: &Physics) -> bool {
+    !physics.on_ground && physics.delta.y < 0.
+}
+
+/// Whether there's nothing stopping an entity at this position from getting
+/// critical hits. Entities that are in water, climbing, riding something, or
+/// blind can't get them.
+pub fn can_crit_at(
+    instance_holder: &InstanceHolder,
+    position: &Position,
+    active_effects: Option<&ActiveEffects>,
+    vehicle: Option<&Vehicle>,
+) -> bool {
+    if vehicle.is_some()
+        || active_effects.is_some_and(|effects| effects.has(azalea_registry::MobEffect::Blindness))
+    {
+        return false;
+    }
+    let world = instance_holder.instance.read();
+    let in_water = world
+        .get_block_state(&position.into())
+        .is_some_and(|state| FluidState::from(state).fluid == azalea_registry::Fluid::Water);
+    !in_water && !on_climbable(&world, position)
 }
 
+/// The kind of hit that our last attack was. See [`Client::last_attack_kind`].
+#[derive(Default, Component, Clone, Copy, Debug, Deref, DerefMut)]
+pub struct LastAttackKind(pub AttackKind);
+
 #[derive(Event)]
 pub struct AttackEvent {
     pub entity: Entity,
     pub target: MinecraftEntityId,
 }
+#[allow(clippy::type_complexity)]
 pub fn handle_attack_event(
     mut events: EventReader<AttackEvent>,
     mut query: Query<(
         &LocalGameMode,
         &mut TicksSinceLastAttack,
-        &mut Physics,
         &mut Sprinting,
         &mut ShiftKeyDown,
+        &Position,
+        &EyeHeight,
+        &Reach,
+        &EntityIdIndex,
+        (
+            &AttackStrengthScale,
+            &Attributes,
+            &InventoryComponent,
+            &InstanceHolder,
+            Option<&ActiveEffects>,
+            Option<&Vehicle>,
+            &mut LastAttackKind,
+        ),
     )>,
+    mut physics_query: Query<&mut Physics>,
     mut send_packet_events: EventWriter<SendPacketEvent>,
     mut swing_arm_event: EventWriter<SwingArmEvent>,
+    mut out_of_reach_events: EventWriter<OutOfReachEvent>,
 ) {
     for event in events.iter() {
-        let (game_mode, mut ticks_since_last_attack, mut physics, mut sprinting, sneaking) =
-            query.get_mut(event.entity).unwrap();
+        let (
+            game_mode,
+            mut ticks_since_last_attack,
+            mut sprinting,
+            sneaking,
+            position,
+            eye_height,
+            reach,
+            entity_id_index,
+            (
+                attack_strength_scale,
+                attributes,
+                inventory,
+                instance_holder,
+                active_effects,
+                vehicle,
+                mut last_attack_kind,
+            ),
+        ) = query.get_mut(event.entity).unwrap();
+
+        // we can only check the reach if we know where the target is, otherwise we
+        // let the server decide
+        let target = entity_id_index.get(&event.target).and_then(|target| {
+            let physics = physics_query.get(target).ok()?;
+            Some((target, physics.bounding_box))
+        });
+        if let Some((target, target_box)) = target {
+            if !check_reach(
+                event.entity,
+                &position.up(**eye_height as f64),
+                ReachTarget::Entity(target),
+                &target_box,
+                reach.entity_range(game_mode.current),
+                &mut out_of_reach_events,
+            ) {
+                continue;
+            }
+        }
 
         swing_arm_event.send(SwingArmEvent {
             entity: event.entity,
+            hand: InteractionHand::MainHand,
         });
         send_packet_events.send(SendPacketEvent {
             entity: event.entity,
@@ -98,6 +323,16 @@ pub fn handle_attack_event(
             continue;
         };
 
+        let mut physics = physics_query.get_mut(event.entity).unwrap();
+        **last_attack_kind = get_attack_kind(
+            **attack_strength_scale,
+            **sprinting,
+            &physics,
+            attributes,
+            can_crit_at(instance_holder, position, active_effects, vehicle),
+            &inventory.held_item(),
+        );
+
         ticks_since_last_attack.0 = 0;
 
         physics.delta = physics.delta.multiply(0.6, 1.0, 0.6);
@@ -109,6 +344,8 @@ pub fn handle_attack_event(
 pub struct AttackBundle {
     pub ticks_since_last_attack: TicksSinceLastAttack,
     pub attack_strength_scale: AttackStrengthScale,
+    pub last_item_in_main_hand: LastItemInMainHand,
+    pub last_attack_kind: LastAttackKind,
 }
 
 #[derive(Default, Component, Clone, Deref, DerefMut)]
@@ -119,6 +356,34 @@ pub fn increment_ticks_since_last_attack(mut query: Query<&mut TicksSinceLastAtt
     }
 }
 
+/// The item that we were holding as of the last tick. Switching to a different
+/// item resets our attack cooldown.
+#[derive(Default, Component, Clone, Deref, DerefMut)]
+pub struct LastItemInMainHand(pub ItemSlot);
+pub fn reset_attack_strength_on_item_switch(
+    mut query: Query<(
+        &InventoryComponent,
+        &mut LastItemInMainHand,
+        &mut TicksSinceLastAttack,
+    )>,
+) {
+    for (inventory, mut last_item_in_main_hand, mut ticks_since_last_attack) in &mut query {
+        let held_item = inventory.held_item();
+        if held_item == **last_item_in_main_hand {
+            continue;
+        }
+        // vanilla only resets the cooldown if it's a different kind of item, so
+        // e.g. a sword taking durability damage doesn't reset it
+        if held_item.kind() != last_item_in_main_hand.kind() {
+            **ticks_since_last_attack = 0;
+        }
+        **last_item_in_main_hand = held_item;
+    }
+}
+
+/// How charged our attack is, from 0 to 1. This goes back to 0 when we attack
+/// or switch to a different item, and recharges at a rate that depends on the
+/// attack speed of the item we're holding.
 #[derive(Default, Component, Clone, Deref, DerefMut)]
 pub struct AttackStrengthScale(pub f32);
 pub fn update_attack_strength_scale(
@@ -145,3 +410,243 @@ pub fn get_attack_strength_scale(
     let attack_strength = (ticks_since_last_attack as f32 + in_ticks) / attack_strength_delay;
     attack_strength.clamp(0., 1.)
 }
+
+/// A component that's present when we're waiting for our attack cooldown to
+/// recharge so we can attack an entity. See [`Client::attack_when_ready`].
+#[derive(Component, Clone, Debug)]
+pub struct PendingAttack {
+    pub target: MinecraftEntityId,
+    pub timing: AttackTiming,
+}
+
+/// When a [`PendingAttack`] happens.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AttackTiming {
+    /// As soon as our attack is fully charged.
+    #[default]
+    Charged,
+    /// When our attack is charged and we're falling, jumping whenever we're on
+    /// the ground. See [`Client::attack_crit`].
+    Critical,
+    /// When our attack is charged and it would be a sweep attack. See
+    /// [`Client::attack_sweep`].
+    Sweep,
+}
+
+#[allow(clippy::type_complexity)]
+pub fn attack_when_ready(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &PendingAttack,
+        &AttackStrengthScale,
+        &mut Sprinting,
+        &Physics,
+        &mut Attributes,
+        &InventoryComponent,
+        &mut Jumping,
+        &mut PhysicsState,
+        (
+            &InstanceHolder,
+            &Position,
+            Option<&ActiveEffects>,
+            Option<&Vehicle>,
+        ),
+    )>,
+    mut attack_events: EventWriter<AttackEvent>,
+) {
+    for (
+        entity,
+        pending_attack,
+        attack_strength_scale,
+        mut sprinting,
+        physics,
+        mut attributes,
+        inventory,
+        mut jumping,
+        mut physics_state,
+        (instance_holder, position, active_effects, vehicle),
+    ) in &mut query
+    {
+        let ready = match pending_attack.timing {
+            AttackTiming::Charged => true,
+            AttackTiming::Critical => {
+                if can_crit_at(instance_holder, position, active_effects, vehicle) {
+                    // jump again every time we land until the attack happens
+                    **jumping = physics.on_ground;
+                    // sprinting turns the hit into a knockback attack, so the server
+                    // has to know that we stopped before we can attack
+                    if **sprinting {
+                        physics_state.trying_to_sprint = false;
+                        set_sprinting(false, &mut sprinting, &mut attributes);
+                    }
+                    is_falling(physics) && !physics_state.was_sprinting
+                } else {
+                    true
+                }
+            }
+            AttackTiming::Sweep => {
+                let held_item = inventory.held_item();
+                !azalea_registry::tags::items::SWORDS.contains(&held_item.kind())
+                    || get_attack_kind(
+                        **attack_strength_scale,
+                        **sprinting,
+                        physics,
+                        &attributes,
+                        false,
+                        &held_item,
+                    ) == AttackKind::Sweep
+            }
+        };
+        if !ready || **attack_strength_scale < 1. {
+            continue;
+        }
+        if pending_attack.timing == AttackTiming::Critical {
+            **jumping = false;
+        }
+        attack_events.send(AttackEvent {
+            entity,
+            target: pending_attack.target,
+        });
+        commands.entity(entity).remove::<PendingAttack>();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_core::position::Vec3;
+    use azalea_entity::{attributes::AttributeInstance, EntityDimensions};
+    use azalea_inventory::ItemSlotData;
+    use azalea_registry::Item;
+
+    use super::*;
+
+    fn physics(on_ground: bool, delta: Vec3) -> Physics {
+        let mut physics = Physics::new(
+            EntityDimensions {
+                width: 0.6,
+                height: 1.8,
+            },
+            &Vec3::default(),
+        );
+        physics.on_ground = on_ground;
+        physics.delta = delta;
+        physics
+    }
+
+    fn attributes() -> Attributes {
+        Attributes {
+            speed: AttributeInstance::new(0.1),
+            attack_speed: AttributeInstance::new(4.),
+        }
+    }
+
+    fn held(kind: Item) -> ItemSlot {
+        ItemSlot::Present(ItemSlotData {
+            kind,
+            count: 1,
+            nbt: Default::default(),
+        })
+    }
+
+    #[test]
+    fn test_falling_attack_is_critical() {
+        let falling = physics(false, Vec3::new(0., -0.2, 0.));
+        assert_eq!(
+            get_attack_kind(1., false, &falling, &attributes(), true, &ItemSlot::Empty),
+            AttackKind::Critical
+        );
+        // something like water or blindness is stopping us from getting one
+        assert_eq!(
+            get_attack_kind(1., false, &falling, &attributes(), false, &ItemSlot::Empty),
+            AttackKind::Normal
+        );
+        // we're still going up from the jump
+        let rising = physics(false, Vec3::new(0., 0.2, 0.));
+        assert_eq!(
+            get_attack_kind(1., false, &rising, &attributes(), true, &ItemSlot::Empty),
+            AttackKind::Normal
+        );
+    }
+
+    #[test]
+    fn test_sprinting_attack_is_knockback() {
+        // sprinting wins over falling
+        let falling = physics(false, Vec3::new(0., -0.2, 0.));
+        assert_eq!(
+            get_attack_kind(1., true, &falling, &attributes(), true, &ItemSlot::Empty),
+            AttackKind::Knockback
+        );
+    }
+
+    #[test]
+    fn test_sword_on_ground_is_sweep() {
+        let standing = physics(true, Vec3::default());
+        assert_eq!(
+            get_attack_kind(
+                1.,
+                false,
+                &standing,
+                &attributes(),
+                true,
+                &held(Item::DiamondSword)
+            ),
+            AttackKind::Sweep
+        );
+        // only swords sweep
+        assert_eq!(
+            get_attack_kind(
+                1.,
+                false,
+                &standing,
+                &attributes(),
+                true,
+                &held(Item::DiamondAxe)
+            ),
+            AttackKind::Normal
+        );
+    }
+
+    #[test]
+    fn test_sweep_needs_walking_slower_than_our_speed() {
+        let sword = held(Item::IronSword);
+        // 0.1 * 0.6 is less than our speed of 0.1
+        let walking = physics(true, Vec3::new(0.1, 0., 0.));
+        assert_eq!(
+            get_attack_kind(1., false, &walking, &attributes(), true, &sword),
+            AttackKind::Sweep
+        );
+        // 0.2 * 0.6 isn't
+        let running = physics(true, Vec3::new(0.2, 0., 0.));
+        assert_eq!(
+            get_attack_kind(1., false, &running, &attributes(), true, &sword),
+            AttackKind::Normal
+        );
+    }
+
+    #[test]
+    fn test_weak_attack_is_normal() {
+        let falling = physics(false, Vec3::new(0., -0.2, 0.));
+        let standing = physics(true, Vec3::default());
+        let sword = held(Item::DiamondSword);
+        for strength in [0., 0.5, 0.9] {
+            assert_eq!(
+                get_attack_kind(strength, true, &standing, &attributes(), true, &sword),
+                AttackKind::Normal
+            );
+            assert_eq!(
+                get_attack_kind(strength, false, &falling, &attributes(), true, &sword),
+                AttackKind::Normal
+            );
+            assert_eq!(
+                get_attack_kind(strength, false, &standing, &attributes(), true, &sword),
+                AttackKind::Normal
+            );
+        }
+        // just over the threshold is charged enough
+        assert_eq!(
+            get_attack_kind(0.91, false, &falling, &attributes(), true, &sword),
+            AttackKind::Critical
+        );
+    }
+}
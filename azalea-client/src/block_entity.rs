@@ -0,0 +1,208 @@
+//! The extra data that some blocks have, like the text on signs and the
+//! patterns on banners.
+//!
+//! The raw NBT is stored in the [`Instance`](azalea_world::Instance), and
+//! [`BlockEntity`] parses the parts of it that bots usually care about.
+
+use azalea_chat::FormattedText;
+use azalea_core::position::BlockPos;
+use azalea_nbt::{Nbt, NbtCompound, NbtList};
+use azalea_registry::BlockEntityKind;
+use azalea_world::BlockEntityData;
+use uuid::Uuid;
+
+use crate::Client;
+
+/// A block entity with its NBT parsed into something more useful.
+///
+/// Block entities that aren't parsed (or that had NBT we didn't understand)
+/// are [`BlockEntity::Other`], so you can still read their NBT yourself.
+///
+/// ```
+/// # use azalea_client::{Client, block_entity::BlockEntity};
+/// # use azalea_core::position::BlockPos;
+/// # fn example(client: &Client) {
+/// if let Some(BlockEntity::Sign(sign)) = client.block_entity(&BlockPos::new(0, 64, 0)) {
+///     println!("{}", sign.front.text());
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub enum BlockEntity {
+    Sign(Sign),
+    HangingSign(Sign),
+    Skull(Skull),
+    Banner(Banner),
+    Other(BlockEntityData),
+}
+
+impl From<BlockEntityData> for BlockEntity {
+    fn from(data: BlockEntityData) -> Self {
+        let parsed = match data.kind {
+            BlockEntityKind::Sign => Sign::from_nbt(&data.nbt).map(BlockEntity::Sign),
+            BlockEntityKind::HangingSign => Sign::from_nbt(&data.nbt).map(BlockEntity::HangingSign),
+            BlockEntityKind::Skull => Some(BlockEntity::Skull(Skull::from_nbt(&data.nbt))),
+            BlockEntityKind::Banner => Banner::from_nbt(&data.nbt).map(BlockEntity::Banner),
+            _ => None,
+        };
+        parsed.unwrap_or(BlockEntity::Other(data))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sign {
+    pub front: SignText,
+    pub back: SignText,
+    /// Whether the sign was waxed with honeycomb, which stops players from
+    /// editing it.
+    pub is_waxed: bool,
+}
+
+impl Sign {
+    fn from_nbt(nbt: &NbtCompound) -> Option<Self> {
+        Some(Self {
+            front: SignText::from_nbt(nbt.get("front_text")?.as_compound()?)?,
+            back: SignText::from_nbt(nbt.get("back_text")?.as_compound()?)?,
+            is_waxed: nbt_as_bool(nbt.get("is_waxed")),
+        })
+    }
+}
+
+/// One side of a sign.
+#[derive(Clone, Debug)]
+pub struct SignText {
+    pub messages: [FormattedText; 4],
+    /// The name of the dye color that the text was dyed with, like `black` or
+    /// `light_blue`.
+    pub color: String,
+    pub has_glowing_text: bool,
+}
+
+impl SignText {
+    fn from_nbt(nbt: &NbtCompound) -> Option<Self> {
+        let Nbt::List(NbtList::String(messages)) = nbt.get("messages")? else {
+            return None;
+        };
+        let messages: Vec<FormattedText> = messages
+            .iter()
+            .map(|message| {
+                // the messages are usually json, but vanilla treats them as literal text if
+                // they can't be parsed
+                serde_json::from_str(message).unwrap_or_else(|_| FormattedText::from(&**message))
+            })
+            .collect();
+        Some(Self {
+            messages: messages.try_into().ok()?,
+            color: nbt
+                .get("color")
+                .and_then(|color| color.as_string())
+                .map_or_else(|| "black".to_string(), |color| color.to_string()),
+            has_glowing_text: nbt_as_bool(nbt.get("has_glowing_text")),
+        })
+    }
+
+    /// The plain text of every line on this side of the sign, joined with
+    /// newlines.
+    pub fn text(&self) -> String {
+        self.messages
+            .iter()
+            .map(|message| message.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// A mob or player head.
+#[derive(Clone, Debug, Default)]
+pub struct Skull {
+    /// The name of the player whose head this is, if it's a player head.
+    pub owner_name: Option<String>,
+    pub owner_uuid: Option<Uuid>,
+}
+
+impl Skull {
+    fn from_nbt(nbt: &NbtCompound) -> Self {
+        let Some(owner) = nbt.get("SkullOwner").and_then(|owner| owner.as_compound()) else {
+            return Self::default();
+        };
+        Self {
+            owner_name: owner
+                .get("Name")
+                .and_then(|name| name.as_string())
+                .map(|name| name.to_string()),
+            owner_uuid: owner
+                .get("Id")
+                .and_then(|id| id.as_int_array())
+                .and_then(|id| uuid_from_int_array(id)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Banner {
+    /// The patterns on the banner, from the bottom layer to the top. This
+    /// doesn't include the base color, which is part of the block state.
+    pub patterns: Vec<BannerPattern>,
+    pub custom_name: Option<FormattedText>,
+}
+
+impl Banner {
+    fn from_nbt(nbt: &NbtCompound) -> Option<Self> {
+        let patterns = match nbt.get("Patterns") {
+            Some(Nbt::List(NbtList::Compound(patterns))) => patterns
+                .iter()
+                .map(|pattern| {
+                    Some(BannerPattern {
+                        pattern: pattern.get("Pattern")?.as_string()?.to_string(),
+                        color: *pattern.get("Color")?.as_int()?,
+                    })
+                })
+                .collect::<Option<Vec<_>>>()?,
+            Some(Nbt::List(NbtList::Empty)) | None => Vec::new(),
+            Some(_) => return None,
+        };
+        Some(Self {
+            patterns,
+            custom_name: nbt
+                .get("CustomName")
+                .and_then(|name| name.as_string())
+                .and_then(|name| serde_json::from_str(name).ok()),
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BannerPattern {
+    /// The short code for the pattern, like `bs` for a bottom stripe.
+    pub pattern: String,
+    /// The id of the dye color, where 0 is white and 15 is black.
+    pub color: i32,
+}
+
+fn nbt_as_bool(nbt: Option<&Nbt>) -> bool {
+    matches!(nbt, Some(Nbt::Byte(b)) if *b != 0)
+}
+
+fn uuid_from_int_array(ints: &[i32]) -> Option<Uuid> {
+    let [a, b, c, d]: [i32; 4] = ints.try_into().ok()?;
+    let most_significant_bits = ((a as u32 as u64) << 32) | (b as u32 as u64);
+    let least_significant_bits = ((c as u32 as u64) << 32) | (d as u32 as u64);
+    Some(Uuid::from_u64_pair(
+        most_significant_bits,
+        least_significant_bits,
+    ))
+}
+
+impl Client {
+    /// Get the block entity at the given position, like a sign or a banner.
+    ///
+    /// This returns `None` if there's no block entity there or the chunk
+    /// isn't loaded. Note that the server doesn't send the items in
+    /// containers like chests, you have to open them for that.
+    pub fn block_entity(&self, pos: &BlockPos) -> Option<BlockEntity> {
+        self.world()
+            .read()
+            .get_block_entity(pos)
+            .map(BlockEntity::from)
+    }
+}
@@ -0,0 +1,48 @@
+//! The client and server "brand", which is the name of the client or server
+//! software that's being used (like `vanilla`, `fabric`, or `Paper`).
+
+use std::io::Cursor;
+
+use azalea_buf::McBufReadable;
+use azalea_core::resource_location::ResourceLocation;
+use bevy_ecs::{component::Component, system::Resource};
+use derive_more::{Deref, DerefMut};
+
+/// The brand that we tell servers we're using when we join. This defaults to
+/// `vanilla`, but some servers and anticheats act differently depending on
+/// the brand, so you can replace this resource to pretend to be another
+/// client.
+///
+/// ```
+/// # use azalea_client::brand::ClientBrand;
+/// # fn example(app: &mut bevy_app::App) {
+/// app.insert_resource(ClientBrand("fabric".to_string()));
+/// # }
+/// ```
+#[derive(Resource, Clone, Debug, Deref, DerefMut)]
+pub struct ClientBrand(pub String);
+impl Default for ClientBrand {
+    fn default() -> Self {
+        // they don't have to know :)
+        Self("vanilla".to_string())
+    }
+}
+
+/// A component that contains the brand of the server we're connected to. This
+/// is only present once the server tells us its brand.
+#[derive(Component, Clone, Debug, Deref, DerefMut)]
+pub struct ServerBrand(pub String);
+
+/// The identifier of the custom payload packet that's used for sending brands.
+pub fn brand_identifier() -> ResourceLocation {
+    ResourceLocation::new("brand")
+}
+
+/// Read the brand from the data of a custom payload packet, or return `None`
+/// if the packet isn't a brand packet.
+pub fn read_brand_payload(identifier: &ResourceLocation, data: &[u8]) -> Option<String> {
+    if *identifier != brand_identifier() {
+        return None;
+    }
+    String::read_from(&mut Cursor::new(data)).ok()
+}
@@ -1,28 +1,42 @@
 //! Implementations of chat-related features.
 
 use azalea_chat::FormattedText;
-use azalea_protocol::packets::game::{
-    clientbound_player_chat_packet::ClientboundPlayerChatPacket,
-    clientbound_system_chat_packet::ClientboundSystemChatPacket,
-    serverbound_chat_command_packet::ServerboundChatCommandPacket,
-    serverbound_chat_packet::{LastSeenMessagesUpdate, ServerboundChatPacket},
+use azalea_crypto::{MessageSignature, SignChatMessageOptions};
+use azalea_protocol::packets::{
+    game::{
+        clientbound_player_chat_packet::{ChatType, ClientboundPlayerChatPacket},
+        clientbound_system_chat_packet::ClientboundSystemChatPacket,
+        serverbound_chat_command_packet::{ArgumentSignature, ServerboundChatCommandPacket},
+        serverbound_chat_packet::{LastSeenMessagesUpdate, ServerboundChatPacket},
+        serverbound_chat_session_update_packet::{
+            ProfilePublicKeyData, RemoteChatSessionData, ServerboundChatSessionUpdatePacket,
+        },
+    },
+    ConnectionProtocol,
 };
 use bevy_app::{App, Plugin, Update};
 use bevy_ecs::{
+    component::Component,
     entity::Entity,
     event::{EventReader, EventWriter},
     prelude::Event,
     schedule::IntoSystemConfigs,
+    system::{Commands, Query},
 };
+use log::warn;
 use std::{
     sync::Arc,
     time::{SystemTime, UNIX_EPOCH},
 };
+use thiserror::Error;
 use uuid::Uuid;
 
 use crate::{
     client::Client,
+    commands::CommandTree,
     local_player::{handle_send_packet_event, SendPacketEvent},
+    packet_handling::ProtocolStateChangedEvent,
+    Account,
 };
 
 /// A chat packet, either a system message or a chat message.
@@ -66,6 +80,9 @@ impl ChatPacket {
                 if p.overlay {
                     return (None, message);
                 }
+                if let Some(whisper) = self.whisper() {
+                    return (Some(whisper.sender), whisper.content);
+                }
                 // It's a system message, so we'll have to match the content
                 // with regex
                 if let Some(m) = regex!("^<([a-zA-Z_0-9]{1,16})> (.+)$").captures(&message) {
@@ -116,11 +133,150 @@ impl ChatPacket {
     pub fn is_whisper(&self) -> bool {
         match self.message() {
             FormattedText::Text(_) => false,
-            FormattedText::Translatable(t) => t.key == "commands.message.display.incoming",
+            FormattedText::Translatable(t) => t.key == INCOMING_WHISPER_TRANSLATION_KEY,
+        }
+    }
+
+    /// If this is a whisper that someone sent us with /msg (or aliases), get
+    /// who sent it and what they said.
+    ///
+    /// Like [`Self::is_whisper`], this won't work on servers that use their own
+    /// whisper system.
+    ///
+    /// ```
+    /// # use azalea_client::chat::ChatPacket;
+    /// # fn example(packet: &ChatPacket) {
+    /// if let Some(whisper) = packet.whisper() {
+    ///     println!("{} whispered to us: {}", whisper.sender, whisper.content);
+    /// }
+    /// # }
+    /// ```
+    pub fn whisper(&self) -> Option<Whisper> {
+        match self {
+            ChatPacket::Player(p) => {
+                if p.chat_type.chat_type != ChatType::MsgCommandIncoming {
+                    return None;
+                }
+                Some(Whisper {
+                    sender: p.chat_type.name.to_string(),
+                    content: p.body.content.clone(),
+                })
+            }
+            ChatPacket::System(p) => {
+                let FormattedText::Translatable(t) = &p.content else {
+                    return None;
+                };
+                if t.key != INCOMING_WHISPER_TRANSLATION_KEY {
+                    return None;
+                }
+                // the arguments are the sender's name and the message
+                let [sender, content] = t.args.as_slice() else {
+                    return None;
+                };
+                Some(Whisper {
+                    sender: sender.to_string(),
+                    content: content.to_string(),
+                })
+            }
         }
     }
 }
 
+/// The translation key that vanilla uses for whispers that were sent to us.
+const INCOMING_WHISPER_TRANSLATION_KEY: &str = "commands.message.display.incoming";
+
+/// A private message that someone sent us. See [`ChatPacket::whisper`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Whisper {
+    pub sender: String,
+    pub content: String,
+}
+
+/// The command that [`Client::whisper`] uses to send private messages, since
+/// servers don't all use the same one.
+///
+/// `{name}` is replaced with the username of the player that we're whispering
+/// to, and `{message}` is replaced with the message. The default is
+/// `msg {name} {message}`.
+///
+/// ```
+/// # use azalea_client::{Client, chat::WhisperCommand};
+/// # fn example(bot: &Client) {
+/// bot.ecs
+///     .lock()
+///     .entity_mut(bot.entity)
+///     .insert(WhisperCommand("tell {name} {message}".to_string()));
+/// # }
+/// ```
+#[derive(Component, Clone, Debug)]
+pub struct WhisperCommand(pub String);
+impl Default for WhisperCommand {
+    fn default() -> Self {
+        Self("msg {name} {message}".to_string())
+    }
+}
+impl WhisperCommand {
+    /// Make the command (without a slash at the front) that sends `message` to
+    /// the player with the given username.
+    pub fn format(&self, username: &str, message: &str) -> Result<String, WhisperError> {
+        // a username with spaces would make the rest of it be part of the message
+        if username.is_empty() || username.contains(|c: char| c.is_whitespace() || c == '{') {
+            return Err(WhisperError::InvalidUsername(username.to_string()));
+        }
+        let message = message.trim();
+        if message.is_empty() {
+            return Err(WhisperError::EmptyMessage);
+        }
+
+        // the message is replaced last so braces in it aren't treated as placeholders
+        Ok(self
+            .0
+            .trim_start_matches('/')
+            .replace("{name}", username)
+            .replace("{message}", message))
+    }
+}
+
+/// How commands are sent to the server, and whether [`Client::chat`] treats
+/// messages that start with a slash as commands.
+///
+/// Since 1.19, commands are sent with their own packet so the arguments that
+/// are shown to other players (like the message in `/msg`) can be signed.
+/// Some servers and proxies on older versions only accept commands as chat
+/// messages, so you can insert [`ChatCommandMode::Chat`] for those.
+///
+/// ```
+/// # use azalea_client::{Client, chat::ChatCommandMode};
+/// # fn example(bot: &Client) {
+/// bot.ecs
+///     .lock()
+///     .entity_mut(bot.entity)
+///     .insert(ChatCommandMode::Chat);
+/// # }
+/// ```
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ChatCommandMode {
+    /// Send commands with the command packet, and sign their arguments if we
+    /// have a [`ChatSession`].
+    #[default]
+    Packet,
+    /// Send commands as chat messages that start with a slash, like clients
+    /// before 1.19 did.
+    Chat,
+    /// Send commands with the command packet, but don't treat messages that
+    /// start with a slash as commands in [`Client::chat`]. Commands can still
+    /// be sent with [`Client::send_chat_command`].
+    Explicit,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum WhisperError {
+    #[error("{0:?} isn't a valid username")]
+    InvalidUsername(String),
+    #[error("Can't send an empty whisper")]
+    EmptyMessage,
+}
+
 impl Client {
     /// Sends chat message to the server. This only sends the chat packet and
     /// not the command packet. The [`Client::chat`] function handles checking
@@ -146,8 +302,49 @@ impl Client {
         self.run_schedule_sender.send(()).unwrap();
     }
 
+    /// Run a command on the server. The slash at the front is optional.
+    ///
+    /// Unlike [`Client::send_command`], this doesn't check the command against
+    /// the ones that the server told us about first. The arguments that have
+    /// to be signed (like the message in `/msg`) are signed if we have a
+    /// [`ChatSession`], and the command is sent as a chat message instead if
+    /// the [`ChatCommandMode`] is [`ChatCommandMode::Chat`].
+    ///
+    /// ```rust,no_run
+    /// # use azalea_client::Client;
+    /// # fn example(bot: &Client) {
+    /// bot.send_chat_command("/say hello");
+    /// # }
+    /// ```
+    pub fn send_chat_command(&self, command: &str) {
+        self.send_command_packet(command.strip_prefix('/').unwrap_or(command));
+    }
+
+    /// Send a private message to a player, using the server's
+    /// [`WhisperCommand`].
+    ///
+    /// ```rust,no_run
+    /// # use azalea_client::Client;
+    /// # fn example(bot: &Client) -> anyhow::Result<()> {
+    /// bot.whisper("Notch", "hi")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn whisper(&self, username: &str, message: &str) -> Result<(), WhisperError> {
+        let command = self
+            .get_component::<WhisperCommand>()
+            .unwrap_or_default()
+            .format(username, message)?;
+        self.send_command_packet(&command);
+        Ok(())
+    }
+
     /// Send a message in chat.
     ///
+    /// Messages that start with a slash are sent as commands (see
+    /// [`Client::send_chat_command`]), unless the [`ChatCommandMode`] is
+    /// [`ChatCommandMode::Explicit`].
+    ///
     /// ```rust,no_run
     /// # use azalea_client::{Client, Event};
     /// # async fn handle(bot: Client, event: Event) -> anyhow::Result<()> {
@@ -173,6 +370,7 @@ impl Plugin for ChatPlugin {
             .add_systems(
                 Update,
                 (
+                    start_chat_session,
                     handle_send_chat_event,
                     handle_send_chat_kind_event.after(handle_send_packet_event),
                 )
@@ -197,10 +395,12 @@ pub struct SendChatEvent {
 
 fn handle_send_chat_event(
     mut events: EventReader<SendChatEvent>,
+    query: Query<&ChatCommandMode>,
     mut send_chat_kind_events: EventWriter<SendChatKindEvent>,
 ) {
     for event in events.iter() {
-        if event.content.starts_with('/') {
+        let mode = query.get(event.entity).copied().unwrap_or_default();
+        if event.content.starts_with('/') && mode != ChatCommandMode::Explicit {
             send_chat_kind_events.send(SendChatKindEvent {
                 entity: event.entity,
                 content: event.content[1..].to_string(),
@@ -238,8 +438,15 @@ pub enum ChatPacketKind {
     Command,
 }
 
+#[allow(clippy::type_complexity)]
 fn handle_send_chat_kind_event(
     mut events: EventReader<SendChatKindEvent>,
+    mut query: Query<(
+        &Account,
+        Option<&mut ChatSession>,
+        Option<&CommandTree>,
+        Option<&ChatCommandMode>,
+    )>,
     mut send_packet_events: EventWriter<SendPacketEvent>,
 ) {
     for event in events.iter() {
@@ -249,36 +456,66 @@ fn handle_send_chat_kind_event(
             .filter(|c| !matches!(c, '\x00'..='\x1F' | '\x7F' | '§'))
             .take(256)
             .collect::<String>();
-        let packet = match event.kind {
-            ChatPacketKind::Message => ServerboundChatPacket {
+        let timestamp = SystemTime::now();
+        let salt = azalea_crypto::make_salt();
+        let (account, mut chat_session, command_tree, mode) = match query.get_mut(event.entity) {
+            Ok((account, chat_session, command_tree, mode)) => (
+                Some(account),
+                chat_session,
+                command_tree,
+                mode.copied().unwrap_or_default(),
+            ),
+            Err(_) => (None, None, None, ChatCommandMode::default()),
+        };
+        // old servers only know about commands in chat
+        let send_as_chat = match event.kind {
+            ChatPacketKind::Message => true,
+            ChatPacketKind::Command => mode == ChatCommandMode::Chat,
+        };
+        let content = match event.kind {
+            ChatPacketKind::Command if send_as_chat => format!("/{content}"),
+            _ => content,
+        };
+        let packet = if send_as_chat {
+            let signature = match (account, chat_session.as_mut()) {
+                (Some(account), Some(chat_session)) => {
+                    chat_session.sign(account, &content, timestamp, salt)
+                }
+                // unsigned chat is fine if the server doesn't enforce secure chat
+                _ => None,
+            };
+            ServerboundChatPacket {
                 message: content,
-                timestamp: SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .expect("Time shouldn't be before epoch")
-                    .as_millis()
-                    .try_into()
-                    .expect("Instant should fit into a u64"),
-                salt: azalea_crypto::make_salt(),
-                signature: None,
+                timestamp: unix_millis(timestamp),
+                salt,
+                signature,
+                // TODO: keep track of the messages we've seen
                 last_seen_messages: LastSeenMessagesUpdate::default(),
             }
-            .get(),
-            ChatPacketKind::Command => {
-                // TODO: chat signing
-                ServerboundChatCommandPacket {
-                    command: content,
-                    timestamp: SystemTime::now()
-                        .duration_since(UNIX_EPOCH)
-                        .expect("Time shouldn't be before epoch")
-                        .as_millis()
-                        .try_into()
-                        .expect("Instant should fit into a u64"),
-                    salt: azalea_crypto::make_salt(),
-                    argument_signatures: vec![],
-                    last_seen_messages: LastSeenMessagesUpdate::default(),
-                }
-                .get()
+            .get()
+        } else {
+            // every argument is signed with the same timestamp and salt, like in vanilla
+            let argument_signatures = match (account, chat_session.as_mut(), command_tree) {
+                (Some(account), Some(chat_session), Some(command_tree)) => command_tree
+                    .signed_arguments(&content)
+                    .into_iter()
+                    .filter_map(|(name, value)| {
+                        let signature = chat_session.sign(account, &value, timestamp, salt)?;
+                        Some(ArgumentSignature { name, signature })
+                    })
+                    .collect(),
+                // the server can't expect signatures if we don't have a session, and we
+                // can't know which arguments to sign without the command tree
+                _ => vec![],
+            };
+            ServerboundChatCommandPacket {
+                command: content,
+                timestamp: unix_millis(timestamp),
+                salt,
+                argument_signatures,
+                last_seen_messages: LastSeenMessagesUpdate::default(),
             }
+            .get()
         };
 
         send_packet_events.send(SendPacketEvent {
@@ -288,8 +525,198 @@ fn handle_send_chat_kind_event(
     }
 }
 
-// TODO
-// MessageSigner, ChatMessageContent, LastSeenMessages
-// fn sign_message() -> MessageSignature {
-//     MessageSignature::default()
-// }
+/// The number of milliseconds since the Unix epoch, which is how timestamps are
+/// sent in chat packets.
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .expect("Time shouldn't be before epoch")
+        .as_millis()
+        .try_into()
+        .expect("Instant should fit into a u64")
+}
+
+/// The chat session that we use to sign our chat messages, so servers with
+/// secure chat enforced will accept them.
+///
+/// This is only present if the [`Account`] had [`Account::certs`] set when we
+/// joined the server. Use [`Account::request_certs`] to get them.
+#[derive(Component, Clone, Debug)]
+pub struct ChatSession {
+    pub session_id: Uuid,
+    /// The number of messages we've signed in this session. This is used as
+    /// the index for the next message, since the server requires that it
+    /// always increases.
+    pub messages_sent: u32,
+}
+
+impl ChatSession {
+    /// Sign a chat message, or return `None` if the account's certificates
+    /// aren't usable.
+    fn sign(
+        &mut self,
+        account: &Account,
+        message: &str,
+        timestamp: SystemTime,
+        salt: u64,
+    ) -> Option<MessageSignature> {
+        let (Some(certs), Some(account_uuid)) = (&account.certs, account.uuid) else {
+            return None;
+        };
+        let signature = azalea_crypto::sign_chat_message(&SignChatMessageOptions {
+            account_uuid,
+            chat_session_uuid: self.session_id,
+            message_index: self.messages_sent,
+            salt,
+            timestamp,
+            message: message.to_string(),
+            private_key: certs.private_key.clone(),
+        });
+        self.messages_sent += 1;
+        Some(signature)
+    }
+}
+
+/// Tell the server about our chat signing key when we join, if we have one.
+///
+/// This is done every time that we go into the game state, since a server
+/// that reconfigures us (like a proxy moving us to another server) expects a
+/// new session.
+fn start_chat_session(
+    mut commands: Commands,
+    mut events: EventReader<ProtocolStateChangedEvent>,
+    query: Query<&Account>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.iter() {
+        if event.to != ConnectionProtocol::Game {
+            continue;
+        }
+        let entity = event.entity;
+        let Ok(account) = query.get(entity) else {
+            continue;
+        };
+        // we don't want to keep using a session from a previous connection
+        commands.entity(entity).remove::<ChatSession>();
+
+        let (Some(certs), Some(_)) = (&account.certs, account.uuid) else {
+            // offline accounts or accounts that didn't request certs just send unsigned
+            // messages
+            continue;
+        };
+        let expires_at = certs.expires_at.timestamp_millis();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("Time shouldn't be before epoch")
+            .as_millis() as i64;
+        if expires_at <= now {
+            warn!(
+                "The chat signing certificates for {} have expired, so chat messages won't be signed. Call Account::request_certs again to refresh them.",
+                account.username
+            );
+            continue;
+        }
+
+        let session_id = azalea_crypto::make_chat_session_id();
+        send_packet_events.send(SendPacketEvent {
+            entity,
+            packet: ServerboundChatSessionUpdatePacket {
+                chat_session: RemoteChatSessionData {
+                    session_id,
+                    profile_public_key: ProfilePublicKeyData {
+                        expires_at: expires_at as u64,
+                        key: certs.public_key_der.clone(),
+                        key_signature: certs.signature_v2.clone(),
+                    },
+                },
+            }
+            .get(),
+        });
+        commands.entity(entity).insert(ChatSession {
+            session_id,
+            messages_sent: 0,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_chat::translatable_component::{StringOrComponent, TranslatableComponent};
+
+    use super::*;
+
+    fn system_chat(content: FormattedText) -> ChatPacket {
+        ChatPacket::System(Arc::new(ClientboundSystemChatPacket {
+            content,
+            overlay: false,
+        }))
+    }
+
+    #[test]
+    fn test_incoming_whisper() {
+        let packet = system_chat(FormattedText::Translatable(TranslatableComponent::new(
+            INCOMING_WHISPER_TRANSLATION_KEY.to_string(),
+            vec![
+                StringOrComponent::String("Notch".to_string()),
+                StringOrComponent::String("hello there".to_string()),
+            ],
+        )));
+        assert!(packet.is_whisper());
+        assert_eq!(
+            packet.whisper(),
+            Some(Whisper {
+                sender: "Notch".to_string(),
+                content: "hello there".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_other_messages_arent_whispers() {
+        // the whisper that we sent to someone else
+        let outgoing = system_chat(FormattedText::Translatable(TranslatableComponent::new(
+            "commands.message.display.outgoing".to_string(),
+            vec![
+                StringOrComponent::String("Notch".to_string()),
+                StringOrComponent::String("hi".to_string()),
+            ],
+        )));
+        assert!(!outgoing.is_whisper());
+        assert_eq!(outgoing.whisper(), None);
+
+        let plain = ChatPacket::new("Notch whispers to you: hi");
+        assert!(!plain.is_whisper());
+        assert_eq!(plain.whisper(), None);
+    }
+
+    #[test]
+    fn test_whisper_command_format() {
+        assert_eq!(
+            WhisperCommand::default().format("Notch", "hi"),
+            Ok("msg Notch hi".to_string())
+        );
+        assert_eq!(
+            WhisperCommand("/tell {name} {message}".to_string()).format("Notch", "  hi  "),
+            Ok("tell Notch hi".to_string())
+        );
+        // placeholders in the message are left alone
+        assert_eq!(
+            WhisperCommand::default().format("Notch", "my {name} is {message}"),
+            Ok("msg Notch my {name} is {message}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_whisper_command_validation() {
+        let command = WhisperCommand::default();
+        for username in ["", "Notch jeb_", "{message}"] {
+            assert_eq!(
+                command.format(username, "hi"),
+                Err(WhisperError::InvalidUsername(username.to_string()))
+            );
+        }
+        assert_eq!(
+            command.format("Notch", "   "),
+            Err(WhisperError::EmptyMessage)
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Keep a limited number of chunks in memory after every client unloads them,
+//! and forget about chunks that nothing is using anymore.
+//!
+//! Chunks in an [`Instance`] are only stored weakly, so normally they're
+//! dropped as soon as the last client that had them loaded forgets them. The
+//! [`ChunkCache`] can keep some of them around for longer (which is useful for
+//! things like pathfinding to places we've already been), but it never keeps
+//! more than [`ChunkCacheSettings::max_unloaded_chunks`] per instance.
+//!
+//! [`Instance`]: azalea_world::Instance
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
+use azalea_core::{position::ChunkPos, resource_location::ResourceLocation};
+use azalea_world::{Chunk, InstanceContainer, InstanceName};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use parking_lot::RwLock;
+
+pub struct ChunkCachePlugin;
+impl Plugin for ChunkCachePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ChunkCacheSettings>()
+            .init_resource::<ChunkCache>()
+            .add_event::<UpdateChunkCacheEvent>()
+            .add_systems(Update, update_chunk_cache);
+    }
+}
+
+/// Limits for the [`ChunkCache`]. You can insert this resource yourself to
+/// change them.
+///
+/// ```
+/// # use azalea_client::chunk_cache::ChunkCacheSettings;
+/// # let mut app = bevy_app::App::new();
+/// app.insert_resource(ChunkCacheSettings {
+///     max_unloaded_chunks: 1024,
+/// });
+/// ```
+#[derive(Resource, Clone, Debug, Default)]
+pub struct ChunkCacheSettings {
+    /// The number of chunks that we keep in memory for each instance after
+    /// no client has them loaded anymore. When there's more than this, the
+    /// ones that were unloaded the longest time ago are dropped first.
+    ///
+    /// This is 0 by default, so chunks are dropped as soon as every client
+    /// unloads them.
+    pub max_unloaded_chunks: usize,
+}
+
+/// Sent when a client loads or unloads chunks or its view distance changes,
+/// so the [`ChunkCache`] for its instance is updated.
+#[derive(Event, Debug, Clone)]
+pub struct UpdateChunkCacheEvent {
+    pub entity: Entity,
+}
+
+/// The chunks that clients have unloaded and that are being kept in memory
+/// by the chunk cache.
+///
+/// Chunks that no client has loaded aren't updated by the server anymore, so
+/// they might be outdated.
+#[derive(Resource, Default)]
+pub struct ChunkCache {
+    instances: HashMap<ResourceLocation, HashMap<ChunkPos, CachedChunk>>,
+    /// The number of chunks that have been unloaded so far, which is used to
+    /// know which chunks were unloaded first.
+    tick: u64,
+}
+
+struct CachedChunk {
+    /// This is only a strong reference to chunks that a client unloaded, the
+    /// cache never keeps chunks that clients still have loaded alive by
+    /// itself.
+    chunk: Arc<RwLock<Chunk>>,
+    /// When the chunk was last unloaded by a client.
+    unloaded_tick: u64,
+}
+
+impl ChunkCache {
+    /// Keep a chunk that a client is about to unload in memory until the next
+    /// time the cache is updated, when it's decided whether to keep it for
+    /// longer.
+    pub fn keep_unloaded(
+        &mut self,
+        instance_name: &ResourceLocation,
+        pos: ChunkPos,
+        chunk: Arc<RwLock<Chunk>>,
+    ) {
+        self.tick += 1;
+        self.instances
+            .entry(instance_name.clone())
+            .or_default()
+            .insert(
+                pos,
+                CachedChunk {
+                    chunk,
+                    unloaded_tick: self.tick,
+                },
+            );
+    }
+
+    /// Returns whether the given chunk is only being kept in memory by the
+    /// cache, which means that no client has it loaded and it might be
+    /// outdated.
+    pub fn is_only_cached(
+        &self,
+        instance_name: &ResourceLocation,
+        pos: &ChunkPos,
+        chunk: &Arc<RwLock<Chunk>>,
+    ) -> bool {
+        let Some(cached) = self
+            .instances
+            .get(instance_name)
+            .and_then(|chunks| chunks.get(pos))
+        else {
+            return false;
+        };
+        // one reference is in the cache and the other is the one we were given
+        Arc::ptr_eq(&cached.chunk, chunk) && Arc::strong_count(chunk) <= 2
+    }
+
+    /// The number of chunks in the given instance that are only being kept in
+    /// memory by the cache.
+    pub fn unloaded_chunk_count(&self, instance_name: &ResourceLocation) -> usize {
+        self.instances.get(instance_name).map_or(0, |chunks| {
+            chunks
+                .values()
+                .filter(|cached| Arc::strong_count(&cached.chunk) == 1)
+                .count()
+        })
+    }
+}
+
+/// Evict the chunks that were unloaded the longest time ago if there's too
+/// many, and remove chunks that were dropped or replaced from the instances.
+///
+/// This only updates the instances that an [`UpdateChunkCacheEvent`] was sent
+/// for, so it doesn't do anything on ticks where no chunks were loaded or
+/// unloaded.
+pub fn update_chunk_cache(
+    mut events: EventReader<UpdateChunkCacheEvent>,
+    query: Query<&InstanceName>,
+    instance_container: Res<InstanceContainer>,
+    settings: Res<ChunkCacheSettings>,
+    mut chunk_cache: ResMut<ChunkCache>,
+) {
+    let updated_instances = events
+        .iter()
+        .filter_map(|event| query.get(event.entity).ok())
+        .map(|instance_name| (**instance_name).clone())
+        .collect::<HashSet<_>>();
+    if updated_instances.is_empty() {
+        return;
+    }
+
+    // instances that every client left don't need to be cached anymore
+    chunk_cache.instances.retain(|instance_name, _| {
+        instance_container
+            .instances
+            .get(instance_name)
+            .is_some_and(|instance| instance.strong_count() > 0)
+    });
+
+    for instance_name in &updated_instances {
+        let Some(instance) = instance_container
+            .instances
+            .get(instance_name)
+            .and_then(|instance| instance.upgrade())
+        else {
+            continue;
+        };
+        let mut instance = instance.write();
+
+        if let Some(cached_chunks) = chunk_cache.instances.get_mut(instance_name) {
+            // a client loaded a newer version of the chunk, so the one we have is
+            // outdated
+            cached_chunks.retain(|pos, cached| {
+                instance
+                    .chunks
+                    .map
+                    .get(pos)
+                    .is_some_and(|chunk| chunk.as_ptr() == Arc::as_ptr(&cached.chunk))
+            });
+
+            // anything more than the reference in the cache means that a client has
+            // it loaded again
+            let unloaded = cached_chunks
+                .iter()
+                .filter(|(_, cached)| Arc::strong_count(&cached.chunk) == 1)
+                .map(|(pos, cached)| (*pos, cached.unloaded_tick))
+                .collect();
+            for pos in chunks_to_evict(unloaded, settings.max_unloaded_chunks) {
+                cached_chunks.remove(&pos);
+            }
+        }
+
+        instance.chunks.remove_dropped_chunks();
+    }
+}
+
+/// Get the positions of the oldest unloaded chunks that have to be dropped so
+/// there's at most `max_unloaded_chunks` left.
+fn chunks_to_evict(
+    mut unloaded: Vec<(ChunkPos, u64)>,
+    max_unloaded_chunks: usize,
+) -> Vec<ChunkPos> {
+    if unloaded.len() <= max_unloaded_chunks {
+        return Vec::new();
+    }
+    unloaded.sort_unstable_by_key(|(_, unloaded_tick)| *unloaded_tick);
+    let evict_count = unloaded.len() - max_unloaded_chunks;
+    unloaded
+        .into_iter()
+        .take(evict_count)
+        .map(|(pos, _)| pos)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evict_oldest_chunks() {
+        let unloaded = vec![
+            (ChunkPos::new(0, 0), 3),
+            (ChunkPos::new(1, 0), 1),
+            (ChunkPos::new(2, 0), 4),
+            (ChunkPos::new(3, 0), 2),
+        ];
+        let mut evicted = chunks_to_evict(unloaded, 2);
+        evicted.sort_by_key(|pos| pos.x);
+        assert_eq!(evicted, vec![ChunkPos::new(1, 0), ChunkPos::new(3, 0)]);
+    }
+
+    #[test]
+    fn test_evict_nothing_under_limit() {
+        let unloaded = vec![(ChunkPos::new(0, 0), 1), (ChunkPos::new(1, 0), 2)];
+        assert!(chunks_to_evict(unloaded.clone(), 2).is_empty());
+        assert!(chunks_to_evict(unloaded, 3).is_empty());
+    }
+
+    #[test]
+    fn test_evict_everything_with_no_limit() {
+        let unloaded = vec![(ChunkPos::new(0, 0), 1), (ChunkPos::new(1, 0), 2)];
+        assert_eq!(chunks_to_evict(unloaded, 0).len(), 2);
+    }
+}
@@ -1,30 +1,48 @@
 use crate::{
     attack::{self, AttackPlugin},
+    brand::{brand_identifier, ClientBrand, ServerBrand},
     chat::ChatPlugin,
     chunk_batching::{ChunkBatchInfo, ChunkBatchingPlugin},
+    chunk_cache::ChunkCachePlugin,
+    connection_stats::{ConnectionStats, ConnectionStatsPlugin},
     disconnect::{DisconnectEvent, DisconnectPlugin},
+    eat::EatPlugin,
+    effects::EffectsPlugin,
     events::{Event, EventPlugin, LocalPlayerEvents},
-    interact::{CurrentSequenceNumber, InteractPlugin},
+    flying::FlyingPlugin,
+    interact::{CurrentSequenceNumber, InteractPlugin, Reach},
     inventory::{InventoryComponent, InventoryPlugin},
     local_player::{
-        death_event, handle_send_packet_event, GameProfileComponent, Hunger, InstanceHolder,
-        PermissionLevel, PlayerAbilities, SendPacketEvent, TabList,
+        death_event, handle_send_packet_event, BossBar, BossBars, Experience, GameProfileComponent,
+        Hunger, InstanceHolder, PermissionLevel, PlayerAbilities, SendPacketEvent, ServerTps,
+        TabList, TabListHeaderFooter,
     },
+    login_query::{LoginQuery, LoginQueryHandlers},
     mining::{self, MinePlugin},
     movement::{LastSentLookDirection, PhysicsState, PlayerMovePlugin},
-    packet_handling::PacketHandlerPlugin,
+    packet_handling::{PacketHandlerPlugin, ProtocolStateChangedEvent},
+    packet_rate_limit::PacketRateLimitPlugin,
     player::retroactively_add_game_profile_component,
     raw_connection::RawConnection,
+    ready::ReadySettings,
+    resource_pack::ResourcePackPolicy,
     respawn::RespawnPlugin,
+    scoreboard::Scoreboard,
+    sign::SignPlugin,
+    sleep::SleepPlugin,
     task_pool::TaskPoolPlugin,
+    vehicle::VehiclePlugin,
+    world_time::{Weather, WorldTime, WorldTimePlugin},
     Account, PlayerInfo, ReceivedRegistries,
 };
 
 use azalea_auth::{game_profile::GameProfile, sessionserver::ClientSessionServerError};
-use azalea_buf::McBufWritable;
+use azalea_block::BlockState;
+use azalea_buf::{McBufWritable, UnsizedByteArray};
 use azalea_chat::FormattedText;
-use azalea_core::{position::Vec3, resource_location::ResourceLocation};
+use azalea_core::position::{BlockPos, Vec3};
 use azalea_entity::{
+    effects::ActiveEffects,
     indexing::{EntityIdIndex, EntityUuidIndex},
     metadata::Health,
     EntityPlugin, EntityUpdateSet, EyeHeight, LocalEntity, Position,
@@ -53,8 +71,10 @@ use azalea_protocol::{
     },
     resolver, ServerAddress,
 };
-use azalea_world::{Instance, InstanceContainer, InstanceName, PartialInstance};
-use bevy_app::{App, FixedUpdate, Plugin, PluginGroup, PluginGroupBuilder, Update};
+use azalea_world::{
+    light::LightLevel, Instance, InstanceContainer, InstanceName, PartialInstance, WorldBorder,
+};
+use bevy_app::{App, FixedUpdate, Main, Plugin, PluginGroup, PluginGroupBuilder, Update};
 use bevy_ecs::{
     bundle::Bundle,
     component::Component,
@@ -209,16 +229,36 @@ impl Client {
         run_schedule_sender: mpsc::UnboundedSender<()>,
     ) -> Result<(Self, mpsc::UnboundedReceiver<Event>), JoinError> {
         let conn = Connection::new(resolved_address).await?;
-        let (mut conn, game_profile) = Self::handshake(conn, account, address).await?;
+        let login_query_handlers = ecs_lock
+            .lock()
+            .get_resource::<LoginQueryHandlers>()
+            .cloned()
+            .unwrap_or_default();
+        let connection_stats = ecs_lock
+            .lock()
+            .get_resource::<ConnectionStats>()
+            .cloned()
+            .unwrap_or_default();
+        let (mut conn, game_profile) = Self::handshake_with_login_query_handlers(
+            conn,
+            account,
+            address,
+            &login_query_handlers,
+        )
+        .await?;
 
         {
             // quickly send the brand here
+            let client_brand = ecs_lock
+                .lock()
+                .get_resource::<ClientBrand>()
+                .cloned()
+                .unwrap_or_default();
             let mut brand_data = Vec::new();
-            // they don't have to know :)
-            "vanilla".write_into(&mut brand_data).unwrap();
+            client_brand.0.write_into(&mut brand_data).unwrap();
             conn.write(
         azalea_protocol::packets::configuration::serverbound_custom_payload_packet::ServerboundCustomPayloadPacket {
-                    identifier: ResourceLocation::new("brand"),
+                    identifier: brand_identifier(),
                     data: brand_data.into(),
                 }
                 .get(),
@@ -266,14 +306,24 @@ impl Client {
                     ConnectionProtocol::Configuration,
                     read_conn,
                     write_conn,
+                    connection_stats,
                 ),
                 received_registries: ReceivedRegistries::default(),
-                local_player_events: LocalPlayerEvents(tx),
+                local_player_events: LocalPlayerEvents::new(tx),
                 game_profile: GameProfileComponent(game_profile),
                 account: account.to_owned(),
             },
             InConfigurationState,
         ));
+        ecs.send_event(ProtocolStateChangedEvent {
+            entity,
+            from: ConnectionProtocol::Login,
+            to: ConnectionProtocol::Configuration,
+        });
+        // this isn't part of a bundle so it doesn't get reset when we reconnect
+        if !ecs.entity(entity).contains::<ClientInformation>() {
+            ecs.entity_mut(entity).insert(ClientInformation::default());
+        }
 
         Ok((client, rx))
     }
@@ -283,10 +333,37 @@ impl Client {
     ///
     /// This will also automatically refresh the account's access token if
     /// it's expired.
+    ///
+    /// Login queries are always answered with no data. Use
+    /// [`Self::handshake_with_login_query_handlers`] if the server needs real
+    /// answers.
     pub async fn handshake(
+        conn: Connection<ClientboundHandshakePacket, ServerboundHandshakePacket>,
+        account: &Account,
+        address: &ServerAddress,
+    ) -> Result<
+        (
+            Connection<ClientboundConfigurationPacket, ServerboundConfigurationPacket>,
+            GameProfile,
+        ),
+        JoinError,
+    > {
+        Self::handshake_with_login_query_handlers(
+            conn,
+            account,
+            address,
+            &LoginQueryHandlers::default(),
+        )
+        .await
+    }
+
+    /// Like [`Self::handshake`], but answers the login queries that have a
+    /// handler in `login_query_handlers`.
+    pub async fn handshake_with_login_query_handlers(
         mut conn: Connection<ClientboundHandshakePacket, ServerboundHandshakePacket>,
         account: &Account,
         address: &ServerAddress,
+        login_query_handlers: &LoginQueryHandlers,
     ) -> Result<
         (
             Connection<ClientboundConfigurationPacket, ServerboundConfigurationPacket>,
@@ -311,9 +388,9 @@ impl Client {
         conn.write(
             ServerboundHelloPacket {
                 name: account.username.clone(),
-                // TODO: pretty sure this should generate an offline-mode uuid instead of just
-                // Uuid::default()
-                profile_id: account.uuid.unwrap_or_default(),
+                profile_id: account
+                    .uuid
+                    .unwrap_or_else(|| azalea_crypto::offline_uuid(&account.username)),
             }
             .get(),
         )
@@ -393,10 +470,16 @@ impl Client {
                 }
                 ClientboundLoginPacket::CustomQuery(p) => {
                     debug!("Got custom query {:?}", p);
+                    let data = login_query_handlers.answer(&LoginQuery {
+                        identifier: &p.identifier,
+                        data: &p.data,
+                        account,
+                        address,
+                    });
                     conn.write(
                         ServerboundCustomQueryAnswerPacket {
                             transaction_id: p.transaction_id,
-                            data: None,
+                            data: data.map(UnsizedByteArray::from),
                         }
                         .get(),
                     )
@@ -409,12 +492,29 @@ impl Client {
     }
 
     /// Write a packet directly to the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if we're not in the game state, if the packet couldn't
+    /// be encoded, or if we're not connected anymore. See [`WritePacketError`]
+    /// for how to tell these apart.
+    ///
+    /// [`WritePacketError`]: crate::raw_connection::WritePacketError
     pub fn write_packet(
         &self,
         packet: ServerboundGamePacket,
     ) -> Result<(), crate::raw_connection::WritePacketError> {
-        self.raw_connection_mut(&mut self.ecs.lock())
-            .write_packet(packet)
+        let ecs = self.ecs.lock();
+        let Some(raw_connection) = ecs.get::<RawConnection>(self.entity) else {
+            return Err(crate::raw_connection::WritePacketError::ConnectionClosed);
+        };
+        if raw_connection.state() != ConnectionProtocol::Game {
+            return Err(crate::raw_connection::WritePacketError::WrongState {
+                expected: ConnectionProtocol::Game,
+                got: raw_connection.state(),
+            });
+        }
+        raw_connection.write_packet(packet)
     }
 
     /// Disconnect this client from the server by ending all tasks.
@@ -424,6 +524,7 @@ impl Client {
     pub fn disconnect(&self) {
         self.ecs.lock().send_event(DisconnectEvent {
             entity: self.entity,
+            reason: None,
         });
     }
 
@@ -483,6 +584,58 @@ impl Client {
         instance_container.get(&world_name).unwrap()
     }
 
+    /// Get the block and sky light at the given position, or `None` if the
+    /// chunk isn't loaded.
+    ///
+    /// Most hostile mobs can only spawn where the block light is 0, so this is
+    /// useful for checking if an area is lit up enough.
+    pub fn light_level(&self, pos: &BlockPos) -> Option<LightLevel> {
+        self.world().read().get_light(pos)
+    }
+
+    /// Get a copy of the border of the world we're in.
+    pub fn world_border(&self) -> WorldBorder {
+        self.world().read().world_border.clone()
+    }
+
+    /// Whether the given block is completely inside the border of the world
+    /// we're in.
+    pub fn is_within_world_border(&self, pos: &BlockPos) -> bool {
+        self.world().read().world_border.is_within_bounds(pos)
+    }
+
+    /// Find every block within `radius` blocks of `near` that matches the
+    /// given function, sorted by distance (closest first).
+    ///
+    /// This only looks at chunks that we have loaded. See
+    /// [`Instance::find_blocks`] for more details.
+    ///
+    /// ```
+    /// # use azalea_block::BlockStates;
+    /// # fn example(bot: &azalea_client::Client) {
+    /// let crafting_table = BlockStates::from(azalea_registry::Block::CraftingTable);
+    /// let crafting_tables =
+    ///     bot.find_blocks(bot.position().into(), 16, |state| crafting_table.contains(&state));
+    /// # }
+    /// ```
+    pub fn find_blocks(
+        &self,
+        near: BlockPos,
+        radius: u32,
+        matcher: impl Fn(BlockState) -> bool,
+    ) -> Vec<BlockPos> {
+        self.world().read().find_blocks(near, radius, matcher)
+    }
+
+    /// Returns whether we're still connected to the server. Packets can't be
+    /// sent after this becomes false.
+    pub fn is_connected(&self) -> bool {
+        self.ecs
+            .lock()
+            .get::<RawConnection>(self.entity)
+            .is_some_and(RawConnection::is_alive)
+    }
+
     /// Returns whether we have a received the login packet yet.
     pub fn logged_in(&self) -> bool {
         // the login packet tells us the world name
@@ -494,6 +647,9 @@ impl Client {
     /// hand). If this is not set before the login packet, the default will
     /// be sent.
     ///
+    /// If you only want to change some of the options, use
+    /// [`Self::update_client_information`] instead.
+    ///
     /// ```rust,no_run
     /// # use azalea_client::{Client, ClientInformation};
     /// # async fn example(bot: Client) -> Result<(), Box<dyn std::error::Error>> {
@@ -509,22 +665,70 @@ impl Client {
         &self,
         client_information: ClientInformation,
     ) -> Result<(), crate::raw_connection::WritePacketError> {
-        {
+        self.update_client_information(|info| *info = client_information)
+    }
+
+    /// Change some of our game options and tell the server about them. The
+    /// packet is only sent if something actually changed.
+    ///
+    /// The options are kept when the client reconnects, and they're sent to
+    /// the server again when we log in.
+    ///
+    /// ```rust,no_run
+    /// # use azalea_client::Client;
+    /// # fn example(bot: Client) -> Result<(), Box<dyn std::error::Error>> {
+    /// // this doesn't change our locale or any other options
+    /// bot.update_client_information(|info| info.view_distance = 4)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update_client_information(
+        &self,
+        update: impl FnOnce(&mut ClientInformation),
+    ) -> Result<(), crate::raw_connection::WritePacketError> {
+        let client_information = {
             let mut ecs = self.ecs.lock();
             let mut client_information_mut = self.query::<&mut ClientInformation>(&mut ecs);
+            let mut client_information = client_information_mut.clone();
+            update(&mut client_information);
+            if client_information == *client_information_mut {
+                return Ok(());
+            }
             *client_information_mut = client_information.clone();
-        }
+            client_information
+        };
 
         if self.logged_in() {
             log::debug!(
                 "Sending client information (already logged in): {:?}",
                 client_information
             );
-            self.write_packet(azalea_protocol::packets::game::serverbound_client_information_packet::ServerboundClientInformationPacket { information: client_information.clone() }.get())?;
+            self.write_packet(azalea_protocol::packets::game::serverbound_client_information_packet::ServerboundClientInformationPacket { information: client_information }.get())?;
         }
 
         Ok(())
     }
+
+    /// Get the game options that we most recently told the server about (or
+    /// that we're going to send when we log in).
+    ///
+    /// This is a shortcut for `bot.component::<ClientInformation>()`.
+    pub fn client_information(&self) -> ClientInformation {
+        self.component::<ClientInformation>()
+    }
+
+    /// Run a game tick right now. This is only useful if the client was
+    /// started with [`TickMode::Manual`], since otherwise ticks already happen
+    /// every 50 milliseconds.
+    pub fn step_tick(&self) {
+        self.step_n_ticks(1);
+    }
+
+    /// Run `n` game ticks right now, one after another. See
+    /// [`Self::step_tick`].
+    pub fn step_n_ticks(&self, n: u32) {
+        step_ticks(&mut self.ecs.lock(), n);
+    }
 }
 
 impl Client {
@@ -558,6 +762,13 @@ impl Client {
         self.component::<Hunger>().to_owned()
     }
 
+    /// Get the experience level and progress of this client.
+    ///
+    /// This is a shortcut for `self.component::<Experience>().to_owned()`.
+    pub fn experience(&self) -> Experience {
+        self.component::<Experience>().to_owned()
+    }
+
     /// Get the username of this client.
     ///
     /// This is a shortcut for
@@ -573,12 +784,45 @@ impl Client {
         self.component::<GameProfileComponent>().uuid
     }
 
+    /// Get an estimate of how many ticks per second the server is running at.
+    /// This will be 20 if the server isn't lagging.
+    ///
+    /// This is a shortcut for `bot.component::<ServerTps>().tps`.
+    pub fn server_tps(&self) -> f32 {
+        self.component::<ServerTps>().tps
+    }
+
+    /// Get the brand of the server we're connected to (like `vanilla` or
+    /// `Paper`), or `None` if the server hasn't sent it.
+    ///
+    /// This is a shortcut for `bot.get_component::<ServerBrand>()`.
+    pub fn server_brand(&self) -> Option<String> {
+        self.get_component::<ServerBrand>().map(|brand| brand.0)
+    }
+
     /// Get a map of player UUIDs to their information in the tab list.
     ///
     /// This is a shortcut for `*bot.component::<TabList>()`.
     pub fn tab_list(&self) -> HashMap<Uuid, PlayerInfo> {
         self.component::<TabList>().deref().clone()
     }
+
+    /// Get the text that's shown above and below the tab list, as
+    /// `(header, footer)`. These are empty if the server didn't set them.
+    ///
+    /// This is a shortcut for getting the fields of [`TabListHeaderFooter`].
+    pub fn tab_list_header_footer(&self) -> (FormattedText, FormattedText) {
+        let header_footer = self.component::<TabListHeaderFooter>();
+        (header_footer.header, header_footer.footer)
+    }
+
+    /// Get a map of the boss bars that the server is currently showing us,
+    /// keyed by their UUID.
+    ///
+    /// This is a shortcut for `*bot.component::<BossBars>()`.
+    pub fn boss_bars(&self) -> HashMap<Uuid, BossBar> {
+        self.component::<BossBars>().deref().clone()
+    }
 }
 
 /// The bundle of components that's shared when we're either in the
@@ -604,14 +848,22 @@ pub struct JoinedClientBundle {
     pub instance_holder: InstanceHolder,
     pub physics_state: PhysicsState,
     pub inventory: InventoryComponent,
-    pub client_information: ClientInformation,
     pub tab_list: TabList,
+    pub tab_list_header_footer: TabListHeaderFooter,
+    pub boss_bars: BossBars,
+    pub scoreboard: Scoreboard,
     pub current_sequence_number: CurrentSequenceNumber,
+    pub reach: Reach,
     pub last_sent_direction: LastSentLookDirection,
     pub abilities: PlayerAbilities,
     pub permission_level: PermissionLevel,
     pub chunk_batch_info: ChunkBatchInfo,
     pub hunger: Hunger,
+    pub experience: Experience,
+    pub active_effects: ActiveEffects,
+    pub server_tps: ServerTps,
+    pub world_time: WorldTime,
+    pub weather: Weather,
 
     pub entity_id_index: EntityIdIndex,
 
@@ -643,10 +895,32 @@ impl Plugin for AzaleaPlugin {
             )
             .add_event::<SendPacketEvent>()
             .init_resource::<InstanceContainer>()
-            .init_resource::<TabList>();
+            .init_resource::<TabList>()
+            .init_resource::<TabListHeaderFooter>()
+            .init_resource::<BossBars>()
+            .init_resource::<Scoreboard>()
+            .init_resource::<ServerTps>()
+            .init_resource::<ClientBrand>()
+            .init_resource::<ResourcePackPolicy>()
+            .init_resource::<ReadySettings>()
+            .init_resource::<LoginQueryHandlers>();
     }
 }
 
+/// When the ECS runs game ticks. See [`start_ecs_runner_with_tick_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TickMode {
+    /// Run a game tick every 50 milliseconds, like vanilla.
+    #[default]
+    RealTime,
+    /// Only run game ticks when [`Client::step_tick`] or [`step_ticks`] is
+    /// called, which is useful for making tests deterministic.
+    ///
+    /// The schedule still runs when we receive packets so they're handled
+    /// right away, but the systems in [`FixedUpdate`] (like physics) don't.
+    Manual,
+}
+
 /// Start running the ECS loop!
 ///
 /// You can create your app with `App::new()`, but don't forget to add
@@ -657,6 +931,25 @@ pub fn start_ecs_runner(
     run_schedule_receiver: mpsc::UnboundedReceiver<()>,
     run_schedule_sender: mpsc::UnboundedSender<()>,
 ) -> Arc<Mutex<World>> {
+    start_ecs_runner_with_tick_mode(
+        app,
+        run_schedule_receiver,
+        run_schedule_sender,
+        TickMode::RealTime,
+    )
+}
+
+/// Same as [`start_ecs_runner`], but game ticks can be made to only happen
+/// when we run them with [`step_ticks`].
+#[doc(hidden)]
+pub fn start_ecs_runner_with_tick_mode(
+    mut app: App,
+    run_schedule_receiver: mpsc::UnboundedReceiver<()>,
+    run_schedule_sender: mpsc::UnboundedSender<()>,
+    tick_mode: TickMode,
+) -> Arc<Mutex<World>> {
+    set_tick_mode(&mut app, tick_mode);
+
     // all resources should have been added by now so we can take the ecs from the
     // app
     let ecs = Arc::new(Mutex::new(app.world));
@@ -666,11 +959,33 @@ pub fn start_ecs_runner(
         app.main_schedule_label,
         run_schedule_receiver,
     ));
-    tokio::spawn(tick_run_schedule_loop(run_schedule_sender));
+    if tick_mode == TickMode::RealTime {
+        tokio::spawn(tick_run_schedule_loop(run_schedule_sender));
+    }
 
     ecs
 }
 
+/// Make the app's [`FixedUpdate`] schedule only run by itself if the tick mode
+/// is [`TickMode::RealTime`].
+fn set_tick_mode(app: &mut App, tick_mode: TickMode) {
+    if tick_mode == TickMode::Manual {
+        // the FixedUpdate schedule only runs once this much time has passed, so this
+        // makes it never run unless we run it ourselves
+        app.insert_resource(FixedTime::new(Duration::MAX));
+    }
+}
+
+/// Run `n` game ticks right now, one after another. This is meant for apps
+/// that were started with [`TickMode::Manual`].
+pub fn step_ticks(ecs: &mut World, n: u32) {
+    for _ in 0..n {
+        ecs.run_schedule(FixedUpdate);
+        ecs.run_schedule(Main);
+        ecs.clear_trackers();
+    }
+}
+
 async fn run_schedule_loop(
     ecs: Arc<Mutex<World>>,
     outer_schedule_label: Box<dyn ScheduleLabel>,
@@ -755,6 +1070,20 @@ impl Plugin for AmbiguityLoggerPlugin {
 
 /// This plugin group will add all the default plugins necessary for Azalea to
 /// work.
+///
+/// Bots that don't need everything can disable or replace the plugins that
+/// aren't in [`MinimalPlugins`]. Note that the plugins in `DefaultBotPlugins`
+/// (in the `azalea` crate) need most of them.
+///
+/// ```
+/// # use azalea_client::DefaultPlugins;
+/// use azalea_physics::PhysicsPlugin;
+/// use bevy_app::{App, PluginGroup};
+///
+/// // a bot that only chats doesn't have to simulate its movement
+/// let mut app = App::new();
+/// app.add_plugins(DefaultPlugins.build().disable::<PhysicsPlugin>());
+/// ```
 pub struct DefaultPlugins;
 
 impl PluginGroup for DefaultPlugins {
@@ -775,9 +1104,19 @@ impl PluginGroup for DefaultPlugins {
             .add(PlayerMovePlugin)
             .add(InteractPlugin)
             .add(RespawnPlugin)
+            .add(VehiclePlugin)
+            .add(WorldTimePlugin)
+            .add(SleepPlugin)
+            .add(SignPlugin)
             .add(MinePlugin)
             .add(AttackPlugin)
+            .add(EatPlugin)
+            .add(EffectsPlugin)
+            .add(FlyingPlugin)
             .add(ChunkBatchingPlugin)
+            .add(ChunkCachePlugin)
+            .add(PacketRateLimitPlugin)
+            .add(ConnectionStatsPlugin)
             .add(TickBroadcastPlugin);
         #[cfg(feature = "log")]
         {
@@ -786,3 +1125,75 @@ impl PluginGroup for DefaultPlugins {
         group
     }
 }
+
+/// The plugins from [`DefaultPlugins`] that are needed to join a server, stay
+/// connected, and receive [`Event`]s. Everything else (like physics, chat,
+/// inventories, and interacting with the world) has to be added yourself.
+///
+/// Methods on [`Client`] that need a plugin that wasn't added will panic, so
+/// for example [`Client::chat`] only works if you also add [`ChatPlugin`].
+///
+/// ```
+/// # use azalea_client::{chat::ChatPlugin, MinimalPlugins};
+/// use bevy_app::App;
+///
+/// let mut app = App::new();
+/// app.add_plugins((MinimalPlugins, ChatPlugin));
+/// ```
+pub struct MinimalPlugins;
+
+impl PluginGroup for MinimalPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        #[allow(unused_mut)]
+        let mut group = PluginGroupBuilder::start::<Self>()
+            .add(TimePlugin)
+            .add(PacketHandlerPlugin)
+            .add(AzaleaPlugin)
+            .add(EntityPlugin)
+            .add(EventPlugin)
+            .add(TaskPoolPlugin::default())
+            .add(DisconnectPlugin)
+            .add(TickBroadcastPlugin);
+        #[cfg(feature = "log")]
+        {
+            group = group.add(bevy_log::LogPlugin::default());
+        }
+        group
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct FixedUpdateRuns(u32);
+
+    fn count_fixed_update_runs(mut runs: ResMut<FixedUpdateRuns>) {
+        runs.0 += 1;
+    }
+
+    #[test]
+    fn test_manual_tick_mode_steps_exact_ticks() {
+        let mut app = App::new();
+        app.add_plugins(TimePlugin)
+            .init_resource::<FixedUpdateRuns>()
+            .add_systems(FixedUpdate, count_fixed_update_runs);
+        set_tick_mode(&mut app, TickMode::Manual);
+        let mut ecs = app.world;
+
+        // running the schedule when we get packets doesn't run any ticks
+        for _ in 0..5 {
+            ecs.run_schedule(Main);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(ecs.resource::<FixedUpdateRuns>().0, 0);
+
+        step_ticks(&mut ecs, 3);
+        assert_eq!(ecs.resource::<FixedUpdateRuns>().0, 3);
+        step_ticks(&mut ecs, 0);
+        assert_eq!(ecs.resource::<FixedUpdateRuns>().0, 3);
+        step_ticks(&mut ecs, 20);
+        assert_eq!(ecs.resource::<FixedUpdateRuns>().0, 23);
+    }
+}
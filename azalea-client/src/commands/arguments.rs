@@ -0,0 +1,657 @@
+//! Brigadier argument types for the parsers that Minecraft commands use.
+//!
+//! These only check that an argument looks right, they don't resolve it to a
+//! value (so an entity selector is parsed but we don't look for the entities
+//! it selects). Every argument is returned as the [`String`] that was parsed,
+//! except for the numbers and booleans that brigadier already knows about.
+
+use std::{any::Any, rc::Rc, sync::Arc};
+
+use azalea_brigadier::{
+    arguments::{
+        bool_argument_type,
+        double_argument_type::double,
+        float_argument_type::float,
+        integer_argument_type::integer,
+        long_argument_type::long,
+        string_argument_type::{greedy_string, string, word},
+        ArgumentType,
+    },
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+};
+use azalea_protocol::packets::game::clientbound_commands_packet::{
+    BrigadierParser, BrigadierString, EntityParser,
+};
+
+/// Get the brigadier argument type that parses the same arguments as the
+/// parser from the commands packet.
+///
+/// Parsers that we don't know about are treated as a single word, where
+/// brackets and quotes can contain spaces.
+pub fn argument_type(parser: &BrigadierParser) -> Arc<dyn ArgumentType + Send + Sync> {
+    match parser {
+        BrigadierParser::Bool => Arc::new(bool_argument_type::bool()),
+        BrigadierParser::Float(_) => Arc::new(float()),
+        BrigadierParser::Double(_) => Arc::new(double()),
+        BrigadierParser::Integer(_) => Arc::new(integer()),
+        BrigadierParser::Long(_) => Arc::new(long()),
+        BrigadierParser::String(BrigadierString::SingleWord) => Arc::new(word()),
+        BrigadierParser::String(BrigadierString::QuotablePhrase) => Arc::new(string()),
+        BrigadierParser::String(BrigadierString::GreedyPhrase) | BrigadierParser::Message => {
+            Arc::new(greedy_string())
+        }
+
+        BrigadierParser::Entity(EntityParser {
+            single,
+            players_only,
+        }) => Arc::new(EntitySelector {
+            single: *single,
+            players_only: *players_only,
+        }),
+        BrigadierParser::GameProfile => Arc::new(EntitySelector {
+            single: false,
+            players_only: true,
+        }),
+        BrigadierParser::ScoreHolder { allows_multiple } => Arc::new(EntitySelector {
+            single: !allows_multiple,
+            players_only: false,
+        }),
+
+        BrigadierParser::BlockPos => Arc::new(Coordinates {
+            count: 3,
+            integers: true,
+            allow_local: true,
+        }),
+        BrigadierParser::Vec3 => Arc::new(Coordinates {
+            count: 3,
+            integers: false,
+            allow_local: true,
+        }),
+        BrigadierParser::ColumnPos => Arc::new(Coordinates {
+            count: 2,
+            integers: true,
+            allow_local: false,
+        }),
+        BrigadierParser::Vec2 | BrigadierParser::Rotation => Arc::new(Coordinates {
+            count: 2,
+            integers: false,
+            allow_local: false,
+        }),
+        BrigadierParser::Angle => Arc::new(Coordinates {
+            count: 1,
+            integers: false,
+            allow_local: false,
+        }),
+
+        BrigadierParser::ResourceLocation
+        | BrigadierParser::Dimension
+        | BrigadierParser::Resource { .. }
+        | BrigadierParser::ResourceKey { .. } => {
+            Arc::new(ResourceLocationArgument { allow_tags: false })
+        }
+        BrigadierParser::Function
+        | BrigadierParser::ResourceOrTag { .. }
+        | BrigadierParser::ResourceOrTagKey { .. } => {
+            Arc::new(ResourceLocationArgument { allow_tags: true })
+        }
+
+        BrigadierParser::IntRange => Arc::new(Range { integers: true }),
+        BrigadierParser::FloatRange => Arc::new(Range { integers: false }),
+        BrigadierParser::Time { min } => Arc::new(Time { min: *min }),
+        BrigadierParser::Uuid => Arc::new(UuidArgument),
+
+        BrigadierParser::GameMode => {
+            Arc::new(OneOf(&["survival", "creative", "adventure", "spectator"]))
+        }
+        BrigadierParser::Color => Arc::new(OneOf(&[
+            "black",
+            "dark_blue",
+            "dark_green",
+            "dark_aqua",
+            "dark_red",
+            "dark_purple",
+            "gold",
+            "gray",
+            "dark_gray",
+            "blue",
+            "green",
+            "aqua",
+            "red",
+            "light_purple",
+            "yellow",
+            "white",
+            "reset",
+        ])),
+        BrigadierParser::EntityAnchor => Arc::new(OneOf(&["feet", "eyes"])),
+        BrigadierParser::Heightmap => Arc::new(OneOf(&[
+            "world_surface",
+            "motion_blocking",
+            "motion_blocking_no_leaves",
+            "ocean_floor",
+        ])),
+        BrigadierParser::TemplateMirror => Arc::new(OneOf(&["none", "front_back", "left_right"])),
+        BrigadierParser::TemplateRotation => Arc::new(OneOf(&[
+            "none",
+            "clockwise_90",
+            "180",
+            "counterclockwise_90",
+        ])),
+        BrigadierParser::Operation => {
+            Arc::new(OneOf(&["=", "+=", "-=", "*=", "/=", "%=", "<", ">", "><"]))
+        }
+
+        // block states, items, nbt, json text, particles, etc.
+        _ => Arc::new(Token),
+    }
+}
+
+fn parse_error(reader: &StringReader, message: impl Into<String>) -> CommandSyntaxException {
+    BuiltInExceptions::DispatcherParseException {
+        message: message.into(),
+    }
+    .create_with_context(reader)
+}
+
+/// Get the text that the reader went over since `start`.
+fn read_since(reader: &StringReader, start: usize) -> String {
+    reader
+        .string()
+        .chars()
+        .skip(start)
+        .take(reader.cursor() - start)
+        .collect()
+}
+
+/// Read until the next space that isn't in quotes or brackets.
+fn read_token(reader: &mut StringReader) -> Result<String, CommandSyntaxException> {
+    let start = reader.cursor();
+    let mut depth = 0usize;
+    let mut quote = None;
+    while reader.can_read() {
+        let c = reader.peek();
+        if let Some(quote_char) = quote {
+            if c == '\\' {
+                reader.skip();
+                if !reader.can_read() {
+                    break;
+                }
+            } else if c == quote_char {
+                quote = None;
+            }
+        } else {
+            match c {
+                ' ' if depth == 0 => break,
+                '"' | '\'' => quote = Some(c),
+                '[' | '{' | '(' => depth += 1,
+                ']' | '}' | ')' => {
+                    if depth == 0 {
+                        return Err(parse_error(reader, format!("Unexpected '{c}'")));
+                    }
+                    depth -= 1;
+                }
+                _ => {}
+            }
+        }
+        reader.skip();
+    }
+
+    if quote.is_some() {
+        return Err(BuiltInExceptions::ReaderExpectedEndOfQuote.create_with_context(reader));
+    }
+    if depth > 0 {
+        return Err(parse_error(reader, "Expected closing bracket"));
+    }
+    if reader.cursor() == start {
+        return Err(BuiltInExceptions::DispatcherUnknownArgument.create_with_context(reader));
+    }
+    Ok(read_since(reader, start))
+}
+
+/// Read until the next space.
+fn read_word(reader: &mut StringReader) -> Result<String, CommandSyntaxException> {
+    let start = reader.cursor();
+    while reader.can_read() && reader.peek() != ' ' {
+        reader.skip();
+    }
+    if reader.cursor() == start {
+        return Err(BuiltInExceptions::DispatcherUnknownArgument.create_with_context(reader));
+    }
+    Ok(read_since(reader, start))
+}
+
+/// A single word where brackets and quotes can contain spaces, used for
+/// arguments that we don't check any further, like NBT and block states.
+struct Token;
+impl ArgumentType for Token {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        Ok(Rc::new(read_token(reader)?))
+    }
+}
+
+/// A player name, UUID, or target selector like `@e[type=cow]`.
+struct EntitySelector {
+    single: bool,
+    players_only: bool,
+}
+impl ArgumentType for EntitySelector {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        let start = reader.cursor();
+        if !reader.can_read() || reader.peek() != '@' {
+            // a player name or uuid
+            return Ok(Rc::new(read_word(reader)?));
+        }
+
+        reader.skip();
+        if !reader.can_read() {
+            return Err(parse_error(reader, "Missing selector type"));
+        }
+        let selector_type = reader.read();
+        if !matches!(selector_type, 'p' | 'a' | 'r' | 's' | 'e') {
+            return Err(parse_error(
+                reader,
+                format!("Unknown selector type '@{selector_type}'"),
+            ));
+        }
+        let options = if reader.can_read() && reader.peek() == '[' {
+            read_token(reader)?
+        } else {
+            String::new()
+        };
+
+        let has_option = |option: &str| {
+            options
+                .trim_matches(|c| c == '[' || c == ']')
+                .split(',')
+                .any(|o| o.replace(' ', "") == option)
+        };
+        if self.single && matches!(selector_type, 'a' | 'e') && !has_option("limit=1") {
+            return Err(parse_error(
+                reader,
+                "Only one entity is allowed, but the provided selector allows more than one",
+            ));
+        }
+        if self.players_only && selector_type == 'e' && !has_option("type=player") {
+            return Err(parse_error(
+                reader,
+                "Only players may be affected by the target selector",
+            ));
+        }
+
+        Ok(Rc::new(read_since(reader, start)))
+    }
+}
+
+/// Coordinates like `~ ~1 ~`, `^ ^ ^2`, or `10 64 -5`.
+struct Coordinates {
+    count: usize,
+    /// Whether absolute coordinates have to be whole numbers.
+    integers: bool,
+    /// Whether local coordinates (with `^`) can be used.
+    allow_local: bool,
+}
+impl ArgumentType for Coordinates {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        let start = reader.cursor();
+        let mut is_local = None;
+        for i in 0..self.count {
+            if i > 0 {
+                reader.expect(' ')?;
+            }
+            if !reader.can_read() {
+                return Err(parse_error(reader, "Incomplete coordinates"));
+            }
+
+            let prefix = reader.peek();
+            let coordinate_is_local = prefix == '^';
+            if coordinate_is_local && !self.allow_local {
+                return Err(parse_error(reader, "Local coordinates can't be used here"));
+            }
+            if is_local.is_some_and(|is_local| is_local != coordinate_is_local) {
+                return Err(parse_error(
+                    reader,
+                    "Cannot mix world & local coordinates (everything must either use ^ or not)",
+                ));
+            }
+            is_local = Some(coordinate_is_local);
+
+            if prefix == '~' || prefix == '^' {
+                reader.skip();
+                if reader.can_read() && reader.peek() != ' ' {
+                    reader.read_double()?;
+                }
+            } else if self.integers {
+                reader.read_int()?;
+            } else {
+                reader.read_double()?;
+            }
+        }
+        Ok(Rc::new(read_since(reader, start)))
+    }
+}
+
+/// A namespaced id like `minecraft:stone` or `stone`, or a tag like
+/// `#minecraft:logs` if `allow_tags` is true.
+struct ResourceLocationArgument {
+    allow_tags: bool,
+}
+impl ArgumentType for ResourceLocationArgument {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        let start = reader.cursor();
+        if self.allow_tags && reader.can_read() && reader.peek() == '#' {
+            reader.skip();
+        }
+        let id_start = reader.cursor();
+        while reader.can_read()
+            && matches!(reader.peek(), 'a'..='z' | '0'..='9' | '_' | '-' | '.' | ':' | '/')
+        {
+            reader.skip();
+        }
+        let id = read_since(reader, id_start);
+        let is_valid = match id.split_once(':') {
+            Some((namespace, path)) => {
+                !namespace.contains('/') && !path.contains(':') && !path.is_empty()
+            }
+            None => !id.is_empty(),
+        };
+        if !is_valid {
+            reader.cursor = start;
+            return Err(parse_error(reader, "Invalid ID"));
+        }
+        Ok(Rc::new(read_since(reader, start)))
+    }
+}
+
+/// A range of numbers like `1..5`, `..10`, or `3`.
+struct Range {
+    integers: bool,
+}
+impl ArgumentType for Range {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        let start = reader.cursor();
+        let range = read_word(reader)?;
+        let is_number = |s: &str| {
+            if self.integers {
+                s.parse::<i32>().is_ok()
+            } else {
+                s.parse::<f64>().is_ok()
+            }
+        };
+        let is_valid = match range.split_once("..") {
+            Some((min, max)) => {
+                (!min.is_empty() || !max.is_empty())
+                    && (min.is_empty() || is_number(min))
+                    && (max.is_empty() || is_number(max))
+            }
+            None => is_number(&range),
+        };
+        if !is_valid {
+            reader.cursor = start;
+            return Err(parse_error(reader, "Invalid range"));
+        }
+        Ok(Rc::new(range))
+    }
+}
+
+/// An amount of time like `5`, `5t`, `2.5s`, or `1d`.
+struct Time {
+    /// The smallest number of ticks that's allowed.
+    min: i32,
+}
+impl ArgumentType for Time {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        let start = reader.cursor();
+        let amount = reader.read_float()?;
+        let unit = if reader.can_read() && reader.peek() != ' ' {
+            Some(reader.read())
+        } else {
+            None
+        };
+        let ticks_per_unit = match unit {
+            Some('d') => 24000.,
+            Some('s') => 20.,
+            Some('t') | None => 1.,
+            Some(_) => return Err(parse_error(reader, "Invalid unit")),
+        };
+        let ticks = (amount * ticks_per_unit).round() as i32;
+        if ticks < self.min {
+            reader.cursor = start;
+            return Err(parse_error(
+                reader,
+                format!(
+                    "Tick count must not be less than {}, found {ticks}",
+                    self.min
+                ),
+            ));
+        }
+        Ok(Rc::new(read_since(reader, start)))
+    }
+}
+
+/// A UUID like `dd5d9b9e-4a14-4e2e-96b8-f9ab4a1ff470`.
+struct UuidArgument;
+impl ArgumentType for UuidArgument {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        let start = reader.cursor();
+        let uuid = read_word(reader)?;
+        if uuid::Uuid::parse_str(&uuid).is_err() {
+            reader.cursor = start;
+            return Err(parse_error(reader, "Invalid UUID"));
+        }
+        Ok(Rc::new(uuid))
+    }
+}
+
+/// A word that has to be one of the given options.
+struct OneOf(&'static [&'static str]);
+impl ArgumentType for OneOf {
+    fn parse(&self, reader: &mut StringReader) -> Result<Rc<dyn Any>, CommandSyntaxException> {
+        let start = reader.cursor();
+        let value = read_word(reader)?;
+        if !self.0.contains(&value.as_str()) {
+            reader.cursor = start;
+            return Err(parse_error(reader, format!("Invalid value '{value}'")));
+        }
+        Ok(Rc::new(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_core::resource_location::ResourceLocation;
+
+    use super::*;
+
+    /// Parse the input with the argument type for the parser, and return what
+    /// was parsed and what's left.
+    fn parse(
+        parser: BrigadierParser,
+        input: &str,
+    ) -> Result<(String, String), CommandSyntaxException> {
+        let mut reader = StringReader::from(input);
+        let parsed = argument_type(&parser).parse(&mut reader)?;
+        let parsed = parsed
+            .downcast_ref::<String>()
+            .expect("our arguments are parsed as strings")
+            .clone();
+        Ok((parsed, reader.remaining().to_string()))
+    }
+
+    fn parsed(parser: BrigadierParser, input: &str) -> String {
+        parse(parser, input).unwrap().0
+    }
+
+    #[test]
+    fn test_token() {
+        assert_eq!(
+            parse(BrigadierParser::NbtCompoundTag, r#"{"a b": [1, 2]} rest"#).unwrap(),
+            (r#"{"a b": [1, 2]}"#.to_string(), " rest".to_string())
+        );
+        assert_eq!(
+            parsed(BrigadierParser::BlockState, "stone[facing=north]"),
+            "stone[facing=north]"
+        );
+        assert_eq!(
+            parsed(BrigadierParser::NbtTag, r#""a \" b""#),
+            r#""a \" b""#
+        );
+
+        assert!(parse(BrigadierParser::NbtCompoundTag, "{unclosed").is_err());
+        assert!(parse(BrigadierParser::NbtCompoundTag, "a]").is_err());
+        assert!(parse(BrigadierParser::NbtTag, r#""open"#).is_err());
+        assert!(parse(BrigadierParser::NbtTag, "").is_err());
+    }
+
+    #[test]
+    fn test_entity_selector() {
+        let single = || {
+            BrigadierParser::Entity(EntityParser {
+                single: true,
+                players_only: false,
+            })
+        };
+        let players = || {
+            BrigadierParser::Entity(EntityParser {
+                single: false,
+                players_only: true,
+            })
+        };
+        assert_eq!(
+            parse(single(), "Steve hello").unwrap(),
+            ("Steve".to_string(), " hello".to_string())
+        );
+        assert_eq!(parsed(single(), "@s"), "@s");
+        assert_eq!(
+            parsed(single(), "@e[type=cow, limit=1]"),
+            "@e[type=cow, limit=1]"
+        );
+        assert_eq!(parsed(players(), "@a"), "@a");
+        assert_eq!(parsed(players(), "@e[type=player]"), "@e[type=player]");
+        assert_eq!(parsed(BrigadierParser::GameProfile, "@a"), "@a");
+
+        assert!(parse(single(), "@e[type=cow]").is_err());
+        assert!(parse(single(), "@a").is_err());
+        assert!(parse(players(), "@e[type=cow]").is_err());
+        assert!(parse(single(), "@x").is_err());
+        assert!(parse(single(), "@").is_err());
+        assert!(parse(single(), "@e[limit=1").is_err());
+        assert!(parse(single(), "").is_err());
+    }
+
+    #[test]
+    fn test_coordinates() {
+        assert_eq!(
+            parse(BrigadierParser::BlockPos, "~ ~1 ~ stone").unwrap(),
+            ("~ ~1 ~".to_string(), " stone".to_string())
+        );
+        assert_eq!(parsed(BrigadierParser::BlockPos, "^ ^ ^2"), "^ ^ ^2");
+        assert_eq!(parsed(BrigadierParser::BlockPos, "10 64 -5"), "10 64 -5");
+        assert_eq!(
+            parsed(BrigadierParser::Vec3, "1.5 64 ~-0.5"),
+            "1.5 64 ~-0.5"
+        );
+        assert_eq!(parsed(BrigadierParser::ColumnPos, "~ 3"), "~ 3");
+        assert_eq!(parsed(BrigadierParser::Rotation, "90 ~"), "90 ~");
+        assert_eq!(parsed(BrigadierParser::Angle, "-45.5"), "-45.5");
+
+        assert!(parse(BrigadierParser::BlockPos, "1.5 64 0").is_err());
+        assert!(parse(BrigadierParser::BlockPos, "~ ^ ~").is_err());
+        assert!(parse(BrigadierParser::BlockPos, "1 2").is_err());
+        assert!(parse(BrigadierParser::BlockPos, "1 2 ").is_err());
+        assert!(parse(BrigadierParser::BlockPos, "a b c").is_err());
+        assert!(parse(BrigadierParser::ColumnPos, "^ ^").is_err());
+    }
+
+    #[test]
+    fn test_resource_location() {
+        assert_eq!(
+            parse(BrigadierParser::ResourceLocation, "minecraft:stone 1").unwrap(),
+            ("minecraft:stone".to_string(), " 1".to_string())
+        );
+        assert_eq!(
+            parsed(BrigadierParser::Dimension, "the_nether"),
+            "the_nether"
+        );
+        assert_eq!(
+            parsed(BrigadierParser::Function, "#minecraft:tick/load"),
+            "#minecraft:tick/load"
+        );
+        assert_eq!(
+            parsed(
+                BrigadierParser::ResourceOrTag {
+                    registry_key: ResourceLocation::new("block"),
+                },
+                "#logs"
+            ),
+            "#logs"
+        );
+
+        assert!(parse(BrigadierParser::ResourceLocation, "#minecraft:logs").is_err());
+        assert!(parse(BrigadierParser::ResourceLocation, "minecraft:").is_err());
+        assert!(parse(BrigadierParser::ResourceLocation, "a:b:c").is_err());
+        assert!(parse(BrigadierParser::ResourceLocation, "a/b:c").is_err());
+        assert!(parse(BrigadierParser::ResourceLocation, "Stone").is_err());
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(
+            parse(BrigadierParser::IntRange, "1..5 rest").unwrap(),
+            ("1..5".to_string(), " rest".to_string())
+        );
+        assert_eq!(parsed(BrigadierParser::IntRange, "..10"), "..10");
+        assert_eq!(parsed(BrigadierParser::IntRange, "5.."), "5..");
+        assert_eq!(parsed(BrigadierParser::IntRange, "3"), "3");
+        assert_eq!(parsed(BrigadierParser::FloatRange, "1.5..2"), "1.5..2");
+
+        assert!(parse(BrigadierParser::IntRange, "..").is_err());
+        assert!(parse(BrigadierParser::IntRange, "1.5..2").is_err());
+        assert!(parse(BrigadierParser::FloatRange, "abc").is_err());
+        assert!(parse(BrigadierParser::FloatRange, "").is_err());
+    }
+
+    #[test]
+    fn test_time() {
+        let time = |min| BrigadierParser::Time { min };
+        assert_eq!(
+            parse(time(0), "5 rest").unwrap(),
+            ("5".to_string(), " rest".to_string())
+        );
+        assert_eq!(parsed(time(0), "5t"), "5t");
+        assert_eq!(parsed(time(0), "2.5s"), "2.5s");
+        assert_eq!(parsed(time(0), "1d"), "1d");
+
+        assert!(parse(time(0), "5x").is_err());
+        assert!(parse(time(0), "-1").is_err());
+        assert!(parse(time(1), "0").is_err());
+        assert!(parse(time(0), "s").is_err());
+    }
+
+    #[test]
+    fn test_uuid() {
+        assert_eq!(
+            parsed(
+                BrigadierParser::Uuid,
+                "dd5d9b9e-4a14-4e2e-96b8-f9ab4a1ff470"
+            ),
+            "dd5d9b9e-4a14-4e2e-96b8-f9ab4a1ff470"
+        );
+
+        assert!(parse(BrigadierParser::Uuid, "not-a-uuid").is_err());
+        assert!(parse(BrigadierParser::Uuid, "dd5d9b9e-4a14-4e2e-96b8").is_err());
+    }
+
+    #[test]
+    fn test_one_of() {
+        assert_eq!(
+            parse(BrigadierParser::GameMode, "creative Steve").unwrap(),
+            ("creative".to_string(), " Steve".to_string())
+        );
+        assert_eq!(parsed(BrigadierParser::Color, "dark_aqua"), "dark_aqua");
+        assert_eq!(parsed(BrigadierParser::Operation, "+="), "+=");
+
+        assert!(parse(BrigadierParser::GameMode, "hardcore").is_err());
+        assert!(parse(BrigadierParser::GameMode, "Creative").is_err());
+        assert!(parse(BrigadierParser::EntityAnchor, "").is_err());
+    }
+}
@@ -0,0 +1,395 @@
+//! Look at the commands that the server told us about, and check commands
+//! against them before sending them.
+
+pub mod arguments;
+
+use std::sync::Arc;
+
+use azalea_brigadier::{
+    builder::{literal_argument_builder::Literal, required_argument_builder::Argument},
+    command_dispatcher::CommandDispatcher,
+    context::CommandContext,
+    exceptions::{BuiltInExceptions, CommandSyntaxException},
+    string_reader::StringReader,
+    tree::CommandNode,
+};
+use azalea_protocol::packets::game::clientbound_commands_packet::ClientboundCommandsPacket;
+pub use azalea_protocol::packets::game::clientbound_commands_packet::{
+    BrigadierNodeStub, BrigadierParser, NodeType,
+};
+use bevy_ecs::component::Component;
+use derive_more::Deref;
+use parking_lot::RwLock;
+
+use crate::Client;
+
+/// The commands that the server said we can use.
+///
+/// This derefs to a brigadier [`CommandDispatcher`] that can parse commands
+/// like the server would. To see what arguments a command takes, use
+/// [`Self::find`] and [`Self::children`] instead, since those have the
+/// Minecraft parser of every argument and how the server suggests values for
+/// it.
+///
+/// This is inserted into the local player when we receive the commands packet,
+/// and it's used by [`Client::send_command`] to check commands before sending
+/// them.
+///
+/// ```
+/// # use azalea_client::commands::{CommandTree, NodeType};
+/// # fn example(command_tree: &CommandTree) {
+/// // print the arguments of every way to use /give
+/// if let Some(give) = command_tree.find(&["give"]) {
+///     for child in command_tree.children(give) {
+///         if let NodeType::Argument { name, parser, .. } = &child.node_type {
+///             println!("{name}: {parser:?}");
+///         }
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Component, Clone, Deref)]
+pub struct CommandTree {
+    #[deref]
+    pub dispatcher: Arc<CommandDispatcher<()>>,
+    /// The nodes from the commands packet, which the children and redirects
+    /// refer to by their index.
+    pub nodes: Arc<[BrigadierNodeStub]>,
+    pub root_index: usize,
+}
+
+impl CommandTree {
+    /// Build the command tree from the nodes in the commands packet.
+    pub fn new(packet: &ClientboundCommandsPacket) -> Self {
+        let nodes = packet
+            .entries
+            .iter()
+            .map(|entry| {
+                let mut node = CommandNode::default();
+                match &entry.node_type {
+                    NodeType::Root => {}
+                    NodeType::Literal { name } => node.value = Literal::new(name).into(),
+                    NodeType::Argument { name, parser, .. } => {
+                        node.value = Argument::new(name, arguments::argument_type(parser)).into();
+                    }
+                }
+                if entry.is_executable {
+                    // we only check commands, the server is the one that runs them
+                    node.command = Some(Arc::new(|_: &CommandContext<()>| 0));
+                }
+                Arc::new(RwLock::new(node))
+            })
+            .collect::<Vec<_>>();
+
+        // the children have to be added after every node exists since they can be in
+        // any order
+        for (entry, node) in packet.entries.iter().zip(&nodes) {
+            if let Some(redirect) = entry
+                .redirect_node
+                .and_then(|index| nodes.get(index as usize))
+            {
+                node.write().redirect = Some(redirect.clone());
+            }
+            for child in entry
+                .children
+                .iter()
+                .filter_map(|&index| nodes.get(index as usize))
+            {
+                if !Arc::ptr_eq(child, node) {
+                    node.write().add_child(child);
+                }
+            }
+        }
+
+        let root = nodes
+            .get(packet.root_index as usize)
+            .cloned()
+            .unwrap_or_default();
+        Self {
+            dispatcher: Arc::new(CommandDispatcher { root }),
+            nodes: packet.entries.clone().into(),
+            root_index: packet.root_index as usize,
+        }
+    }
+
+    /// The node that every command is a child of.
+    pub fn root(&self) -> Option<&BrigadierNodeStub> {
+        self.nodes.get(self.root_index)
+    }
+
+    /// Get the nodes that can come after this one. If the node redirects to
+    /// another node (like `/execute run` does), this is the children of that
+    /// node instead.
+    pub fn children<'a>(
+        &'a self,
+        node: &'a BrigadierNodeStub,
+    ) -> impl Iterator<Item = &'a BrigadierNodeStub> {
+        let node = node
+            .redirect_node
+            .and_then(|index| self.nodes.get(index as usize))
+            .unwrap_or(node);
+        node.children
+            .iter()
+            .filter_map(|&index| self.nodes.get(index as usize))
+    }
+
+    /// Find a node by the names of the nodes on the way to it, like
+    /// `&["gamemode", "gamemode"]` for the game mode argument of `/gamemode`.
+    pub fn find(&self, path: &[&str]) -> Option<&BrigadierNodeStub> {
+        let mut node = self.root()?;
+        for name in path {
+            node = self
+                .children(node)
+                .find(|child| child.name() == Some(*name))?;
+        }
+        Some(node)
+    }
+
+    /// Check whether the server would be able to parse the command. The
+    /// command shouldn't include the slash at the front.
+    ///
+    /// This doesn't check whether we're allowed to run the command, since the
+    /// server doesn't tell us about commands that we can't run anyways.
+    pub fn validate(&self, command: &str) -> Result<(), CommandSyntaxException> {
+        let parse = self.parse(StringReader::from(command), ());
+        if parse.reader.can_read() {
+            if parse.exceptions.len() == 1 {
+                return Err(parse.exceptions.into_values().next().unwrap());
+            }
+            if parse.context.range.is_empty() {
+                return Err(
+                    BuiltInExceptions::DispatcherUnknownCommand.create_with_context(&parse.reader)
+                );
+            }
+            return Err(
+                BuiltInExceptions::DispatcherUnknownArgument.create_with_context(&parse.reader)
+            );
+        }
+
+        // follow redirects (like /execute ... run) to find the node we ended up at
+        let mut context = &parse.context;
+        while let Some(child) = &context.child {
+            context = child;
+        }
+        if context.command.is_none() {
+            return Err(
+                BuiltInExceptions::DispatcherUnknownCommand.create_with_context(&parse.reader)
+            );
+        }
+        Ok(())
+    }
+
+    /// Get the name and value of every argument in the command that has to be
+    /// signed, which are the ones that use [`BrigadierParser::Message`] (like
+    /// the message in `/msg` and `/say`). The command shouldn't include the
+    /// slash at the front.
+    ///
+    /// This is used to sign commands when we send them, so servers with secure
+    /// chat enforced will accept them.
+    pub fn signed_arguments(&self, command: &str) -> Vec<(String, String)> {
+        let parse = self.parse(StringReader::from(command), ());
+        let Some(mut stub) = self.root() else {
+            return vec![];
+        };
+
+        let mut signed_arguments = Vec::new();
+        // the brigadier nodes don't know what Minecraft parser they use, so we follow
+        // along in our own nodes by name
+        let mut context = Some(&parse.context);
+        while let Some(current) = context {
+            for parsed in &current.nodes {
+                let node = parsed.node.read();
+                let Some(child) = self
+                    .children(stub)
+                    .find(|child| child.name() == Some(node.name()))
+                else {
+                    return signed_arguments;
+                };
+                stub = child;
+                if let NodeType::Argument {
+                    name,
+                    parser: BrigadierParser::Message,
+                    ..
+                } = &stub.node_type
+                {
+                    // the range is in characters, not bytes
+                    let value = command
+                        .chars()
+                        .skip(parsed.range.start())
+                        .take(parsed.range.length())
+                        .collect();
+                    signed_arguments.push((name.clone(), value));
+                }
+            }
+            // redirects (like /execute ... run) continue in a child context, which starts
+            // from the node that we were redirected to
+            context = current.child.as_deref();
+            if context.is_some() {
+                let Some(redirect) = stub
+                    .redirect_node
+                    .and_then(|index| self.nodes.get(index as usize))
+                else {
+                    return signed_arguments;
+                };
+                stub = redirect;
+            }
+        }
+        signed_arguments
+    }
+}
+
+impl Client {
+    /// Send a command to the server, after checking that the server would be
+    /// able to parse it. The `command` argument should not include the slash at
+    /// the front.
+    ///
+    /// The command is sent without being checked if `force` is true or if the
+    /// server hasn't told us what commands it has yet.
+    ///
+    /// ```rust,no_run
+    /// # use azalea_client::Client;
+    /// # fn example(bot: &Client) {
+    /// if let Err(err) = bot.send_command("gamemode creative", false) {
+    ///     println!("invalid command: {err:?}");
+    /// }
+    /// # }
+    /// ```
+    pub fn send_command(&self, command: &str, force: bool) -> Result<(), CommandSyntaxException> {
+        if !force {
+            if let Some(command_tree) = self.get_component::<CommandTree>() {
+                command_tree.validate(command)?;
+            }
+        }
+        self.send_command_packet(command);
+        Ok(())
+    }
+
+    /// Get the commands that the server said we can use, or `None` if the
+    /// server hasn't sent them yet.
+    ///
+    /// This is a shortcut for `bot.get_component::<CommandTree>()`.
+    pub fn command_tree(&self) -> Option<CommandTree> {
+        self.get_component::<CommandTree>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_protocol::packets::game::clientbound_commands_packet::EntityParser;
+
+    use super::*;
+
+    fn literal(name: &str, children: Vec<u32>) -> BrigadierNodeStub {
+        BrigadierNodeStub {
+            is_executable: false,
+            children,
+            redirect_node: None,
+            node_type: NodeType::Literal {
+                name: name.to_string(),
+            },
+        }
+    }
+
+    fn argument(name: &str, parser: BrigadierParser, children: Vec<u32>) -> BrigadierNodeStub {
+        BrigadierNodeStub {
+            is_executable: children.is_empty(),
+            children,
+            redirect_node: None,
+            node_type: NodeType::Argument {
+                name: name.to_string(),
+                parser,
+                suggestions_type: None,
+            },
+        }
+    }
+
+    fn redirect(node: BrigadierNodeStub, redirect_node: u32) -> BrigadierNodeStub {
+        BrigadierNodeStub {
+            redirect_node: Some(redirect_node),
+            ..node
+        }
+    }
+
+    /// A small version of the vanilla command tree with `/say <message>`,
+    /// `/msg <targets> <message>`, `/execute run ...`, and
+    /// `/execute as <targets> ...`.
+    fn command_tree() -> CommandTree {
+        let targets = BrigadierParser::Entity(EntityParser {
+            single: false,
+            players_only: false,
+        });
+        let entries = vec![
+            // 0
+            BrigadierNodeStub {
+                is_executable: false,
+                children: vec![1, 3, 6],
+                redirect_node: None,
+                node_type: NodeType::Root,
+            },
+            // 1
+            literal("say", vec![2]),
+            // 2
+            argument("message", BrigadierParser::Message, vec![]),
+            // 3
+            literal("execute", vec![4, 5]),
+            // 4
+            redirect(literal("run", vec![]), 0),
+            // 5
+            literal("as", vec![9]),
+            // 6
+            literal("msg", vec![7]),
+            // 7
+            argument("targets", targets.clone(), vec![8]),
+            // 8
+            argument("message", BrigadierParser::Message, vec![]),
+            // 9
+            redirect(argument("targets", targets, vec![]), 3),
+        ];
+        CommandTree::new(&ClientboundCommandsPacket {
+            entries,
+            root_index: 0,
+        })
+    }
+
+    fn message(value: &str) -> Vec<(String, String)> {
+        vec![("message".to_string(), value.to_string())]
+    }
+
+    #[test]
+    fn test_signed_arguments_plain() {
+        let command_tree = command_tree();
+        assert_eq!(
+            command_tree.signed_arguments("say hello world"),
+            message("hello world")
+        );
+        // the range is in characters, not bytes
+        assert_eq!(command_tree.signed_arguments("say héllo"), message("héllo"));
+    }
+
+    #[test]
+    fn test_signed_arguments_nested() {
+        let command_tree = command_tree();
+        assert_eq!(
+            command_tree.signed_arguments("msg @a hi there"),
+            message("hi there")
+        );
+        assert_eq!(command_tree.signed_arguments("msg @a"), vec![]);
+    }
+
+    #[test]
+    fn test_signed_arguments_redirected() {
+        let command_tree = command_tree();
+        assert_eq!(
+            command_tree.signed_arguments("execute run say hello"),
+            message("hello")
+        );
+        assert_eq!(
+            command_tree.signed_arguments("execute as @a run say hello"),
+            message("hello")
+        );
+        assert_eq!(
+            command_tree.signed_arguments("execute as @a as @p run msg bot hi"),
+            message("hi")
+        );
+    }
+}
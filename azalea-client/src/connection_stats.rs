@@ -0,0 +1,159 @@
+//! Counters for how much data is going through our connections, which is
+//! useful for debugging bandwidth and protocol issues.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use parking_lot::Mutex;
+
+use crate::Client;
+
+/// A plugin that keeps the packets per second in [`ConnectionStats`] up to
+/// date.
+pub struct ConnectionStatsPlugin;
+impl Plugin for ConnectionStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConnectionStats>()
+            .add_systems(Update, update_packet_rates);
+    }
+}
+
+/// How often the packets per second are recalculated.
+const RATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A resource with the number of bytes and packets that every client in the
+/// ECS has sent and received.
+///
+/// The counters are updated by the tasks that read and write packets, so
+/// they're shared atomics and cloning this is cheap. The byte counts are for
+/// the packets before they're compressed and encrypted, so they won't exactly
+/// match what goes over the network.
+///
+/// ```
+/// # fn example(client: &azalea_client::Client) {
+/// let stats = client.connection_stats();
+/// println!(
+///     "received {} bytes ({} packets/s)",
+///     stats.bytes_received(),
+///     stats.packets_received_per_second()
+/// );
+/// # }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct ConnectionStats(Arc<ConnectionStatsInner>);
+
+#[derive(Default)]
+struct ConnectionStatsInner {
+    bytes_sent: AtomicU64,
+    bytes_received: AtomicU64,
+    packets_sent: AtomicU64,
+    packets_received: AtomicU64,
+    rates: Mutex<PacketRates>,
+}
+
+#[derive(Default)]
+struct PacketRates {
+    last_sample: Option<(Instant, u64, u64)>,
+    packets_sent_per_second: f64,
+    packets_received_per_second: f64,
+}
+
+impl ConnectionStats {
+    pub fn bytes_sent(&self) -> u64 {
+        self.0.bytes_sent.load(Ordering::Relaxed)
+    }
+    pub fn bytes_received(&self) -> u64 {
+        self.0.bytes_received.load(Ordering::Relaxed)
+    }
+    pub fn packets_sent(&self) -> u64 {
+        self.0.packets_sent.load(Ordering::Relaxed)
+    }
+    pub fn packets_received(&self) -> u64 {
+        self.0.packets_received.load(Ordering::Relaxed)
+    }
+
+    /// The number of packets sent in the last second.
+    pub fn packets_sent_per_second(&self) -> f64 {
+        self.0.rates.lock().packets_sent_per_second
+    }
+    /// The number of packets received in the last second.
+    pub fn packets_received_per_second(&self) -> f64 {
+        self.0.rates.lock().packets_received_per_second
+    }
+
+    pub(crate) fn record_sent(&self, bytes: usize) {
+        self.0.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+        self.0.packets_sent.fetch_add(1, Ordering::Relaxed);
+    }
+    pub(crate) fn record_received(&self, bytes: usize) {
+        self.0
+            .bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        self.0.packets_received.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl std::fmt::Debug for ConnectionStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectionStats")
+            .field("bytes_sent", &self.bytes_sent())
+            .field("bytes_received", &self.bytes_received())
+            .field("packets_sent", &self.packets_sent())
+            .field("packets_received", &self.packets_received())
+            .field("packets_sent_per_second", &self.packets_sent_per_second())
+            .field(
+                "packets_received_per_second",
+                &self.packets_received_per_second(),
+            )
+            .finish()
+    }
+}
+
+fn update_packet_rates(stats: Res<ConnectionStats>) {
+    let now = Instant::now();
+    let packets_sent = stats.packets_sent();
+    let packets_received = stats.packets_received();
+
+    let mut rates = stats.0.rates.lock();
+    let Some((last_time, last_sent, last_received)) = rates.last_sample else {
+        rates.last_sample = Some((now, packets_sent, packets_received));
+        return;
+    };
+    let elapsed = now.duration_since(last_time);
+    if elapsed < RATE_INTERVAL {
+        return;
+    }
+    let elapsed = elapsed.as_secs_f64();
+    rates.packets_sent_per_second = (packets_sent - last_sent) as f64 / elapsed;
+    rates.packets_received_per_second = (packets_received - last_received) as f64 / elapsed;
+    rates.last_sample = Some((now, packets_sent, packets_received));
+}
+
+impl Client {
+    /// Get the [`ConnectionStats`] for the ECS this client is in. If you're
+    /// using a swarm, this includes the packets for every bot.
+    pub fn connection_stats(&self) -> ConnectionStats {
+        self.ecs.lock().resource::<ConnectionStats>().clone()
+    }
+
+    /// The size in bytes that packets have to be before they're compressed,
+    /// or `None` if the server didn't enable compression.
+    pub fn compression_threshold(&self) -> Option<u32> {
+        let mut ecs = self.ecs.lock();
+        self.raw_connection(&mut ecs).compression_threshold()
+    }
+
+    /// Whether our connection to the server is encrypted. This is only the case
+    /// for online-mode servers.
+    pub fn is_encrypted(&self) -> bool {
+        let mut ecs = self.ecs.lock();
+        self.raw_connection(&mut ecs).is_encrypted()
+    }
+}
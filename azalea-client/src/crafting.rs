@@ -0,0 +1,511 @@
+//! Crafting items in the 2x2 grid in our inventory or in a crafting table.
+
+use std::{collections::HashMap, ops::RangeInclusive};
+
+use azalea_core::resource_location::ResourceLocation;
+use azalea_inventory::{
+    operations::{ClickOperation, PickupClick, QuickMoveClick},
+    ItemSlot, ItemSlotData, Menu, Player,
+};
+use azalea_protocol::packets::game::clientbound_update_recipes_packet::{
+    ClientboundUpdateRecipesPacket, Ingredient, RecipeData,
+};
+use bevy_ecs::component::Component;
+use thiserror::Error;
+
+use crate::{
+    inventory::{ContainerClickEvent, InventoryComponent},
+    Client,
+};
+
+/// The recipes that the server told us about, which are used to know where
+/// ingredients go in the crafting grid. See [`Client::craft`].
+///
+/// This is every recipe on the server, not only the ones that are unlocked in
+/// our recipe book.
+#[derive(Component, Clone, Debug, Default)]
+pub struct KnownRecipes {
+    pub recipes: HashMap<ResourceLocation, RecipeData>,
+}
+impl From<&ClientboundUpdateRecipesPacket> for KnownRecipes {
+    fn from(packet: &ClientboundUpdateRecipesPacket) -> Self {
+        Self {
+            recipes: packet
+                .recipes
+                .iter()
+                .map(|recipe| (recipe.identifier.clone(), recipe.data.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// The most items that we put in each slot of the crafting grid, since that's
+/// how many fit in one stack.
+pub const MAX_CRAFTS_AT_ONCE: u32 = 64;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CraftError {
+    #[error("The server didn't send us a recipe called {0}")]
+    UnknownRecipe(ResourceLocation),
+    #[error("{0} can't be made in a crafting grid")]
+    UnsupportedRecipe(ResourceLocation),
+    #[error("The open container doesn't have a crafting grid")]
+    NoCraftingGrid,
+    #[error("The recipe needs a 3x3 crafting grid, so it can't be made in our inventory")]
+    GridTooSmall,
+    #[error("The crafting grid has items in it that don't fit in our inventory")]
+    GridNotEmpty,
+    /// We don't have enough of any of the items that can be used for one of the
+    /// ingredients.
+    #[error("We don't have enough of any of these items: {0:?}")]
+    MissingIngredient(Vec<azalea_registry::Item>),
+}
+
+impl Client {
+    /// Craft an item with the given recipe, using the crafting table that we
+    /// have open or the 2x2 grid in our inventory if we don't have a container
+    /// open.
+    ///
+    /// The ingredients are moved from our inventory into the grid (clearing
+    /// anything that was already in it) and the result is shift-clicked into
+    /// our inventory. If `times` is more than 1, that many of each ingredient
+    /// are put in the grid so the shift-click crafts all of them at once, up to
+    /// [`MAX_CRAFTS_AT_ONCE`].
+    ///
+    /// Ingredients that can be one of several items (like any kind of planks)
+    /// use whichever of them we have, and nothing is clicked if we don't have
+    /// enough of every ingredient.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # use azalea_core::resource_location::ResourceLocation;
+    /// # fn example(bot: &mut Client) {
+    /// // make 16 sticks
+    /// if let Err(err) = bot.craft(&ResourceLocation::new("stick"), 4) {
+    ///     println!("couldn't craft sticks: {err}");
+    /// }
+    /// # }
+    /// ```
+    pub fn craft(&mut self, recipe: &ResourceLocation, times: u32) -> Result<(), CraftError> {
+        if times == 0 {
+            return Ok(());
+        }
+        let mut ecs = self.ecs.lock();
+        let (window_id, clicks) = {
+            let (inventory, known_recipes) =
+                self.query::<(&InventoryComponent, Option<&KnownRecipes>)>(&mut ecs);
+            let recipe_data = known_recipes
+                .and_then(|known_recipes| known_recipes.recipes.get(recipe))
+                .ok_or_else(|| CraftError::UnknownRecipe(recipe.clone()))?;
+            let clicks = plan_craft(
+                inventory.menu(),
+                recipe,
+                recipe_data,
+                times.min(MAX_CRAFTS_AT_ONCE),
+            )?;
+            (inventory.id, clicks)
+        };
+
+        for operation in clicks {
+            ecs.send_event(ContainerClickEvent {
+                entity: self.entity,
+                window_id,
+                operation,
+            });
+        }
+        Ok(())
+    }
+
+    /// Get the ids of the crafting recipes that make the given item, which can
+    /// be passed to [`Client::craft`].
+    pub fn recipes_for(&self, item: azalea_registry::Item) -> Vec<ResourceLocation> {
+        let mut ecs = self.ecs.lock();
+        let Some(known_recipes) = self.query::<Option<&KnownRecipes>>(&mut ecs) else {
+            return Vec::new();
+        };
+        known_recipes
+            .recipes
+            .iter()
+            .filter(|(_, data)| match data {
+                RecipeData::CraftingShaped(recipe) => recipe.result.kind() == item,
+                RecipeData::CraftingShapeless(recipe) => recipe.result.kind() == item,
+                _ => false,
+            })
+            .map(|(identifier, _)| identifier.clone())
+            .collect()
+    }
+}
+
+/// The crafting grid in the given menu, as the width of the grid and the
+/// slots that it's made of.
+fn crafting_grid(menu: &Menu) -> Result<(usize, RangeInclusive<usize>), CraftError> {
+    match menu {
+        Menu::Player(_) => Ok((2, Player::CRAFT_SLOTS)),
+        Menu::Crafting { .. } => Ok((3, Menu::CRAFTING_GRID_SLOTS)),
+        _ => Err(CraftError::NoCraftingGrid),
+    }
+}
+
+/// Work out the clicks that put the ingredients for `times` crafts in the grid
+/// and then shift-click the result.
+///
+/// The server works out what the result is, so we can't know whether the last
+/// click actually crafted anything until it tells us.
+fn plan_craft(
+    menu: &Menu,
+    identifier: &ResourceLocation,
+    recipe: &RecipeData,
+    times: u32,
+) -> Result<Vec<ClickOperation>, CraftError> {
+    let (grid_width, grid_slots) = crafting_grid(menu)?;
+    let result_slot = *grid_slots.start() - 1;
+
+    // the grid slot that each ingredient goes in
+    let mut cells: Vec<(usize, &Ingredient)> = Vec::new();
+    match recipe {
+        RecipeData::CraftingShaped(recipe) => {
+            if recipe.width > grid_width || recipe.height > grid_width {
+                return Err(CraftError::GridTooSmall);
+            }
+            for y in 0..recipe.height {
+                for x in 0..recipe.width {
+                    let ingredient = &recipe.ingredients[y * recipe.width + x];
+                    cells.push((grid_slots.start() + y * grid_width + x, ingredient));
+                }
+            }
+        }
+        RecipeData::CraftingShapeless(recipe) => {
+            if recipe.ingredients.len() > grid_width * grid_width {
+                return Err(CraftError::GridTooSmall);
+            }
+            for (i, ingredient) in recipe.ingredients.iter().enumerate() {
+                cells.push((grid_slots.start() + i, ingredient));
+            }
+        }
+        _ => return Err(CraftError::UnsupportedRecipe(identifier.clone())),
+    }
+    // empty ingredients are the gaps in shaped recipes
+    cells.retain(|(_, ingredient)| ingredient.allowed.iter().any(ItemSlot::is_present));
+    // fill the ingredients with the fewest alternatives first, so we don't use
+    // up items that they need on ingredients that could've used something else
+    cells.sort_by_key(|(_, ingredient)| ingredient.allowed.len());
+
+    let mut menu = menu.clone();
+    let mut clicks = Vec::new();
+
+    // move whatever was already in the grid back into our inventory
+    for slot in grid_slots.clone() {
+        if menu.slot(slot).is_some_and(ItemSlot::is_present) {
+            clicks.push(QuickMoveClick::Left { slot: slot as u16 }.into());
+            // this is the same thing that simulate_click does for shift-clicks
+            loop {
+                let new_slot_item = menu.quick_move_stack(slot);
+                let slot_item = menu.slot(slot).unwrap();
+                if new_slot_item.is_empty() || slot_item != &new_slot_item {
+                    break;
+                }
+            }
+            if menu.slot(slot).is_some_and(ItemSlot::is_present) {
+                return Err(CraftError::GridNotEmpty);
+            }
+        }
+    }
+
+    let player_slots = menu.player_slots_range();
+    for (cell, ingredient) in cells {
+        let mut needed = times as i8;
+        while needed > 0 {
+            let cell_item = menu.slot(cell).cloned().unwrap_or_default();
+            let source = player_slots.clone().find(|&slot| {
+                let Some(ItemSlot::Present(item)) = menu.slot(slot) else {
+                    return false;
+                };
+                let allowed = ingredient
+                    .allowed
+                    .iter()
+                    .any(|allowed| allowed.kind() == item.kind);
+                // the items have to stack with what we already put in this cell
+                let stacks = match &cell_item {
+                    ItemSlot::Present(cell_item) => cell_item.is_same_item_and_nbt(item),
+                    ItemSlot::Empty => true,
+                };
+                allowed && stacks
+            });
+            let Some(source) = source else {
+                return Err(CraftError::MissingIngredient(
+                    ingredient.allowed.iter().map(ItemSlot::kind).collect(),
+                ));
+            };
+
+            let ItemSlot::Present(mut carried) = menu.slot(source).cloned().unwrap() else {
+                unreachable!("we only pick sources that have an item");
+            };
+            clicks.push(
+                PickupClick::Left {
+                    slot: Some(source as u16),
+                }
+                .into(),
+            );
+
+            let placing = needed.min(carried.count);
+            if placing == carried.count {
+                // put the whole stack in
+                clicks.push(
+                    PickupClick::Left {
+                        slot: Some(cell as u16),
+                    }
+                    .into(),
+                );
+                *menu.slot_mut(source).unwrap() = ItemSlot::Empty;
+            } else {
+                // right-clicking puts one item in at a time, then we put the rest back
+                for _ in 0..placing {
+                    clicks.push(
+                        PickupClick::Right {
+                            slot: Some(cell as u16),
+                        }
+                        .into(),
+                    );
+                }
+                clicks.push(
+                    PickupClick::Left {
+                        slot: Some(source as u16),
+                    }
+                    .into(),
+                );
+                carried.count -= placing;
+                *menu.slot_mut(source).unwrap() = ItemSlot::Present(carried.clone());
+            }
+
+            *menu.slot_mut(cell).unwrap() = ItemSlot::Present(ItemSlotData {
+                count: cell_item.count() + placing,
+                ..carried
+            });
+            needed -= placing;
+        }
+    }
+
+    // shift-clicking the result crafts as many times as the ingredients allow
+    clicks.push(
+        QuickMoveClick::Left {
+            slot: result_slot as u16,
+        }
+        .into(),
+    );
+    Ok(clicks)
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_protocol::packets::game::clientbound_update_recipes_packet::{
+        CraftingBookCategory, ShapedRecipe, ShapelessRecipe,
+    };
+    use azalea_registry::{Item, MenuKind};
+
+    use super::*;
+
+    fn stack(kind: Item, count: i8) -> ItemSlot {
+        ItemSlot::Present(ItemSlotData {
+            kind,
+            count,
+            nbt: Default::default(),
+        })
+    }
+
+    fn ingredient(allowed: &[Item]) -> Ingredient {
+        Ingredient {
+            allowed: allowed.iter().map(|&kind| stack(kind, 1)).collect(),
+        }
+    }
+
+    fn shaped(width: usize, height: usize, ingredients: Vec<Ingredient>) -> RecipeData {
+        RecipeData::CraftingShaped(ShapedRecipe {
+            width,
+            height,
+            group: String::new(),
+            category: CraftingBookCategory::Misc,
+            ingredients,
+            result: stack(Item::Stick, 4),
+            show_notification: false,
+        })
+    }
+
+    fn shapeless(ingredients: Vec<Ingredient>) -> RecipeData {
+        RecipeData::CraftingShapeless(ShapelessRecipe {
+            group: String::new(),
+            category: CraftingBookCategory::Misc,
+            ingredients,
+            result: stack(Item::CoarseDirt, 4),
+        })
+    }
+
+    /// Put items in the player's inventory slots of the menu, starting from
+    /// the first one.
+    fn inventory_with(container: Option<Menu>, items: &[ItemSlot]) -> InventoryComponent {
+        let mut inventory = InventoryComponent::default();
+        if let Some(container) = container {
+            inventory.id = 1;
+            inventory.container_menu = Some(container);
+        }
+        let first_player_slot = *inventory.menu().player_slots_range().start();
+        for (i, item) in items.iter().enumerate() {
+            *inventory
+                .menu_mut()
+                .slot_mut(first_player_slot + i)
+                .unwrap() = item.clone();
+        }
+        inventory
+    }
+
+    /// Do a pickup click like the server would.
+    /// [`InventoryComponent::simulate_click`] doesn't support clicking on slots
+    /// that have items in them yet, so this only handles the clicks that
+    /// [`plan_craft`] makes.
+    fn pickup(inventory: &mut InventoryComponent, operation: &ClickOperation) {
+        let mut carried = std::mem::take(&mut inventory.carried);
+        let (ClickOperation::Pickup(PickupClick::Left { slot: Some(slot) })
+        | ClickOperation::Pickup(PickupClick::Right { slot: Some(slot) })) = operation
+        else {
+            panic!("unexpected click {operation:?}");
+        };
+        let slot_item = inventory.menu_mut().slot_mut(*slot as usize).unwrap();
+        match (operation, &mut carried, &mut *slot_item) {
+            (ClickOperation::Pickup(PickupClick::Right { .. }), ItemSlot::Present(_), _) => {
+                let placed = carried.split(1);
+                match slot_item {
+                    ItemSlot::Present(slot_item) => slot_item.count += 1,
+                    ItemSlot::Empty => *slot_item = placed,
+                }
+            }
+            (_, ItemSlot::Present(carried_item), ItemSlot::Present(slot_item))
+                if carried_item.is_same_item_and_nbt(slot_item) =>
+            {
+                slot_item.count += carried_item.count;
+                carried = ItemSlot::Empty;
+            }
+            _ => std::mem::swap(&mut carried, slot_item),
+        }
+        inventory.carried = carried;
+    }
+
+    /// Plan the craft and click everything except the result, so we can check
+    /// what ended up in the grid.
+    fn fill_grid(
+        inventory: &mut InventoryComponent,
+        recipe: &RecipeData,
+        times: u32,
+    ) -> Result<(), CraftError> {
+        let clicks = plan_craft(
+            inventory.menu(),
+            &ResourceLocation::new("test"),
+            recipe,
+            times,
+        )?;
+        let (last, clicks) = clicks.split_last().unwrap();
+        let (_, grid_slots) = crafting_grid(inventory.menu())?;
+        assert!(matches!(
+            last,
+            ClickOperation::QuickMove(QuickMoveClick::Left { slot })
+                if *slot as usize == grid_slots.start() - 1
+        ));
+        for click in clicks {
+            pickup(inventory, click);
+        }
+        assert_eq!(inventory.carried, ItemSlot::Empty);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shaped() {
+        let mut inventory = inventory_with(None, &[stack(Item::BirchPlanks, 10)]);
+        let planks = ingredient(&[Item::OakPlanks, Item::BirchPlanks]);
+        let sticks = shaped(1, 2, vec![planks.clone(), planks]);
+
+        fill_grid(&mut inventory, &sticks, 4).unwrap();
+        let menu = inventory.menu();
+        // the second row of the 2x2 grid starts at the third slot
+        assert_eq!(menu.slot(1), Some(&stack(Item::BirchPlanks, 4)));
+        assert_eq!(menu.slot(2), Some(&ItemSlot::Empty));
+        assert_eq!(menu.slot(3), Some(&stack(Item::BirchPlanks, 4)));
+        assert_eq!(menu.slot(4), Some(&ItemSlot::Empty));
+        assert_eq!(
+            menu.slot(*menu.player_slots_range().start()),
+            Some(&stack(Item::BirchPlanks, 2))
+        );
+    }
+
+    #[test]
+    fn test_shaped_needs_crafting_table() {
+        let cobblestone = ingredient(&[Item::Cobblestone]);
+        let recipe = shaped(3, 3, vec![cobblestone; 9]);
+
+        let inventory = inventory_with(None, &[stack(Item::Cobblestone, 9)]);
+        assert_eq!(
+            plan_craft(inventory.menu(), &ResourceLocation::new("test"), &recipe, 1).unwrap_err(),
+            CraftError::GridTooSmall
+        );
+
+        let mut inventory = inventory_with(
+            Some(Menu::from_kind(MenuKind::Crafting)),
+            &[stack(Item::Cobblestone, 9)],
+        );
+        fill_grid(&mut inventory, &recipe, 1).unwrap();
+        for slot in Menu::CRAFTING_GRID_SLOTS {
+            assert_eq!(
+                inventory.menu().slot(slot),
+                Some(&stack(Item::Cobblestone, 1))
+            );
+        }
+    }
+
+    #[test]
+    fn test_shapeless() {
+        let mut inventory = inventory_with(None, &[stack(Item::Dirt, 5), stack(Item::Gravel, 5)]);
+        let recipe = shapeless(vec![ingredient(&[Item::Dirt]), ingredient(&[Item::Gravel])]);
+
+        fill_grid(&mut inventory, &recipe, 2).unwrap();
+        let menu = inventory.menu();
+        assert_eq!(menu.slot(1), Some(&stack(Item::Dirt, 2)));
+        assert_eq!(menu.slot(2), Some(&stack(Item::Gravel, 2)));
+        assert_eq!(menu.slot(3), Some(&ItemSlot::Empty));
+    }
+
+    #[test]
+    fn test_ingredient_alternatives() {
+        // the birch has to be saved for the ingredient that can only be birch
+        let mut inventory = inventory_with(
+            None,
+            &[stack(Item::BirchPlanks, 1), stack(Item::OakPlanks, 1)],
+        );
+        let recipe = shapeless(vec![
+            ingredient(&[Item::OakPlanks, Item::BirchPlanks]),
+            ingredient(&[Item::BirchPlanks]),
+        ]);
+
+        fill_grid(&mut inventory, &recipe, 1).unwrap();
+        let menu = inventory.menu();
+        assert_eq!(menu.slot(1), Some(&stack(Item::OakPlanks, 1)));
+        assert_eq!(menu.slot(2), Some(&stack(Item::BirchPlanks, 1)));
+    }
+
+    #[test]
+    fn test_missing_ingredient() {
+        let recipe = shapeless(vec![
+            ingredient(&[Item::Dirt]),
+            ingredient(&[Item::Diamond, Item::Emerald]),
+        ]);
+
+        let inventory = inventory_with(None, &[stack(Item::Dirt, 5)]);
+        assert_eq!(
+            plan_craft(inventory.menu(), &ResourceLocation::new("test"), &recipe, 1).unwrap_err(),
+            CraftError::MissingIngredient(vec![Item::Diamond, Item::Emerald])
+        );
+
+        // we have some, but not enough for every craft
+        let inventory = inventory_with(None, &[stack(Item::Dirt, 5), stack(Item::Emerald, 2)]);
+        assert_eq!(
+            plan_craft(inventory.menu(), &ResourceLocation::new("test"), &recipe, 3).unwrap_err(),
+            CraftError::MissingIngredient(vec![Item::Diamond, Item::Emerald])
+        );
+    }
+}
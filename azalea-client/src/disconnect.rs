@@ -1,31 +1,49 @@
 //! Disconnect a client from the server.
 
+use std::time::{Duration, Instant};
+
+use azalea_chat::FormattedText;
+use azalea_protocol::packets::ConnectionProtocol;
 use bevy_app::{App, Plugin, PostUpdate};
 use bevy_ecs::{
     component::Component,
     entity::Entity,
-    event::{EventReader, EventWriter},
+    event::{EventReader, EventWriter, Events},
     prelude::Event,
     query::Changed,
     schedule::IntoSystemConfigs,
-    system::{Commands, Query},
+    system::{Commands, Query, Res, ResMut, Resource},
 };
 use derive_more::Deref;
+use log::{error, warn};
+use tokio::sync::oneshot;
 
-use crate::{client::JoinedClientBundle, raw_connection::RawConnection};
+use crate::{
+    client::JoinedClientBundle,
+    packet_handling::{configuration, game},
+    packet_rate_limit::PacketRateLimiter,
+    raw_connection::RawConnection,
+    Client,
+};
 
 pub struct DisconnectPlugin;
 impl Plugin for DisconnectPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<DisconnectEvent>().add_systems(
-            PostUpdate,
-            (
-                update_read_packets_task_running_component,
-                disconnect_on_connection_dead,
-                remove_components_from_disconnected_players,
-            )
-                .chain(),
-        );
+        app.add_event::<DisconnectEvent>()
+            .add_event::<FlushAndCloseEvent>()
+            .init_resource::<KeepAliveWatchdog>()
+            .add_systems(
+                PostUpdate,
+                (
+                    handle_flush_and_close_event,
+                    update_read_packets_task_running_component,
+                    update_last_packet_received,
+                    disconnect_on_connection_dead,
+                    disconnect_on_keep_alive_timeout,
+                    remove_components_from_disconnected_players,
+                )
+                    .chain(),
+            );
     }
 }
 
@@ -33,6 +51,10 @@ impl Plugin for DisconnectPlugin {
 #[derive(Event)]
 pub struct DisconnectEvent {
     pub entity: Entity,
+    /// Why we were disconnected, if we know. This is the message that the
+    /// server sent us, or "Timed out" if the [`KeepAliveWatchdog`] noticed
+    /// that the server stopped talking to us.
+    pub reason: Option<FormattedText>,
 }
 
 /// System that removes the [`JoinedClientBundle`] from the entity when it
@@ -41,11 +63,88 @@ pub fn remove_components_from_disconnected_players(
     mut commands: Commands,
     mut events: EventReader<DisconnectEvent>,
 ) {
-    for DisconnectEvent { entity } in events.iter() {
+    for DisconnectEvent { entity, .. } in events.iter() {
         commands.entity(*entity).remove::<JoinedClientBundle>();
     }
 }
 
+/// An event that makes a client close its connection once every packet that
+/// was queued before it has been written.
+///
+/// This is handled in [`PostUpdate`], so packets sent by systems in `Update`
+/// (like chat messages) are guaranteed to be sent first. Packets that are
+/// still waiting in our [`PacketRateLimiter`] are sent immediately. You
+/// probably want to use [`Client::disconnect_graceful`] instead of sending
+/// this yourself.
+#[derive(Event)]
+pub struct FlushAndCloseEvent {
+    pub entity: Entity,
+    /// Notified when the connection has been shut down.
+    pub closed_sender: oneshot::Sender<()>,
+}
+
+pub fn handle_flush_and_close_event(
+    mut events: ResMut<Events<FlushAndCloseEvent>>,
+    mut query: Query<(&RawConnection, Option<&mut PacketRateLimiter>)>,
+) {
+    // we drain the events so we can take ownership of the senders
+    for FlushAndCloseEvent {
+        entity,
+        closed_sender,
+    } in events.drain()
+    {
+        // if the client doesn't have a connection then the sender gets dropped, which
+        // tells the caller that there's nothing to close
+        let Ok((raw_connection, rate_limiter)) = query.get_mut(entity) else {
+            continue;
+        };
+        // the rate limiter won't get another chance to send these
+        if let Some(mut rate_limiter) = rate_limiter {
+            let queued = rate_limiter.take_queued();
+            if raw_connection.state() == ConnectionProtocol::Game {
+                for packet in queued {
+                    if let Err(e) = raw_connection.write_packet(packet) {
+                        error!("Failed to send packet: {e}");
+                    }
+                }
+            }
+        }
+        raw_connection.close_after_flush(closed_sender);
+    }
+}
+
+impl Client {
+    /// Disconnect from the server, but only after every packet we've already
+    /// sent (like a last chat message) has actually been written.
+    ///
+    /// Minecraft doesn't have a way for clients to tell the server why they're
+    /// leaving, so `reason` is only used for our own [`DisconnectEvent`].
+    ///
+    /// ```no_run
+    /// # async fn example(bot: azalea_client::Client) {
+    /// bot.chat("goodbye!");
+    /// bot.disconnect_graceful(None).await;
+    /// # }
+    /// ```
+    pub async fn disconnect_graceful(&self, reason: Option<FormattedText>) {
+        let (closed_sender, closed_receiver) = oneshot::channel();
+        self.ecs.lock().send_event(FlushAndCloseEvent {
+            entity: self.entity,
+            closed_sender,
+        });
+        // make sure the event gets handled soon instead of waiting for the next tick
+        let _ = self.run_schedule_sender.send(());
+
+        // this errors if we were already disconnected, which is fine
+        let _ = closed_receiver.await;
+
+        self.ecs.lock().send_event(DisconnectEvent {
+            entity: self.entity,
+            reason,
+        });
+    }
+}
+
 #[derive(Component, Clone, Copy, Debug, Deref)]
 pub struct IsConnectionAlive(bool);
 
@@ -64,7 +163,84 @@ fn disconnect_on_connection_dead(
 ) {
     for (entity, &is_connection_alive) in &query {
         if !*is_connection_alive {
-            disconnect_events.send(DisconnectEvent { entity });
+            disconnect_events.send(DisconnectEvent {
+                entity,
+                reason: None,
+            });
+        }
+    }
+}
+
+/// A resource that makes clients disconnect if the server hasn't sent them any
+/// packets in a while.
+///
+/// The server sends a keep-alive packet every 15 seconds, so if we haven't
+/// received anything for much longer than that then the connection has
+/// probably stalled, even if the TCP connection hasn't been closed yet.
+///
+/// This is disabled by default. To enable it, insert the resource with a
+/// timeout:
+///
+/// ```
+/// # use std::time::Duration;
+/// # use azalea_client::disconnect::KeepAliveWatchdog;
+/// # fn example(app: &mut bevy_app::App) {
+/// app.insert_resource(KeepAliveWatchdog {
+///     timeout: Some(Duration::from_secs(30)),
+/// });
+/// # }
+/// ```
+#[derive(Resource, Clone, Debug, Default)]
+pub struct KeepAliveWatchdog {
+    /// How long we can go without receiving a packet before we disconnect, or
+    /// `None` to never time out.
+    pub timeout: Option<Duration>,
+}
+
+/// The last time that a client received a packet from the server. This is
+/// used by the [`KeepAliveWatchdog`].
+#[derive(Component, Clone, Copy, Debug, Deref)]
+pub struct LastPacketReceived(pub Instant);
+
+fn update_last_packet_received(
+    mut commands: Commands,
+    mut game_packet_events: EventReader<game::PacketEvent>,
+    mut configuration_packet_events: EventReader<configuration::PacketEvent>,
+) {
+    let now = Instant::now();
+    for entity in game_packet_events
+        .iter()
+        .map(|e| e.entity)
+        .chain(configuration_packet_events.iter().map(|e| e.entity))
+    {
+        commands.entity(entity).insert(LastPacketReceived(now));
+    }
+}
+
+fn disconnect_on_keep_alive_timeout(
+    mut commands: Commands,
+    watchdog: Res<KeepAliveWatchdog>,
+    query: Query<(Entity, &LastPacketReceived, Option<&RawConnection>)>,
+    mut disconnect_events: EventWriter<DisconnectEvent>,
+) {
+    let Some(timeout) = watchdog.timeout else {
+        return;
+    };
+    for (entity, last_packet_received, raw_connection) in &query {
+        if last_packet_received.elapsed() < timeout {
+            continue;
+        }
+        warn!("Haven't received a packet in {timeout:?}, disconnecting");
+        // the connection has stalled, so waiting for our packets to be flushed could
+        // take forever
+        if let Some(raw_connection) = raw_connection {
+            raw_connection.close();
         }
+        // so we don't send the event again every update
+        commands.entity(entity).remove::<LastPacketReceived>();
+        disconnect_events.send(DisconnectEvent {
+            entity,
+            reason: Some(FormattedText::from("Timed out")),
+        });
     }
 }
@@ -0,0 +1,337 @@
+//! Eating food from the hotbar.
+
+use azalea_core::{direction::Direction, position::BlockPos};
+use azalea_entity::metadata::Health;
+use azalea_physics::PhysicsSet;
+use azalea_protocol::packets::game::{
+    serverbound_interact_packet::InteractionHand,
+    serverbound_player_action_packet::{self, ServerboundPlayerActionPacket},
+    serverbound_use_item_packet::ServerboundUseItemPacket,
+};
+use azalea_registry::Item;
+use bevy_app::{App, FixedUpdate, Plugin, Update};
+use bevy_ecs::prelude::*;
+use log::warn;
+
+use crate::{
+    interact::CurrentSequenceNumber,
+    inventory::{select_hotbar_slot, InventoryComponent, InventorySet},
+    local_player::{Hunger, SendPacketEvent},
+    movement::{PhysicsState, WalkDirection},
+    Client,
+};
+
+/// A plugin that lets clients eat food from their hotbar.
+pub struct EatPlugin;
+impl Plugin for EatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StartEatingEvent>()
+            .add_event::<StopEatingEvent>()
+            .add_systems(FixedUpdate, tick_eating.before(PhysicsSet))
+            .add_systems(
+                Update,
+                (
+                    interrupt_eating,
+                    handle_start_eating_event,
+                    handle_stop_eating_event,
+                )
+                    .chain()
+                    .after(InventorySet),
+            );
+    }
+}
+
+/// The number of ticks it takes to eat most food.
+pub const EAT_DURATION_TICKS: u32 = 32;
+/// The number of ticks it takes to eat food that's [`FoodProperties::fast`],
+/// like dried kelp.
+pub const FAST_EAT_DURATION_TICKS: u32 = 16;
+/// How many extra ticks we wait for the server to tell us that we finished
+/// eating before we give up and assume we're done.
+const EXTRA_EAT_TICKS: u32 = 5;
+
+/// The vanilla properties of an item that can be eaten.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FoodProperties {
+    /// How many points of the hunger bar this food restores.
+    pub nutrition: u32,
+    pub saturation_modifier: f32,
+    /// Whether this food can be eaten even if our hunger bar is full.
+    pub always_edible: bool,
+    /// Whether this food takes half as long to eat as normal.
+    pub fast: bool,
+    /// Whether eating this food can give us a negative effect, like poison or
+    /// being teleported.
+    pub harmful: bool,
+}
+
+impl FoodProperties {
+    const fn new(nutrition: u32, saturation_modifier: f32) -> Self {
+        Self {
+            nutrition,
+            saturation_modifier,
+            always_edible: false,
+            fast: false,
+            harmful: false,
+        }
+    }
+    const fn always_edible(mut self) -> Self {
+        self.always_edible = true;
+        self
+    }
+    const fn fast(mut self) -> Self {
+        self.fast = true;
+        self
+    }
+    const fn harmful(mut self) -> Self {
+        self.harmful = true;
+        self
+    }
+
+    /// How much saturation this food gives us when we eat it.
+    pub fn saturation(&self) -> f32 {
+        self.nutrition as f32 * self.saturation_modifier * 2.
+    }
+
+    /// How many ticks it takes to eat this food.
+    pub fn eat_duration(&self) -> u32 {
+        if self.fast {
+            FAST_EAT_DURATION_TICKS
+        } else {
+            EAT_DURATION_TICKS
+        }
+    }
+}
+
+/// Get the [`FoodProperties`] of an item, or `None` if it can't be eaten.
+///
+/// Drinks like honey bottles and potions aren't included.
+pub fn food_properties(item: Item) -> Option<FoodProperties> {
+    let properties = match item {
+        Item::Apple => FoodProperties::new(4, 0.3),
+        Item::BakedPotato => FoodProperties::new(5, 0.6),
+        Item::Beef => FoodProperties::new(3, 0.3),
+        Item::Beetroot => FoodProperties::new(1, 0.6),
+        Item::BeetrootSoup => FoodProperties::new(6, 0.6),
+        Item::Bread => FoodProperties::new(5, 0.6),
+        Item::Carrot => FoodProperties::new(3, 0.6),
+        Item::Chicken => FoodProperties::new(2, 0.3).harmful(),
+        Item::ChorusFruit => FoodProperties::new(4, 0.3).always_edible().harmful(),
+        Item::Cod => FoodProperties::new(2, 0.1),
+        Item::CookedBeef => FoodProperties::new(8, 0.8),
+        Item::CookedChicken => FoodProperties::new(6, 0.6),
+        Item::CookedCod => FoodProperties::new(5, 0.6),
+        Item::CookedMutton => FoodProperties::new(6, 0.8),
+        Item::CookedPorkchop => FoodProperties::new(8, 0.8),
+        Item::CookedRabbit => FoodProperties::new(5, 0.6),
+        Item::CookedSalmon => FoodProperties::new(6, 0.8),
+        Item::Cookie => FoodProperties::new(2, 0.1),
+        Item::DriedKelp => FoodProperties::new(1, 0.3).fast(),
+        Item::EnchantedGoldenApple => FoodProperties::new(4, 1.2).always_edible(),
+        Item::GoldenApple => FoodProperties::new(4, 1.2).always_edible(),
+        Item::GoldenCarrot => FoodProperties::new(6, 1.2),
+        Item::GlowBerries => FoodProperties::new(2, 0.1),
+        Item::MelonSlice => FoodProperties::new(2, 0.3),
+        Item::MushroomStew => FoodProperties::new(6, 0.6),
+        Item::Mutton => FoodProperties::new(2, 0.3),
+        Item::PoisonousPotato => FoodProperties::new(2, 0.3).harmful(),
+        Item::Porkchop => FoodProperties::new(3, 0.3),
+        Item::Potato => FoodProperties::new(1, 0.3),
+        Item::Pufferfish => FoodProperties::new(1, 0.1).harmful(),
+        Item::PumpkinPie => FoodProperties::new(8, 0.3),
+        Item::Rabbit => FoodProperties::new(3, 0.3),
+        Item::RabbitStew => FoodProperties::new(10, 0.6),
+        Item::RottenFlesh => FoodProperties::new(4, 0.1).harmful(),
+        Item::Salmon => FoodProperties::new(2, 0.1),
+        Item::SpiderEye => FoodProperties::new(2, 0.8).harmful(),
+        Item::SuspiciousStew => FoodProperties::new(6, 0.6).always_edible().harmful(),
+        Item::SweetBerries => FoodProperties::new(2, 0.1),
+        Item::TropicalFish => FoodProperties::new(1, 0.1),
+        _ => return None,
+    };
+    Some(properties)
+}
+
+impl Client {
+    /// Start eating the food in the given hotbar slot (0-8). This will switch
+    /// to that slot if it's not already selected.
+    ///
+    /// You should probably use `bot.eat(slot).await` from the `azalea` crate
+    /// instead, which waits until we're done eating.
+    pub fn start_eating(&mut self, slot: u8) {
+        self.ecs.lock().send_event(StartEatingEvent {
+            entity: self.entity,
+            slot,
+        });
+    }
+
+    /// Stop eating, if we're currently eating something.
+    pub fn stop_eating(&mut self) {
+        self.ecs.lock().send_event(StopEatingEvent {
+            entity: self.entity,
+        });
+    }
+
+    /// Whether we're currently in the middle of eating something.
+    pub fn is_eating(&self) -> bool {
+        self.get_component::<Eating>().is_some()
+    }
+}
+
+/// A component that's present while we're eating something.
+///
+/// It gets removed when the server tells us we finished eating, when we stop
+/// eating with a [`StopEatingEvent`], or a few ticks after the food should've
+/// been eaten.
+#[derive(Component, Clone, Debug)]
+pub struct Eating {
+    /// The hotbar slot of the food we're eating.
+    pub slot: u8,
+    pub item: Item,
+    /// The number of ticks since we started eating.
+    pub ticks: u32,
+    /// The number of ticks it should take to eat the food.
+    pub duration: u32,
+    /// Our health as of the last tick, so we can tell when we take damage.
+    last_health: f32,
+}
+
+/// Start eating the food in a hotbar slot. This does nothing if the item
+/// can't be eaten or we're not hungry enough to eat it.
+#[derive(Event)]
+pub struct StartEatingEvent {
+    pub entity: Entity,
+    /// The hotbar slot, from 0 to 8.
+    pub slot: u8,
+}
+
+#[allow(clippy::type_complexity)]
+pub fn handle_start_eating_event(
+    mut commands: Commands,
+    mut events: EventReader<StartEatingEvent>,
+    mut query: Query<
+        (
+            &mut InventoryComponent,
+            &mut CurrentSequenceNumber,
+            &Hunger,
+            &Health,
+        ),
+        Without<Eating>,
+    >,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.iter() {
+        let Ok((mut inventory, mut sequence_number, hunger, health)) = query.get_mut(event.entity)
+        else {
+            continue;
+        };
+
+        let hotbar_slots =
+            &inventory.inventory_menu.slots()[inventory.inventory_menu.hotbar_slots_range()];
+        let Some(item_slot) = hotbar_slots.get(event.slot as usize) else {
+            warn!("Tried to eat from invalid hotbar slot {}", event.slot);
+            continue;
+        };
+        let item = item_slot.kind();
+        let Some(food) = food_properties(item) else {
+            warn!("Tried to eat {item:?}, which isn't food");
+            continue;
+        };
+        if hunger.food >= 20 && !food.always_edible {
+            // not hungry
+            continue;
+        }
+
+        select_hotbar_slot(
+            event.entity,
+            &mut inventory,
+            event.slot,
+            &mut send_packet_events,
+        );
+
+        *sequence_number += 1;
+        send_packet_events.send(SendPacketEvent {
+            entity: event.entity,
+            packet: ServerboundUseItemPacket {
+                hand: InteractionHand::MainHand,
+                sequence: **sequence_number,
+            }
+            .get(),
+        });
+
+        commands.entity(event.entity).insert(Eating {
+            slot: event.slot,
+            item,
+            ticks: 0,
+            duration: food.eat_duration(),
+            last_health: **health,
+        });
+    }
+}
+
+/// Stop eating whatever we're eating. This is like letting go of right click.
+#[derive(Event)]
+pub struct StopEatingEvent {
+    pub entity: Entity,
+}
+
+pub fn handle_stop_eating_event(
+    mut commands: Commands,
+    mut events: EventReader<StopEatingEvent>,
+    query: Query<(), With<Eating>>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.iter() {
+        if query.get(event.entity).is_err() {
+            continue;
+        }
+
+        send_packet_events.send(SendPacketEvent {
+            entity: event.entity,
+            packet: ServerboundPlayerActionPacket {
+                action: serverbound_player_action_packet::Action::ReleaseUseItem,
+                pos: BlockPos::default(),
+                direction: Direction::Down,
+                sequence: 0,
+            }
+            .get(),
+        });
+        commands.entity(event.entity).remove::<Eating>();
+    }
+}
+
+/// Stop eating if we take damage, start moving, or stop holding the food.
+pub fn interrupt_eating(
+    mut query: Query<(
+        Entity,
+        &mut Eating,
+        &Health,
+        &PhysicsState,
+        &InventoryComponent,
+    )>,
+    mut stop_eating_events: EventWriter<StopEatingEvent>,
+) {
+    for (entity, mut eating, health, physics_state, inventory) in &mut query {
+        let took_damage = **health < eating.last_health;
+        eating.last_health = **health;
+
+        let is_moving = !matches!(physics_state.move_direction, WalkDirection::None);
+        let switched_item = inventory.selected_hotbar_slot != eating.slot
+            || inventory.held_item().kind() != eating.item;
+
+        if took_damage || is_moving || switched_item {
+            stop_eating_events.send(StopEatingEvent { entity });
+        }
+    }
+}
+
+/// Count how long we've been eating for, and stop if the server never told us
+/// that we finished.
+pub fn tick_eating(mut commands: Commands, mut query: Query<(Entity, &mut Eating)>) {
+    for (entity, mut eating) in &mut query {
+        eating.ticks += 1;
+        if eating.ticks > eating.duration + EXTRA_EAT_TICKS {
+            commands.entity(entity).remove::<Eating>();
+        }
+    }
+}
@@ -0,0 +1,34 @@
+//! Keep track of the status effects (like speed or poison) that entities have.
+
+pub use azalea_entity::effects::{ActiveEffects, MobEffectData};
+use azalea_physics::PhysicsSet;
+use bevy_app::{App, FixedUpdate, Plugin};
+use bevy_ecs::prelude::*;
+
+use crate::Client;
+
+/// A plugin that counts down the durations of [`ActiveEffects`] every tick.
+///
+/// The effects themselves are updated from the `UpdateMobEffect` and
+/// `RemoveMobEffect` packets.
+pub struct EffectsPlugin;
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, tick_effects.before(PhysicsSet));
+    }
+}
+
+pub fn tick_effects(mut query: Query<&mut ActiveEffects>) {
+    for mut active_effects in query.iter_mut() {
+        active_effects.tick();
+    }
+}
+
+impl Client {
+    /// Get the status effects that we currently have, like speed or poison.
+    ///
+    /// This is a shortcut for `bot.component::<ActiveEffects>().clone()`.
+    pub fn effects(&self) -> ActiveEffects {
+        self.component::<ActiveEffects>()
+    }
+}
@@ -1,14 +1,18 @@
 use std::sync::Arc;
 
+use azalea_core::position::Vec3;
+use azalea_entity::{indexing::EntityUuidIndex, metadata::Player, EntityKind, LoadedBy, Position};
+use azalea_world::{InstanceName, MinecraftEntityId};
 use bevy_ecs::{
     component::Component,
     entity::Entity,
-    query::{ROQueryItem, ReadOnlyWorldQuery, WorldQuery},
+    query::{ROQueryItem, ReadOnlyWorldQuery, With, WorldQuery},
     world::World,
 };
 use parking_lot::Mutex;
+use uuid::Uuid;
 
-use crate::Client;
+use crate::{local_player::TabList, Client};
 
 impl Client {
     /// A convenience function for getting components of our player's entity.
@@ -81,6 +85,134 @@ impl Client {
         let components = q.get(&ecs, entity).ok();
         components.cloned()
     }
+
+    /// Get the nearest entity whose kind matches the given predicate, not
+    /// including our own player.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # fn example(mut bot: Client) {
+    /// let nearest_zombie =
+    ///     bot.nearest_entity_by(|kind| *kind == azalea_registry::EntityKind::Zombie);
+    /// # }
+    /// ```
+    pub fn nearest_entity_by(
+        &self,
+        predicate: impl Fn(&azalea_registry::EntityKind) -> bool,
+    ) -> Option<Entity> {
+        self.entities_by_distance(f64::INFINITY, predicate)
+            .into_iter()
+            .next()
+    }
+
+    /// Get every entity within the given number of blocks of our player, sorted
+    /// from nearest to farthest. Our own player isn't included.
+    pub fn entities_within(&self, radius: f64) -> Vec<Entity> {
+        self.entities_by_distance(radius, |_| true)
+    }
+
+    /// Get the position of the player with the given UUID.
+    ///
+    /// Players that are in the tab list but too far away for the server to
+    /// send us their entity don't have a position, so this returns `None` for
+    /// them.
+    pub fn player_position(&self, uuid: Uuid) -> Option<Vec3> {
+        let mut ecs = self.ecs.lock();
+        let entity = ecs.resource::<EntityUuidIndex>().get(&uuid)?;
+        self.loaded_player_position(&mut ecs, entity)
+    }
+
+    /// Get the UUID of the player in the tab list with the given username,
+    /// which can be passed to [`Self::player_position`]. Usernames aren't case
+    /// sensitive.
+    pub fn player_uuid(&self, username: &str) -> Option<Uuid> {
+        let mut ecs = self.ecs.lock();
+        let tab_list = self.query::<&TabList>(&mut ecs);
+        tab_list
+            .values()
+            .find(|player_info| player_info.profile.name.eq_ignore_ascii_case(username))
+            .map(|player_info| player_info.uuid)
+    }
+
+    /// Get the closest player to us that's in the tab list, and how many
+    /// blocks away they are. Our own player isn't included.
+    ///
+    /// Player entities that aren't in the tab list (like the NPCs that some
+    /// servers have) are ignored.
+    pub fn closest_player(&self) -> Option<(Entity, f64)> {
+        let mut ecs = self.ecs.lock();
+        let (&our_position, tab_list) = self.query::<(&Position, &TabList)>(&mut ecs);
+        let uuids = tab_list.keys().copied().collect::<Vec<_>>();
+
+        let entity_uuid_index = ecs.resource::<EntityUuidIndex>();
+        let entities = uuids
+            .iter()
+            .filter_map(|uuid| entity_uuid_index.get(uuid))
+            .filter(|entity| *entity != self.entity)
+            .collect::<Vec<_>>();
+
+        entities
+            .into_iter()
+            .filter_map(|entity| {
+                let position = self.loaded_player_position(&mut ecs, entity)?;
+                Some((entity, our_position.distance_to(&position)))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    /// Get the position of the player entity if it's in the same world as us
+    /// and it isn't about to be despawned.
+    fn loaded_player_position(&self, ecs: &mut World, entity: Entity) -> Option<Vec3> {
+        let our_instance_name = ecs
+            .query::<&InstanceName>()
+            .get(ecs, self.entity)
+            .ok()?
+            .clone();
+        let mut query = ecs.query_filtered::<(&Position, &InstanceName, &LoadedBy), With<Player>>();
+        let (position, instance_name, loaded_by) = query.get(ecs, entity).ok()?;
+        if *instance_name != our_instance_name || loaded_by.is_empty() {
+            return None;
+        }
+        Some(**position)
+    }
+
+    /// Get all the entities in our world that are within `max_distance` blocks
+    /// and match the predicate, sorted by their distance from us.
+    ///
+    /// Entities that are about to be despawned (because no clients have them
+    /// loaded anymore) are skipped.
+    pub(crate) fn entities_by_distance(
+        &self,
+        max_distance: f64,
+        predicate: impl Fn(&azalea_registry::EntityKind) -> bool,
+    ) -> Vec<Entity> {
+        let mut ecs = self.ecs.lock();
+
+        let mut our_query = ecs.query::<(&Position, &InstanceName)>();
+        let Ok((&our_position, our_instance_name)) = our_query.get(&ecs, self.entity) else {
+            return Vec::new();
+        };
+        let our_instance_name = our_instance_name.clone();
+
+        let mut query = ecs.query_filtered::<
+            (Entity, &Position, &InstanceName, &EntityKind, &LoadedBy),
+            With<MinecraftEntityId>,
+        >();
+        let mut entities = query
+            .iter(&ecs)
+            .filter(|(entity, _, instance_name, kind, loaded_by)| {
+                *entity != self.entity
+                    && **instance_name == our_instance_name
+                    && !loaded_by.is_empty()
+                    && predicate(kind)
+            })
+            .map(|(entity, position, ..)| (entity, our_position.distance_to(position)))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .collect::<Vec<_>>();
+        entities.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        entities.into_iter().map(|(entity, _)| entity).collect()
+    }
 }
 
 pub trait EntityPredicate<Q: ReadOnlyWorldQuery, Filter: ReadOnlyWorldQuery> {
@@ -1,10 +1,14 @@
 //! Defines the [`Event`] enum and makes those events trigger when they're sent
 //! in the ECS.
 
-use std::sync::Arc;
+use std::{future::Future, sync::Arc, time::Duration};
 
-use azalea_protocol::packets::game::{
-    clientbound_player_combat_kill_packet::ClientboundPlayerCombatKillPacket, ClientboundGamePacket,
+use azalea_protocol::packets::{
+    game::{
+        clientbound_player_combat_kill_packet::ClientboundPlayerCombatKillPacket,
+        ClientboundGamePacket,
+    },
+    ConnectionProtocol,
 };
 use azalea_world::{InstanceName, MinecraftEntityId};
 use bevy_app::{App, FixedUpdate, Plugin, PreUpdate, Update};
@@ -16,15 +20,21 @@ use bevy_ecs::{
     system::Query,
 };
 use derive_more::{Deref, DerefMut};
-use tokio::sync::mpsc;
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc,
+};
 
 use crate::{
     chat::{ChatPacket, ChatReceivedEvent},
-    packet_handling::game::{
-        AddPlayerEvent, DeathEvent, KeepAliveEvent, PacketEvent, RemovePlayerEvent,
-        UpdatePlayerEvent,
+    packet_handling::{
+        game::{
+            AddPlayerEvent, DeathEvent, KeepAliveEvent, PacketEvent, RemovePlayerEvent,
+            UpdatePlayerEvent,
+        },
+        ProtocolStateChangedEvent,
     },
-    PlayerInfo,
+    Client, PlayerInfo,
 };
 
 // (for contributors):
@@ -93,15 +103,123 @@ pub enum Event {
     Death(Option<Arc<ClientboundPlayerCombatKillPacket>>),
     /// A `KeepAlive` packet was sent by the server.
     KeepAlive(u64),
+    /// Our connection switched to a different protocol state, like from
+    /// configuration to game. This also happens when the server sends us back
+    /// to the configuration state.
+    ///
+    /// If you need to do something before any packets in the new state are
+    /// handled, use a system that reads the
+    /// [`ProtocolStateChangedEvent`] instead.
+    ProtocolStateChanged {
+        from: ConnectionProtocol,
+        to: ConnectionProtocol,
+    },
 }
 
 /// A component that contains an event sender for events that are only
 /// received by local players. The receiver for this is returned by
 /// [`Client::start_client`].
 ///
-/// [`Client::start_client`]: crate::Client::start_client
+/// There's only one receiver, so every event is also sent to a
+/// [`broadcast`] channel that anything else can [`Self::subscribe`] to
+/// without taking events away from it.
+///
+/// This derefs to [`Self::sender`].
 #[derive(Component, Deref, DerefMut)]
-pub struct LocalPlayerEvents(pub mpsc::UnboundedSender<Event>);
+pub struct LocalPlayerEvents {
+    #[deref]
+    #[deref_mut]
+    pub sender: mpsc::UnboundedSender<Event>,
+    pub broadcast: broadcast::Sender<Event>,
+}
+
+impl LocalPlayerEvents {
+    /// The number of events that the broadcast channel keeps for subscribers
+    /// that haven't received them yet. This is big since every packet is an
+    /// event.
+    pub const BROADCAST_CAPACITY: usize = 512;
+
+    pub fn new(sender: mpsc::UnboundedSender<Event>) -> Self {
+        Self {
+            sender,
+            broadcast: broadcast::channel(Self::BROADCAST_CAPACITY).0,
+        }
+    }
+
+    /// Send an event to the receiver and to everything subscribed to the
+    /// broadcast channel.
+    pub fn send(&self, event: Event) -> Result<(), Box<mpsc::error::SendError<Event>>> {
+        // don't clone every packet if nothing is listening
+        if self.broadcast.receiver_count() > 0 {
+            let _ = self.broadcast.send(event.clone());
+        }
+        self.sender.send(event).map_err(Box::new)
+    }
+
+    /// Get a receiver for every event that's sent after this is called.
+    ///
+    /// If the receiver falls more than [`Self::BROADCAST_CAPACITY`] events
+    /// behind, the oldest ones are skipped and it gets a
+    /// [`RecvError::Lagged`].
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.broadcast.subscribe()
+    }
+}
+
+impl Client {
+    /// Wait for the next event that matches the predicate, or return `None` if
+    /// the timeout passes or the client disconnects first.
+    ///
+    /// This doesn't take any events away from the receiver returned by
+    /// [`Client::join`], so it's fine to use while you're also handling events
+    /// there.
+    ///
+    /// We start listening when this is called rather than when the future is
+    /// awaited, and events that were sent before that are never matched. So if
+    /// you're waiting for the response to something, call this before doing
+    /// it and await the future afterwards.
+    ///
+    /// ```rust,no_run
+    /// # use azalea_client::{Client, Event};
+    /// # use std::time::Duration;
+    /// # async fn example(bot: &Client) {
+    /// let response = bot.wait_for_event(
+    ///     |event| matches!(event, Event::Chat(m) if m.content().contains("players online")),
+    ///     Duration::from_secs(5),
+    /// );
+    /// bot.chat("/list");
+    /// let response = response.await;
+    /// # }
+    /// ```
+    pub fn wait_for_event(
+        &self,
+        predicate: impl Fn(&Event) -> bool,
+        timeout: Duration,
+    ) -> impl Future<Output = Option<Event>> {
+        let receiver = self
+            .ecs
+            .lock()
+            .get::<LocalPlayerEvents>(self.entity)
+            .map(LocalPlayerEvents::subscribe);
+
+        async move {
+            let mut receiver = receiver?;
+            tokio::time::timeout(timeout, async {
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) if predicate(&event) => return Some(event),
+                        // if we fell behind, the events we missed are gone so just keep going
+                        Ok(_) | Err(RecvError::Lagged(_)) => {}
+                        Err(RecvError::Closed) => return None,
+                    }
+                }
+            })
+            .await
+            .ok()
+            .flatten()
+        }
+    }
+}
 
 pub struct EventPlugin;
 impl Plugin for EventPlugin {
@@ -117,6 +235,7 @@ impl Plugin for EventPlugin {
                 remove_player_listener,
                 keepalive_listener,
                 death_listener,
+                protocol_state_changed_listener,
             ),
         )
         .add_systems(
@@ -229,3 +348,19 @@ fn keepalive_listener(query: Query<&LocalPlayerEvents>, mut events: EventReader<
             .unwrap();
     }
 }
+
+fn protocol_state_changed_listener(
+    query: Query<&LocalPlayerEvents>,
+    mut events: EventReader<ProtocolStateChangedEvent>,
+) {
+    for event in events.iter() {
+        if let Ok(local_player_events) = query.get(event.entity) {
+            local_player_events
+                .send(Event::ProtocolStateChanged {
+                    from: event.from,
+                    to: event.to,
+                })
+                .unwrap();
+        }
+    }
+}
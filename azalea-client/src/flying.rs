@@ -0,0 +1,136 @@
+//! Flying like in creative or spectator mode.
+
+use azalea_core::game_type::GameMode;
+use azalea_entity::{Flying, LocalEntity, Physics};
+use azalea_physics::PhysicsSet;
+use azalea_protocol::packets::game::serverbound_player_abilities_packet::ServerboundPlayerAbilitiesPacket;
+use bevy_app::{App, FixedUpdate, Plugin, Update};
+use bevy_ecs::prelude::*;
+use log::warn;
+
+use crate::{
+    local_player::{handle_send_packet_event, LocalGameMode, PlayerAbilities, SendPacketEvent},
+    Client,
+};
+
+/// A plugin that lets clients start and stop flying if the server lets them,
+/// and makes the physics fly while they are. See [`Client::set_flying`].
+pub struct FlyingPlugin;
+impl Plugin for FlyingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SetFlyingEvent>()
+            .add_systems(
+                Update,
+                (
+                    handle_set_flying_event.before(handle_send_packet_event),
+                    update_flying_component,
+                )
+                    .chain(),
+            )
+            .add_systems(FixedUpdate, stop_flying_on_ground.after(PhysicsSet));
+    }
+}
+
+impl Client {
+    /// Start or stop flying, like double-tapping space in creative mode.
+    ///
+    /// While we're flying, jumping makes us go up and sneaking makes us go
+    /// down. We stop flying when we land on the ground (unless we're in
+    /// spectator mode).
+    ///
+    /// Returns `false` and does nothing if we tried to start flying but the
+    /// server hasn't given us the ability to fly. See [`Client::can_fly`].
+    pub fn set_flying(&mut self, flying: bool) -> bool {
+        if flying && !self.can_fly() {
+            return false;
+        }
+        self.ecs.lock().send_event(SetFlyingEvent {
+            entity: self.entity,
+            flying,
+        });
+        true
+    }
+
+    /// Whether the server lets us fly, which is usually only true in creative
+    /// and spectator mode.
+    pub fn can_fly(&self) -> bool {
+        self.component::<PlayerAbilities>().can_fly
+    }
+
+    /// Whether we're currently flying.
+    pub fn is_flying(&self) -> bool {
+        self.component::<PlayerAbilities>().flying
+    }
+}
+
+/// An event that makes a client start or stop flying. This is ignored if it's
+/// trying to make us fly and the server hasn't given us the ability to.
+#[derive(Event, Debug, Clone)]
+pub struct SetFlyingEvent {
+    pub entity: Entity,
+    pub flying: bool,
+}
+pub fn handle_set_flying_event(
+    mut events: EventReader<SetFlyingEvent>,
+    mut query: Query<&mut PlayerAbilities>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.iter() {
+        let Ok(mut abilities) = query.get_mut(event.entity) else {
+            continue;
+        };
+        if event.flying && !abilities.can_fly {
+            warn!("Tried to start flying but the server didn't give us the ability to fly");
+            continue;
+        }
+        if abilities.flying == event.flying {
+            continue;
+        }
+
+        abilities.flying = event.flying;
+        send_packet_events.send(SendPacketEvent {
+            entity: event.entity,
+            packet: ServerboundPlayerAbilitiesPacket {
+                is_flying: event.flying,
+            }
+            .get(),
+        });
+    }
+}
+
+/// Add or remove the [`Flying`] component when our abilities change, so the
+/// physics knows whether we're flying.
+#[allow(clippy::type_complexity)]
+pub fn update_flying_component(
+    mut commands: Commands,
+    query: Query<(Entity, &PlayerAbilities), (Changed<PlayerAbilities>, With<LocalEntity>)>,
+) {
+    for (entity, abilities) in &query {
+        if abilities.flying {
+            commands.entity(entity).insert(Flying {
+                speed: abilities.flying_speed,
+            });
+        } else {
+            commands.entity(entity).remove::<Flying>();
+        }
+    }
+}
+
+/// Stop flying when we touch the ground, like vanilla does.
+pub fn stop_flying_on_ground(
+    mut query: Query<(Entity, &Physics, &LocalGameMode, &mut PlayerAbilities)>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for (entity, physics, game_mode, mut abilities) in &mut query {
+        // players in spectator mode are always flying
+        if !abilities.flying || !physics.on_ground || game_mode.current == GameMode::Spectator {
+            continue;
+        }
+
+        abilities.flying = false;
+        send_packet_events.send(SendPacketEvent {
+            entity,
+            packet: ServerboundPlayerAbilitiesPacket { is_flying: false }.get(),
+        });
+    }
+}
@@ -1,24 +1,27 @@
 use std::ops::AddAssign;
 
-use azalea_block::BlockState;
+use azalea_block::{Block, BlockState};
 use azalea_core::{
+    aabb::AABB,
     block_hit_result::BlockHitResult,
     direction::Direction,
     game_type::GameMode,
     position::{BlockPos, Vec3},
 };
 use azalea_entity::{
-    clamp_look_direction, view_vector, Attributes, EyeHeight, LocalEntity, LookDirection, Position,
+    clamp_look_direction, direction_looking_at, metadata::ShiftKeyDown, view_vector, Attributes,
+    EyeHeight, LocalEntity, LookDirection, Physics, Position,
 };
 use azalea_inventory::{ItemSlot, ItemSlotData};
 use azalea_nbt::NbtList;
 use azalea_physics::clip::{BlockShapeType, ClipContext, FluidPickType};
 use azalea_protocol::packets::game::{
-    serverbound_interact_packet::InteractionHand,
+    serverbound_interact_packet::{self, InteractionHand, ServerboundInteractPacket},
     serverbound_swing_packet::ServerboundSwingPacket,
     serverbound_use_item_on_packet::{BlockHit, ServerboundUseItemOnPacket},
 };
-use azalea_world::{Instance, InstanceContainer, InstanceName};
+use azalea_registry::tags::blocks::REPLACEABLE;
+use azalea_world::{Instance, InstanceContainer, InstanceName, MinecraftEntityId};
 use bevy_app::{App, Plugin, Update};
 use bevy_ecs::{
     component::Component,
@@ -33,7 +36,7 @@ use log::warn;
 
 use crate::{
     attack::handle_attack_event,
-    inventory::{InventoryComponent, InventorySet},
+    inventory::{select_hotbar_slot, InventoryComponent, InventorySet},
     local_player::{
         handle_send_packet_event, LocalGameMode, PermissionLevel, PlayerAbilities, SendPacketEvent,
     },
@@ -46,13 +49,18 @@ pub struct InteractPlugin;
 impl Plugin for InteractPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<BlockInteractEvent>()
+            .add_event::<PlaceBlockEvent>()
+            .add_event::<EntityInteractEvent>()
             .add_event::<SwingArmEvent>()
+            .add_event::<OutOfReachEvent>()
             .add_systems(
                 Update,
                 (
                     (
                         update_hit_result_component.after(clamp_look_direction),
                         handle_block_interact_event,
+                        handle_place_block_event,
+                        handle_entity_interact_event,
                         handle_swing_arm_event,
                     )
                         .before(handle_send_packet_event)
@@ -74,13 +82,228 @@ impl Client {
     /// block you clicked (like toggling a lever).
     ///
     /// Note that this may trigger anticheats as it doesn't take into account
-    /// whether you're actually looking at the block.
+    /// whether you're actually looking at the block. Blocks that are out of
+    /// [`Reach`] aren't clicked.
     pub fn block_interact(&mut self, position: BlockPos) {
         self.ecs.lock().send_event(BlockInteractEvent {
             entity: self.entity,
             position,
         });
     }
+
+    /// Place a block from our hotbar against the given face of the block at
+    /// `target`. This looks at the face and switches to a hotbar slot with a
+    /// block in it if we aren't already holding one.
+    ///
+    /// If the target is a replaceable block like grass, then the new block
+    /// will replace it instead of being placed next to it, like in vanilla.
+    ///
+    /// The [`PlacingBlock`] component is present until the server acknowledges
+    /// the placement.
+    pub fn place_block(&mut self, target: BlockPos, face: Direction) {
+        self.ecs.lock().send_event(PlaceBlockEvent {
+            entity: self.entity,
+            target,
+            face,
+        });
+    }
+
+    /// Right click an entity, like to trade with a villager, ride a horse, or
+    /// put a lead on an animal. Sneak with [`Client::set_sneaking`] first if
+    /// you want to sneak-click it.
+    ///
+    /// This doesn't look at the entity, so you might have to do that yourself
+    /// for servers with anticheats.
+    pub fn interact_entity(&mut self, entity: Entity, hand: InteractionHand) {
+        self.ecs.lock().send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: entity,
+            hand,
+            location: None,
+        });
+    }
+
+    /// Right click a specific point on an entity's hitbox. `location` is
+    /// relative to the entity's position (so the bottom center of its hitbox
+    /// is `Vec3::default()`).
+    ///
+    /// Most entities don't care where they're clicked and only respond to
+    /// [`Client::interact_entity`], but armor stands use this to decide which
+    /// item to take. Vanilla sends this and then `interact_entity` when you
+    /// right click an entity.
+    pub fn interact_entity_at(&mut self, entity: Entity, hand: InteractionHand, location: Vec3) {
+        self.ecs.lock().send_event(EntityInteractEvent {
+            entity: self.entity,
+            target: entity,
+            hand,
+            location: Some(location),
+        });
+    }
+
+    /// Swing the arm for the given hand. Other players will see us swing,
+    /// but this doesn't attack or interact with anything.
+    pub fn swing_arm(&mut self, hand: InteractionHand) {
+        self.ecs.lock().send_event(SwingArmEvent {
+            entity: self.entity,
+            hand,
+        });
+    }
+}
+
+/// How far away we can attack and interact with things, measured from our eyes
+/// to the closest point of the target.
+///
+/// Attacks and interactions with things that are farther away than this
+/// aren't sent, since the server would ignore them and anticheats might flag
+/// us for trying. An [`OutOfReachEvent`] is sent instead.
+///
+/// The defaults are the same as vanilla, but you can replace this component if
+/// the server lets you reach farther (or if you want to be more careful).
+#[derive(Component, Clone, Debug)]
+pub struct Reach {
+    /// How far we can attack and interact with entities from.
+    pub entity: f64,
+    /// How far we can interact with blocks from.
+    pub block: f64,
+    /// How far we can attack and interact with entities from in creative mode.
+    pub creative_entity: f64,
+    /// How far we can interact with blocks from in creative mode.
+    pub creative_block: f64,
+}
+impl Default for Reach {
+    fn default() -> Self {
+        Self {
+            entity: 3.,
+            block: 4.5,
+            creative_entity: 6.,
+            creative_block: 5.,
+        }
+    }
+}
+impl Reach {
+    pub fn entity_range(&self, game_mode: GameMode) -> f64 {
+        if game_mode == GameMode::Creative {
+            self.creative_entity
+        } else {
+            self.entity
+        }
+    }
+    pub fn block_range(&self, game_mode: GameMode) -> f64 {
+        if game_mode == GameMode::Creative {
+            self.creative_block
+        } else {
+            self.block
+        }
+    }
+}
+
+/// Sent when we didn't attack or interact with something because it was
+/// farther away than our [`Reach`].
+#[derive(Event, Debug, Clone)]
+pub struct OutOfReachEvent {
+    pub entity: Entity,
+    pub target: ReachTarget,
+    /// How far away the target was from our eyes.
+    pub distance: f64,
+    /// How far we could've reached.
+    pub reach: f64,
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReachTarget {
+    Entity(Entity),
+    Block(BlockPos),
+}
+
+/// Returns whether the target's hitbox is within `reach` of our eyes, and
+/// sends an [`OutOfReachEvent`] if it isn't.
+pub fn check_reach(
+    entity: Entity,
+    eye_position: &Vec3,
+    target: ReachTarget,
+    target_box: &AABB,
+    reach: f64,
+    out_of_reach_events: &mut EventWriter<OutOfReachEvent>,
+) -> bool {
+    let distance_sqr = target_box.distance_to_sqr(eye_position);
+    if distance_sqr <= reach * reach {
+        return true;
+    }
+    out_of_reach_events.send(OutOfReachEvent {
+        entity,
+        target,
+        distance: distance_sqr.sqrt(),
+        reach,
+    });
+    false
+}
+
+/// The hitbox of a full block at the given position, which is what we use to
+/// check if a block is in reach.
+fn block_box(position: BlockPos) -> AABB {
+    AABB::of_size(position.center(), 1., 1., 1.)
+}
+
+/// Right click an entity, like to trade with a villager or ride a horse.
+///
+/// If `location` is present, this sends an `InteractAt` action instead of
+/// `Interact`. Vanilla sends both when right clicking an entity, and some
+/// entities (like armor stands) only respond to `InteractAt`.
+#[derive(Event, Debug)]
+pub struct EntityInteractEvent {
+    pub entity: Entity,
+    /// The entity that's being interacted with.
+    pub target: Entity,
+    pub hand: InteractionHand,
+    /// The position on the target's hitbox that was clicked, relative to the
+    /// target's position.
+    pub location: Option<Vec3>,
+}
+pub fn handle_entity_interact_event(
+    mut events: EventReader<EntityInteractEvent>,
+    query: Query<(&ShiftKeyDown, &Position, &EyeHeight, &LocalGameMode, &Reach)>,
+    target_query: Query<(&MinecraftEntityId, &Physics)>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+    mut out_of_reach_events: EventWriter<OutOfReachEvent>,
+) {
+    for event in events.iter() {
+        let Ok((target_id, target_physics)) = target_query.get(event.target) else {
+            warn!("Tried to interact with an entity that doesn't exist");
+            continue;
+        };
+        let Ok((sneaking, position, eye_height, game_mode, reach)) = query.get(event.entity) else {
+            warn!("Sent EntityInteractEvent for entity that doesn't have the required components");
+            continue;
+        };
+        if !check_reach(
+            event.entity,
+            &position.up(**eye_height as f64),
+            ReachTarget::Entity(event.target),
+            &target_physics.bounding_box,
+            reach.entity_range(game_mode.current),
+            &mut out_of_reach_events,
+        ) {
+            continue;
+        }
+
+        let action = match event.location {
+            Some(location) => serverbound_interact_packet::ActionType::InteractAt {
+                location,
+                hand: event.hand,
+            },
+            None => serverbound_interact_packet::ActionType::Interact { hand: event.hand },
+        };
+        send_packet_events.send(SendPacketEvent {
+            entity: event.entity,
+            packet: ServerboundInteractPacket {
+                entity_id: **target_id,
+                action,
+                // the server uses this to decide things like whether to open a villager's
+                // trades or leash a mob to a fence
+                using_secondary_action: **sneaking,
+            }
+            .get(),
+        });
+    }
 }
 
 /// Right click a block. The behavior of this depends on the target block,
@@ -105,21 +328,65 @@ impl AddAssign<u32> for CurrentSequenceNumber {
     }
 }
 
+/// Place a block from the hotbar against a face of another block.
+#[derive(Event)]
+pub struct PlaceBlockEvent {
+    pub entity: Entity,
+    /// The block that we're clicking on.
+    pub target: BlockPos,
+    /// The face of the target block that we're clicking on.
+    pub face: Direction,
+}
+
+/// A component that's present while we're waiting for the server to
+/// acknowledge a block that we placed.
+#[derive(Component, Clone, Debug)]
+pub struct PlacingBlock {
+    /// Where the new block should end up.
+    pub position: BlockPos,
+    /// The [`CurrentSequenceNumber`] that we sent with the placement. The
+    /// server acknowledges every change up to this number at once.
+    pub sequence: u32,
+}
+
 /// A component that contains the block that the player is currently looking at.
 #[derive(Component, Clone, Debug, Deref, DerefMut)]
 pub struct HitResultComponent(BlockHitResult);
 
+#[allow(clippy::type_complexity)]
 pub fn handle_block_interact_event(
     mut events: EventReader<BlockInteractEvent>,
-    mut query: Query<(Entity, &mut CurrentSequenceNumber, &HitResultComponent)>,
+    mut query: Query<(
+        Entity,
+        &mut CurrentSequenceNumber,
+        &HitResultComponent,
+        &Position,
+        &EyeHeight,
+        &LocalGameMode,
+        &Reach,
+    )>,
     mut send_packet_events: EventWriter<SendPacketEvent>,
+    mut out_of_reach_events: EventWriter<OutOfReachEvent>,
 ) {
     for event in events.iter() {
-        let Ok((entity, mut sequence_number, hit_result)) = query.get_mut(event.entity) else {
+        let Ok((entity, mut sequence_number, hit_result, position, eye_height, game_mode, reach)) =
+            query.get_mut(event.entity)
+        else {
             warn!("Sent BlockInteractEvent for entity that doesn't have the required components");
             continue;
         };
 
+        if !check_reach(
+            entity,
+            &position.up(**eye_height as f64),
+            ReachTarget::Block(event.position),
+            &block_box(event.position),
+            reach.block_range(game_mode.current),
+            &mut out_of_reach_events,
+        ) {
+            continue;
+        }
+
         // TODO: check to make sure we're within the world border
 
         *sequence_number += 1;
@@ -160,6 +427,132 @@ pub fn handle_block_interact_event(
     }
 }
 
+#[allow(clippy::type_complexity)]
+pub fn handle_place_block_event(
+    mut commands: Commands,
+    mut events: EventReader<PlaceBlockEvent>,
+    mut query: Query<(
+        &mut InventoryComponent,
+        &mut CurrentSequenceNumber,
+        &mut LookDirection,
+        &Position,
+        &EyeHeight,
+        &InstanceName,
+        &LocalGameMode,
+        &Reach,
+    )>,
+    instance_container: Res<InstanceContainer>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+    mut swing_arm_events: EventWriter<SwingArmEvent>,
+    mut out_of_reach_events: EventWriter<OutOfReachEvent>,
+) {
+    for event in events.iter() {
+        let Ok((
+            mut inventory,
+            mut sequence_number,
+            mut look_direction,
+            position,
+            eye_height,
+            instance_name,
+            game_mode,
+            reach,
+        )) = query.get_mut(event.entity)
+        else {
+            warn!("Sent PlaceBlockEvent for entity that doesn't have the required components");
+            continue;
+        };
+
+        let eye_position = position.up(**eye_height as f64);
+        if !check_reach(
+            event.entity,
+            &eye_position,
+            ReachTarget::Block(event.target),
+            &block_box(event.target),
+            reach.block_range(game_mode.current),
+            &mut out_of_reach_events,
+        ) {
+            continue;
+        }
+
+        let Some(slot) = placeable_hotbar_slot(&inventory) else {
+            warn!("Tried to place a block but there's no block in the hotbar");
+            continue;
+        };
+        select_hotbar_slot(event.entity, &mut inventory, slot, &mut send_packet_events);
+
+        let Some(instance_lock) = instance_container.get(instance_name) else {
+            continue;
+        };
+        let target_block = instance_lock
+            .read()
+            .get_block_state(&event.target)
+            .unwrap_or_default();
+        let target_block = Box::<dyn Block>::from(target_block).as_registry_block();
+        // clicking a replaceable block makes the new block replace it
+        let placed_position = if REPLACEABLE.contains(&target_block) {
+            event.target
+        } else {
+            let normal = event.face.normal();
+            event.target + BlockPos::new(normal.x as i32, normal.y as i32, normal.z as i32)
+        };
+
+        // click on the center of the face
+        let location = event.target.center() + event.face.normal() * 0.5;
+        (look_direction.y_rot, look_direction.x_rot) =
+            direction_looking_at(&eye_position, &location);
+
+        *sequence_number += 1;
+        send_packet_events.send(SendPacketEvent {
+            entity: event.entity,
+            packet: ServerboundUseItemOnPacket {
+                hand: InteractionHand::MainHand,
+                block_hit: BlockHit {
+                    block_pos: event.target,
+                    direction: event.face,
+                    location,
+                    inside: false,
+                },
+                sequence: **sequence_number,
+            }
+            .get(),
+        });
+        swing_arm_events.send(SwingArmEvent {
+            entity: event.entity,
+            hand: InteractionHand::MainHand,
+        });
+
+        commands.entity(event.entity).insert(PlacingBlock {
+            position: placed_position,
+            sequence: **sequence_number,
+        });
+    }
+}
+
+/// Get the hotbar slot that we should place blocks from. This is the selected
+/// slot if we're holding a block, and otherwise the first slot with a block.
+fn placeable_hotbar_slot(inventory: &InventoryComponent) -> Option<u8> {
+    if block_for_item(inventory.held_item().kind()).is_some() {
+        return Some(inventory.selected_hotbar_slot);
+    }
+    let hotbar_slots =
+        &inventory.inventory_menu.slots()[inventory.inventory_menu.hotbar_slots_range()];
+    hotbar_slots
+        .iter()
+        .position(|item_slot| block_for_item(item_slot.kind()).is_some())
+        .map(|slot| slot as u8)
+}
+
+/// Get the block that an item places, or `None` if it's not a block item.
+///
+/// This only works for items that have the same name as their block, so
+/// things like redstone dust and seeds aren't included.
+pub fn block_for_item(item: azalea_registry::Item) -> Option<azalea_registry::Block> {
+    if item == azalea_registry::Item::Air {
+        return None;
+    }
+    item.to_string().parse().ok()
+}
+
 #[allow(clippy::type_complexity)]
 pub fn update_hit_result_component(
     mut commands: Commands,
@@ -167,6 +560,7 @@ pub fn update_hit_result_component(
         Entity,
         Option<&mut HitResultComponent>,
         &LocalGameMode,
+        &Reach,
         &Position,
         &EyeHeight,
         &LookDirection,
@@ -174,14 +568,18 @@ pub fn update_hit_result_component(
     )>,
     instance_container: Res<InstanceContainer>,
 ) {
-    for (entity, hit_result_ref, game_mode, position, eye_height, look_direction, world_name) in
-        &mut query
+    for (
+        entity,
+        hit_result_ref,
+        game_mode,
+        reach,
+        position,
+        eye_height,
+        look_direction,
+        world_name,
+    ) in &mut query
     {
-        let pick_range = if game_mode.current == GameMode::Creative {
-            6.
-        } else {
-            4.5
-        };
+        let pick_range = reach.block_range(game_mode.current);
         let eye_position = Vec3 {
             x: position.x,
             y: position.y + **eye_height as f64,
@@ -306,6 +704,7 @@ pub fn can_use_game_master_blocks(
 #[derive(Event)]
 pub struct SwingArmEvent {
     pub entity: Entity,
+    pub hand: InteractionHand,
 }
 pub fn handle_swing_arm_event(
     mut events: EventReader<SwingArmEvent>,
@@ -314,10 +713,7 @@ pub fn handle_swing_arm_event(
     for event in events.iter() {
         send_packet_events.send(SendPacketEvent {
             entity: event.entity,
-            packet: ServerboundSwingPacket {
-                hand: InteractionHand::MainHand,
-            }
-            .get(),
+            packet: ServerboundSwingPacket { hand: event.hand }.get(),
         });
     }
 }
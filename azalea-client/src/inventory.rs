@@ -1,6 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
 use azalea_chat::FormattedText;
+use azalea_core::{direction::Direction, position::BlockPos};
 pub use azalea_inventory::*;
 use azalea_inventory::{
     item::MaxStackSizeExt,
@@ -12,6 +13,8 @@ use azalea_inventory::{
 use azalea_protocol::packets::game::{
     serverbound_container_click_packet::ServerboundContainerClickPacket,
     serverbound_container_close_packet::ServerboundContainerClosePacket,
+    serverbound_player_action_packet::{self, ServerboundPlayerActionPacket},
+    serverbound_set_carried_item_packet::ServerboundSetCarriedItemPacket,
 };
 use azalea_registry::MenuKind;
 use bevy_app::{App, Plugin, Update};
@@ -20,8 +23,9 @@ use bevy_ecs::{
     entity::Entity,
     event::EventReader,
     prelude::{Event, EventWriter},
+    query::Changed,
     schedule::{IntoSystemConfigs, SystemSet},
-    system::Query,
+    system::{Commands, Query},
 };
 use log::warn;
 
@@ -39,11 +43,17 @@ impl Plugin for InventoryPlugin {
             .add_event::<CloseContainerEvent>()
             .add_event::<ContainerClickEvent>()
             .add_event::<SetContainerContentEvent>()
+            .add_event::<DropItemEvent>()
+            .add_event::<SetSelectedHotbarSlotEvent>()
+            .add_event::<InventoryUpdatedEvent>()
+            .add_systems(Update, detect_inventory_changes.after(InventorySet))
             .add_systems(
                 Update,
                 (
                     handle_menu_opened_event,
                     handle_set_container_content_event,
+                    handle_set_selected_hotbar_slot_event,
+                    handle_drop_item_event,
                     handle_container_click_event,
                     handle_container_close_event.before(handle_send_packet_event),
                     handle_client_side_close_container_event,
@@ -66,6 +76,64 @@ impl Client {
         let inventory = self.query::<&InventoryComponent>(&mut ecs);
         inventory.menu().clone()
     }
+
+    /// Select a slot in our hotbar (0-8). The item in it will be used for
+    /// things like mining and attacking.
+    ///
+    /// Invalid slots are ignored, and nothing is sent to the server if the slot
+    /// is already selected.
+    pub fn set_held_slot(&mut self, slot: u8) {
+        self.ecs.lock().send_event(SetSelectedHotbarSlotEvent {
+            entity: self.entity,
+            slot,
+        });
+    }
+
+    /// Get the index of the hotbar slot that we currently have selected (0-8).
+    pub fn held_slot(&self) -> u8 {
+        let mut ecs = self.ecs.lock();
+        self.query::<&InventoryComponent>(&mut ecs)
+            .selected_hotbar_slot
+    }
+
+    /// Get the item in the hotbar slot that we currently have selected.
+    pub fn held_item(&self) -> ItemSlot {
+        let mut ecs = self.ecs.lock();
+        self.query::<&InventoryComponent>(&mut ecs).held_item()
+    }
+
+    /// Drop one item from the given slot in the current menu.
+    ///
+    /// The slot index is for [`Client::menu`], so if a container is open then
+    /// the indexes of the player's inventory slots will be different. Use
+    /// [`Menu::player_slots_range`] to find them.
+    pub fn drop_item(&mut self, slot: u16) {
+        self.ecs.lock().send_event(DropItemEvent {
+            entity: self.entity,
+            slot: Some(slot),
+            all: false,
+        });
+    }
+
+    /// Drop the whole stack in the given slot in the current menu. See
+    /// [`Client::drop_item`] for how slots are indexed.
+    pub fn drop_stack(&mut self, slot: u16) {
+        self.ecs.lock().send_event(DropItemEvent {
+            entity: self.entity,
+            slot: Some(slot),
+            all: true,
+        });
+    }
+
+    /// Drop the item we're holding in our hand, like pressing Q in the vanilla
+    /// client. If `all` is true then the whole stack is dropped.
+    pub fn drop_held(&mut self, all: bool) {
+        self.ecs.lock().send_event(DropItemEvent {
+            entity: self.entity,
+            slot: None,
+            all,
+        });
+    }
 }
 
 /// A component present on all local players that have an inventory.
@@ -657,12 +725,12 @@ pub struct ContainerClickEvent {
     pub operation: ClickOperation,
 }
 pub fn handle_container_click_event(
-    mut query: Query<(Entity, &mut InventoryComponent)>,
+    mut query: Query<(Entity, &mut InventoryComponent, &PlayerAbilities)>,
     mut events: EventReader<ContainerClickEvent>,
     mut send_packet_events: EventWriter<SendPacketEvent>,
 ) {
     for event in events.iter() {
-        let (entity, mut inventory) = query.get_mut(event.entity).unwrap();
+        let (entity, mut inventory, player_abilities) = query.get_mut(event.entity).unwrap();
         if inventory.id != event.window_id {
             warn!(
                 "Tried to click container with ID {}, but the current container ID is {}",
@@ -671,16 +739,14 @@ pub fn handle_container_click_event(
             continue;
         }
 
-        let menu = inventory.menu_mut();
-        let old_slots = menu.slots().clone();
-
-        // menu.click(&event.operation);
+        let old_slots = inventory.menu().slots();
+        inventory.simulate_click(&event.operation, player_abilities);
+        let new_slots = inventory.menu().slots();
 
         // see which slots changed after clicking and put them in the hashmap
         // the server uses this to check if we desynced
         let mut changed_slots: HashMap<u16, ItemSlot> = HashMap::new();
-        for (slot_index, old_slot) in old_slots.iter().enumerate() {
-            let new_slot = &menu.slots()[slot_index];
+        for (slot_index, (old_slot, new_slot)) in old_slots.iter().zip(&new_slots).enumerate() {
             if old_slot != new_slot {
                 changed_slots.insert(slot_index as u16, new_slot.clone());
             }
@@ -702,6 +768,114 @@ pub fn handle_container_click_event(
     }
 }
 
+/// Select a slot in the hotbar, like by pressing a number key in vanilla.
+#[derive(Event, Debug)]
+pub struct SetSelectedHotbarSlotEvent {
+    pub entity: Entity,
+    /// The index of the slot in the hotbar, from 0 to 8.
+    pub slot: u8,
+}
+fn handle_set_selected_hotbar_slot_event(
+    mut events: EventReader<SetSelectedHotbarSlotEvent>,
+    mut query: Query<&mut InventoryComponent>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.iter() {
+        if event.slot >= 9 {
+            warn!("Tried to select invalid hotbar slot {}", event.slot);
+            continue;
+        }
+        let Ok(mut inventory) = query.get_mut(event.entity) else {
+            continue;
+        };
+        select_hotbar_slot(
+            event.entity,
+            &mut inventory,
+            event.slot,
+            &mut send_packet_events,
+        );
+    }
+}
+
+/// Change the selected hotbar slot and tell the server about it, unless it was
+/// already selected.
+pub fn select_hotbar_slot(
+    entity: Entity,
+    inventory: &mut InventoryComponent,
+    slot: u8,
+    send_packet_events: &mut EventWriter<SendPacketEvent>,
+) {
+    if inventory.selected_hotbar_slot == slot {
+        return;
+    }
+    inventory.selected_hotbar_slot = slot;
+    send_packet_events.send(SendPacketEvent {
+        entity,
+        packet: ServerboundSetCarriedItemPacket { slot: slot as u16 }.get(),
+    });
+}
+
+/// Drop items from our inventory or the container we have open.
+#[derive(Event, Debug)]
+pub struct DropItemEvent {
+    pub entity: Entity,
+    /// The index of the slot in the current menu to drop from, or `None` to
+    /// drop the item we're holding.
+    pub slot: Option<u16>,
+    /// Whether the whole stack should be dropped instead of a single item.
+    pub all: bool,
+}
+pub fn handle_drop_item_event(
+    mut query: Query<&mut InventoryComponent>,
+    mut events: EventReader<DropItemEvent>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+    mut container_click_events: EventWriter<ContainerClickEvent>,
+) {
+    for event in events.iter() {
+        let Ok(mut inventory) = query.get_mut(event.entity) else {
+            continue;
+        };
+
+        let Some(slot) = event.slot else {
+            // dropping the held item doesn't go through the menu, so it works the same
+            // whether or not a container is open
+            let held_index = *inventory.inventory_menu.hotbar_slots_range().start()
+                + inventory.selected_hotbar_slot as usize;
+            if let Some(held) = inventory.inventory_menu.slot_mut(held_index) {
+                let count = if event.all { held.count() as u8 } else { 1 };
+                held.split(count);
+            }
+
+            let action = if event.all {
+                serverbound_player_action_packet::Action::DropAllItems
+            } else {
+                serverbound_player_action_packet::Action::DropItem
+            };
+            send_packet_events.send(SendPacketEvent {
+                entity: event.entity,
+                packet: ServerboundPlayerActionPacket {
+                    action,
+                    pos: BlockPos::default(),
+                    direction: Direction::Down,
+                    sequence: 0,
+                }
+                .get(),
+            });
+            continue;
+        };
+
+        container_click_events.send(ContainerClickEvent {
+            entity: event.entity,
+            window_id: inventory.id,
+            operation: ClickOperation::Throw(if event.all {
+                ThrowClick::All { slot }
+            } else {
+                ThrowClick::Single { slot }
+            }),
+        });
+    }
+}
+
 /// Sent from the server when the contents of a container are replaced. Usually
 /// triggered by the `ContainerSetContent` packet.
 #[derive(Event)]
@@ -733,3 +907,206 @@ fn handle_set_container_content_event(
         }
     }
 }
+
+/// An event that's sent when the items in our inventory or the container
+/// that we have open change.
+///
+/// This is sent for every way that slots can change, including the server
+/// replacing all of the slots, the server setting a single slot, and us
+/// clicking in the menu. The changes are found by comparing the slots to
+/// what they were the last time this event was sent.
+#[derive(Event, Debug, Clone)]
+pub struct InventoryUpdatedEvent {
+    pub entity: Entity,
+    /// The ID of the menu that changed. This is 0 for our inventory, or the
+    /// same as [`InventoryComponent::id`] for the container that's open.
+    pub container_id: u8,
+    /// The slots that changed, sorted by their index in the menu.
+    pub changes: Vec<SlotChange>,
+}
+
+/// A slot that has different contents than it used to. See
+/// [`InventoryUpdatedEvent`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotChange {
+    /// The index of the slot in the menu.
+    pub slot: usize,
+    pub old: ItemSlot,
+    pub new: ItemSlot,
+}
+
+impl SlotChange {
+    /// Whether the slot had something in it and is now empty.
+    pub fn emptied(&self) -> bool {
+        self.old.is_present() && self.new.is_empty()
+    }
+
+    /// Whether the slot was empty and now has something in it.
+    pub fn filled(&self) -> bool {
+        self.old.is_empty() && self.new.is_present()
+    }
+
+    /// How many more items are in the slot than before. This is negative if
+    /// items were removed, and it doesn't check whether the item is the same
+    /// kind.
+    pub fn count_difference(&self) -> i32 {
+        self.new.count() as i32 - self.old.count() as i32
+    }
+}
+
+/// The slots of our inventory and the open container as of the last
+/// [`InventoryUpdatedEvent`], so we can tell what changed.
+#[derive(Component, Clone, Debug, Default)]
+pub struct InventorySnapshot {
+    pub inventory: Vec<ItemSlot>,
+    pub container_id: u8,
+    pub container: Vec<ItemSlot>,
+}
+
+/// Get the slots that are different between the two lists. Slots that are
+/// missing from one of the lists are treated as empty.
+fn diff_slots(old: &[ItemSlot], new: &[ItemSlot]) -> Vec<SlotChange> {
+    let empty = ItemSlot::Empty;
+    (0..old.len().max(new.len()))
+        .filter_map(|slot| {
+            let old = old.get(slot).unwrap_or(&empty);
+            let new = new.get(slot).unwrap_or(&empty);
+            (old != new).then(|| SlotChange {
+                slot,
+                old: old.clone(),
+                new: new.clone(),
+            })
+        })
+        .collect()
+}
+
+pub fn detect_inventory_changes(
+    mut commands: Commands,
+    mut query: Query<
+        (Entity, &InventoryComponent, Option<&mut InventorySnapshot>),
+        Changed<InventoryComponent>,
+    >,
+    mut inventory_updated_events: EventWriter<InventoryUpdatedEvent>,
+) {
+    for (entity, inventory, snapshot) in &mut query {
+        let new_snapshot = InventorySnapshot {
+            inventory: inventory.inventory_menu.slots(),
+            container_id: inventory.id,
+            container: inventory
+                .container_menu
+                .as_ref()
+                .map(|menu| menu.slots())
+                .unwrap_or_default(),
+        };
+        let Some(mut snapshot) = snapshot else {
+            // the first snapshot is what we compare against later
+            commands.entity(entity).insert(new_snapshot);
+            continue;
+        };
+
+        let changes = diff_slots(&snapshot.inventory, &new_snapshot.inventory);
+        if !changes.is_empty() {
+            inventory_updated_events.send(InventoryUpdatedEvent {
+                entity,
+                container_id: 0,
+                changes,
+            });
+        }
+
+        if new_snapshot.container_id != 0 {
+            // a container that we just opened is compared against an empty one
+            let old_container: &[ItemSlot] = if snapshot.container_id == new_snapshot.container_id {
+                &snapshot.container
+            } else {
+                &[]
+            };
+            let changes = diff_slots(old_container, &new_snapshot.container);
+            if !changes.is_empty() {
+                inventory_updated_events.send(InventoryUpdatedEvent {
+                    entity,
+                    container_id: new_snapshot.container_id,
+                    changes,
+                });
+            }
+        }
+
+        *snapshot = new_snapshot;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_inventory::ItemSlotData;
+    use azalea_registry::Item;
+
+    use super::*;
+
+    fn item(kind: Item, count: i8) -> ItemSlot {
+        ItemSlot::Present(ItemSlotData {
+            kind,
+            count,
+            nbt: Default::default(),
+        })
+    }
+
+    #[test]
+    fn test_diff_slots_unchanged() {
+        let slots = vec![ItemSlot::Empty, item(Item::Stone, 64)];
+        assert!(diff_slots(&slots, &slots).is_empty());
+    }
+
+    #[test]
+    fn test_diff_slots_added() {
+        let old = vec![ItemSlot::Empty, item(Item::Stone, 64)];
+        let new = vec![item(Item::Dirt, 3), item(Item::Stone, 64)];
+        let changes = diff_slots(&old, &new);
+        assert_eq!(
+            changes,
+            vec![SlotChange {
+                slot: 0,
+                old: ItemSlot::Empty,
+                new: item(Item::Dirt, 3),
+            }]
+        );
+        assert!(changes[0].filled());
+        assert_eq!(changes[0].count_difference(), 3);
+    }
+
+    #[test]
+    fn test_diff_slots_removed() {
+        let old = vec![ItemSlot::Empty, item(Item::Stone, 64)];
+        let new = vec![ItemSlot::Empty, ItemSlot::Empty];
+        let changes = diff_slots(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].slot, 1);
+        assert!(changes[0].emptied());
+        assert_eq!(changes[0].count_difference(), -64);
+    }
+
+    #[test]
+    fn test_diff_slots_count_changed() {
+        let old = vec![item(Item::Stone, 64), item(Item::Dirt, 1)];
+        let new = vec![item(Item::Stone, 60), item(Item::Dirt, 1)];
+        let changes = diff_slots(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].slot, 0);
+        assert!(!changes[0].emptied() && !changes[0].filled());
+        assert_eq!(changes[0].count_difference(), -4);
+    }
+
+    #[test]
+    fn test_diff_slots_different_lengths() {
+        // slots that are missing from one side are treated as empty, like when a
+        // container is opened
+        let old = vec![item(Item::Stone, 1)];
+        let new = vec![item(Item::Stone, 1), ItemSlot::Empty, item(Item::Dirt, 2)];
+        let changes = diff_slots(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].slot, 2);
+        assert!(changes[0].filled());
+
+        let changes = diff_slots(&new, &old);
+        assert_eq!(changes.len(), 1);
+        assert!(changes[0].emptied());
+    }
+}
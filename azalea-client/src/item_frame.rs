@@ -0,0 +1,94 @@
+//! Reading and using item frames and paintings.
+//!
+//! Some servers use walls of item frames as menus, so these make it easy to
+//! see what's in them.
+
+use azalea_entity::metadata::{ItemFrame, ItemFrameItem, PaintingVariant, Rotation};
+use azalea_inventory::ItemSlot;
+use azalea_protocol::packets::game::serverbound_interact_packet::InteractionHand;
+use azalea_registry::EntityKind;
+use azalea_world::MinecraftEntityId;
+use bevy_ecs::{entity::Entity, query::With};
+
+use crate::Client;
+
+/// The number of different rotations that an item in an item frame can have.
+/// Each one is 45 degrees clockwise from the last.
+pub const ITEM_FRAME_ROTATIONS: u8 = 8;
+
+/// What's in an item frame. See [`Client::item_frame_contents`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemFrameContents {
+    /// The item in the frame, which is [`ItemSlot::Empty`] if there isn't one.
+    pub item: ItemSlot,
+    /// How many times the item has been rotated 45 degrees clockwise, from 0
+    /// to 7.
+    pub rotation: u8,
+}
+
+impl Client {
+    /// Get the item in an item frame (including glow item frames) and how
+    /// it's rotated, or `None` if the entity isn't an item frame.
+    pub fn item_frame_contents(&self, entity: Entity) -> Option<ItemFrameContents> {
+        let mut ecs = self.ecs.lock();
+        let mut query =
+            ecs.query_filtered::<(Option<&ItemFrameItem>, Option<&Rotation>), With<ItemFrame>>();
+        let (item, rotation) = query.get(&ecs, entity).ok()?;
+        Some(ItemFrameContents {
+            item: item.map(|item| item.0.clone()).unwrap_or_default(),
+            rotation: rotation.map_or(0, |rotation| {
+                rotation.rem_euclid(ITEM_FRAME_ROTATIONS as i32) as u8
+            }),
+        })
+    }
+
+    /// Get every item frame within the given number of blocks of us and
+    /// what's in them, sorted from nearest to farthest.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # use azalea_inventory::ItemSlot;
+    /// # fn example(bot: &Client) {
+    /// for (_, contents) in bot.item_frames_within(8.) {
+    ///     if let ItemSlot::Present(item) = &contents.item {
+    ///         println!("there's {} {} in this frame", item.count, item.kind);
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn item_frames_within(&self, radius: f64) -> Vec<(Entity, ItemFrameContents)> {
+        self.entities_by_distance(radius, |kind| {
+            matches!(kind, EntityKind::ItemFrame | EntityKind::GlowItemFrame)
+        })
+        .into_iter()
+        .filter_map(|entity| Some((entity, self.item_frame_contents(entity)?)))
+        .collect()
+    }
+
+    /// Right click an item frame to rotate the item in it by 45 degrees.
+    ///
+    /// Note that if the frame is empty, this puts the item we're holding in it
+    /// instead.
+    pub fn rotate_item_frame(&mut self, entity: Entity) {
+        self.interact_entity(entity, InteractionHand::MainHand);
+    }
+
+    /// Punch an item frame to knock the item out of it.
+    ///
+    /// Note that if the frame is empty, this breaks the frame itself (unless
+    /// we're in adventure mode).
+    pub fn remove_item_from_frame(&mut self, entity: Entity) {
+        let Some(entity_id) = self.get_entity_component::<MinecraftEntityId>(entity) else {
+            return;
+        };
+        self.attack(entity_id);
+    }
+
+    /// Get the painting that a painting entity is showing, or `None` if the
+    /// entity isn't a painting.
+    pub fn painting_variant(&self, entity: Entity) -> Option<azalea_registry::PaintingVariant> {
+        let mut ecs = self.ecs.lock();
+        let mut query = ecs.query::<&PaintingVariant>();
+        query.get(&ecs, entity).ok().map(|variant| **variant)
+    }
+}
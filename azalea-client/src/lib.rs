@@ -12,35 +12,62 @@
 
 mod account;
 pub mod attack;
+pub mod block_entity;
+pub mod brand;
 pub mod chat;
 pub mod chunk_batching;
+pub mod chunk_cache;
 mod client;
+pub mod commands;
+pub mod connection_stats;
+pub mod crafting;
 pub mod disconnect;
+pub mod eat;
+pub mod effects;
 mod entity_query;
 mod events;
+pub mod flying;
 mod get_mc_dir;
 pub mod interact;
 pub mod inventory;
+pub mod item_frame;
 mod local_player;
+pub mod login_query;
 pub mod mining;
 pub mod movement;
 pub mod packet_handling;
+pub mod packet_rate_limit;
+pub mod physics_snapshot;
 pub mod ping;
 mod player;
 pub mod raw_connection;
+pub mod raycast;
+pub mod ready;
 pub mod received_registries;
+pub mod resource_pack;
 pub mod respawn;
+pub mod scoreboard;
+pub mod sign;
+pub mod sleep;
 pub mod task_pool;
+pub mod trading;
+pub mod vehicle;
+pub mod world_time;
 
 pub use account::{Account, AccountOpts};
 pub use azalea_protocol::packets::configuration::serverbound_client_information_packet::ClientInformation;
 pub use client::{
-    start_ecs_runner, Client, DefaultPlugins, JoinError, JoinedClientBundle, TickBroadcast,
+    start_ecs_runner, start_ecs_runner_with_tick_mode, step_ticks, Client, DefaultPlugins,
+    JoinError, JoinedClientBundle, LocalPlayerBundle, MinimalPlugins, TickBroadcast, TickMode,
 };
 pub use events::Event;
-pub use local_player::{GameProfileComponent, InstanceHolder, SendPacketEvent, TabList};
+pub use local_player::{
+    BossBar, BossBars, Experience, GameProfileComponent, Hardcore, Hunger, InstanceHolder,
+    LocalGameMode, PlayerAbilities, SendPacketEvent, ServerTps, TabList, TabListHeaderFooter,
+};
 pub use movement::{
-    PhysicsState, SprintDirection, StartSprintEvent, StartWalkEvent, WalkDirection,
+    PhysicsState, SprintDirection, StartSprintEvent, StartTimedWalkEvent, StartWalkEvent,
+    TimedWalkFinishedEvent, WalkDirection,
 };
 pub use player::PlayerInfo;
 pub use received_registries::ReceivedRegistries;
@@ -1,10 +1,18 @@
-use std::{collections::HashMap, io, sync::Arc};
+use std::{collections::HashMap, io, sync::Arc, time::Instant};
 
 use azalea_auth::game_profile::GameProfile;
+use azalea_chat::FormattedText;
 use azalea_core::game_type::GameMode;
 use azalea_entity::Dead;
-use azalea_protocol::packets::game::{
-    clientbound_player_abilities_packet::ClientboundPlayerAbilitiesPacket, ServerboundGamePacket,
+use azalea_protocol::packets::{
+    game::{
+        clientbound_boss_event_packet::{
+            self, BossBarColor, BossBarOverlay, Operation, Properties,
+        },
+        clientbound_player_abilities_packet::ClientboundPlayerAbilitiesPacket,
+        ServerboundGamePacket,
+    },
+    ConnectionProtocol,
 };
 use azalea_world::{Instance, PartialInstance};
 use bevy_ecs::{
@@ -12,7 +20,7 @@ use bevy_ecs::{
     system::Query,
 };
 use derive_more::{Deref, DerefMut};
-use log::error;
+use log::{debug, error, warn};
 use parking_lot::RwLock;
 use thiserror::Error;
 use tokio::sync::mpsc;
@@ -20,6 +28,7 @@ use uuid::Uuid;
 
 use crate::{
     events::{Event as AzaleaEvent, LocalPlayerEvents},
+    packet_rate_limit::PacketRateLimiter,
     raw_connection::RawConnection,
     ClientInformation, PlayerInfo,
 };
@@ -53,6 +62,12 @@ pub struct LocalGameMode {
     pub previous: Option<GameMode>,
 }
 
+/// Whether the server is in hardcore mode, where players are put in spectator
+/// mode instead of respawning after they die. This is only present on local
+/// players.
+#[derive(Component, Clone, Copy, Debug, Default, Deref, DerefMut)]
+pub struct Hardcore(pub bool);
+
 /// A component that contains the abilities the player has, like flying
 /// or instantly breaking blocks. This is only present on local players.
 #[derive(Clone, Debug, Component, Default)]
@@ -100,6 +115,94 @@ pub struct PermissionLevel(pub u8);
 #[derive(Component, Resource, Clone, Debug, Deref, DerefMut, Default)]
 pub struct TabList(HashMap<Uuid, PlayerInfo>);
 
+/// A component that contains the text that's shown above and below the tab
+/// list. Servers often put things like their name or how many players are
+/// online here.
+///
+/// The header and footer are empty if the server hasn't set them (or if it
+/// cleared them), in which case they aren't shown.
+///
+/// ```
+/// # fn example(client: &azalea_client::Client) {
+/// let (header, footer) = client.tab_list_header_footer();
+/// println!("{}\n...\n{}", header.to_ansi(), footer.to_ansi());
+/// # }
+/// ```
+#[derive(Component, Resource, Clone, Debug, Default)]
+pub struct TabListHeaderFooter {
+    pub header: FormattedText,
+    pub footer: FormattedText,
+}
+
+/// A component that contains the boss bars that the server is currently
+/// showing us, keyed by their UUID.
+///
+/// ```
+/// # fn example(client: &azalea_client::Client) {
+/// for boss_bar in client.boss_bars().values() {
+///     println!("{}: {:.0}%", boss_bar.title, boss_bar.progress * 100.);
+/// }
+/// # }
+/// ```
+#[derive(Component, Resource, Clone, Debug, Deref, DerefMut, Default)]
+pub struct BossBars(HashMap<Uuid, BossBar>);
+impl BossBars {
+    /// Update the boss bars from an operation in a
+    /// [`ClientboundBossEventPacket`].
+    ///
+    /// [`ClientboundBossEventPacket`]: clientbound_boss_event_packet::ClientboundBossEventPacket
+    pub fn handle_operation(&mut self, id: Uuid, operation: &Operation) {
+        if let Operation::Add(add) = operation {
+            self.insert(id, add.clone().into());
+            return;
+        }
+        if let Operation::Remove = operation {
+            self.remove(&id);
+            return;
+        }
+
+        let Some(boss_bar) = self.get_mut(&id) else {
+            warn!("Ignoring boss event for unknown boss bar {id}");
+            return;
+        };
+        match operation {
+            Operation::UpdateProgress(progress) => boss_bar.progress = *progress,
+            Operation::UpdateName(name) => boss_bar.title = name.clone(),
+            Operation::UpdateStyle(style) => {
+                boss_bar.color = style.color;
+                boss_bar.division = style.overlay;
+            }
+            Operation::UpdateProperties(properties) => boss_bar.flags = *properties,
+            // handled above
+            Operation::Add(_) | Operation::Remove => {}
+        }
+    }
+}
+
+/// A bar at the top of the screen that the server is showing us. Servers
+/// often use these for displaying information in minigames.
+#[derive(Clone, Debug)]
+pub struct BossBar {
+    pub title: FormattedText,
+    /// How full the bar is, from 0 to 1.
+    pub progress: f32,
+    pub color: BossBarColor,
+    /// How many notches the bar is divided into.
+    pub division: BossBarOverlay,
+    pub flags: Properties,
+}
+impl From<clientbound_boss_event_packet::AddOperation> for BossBar {
+    fn from(operation: clientbound_boss_event_packet::AddOperation) -> Self {
+        Self {
+            title: operation.name,
+            progress: operation.progress,
+            color: operation.style.color,
+            division: operation.style.overlay,
+            flags: operation.properties,
+        }
+    }
+}
+
 #[derive(Component, Clone)]
 pub struct Hunger {
     /// The main hunger bar. Goes from 0 to 20.
@@ -119,6 +222,88 @@ impl Default for Hunger {
     }
 }
 
+/// The player's experience, which is used for enchanting and repairing items.
+///
+/// This is updated whenever the server sends us a new value, like after
+/// picking up an experience orb or spending levels on an enchantment.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Experience {
+    /// The number shown above the experience bar.
+    pub level: u32,
+    /// How full the experience bar is, from 0 to 1.
+    pub progress: f32,
+    /// The total number of experience points that the player has collected
+    /// since they last died, including ones that were spent.
+    pub total: u32,
+}
+
+impl Experience {
+    /// The number of experience points that are needed to go from the current
+    /// level to the next one.
+    pub fn points_for_next_level(&self) -> u32 {
+        match self.level {
+            0..=15 => 2 * self.level + 7,
+            16..=30 => 5 * self.level - 38,
+            _ => 9 * self.level - 158,
+        }
+    }
+}
+
+/// An estimate of how many ticks per second the server is running at, based on
+/// how quickly the world's game time goes up.
+///
+/// This starts at 20 and gets updated every time the server sends us the time
+/// (which is usually once per second). Use [`Client::server_tps`] to get it.
+///
+/// Like [`TabList`], this is also a resource that has the estimate from the
+/// client that most recently got the time, which is useful in a swarm where
+/// every bot is on the same server.
+///
+/// [`Client::server_tps`]: crate::Client::server_tps
+#[derive(Component, Resource, Clone, Debug)]
+pub struct ServerTps {
+    /// The smoothed ticks per second.
+    pub tps: f32,
+    /// When we last got the game time and what it was.
+    last_time_update: Option<(Instant, u64)>,
+}
+
+impl ServerTps {
+    /// How much each new measurement affects the average, from 0 to 1.
+    const SMOOTHING: f32 = 0.3;
+
+    /// Update the estimate with a new game time from the server.
+    pub fn update(&mut self, game_time: u64) {
+        self.update_at(game_time, Instant::now());
+    }
+
+    fn update_at(&mut self, game_time: u64, now: Instant) {
+        if let Some((last_instant, last_game_time)) = self.last_time_update {
+            // if the game time didn't go up then time is frozen (or the server reset it),
+            // so we keep our last estimate instead of reporting 0
+            if game_time > last_game_time {
+                let elapsed = now.duration_since(last_instant).as_secs_f32();
+                if elapsed > 0. {
+                    // servers can do a few extra ticks to catch up after lagging, but that
+                    // doesn't mean they're running faster than 20 tps
+                    let measured_tps = ((game_time - last_game_time) as f32 / elapsed).min(20.);
+                    self.tps += (measured_tps - self.tps) * Self::SMOOTHING;
+                }
+            }
+        }
+        self.last_time_update = Some((now, game_time));
+    }
+}
+
+impl Default for ServerTps {
+    fn default() -> Self {
+        ServerTps {
+            tps: 20.,
+            last_time_update: None,
+        }
+    }
+}
+
 impl InstanceHolder {
     /// Create a new `InstanceHolder`.
     pub fn new(entity: Entity, world: Arc<RwLock<Instance>>) -> Self {
@@ -170,10 +355,25 @@ pub struct SendPacketEvent {
 
 pub fn handle_send_packet_event(
     mut send_packet_events: EventReader<SendPacketEvent>,
-    mut query: Query<&mut RawConnection>,
+    mut query: Query<(&RawConnection, Option<&mut PacketRateLimiter>)>,
 ) {
     for event in send_packet_events.iter() {
-        if let Ok(raw_connection) = query.get_mut(event.entity) {
+        if let Ok((raw_connection, rate_limiter)) = query.get_mut(event.entity) {
+            if let Some(mut rate_limiter) = rate_limiter {
+                if !rate_limiter.is_exempt(&event.packet) {
+                    rate_limiter.enqueue(event.packet.clone());
+                    continue;
+                }
+            }
+            if raw_connection.state() != ConnectionProtocol::Game {
+                // this happens when the server sends us back to the configuration state
+                debug!(
+                    "Not sending game packet because we're in the {:?} state: {:?}",
+                    raw_connection.state(),
+                    event.packet
+                );
+                continue;
+            }
             // debug!("Sending packet: {:?}", event.packet);
             if let Err(e) = raw_connection.write_packet(event.packet.clone()) {
                 error!("Failed to send packet: {e}");
@@ -181,3 +381,67 @@ pub fn handle_send_packet_event(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_server_tps_at_full_speed() {
+        let start = Instant::now();
+        let mut server_tps = ServerTps::default();
+        server_tps.update_at(1000, start);
+        server_tps.update_at(1020, start + Duration::from_secs(1));
+        assert_eq!(server_tps.tps, 20.);
+    }
+
+    #[test]
+    fn test_server_tps_lagging() {
+        let start = Instant::now();
+        let mut server_tps = ServerTps::default();
+        server_tps.update_at(1000, start);
+        // the first update doesn't have anything to compare to
+        assert_eq!(server_tps.tps, 20.);
+
+        // 10 ticks in a second, the estimate moves part of the way towards it
+        server_tps.update_at(1010, start + Duration::from_secs(1));
+        assert!((server_tps.tps - 17.).abs() < 0.001);
+
+        for i in 2..50 {
+            server_tps.update_at(1000 + i * 10, start + Duration::from_secs(i));
+        }
+        assert!((server_tps.tps - 10.).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_server_tps_catching_up_is_capped() {
+        let start = Instant::now();
+        let mut server_tps = ServerTps::default();
+        server_tps.update_at(1000, start);
+        server_tps.update_at(1060, start + Duration::from_secs(1));
+        assert_eq!(server_tps.tps, 20.);
+    }
+
+    #[test]
+    fn test_server_tps_frozen_time() {
+        let start = Instant::now();
+        let mut server_tps = ServerTps::default();
+        server_tps.update_at(1000, start);
+        server_tps.update_at(1010, start + Duration::from_secs(1));
+        let tps = server_tps.tps;
+
+        // the game time stops going up, which shouldn't look like the server
+        // stopped ticking
+        for i in 2..10 {
+            server_tps.update_at(1010, start + Duration::from_secs(i));
+        }
+        assert_eq!(server_tps.tps, tps);
+
+        // and once it starts again we measure from the last update instead of
+        // from when it froze
+        server_tps.update_at(1030, start + Duration::from_secs(10));
+        assert!(server_tps.tps > tps);
+    }
+}
@@ -0,0 +1,85 @@
+//! Answer the custom queries that servers send while we're logging in.
+//!
+//! Vanilla clients never answer these, but modded servers and proxies use them
+//! for things like Velocity's modern forwarding and Forge's mod list
+//! handshake.
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use azalea_core::resource_location::ResourceLocation;
+use azalea_protocol::ServerAddress;
+use bevy_ecs::system::Resource;
+
+use crate::Account;
+
+/// A login query that the server sent us.
+pub struct LoginQuery<'a> {
+    /// The channel that the query was sent on, like `velocity:player_info`.
+    pub identifier: &'a ResourceLocation,
+    pub data: &'a [u8],
+    /// The account that's logging in.
+    pub account: &'a Account,
+    /// The address that we're connecting to.
+    pub address: &'a ServerAddress,
+}
+
+/// A function that returns the data to answer a login query with, or `None`
+/// to tell the server that we don't understand it.
+pub type LoginQueryHandler = Arc<dyn Fn(&LoginQuery) -> Option<Vec<u8>> + Send + Sync>;
+
+/// The functions used to answer login queries, keyed by the query's
+/// identifier. Queries without a handler are answered with no data, like the
+/// vanilla client does.
+///
+/// ```
+/// # use azalea_client::login_query::LoginQueryHandlers;
+/// # use azalea_core::resource_location::ResourceLocation;
+/// # fn example(app: &mut bevy_app::App) {
+/// app.insert_resource(LoginQueryHandlers::default().with(
+///     ResourceLocation::new("velocity:player_info"),
+///     |query| {
+///         // sign the player's info with the forwarding secret here
+/// #       None
+///     },
+/// ));
+/// # }
+/// ```
+#[derive(Resource, Clone, Default)]
+pub struct LoginQueryHandlers {
+    handlers: HashMap<ResourceLocation, LoginQueryHandler>,
+}
+
+impl LoginQueryHandlers {
+    /// Answer queries with the given identifier using the handler, replacing
+    /// the previous handler for it if there was one.
+    pub fn insert(
+        &mut self,
+        identifier: ResourceLocation,
+        handler: impl Fn(&LoginQuery) -> Option<Vec<u8>> + Send + Sync + 'static,
+    ) {
+        self.handlers.insert(identifier, Arc::new(handler));
+    }
+
+    /// Like [`Self::insert`], but returns `self` so it can be chained.
+    #[must_use]
+    pub fn with(
+        mut self,
+        identifier: ResourceLocation,
+        handler: impl Fn(&LoginQuery) -> Option<Vec<u8>> + Send + Sync + 'static,
+    ) -> Self {
+        self.insert(identifier, handler);
+        self
+    }
+
+    /// Get the data that we should answer the query with.
+    pub fn answer(&self, query: &LoginQuery) -> Option<Vec<u8>> {
+        let handler = self.handlers.get(query.identifier)?;
+        handler(query)
+    }
+}
+
+impl fmt::Debug for LoginQueryHandlers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.handlers.keys()).finish()
+    }
+}
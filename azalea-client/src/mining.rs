@@ -3,8 +3,9 @@ use azalea_core::{direction::Direction, game_type::GameMode, position::BlockPos}
 use azalea_entity::{mining::get_mine_progress, FluidOnEyes, Physics};
 use azalea_inventory::ItemSlot;
 use azalea_physics::PhysicsSet;
-use azalea_protocol::packets::game::serverbound_player_action_packet::{
-    self, ServerboundPlayerActionPacket,
+use azalea_protocol::packets::game::{
+    serverbound_interact_packet::InteractionHand,
+    serverbound_player_action_packet::{self, ServerboundPlayerActionPacket},
 };
 use azalea_world::{InstanceContainer, InstanceName};
 use bevy_app::{App, FixedUpdate, Plugin, Update};
@@ -494,7 +495,10 @@ fn continue_mining_block(
                 }
                 .get(),
             });
-            swing_arm_events.send(SwingArmEvent { entity });
+            swing_arm_events.send(SwingArmEvent {
+                entity,
+                hand: InteractionHand::MainHand,
+            });
         } else if is_same_mining_target(
             mining.pos,
             inventory,
@@ -550,7 +554,10 @@ fn continue_mining_block(
                 position: mining.pos,
                 destroy_stage: mine_progress.destroy_stage(),
             });
-            swing_arm_events.send(SwingArmEvent { entity });
+            swing_arm_events.send(SwingArmEvent {
+                entity,
+                hand: InteractionHand::MainHand,
+            });
         } else {
             start_mining_events.send(StartMiningBlockWithDirectionEvent {
                 entity,
@@ -559,6 +566,9 @@ fn continue_mining_block(
             })
         }
 
-        swing_arm_events.send(SwingArmEvent { entity });
+        swing_arm_events.send(SwingArmEvent {
+            entity,
+            hand: InteractionHand::MainHand,
+        });
     }
 }
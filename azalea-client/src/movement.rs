@@ -1,7 +1,14 @@
 use crate::client::Client;
 use crate::local_player::SendPacketEvent;
-use azalea_entity::{metadata::Sprinting, Attributes, Jumping};
-use azalea_entity::{InLoadedChunk, LastSentPosition, LookDirection, Physics, Position};
+use azalea_core::position::Vec3;
+use azalea_entity::{
+    metadata::{ShiftKeyDown, Sprinting},
+    Attributes, Flying, Jumping,
+};
+use azalea_entity::{
+    view_vector, InLoadedChunk, LastSentPosition, LocalEntity, LookDirection, Physics, Position,
+    Vehicle,
+};
 use azalea_physics::{ai_step, PhysicsSet};
 use azalea_protocol::packets::game::serverbound_player_command_packet::ServerboundPlayerCommandPacket;
 use azalea_protocol::packets::game::{
@@ -14,9 +21,14 @@ use azalea_world::{MinecraftEntityId, MoveEntityError};
 use bevy_app::{App, FixedUpdate, Plugin, Update};
 use bevy_ecs::prelude::{Event, EventWriter};
 use bevy_ecs::{
-    component::Component, entity::Entity, event::EventReader, query::With,
-    schedule::IntoSystemConfigs, system::Query,
+    component::Component,
+    entity::Entity,
+    event::EventReader,
+    query::{With, Without},
+    schedule::IntoSystemConfigs,
+    system::{Commands, Query},
 };
+use derive_more::{Deref, DerefMut};
 use std::backtrace::Backtrace;
 use thiserror::Error;
 
@@ -43,16 +55,31 @@ pub struct PlayerMovePlugin;
 impl Plugin for PlayerMovePlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<StartWalkEvent>()
+            .add_event::<StartTimedWalkEvent>()
+            .add_event::<TimedWalkFinishedEvent>()
             .add_event::<StartSprintEvent>()
-            .add_systems(Update, (sprint_listener, walk_listener).chain())
+            .add_event::<ApplyImpulseEvent>()
+            .add_event::<KnockbackEvent>()
+            .add_systems(
+                Update,
+                (
+                    sprint_listener,
+                    walk_listener,
+                    timed_walk_listener,
+                    handle_apply_impulse_event,
+                )
+                    .chain(),
+            )
             .add_systems(
                 FixedUpdate,
                 (
+                    tick_timed_walk.before(PhysicsSet),
                     (tick_controls, local_player_ai_step)
                         .chain()
                         .in_set(PhysicsSet)
                         .before(ai_step),
-                    send_sprinting_if_needed.after(azalea_entity::update_in_loaded_chunk),
+                    (send_sprinting_if_needed, send_sneaking_if_needed)
+                        .after(azalea_entity::update_in_loaded_chunk),
                     send_position.after(PhysicsSet),
                 )
                     .chain(),
@@ -79,6 +106,24 @@ impl Client {
         **jumping_ref
     }
 
+    /// Set whether we're sneaking. This acts as if you held shift in vanilla,
+    /// so we move slower and won't walk off the edges of blocks.
+    ///
+    /// The server is told about it on the next tick. Sneaking stops when we
+    /// respawn.
+    pub fn set_sneaking(&mut self, sneaking: bool) {
+        let mut ecs = self.ecs.lock();
+        let mut shift_key_down = self.query::<&mut ShiftKeyDown>(&mut ecs);
+        **shift_key_down = sneaking;
+    }
+
+    /// Returns whether we're currently sneaking.
+    pub fn sneaking(&self) -> bool {
+        let mut ecs = self.ecs.lock();
+        let shift_key_down = self.query::<&ShiftKeyDown>(&mut ecs);
+        **shift_key_down
+    }
+
     /// Sets the direction the client is looking. `y_rot` is yaw (looking to the
     /// side), `x_rot` is pitch (looking up and down). You can get these
     /// numbers from the vanilla f3 screen.
@@ -89,6 +134,53 @@ impl Client {
 
         (look_direction.y_rot, look_direction.x_rot) = (y_rot, x_rot);
     }
+
+    /// Get the direction the client is looking as `(y_rot, x_rot)`, which is
+    /// the same order that [`Self::set_direction`] takes them in.
+    ///
+    /// This is the direction that we last sent to the server, so it doesn't
+    /// change until the tick after we call `set_direction` or send a
+    /// `LookAtEvent`. The direction that we'll send next is in the
+    /// [`LookDirection`] component.
+    pub fn look_direction(&self) -> (f32, f32) {
+        let last_sent_direction = self.component::<LastSentLookDirection>();
+        (last_sent_direction.y_rot, last_sent_direction.x_rot)
+    }
+
+    /// Get a vector with a length of 1 that points in the direction the client
+    /// is looking.
+    ///
+    /// Like [`Self::look_direction`], this is the direction that we last sent
+    /// to the server.
+    pub fn look_vector(&self) -> Vec3 {
+        let (y_rot, x_rot) = self.look_direction();
+        view_vector(&LookDirection { x_rot, y_rot })
+    }
+
+    /// Get our current velocity, in blocks per tick.
+    ///
+    /// This is a shortcut for `bot.component::<Physics>().delta`.
+    pub fn velocity(&self) -> Vec3 {
+        self.component::<Physics>().delta
+    }
+
+    /// Replace our velocity, in blocks per tick. This takes effect on the next
+    /// physics tick.
+    pub fn set_velocity(&mut self, velocity: Vec3) {
+        let mut ecs = self.ecs.lock();
+        let mut physics = self.query::<&mut Physics>(&mut ecs);
+        physics.delta = velocity;
+    }
+
+    /// Add to our velocity, like the knockback from an explosion would.
+    ///
+    /// This is a shortcut for sending an [`ApplyImpulseEvent`].
+    pub fn apply_impulse(&mut self, impulse: Vec3) {
+        self.ecs.lock().send_event(ApplyImpulseEvent {
+            entity: self.entity,
+            impulse,
+        });
+    }
 }
 
 /// A component that contains the look direction that was last sent over the
@@ -99,6 +191,15 @@ pub struct LastSentLookDirection {
     pub y_rot: f32,
 }
 
+/// A component that makes us tell the server that we're on the ground in our
+/// movement packets when it's true, even if we're not actually on the ground.
+///
+/// This is cheating and will get you banned on servers with an anticheat. It's
+/// used by the `NoFallPlugin` in the `azalea` crate, and it doesn't affect our
+/// own physics.
+#[derive(Debug, Component, Clone, Default, Deref, DerefMut)]
+pub struct SpoofOnGround(pub bool);
+
 /// Component for entities that can move and sprint. Usually only in
 /// [`LocalPlayer`] entities.
 #[derive(Default, Component, Clone)]
@@ -107,6 +208,10 @@ pub struct PhysicsState {
     /// player moved enough. This is that tick counter.
     pub position_remainder: u32,
     pub was_sprinting: bool,
+    /// Whether the server thinks we're sneaking. This is compared to
+    /// [`ShiftKeyDown`] to tell when we need to tell it that we started or
+    /// stopped.
+    pub was_sneaking: bool,
     // Whether we're going to try to start sprinting this tick. Equivalent to
     // holding down ctrl for a tick.
     pub trying_to_sprint: bool,
@@ -127,8 +232,10 @@ pub fn send_position(
             &mut LastSentPosition,
             &mut Physics,
             &mut LastSentLookDirection,
+            Option<&SpoofOnGround>,
         ),
-        With<InLoadedChunk>,
+        // while riding something, the vehicle module sends our input instead
+        (With<InLoadedChunk>, Without<Vehicle>),
     >,
     mut send_packet_events: EventWriter<SendPacketEvent>,
 ) {
@@ -140,8 +247,13 @@ pub fn send_position(
         mut last_sent_position,
         mut physics,
         mut last_direction,
+        spoof_on_ground,
     ) in query.iter_mut()
     {
+        // this only changes what we tell the server, our physics still use the
+        // real value
+        let on_ground = physics.on_ground || spoof_on_ground.is_some_and(|spoof| **spoof);
+
         let packet = {
             // TODO: the camera being able to be controlled by other entities isn't
             // implemented yet if !self.is_controlled_camera() { return };
@@ -161,9 +273,6 @@ pub fn send_position(
                 || physics_state.position_remainder >= 20;
             let sending_direction = y_rot_delta != 0.0 || x_rot_delta != 0.0;
 
-            // if self.is_passenger() {
-            //   TODO: posrot packet for being a passenger
-            // }
             let packet = if sending_position && sending_direction {
                 Some(
                     ServerboundMovePlayerPosRotPacket {
@@ -172,7 +281,7 @@ pub fn send_position(
                         z: position.z,
                         x_rot: direction.x_rot,
                         y_rot: direction.y_rot,
-                        on_ground: physics.on_ground,
+                        on_ground,
                     }
                     .get(),
                 )
@@ -182,7 +291,7 @@ pub fn send_position(
                         x: position.x,
                         y: position.y,
                         z: position.z,
-                        on_ground: physics.on_ground,
+                        on_ground,
                     }
                     .get(),
                 )
@@ -191,17 +300,12 @@ pub fn send_position(
                     ServerboundMovePlayerRotPacket {
                         x_rot: direction.x_rot,
                         y_rot: direction.y_rot,
-                        on_ground: physics.on_ground,
+                        on_ground,
                     }
                     .get(),
                 )
             } else if physics.last_on_ground != physics.on_ground {
-                Some(
-                    ServerboundMovePlayerStatusOnlyPacket {
-                        on_ground: physics.on_ground,
-                    }
-                    .get(),
-                )
+                Some(ServerboundMovePlayerStatusOnlyPacket { on_ground }.get())
             } else {
                 None
             };
@@ -253,11 +357,44 @@ fn send_sprinting_if_needed(
     }
 }
 
+fn send_sneaking_if_needed(
+    mut query: Query<(Entity, &MinecraftEntityId, &ShiftKeyDown, &mut PhysicsState)>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for (entity, minecraft_entity_id, shift_key_down, mut physics_state) in query.iter_mut() {
+        if **shift_key_down != physics_state.was_sneaking {
+            let sneaking_action = if **shift_key_down {
+                azalea_protocol::packets::game::serverbound_player_command_packet::Action::PressShiftKey
+            } else {
+                azalea_protocol::packets::game::serverbound_player_command_packet::Action::ReleaseShiftKey
+            };
+            send_packet_events.send(SendPacketEvent {
+                entity,
+                packet: ServerboundPlayerCommandPacket {
+                    id: **minecraft_entity_id,
+                    action: sneaking_action,
+                    data: 0,
+                }
+                .get(),
+            });
+            physics_state.was_sneaking = **shift_key_down;
+        }
+    }
+}
+
+/// How much slower we move while sneaking.
+pub const SNEAKING_SPEED_MULTIPLIER: f32 = 0.3;
+
 /// Update the impulse from self.move_direction. The multipler is used for
 /// sneaking.
-pub(crate) fn tick_controls(mut query: Query<&mut PhysicsState>) {
-    for mut physics_state in query.iter_mut() {
-        let multiplier: Option<f32> = None;
+pub(crate) fn tick_controls(
+    mut query: Query<(&mut PhysicsState, Option<&ShiftKeyDown>, Option<&Flying>)>,
+) {
+    for (mut physics_state, shift_key_down, flying) in query.iter_mut() {
+        // TODO: swift sneak and crawling
+        // sneaking while flying makes us fly down instead of slowing us down
+        let multiplier: Option<f32> = (shift_key_down.is_some_and(|s| **s) && flying.is_none())
+            .then_some(SNEAKING_SPEED_MULTIPLIER);
 
         let mut forward_impulse: f32 = 0.;
         let mut left_impulse: f32 = 0.;
@@ -355,6 +492,30 @@ impl Client {
         });
     }
 
+    /// Walk in the given direction for a number of ticks and then stop. A
+    /// [`TimedWalkFinishedEvent`] is sent when we stop.
+    ///
+    /// Calling [`Client::walk`], [`Client::sprint`], or this again before
+    /// we're done walking replaces the timed walk.
+    ///
+    /// # Examples
+    ///
+    /// Step forward for 3 ticks
+    /// ```rust,no_run
+    /// # use azalea_client::{Client, WalkDirection};
+    /// # fn example(mut bot: Client) {
+    /// bot.walk_for(WalkDirection::Forward, 3);
+    /// # }
+    /// ```
+    pub fn walk_for(&mut self, direction: WalkDirection, ticks: u32) {
+        let mut ecs = self.ecs.lock();
+        ecs.send_event(StartTimedWalkEvent {
+            entity: self.entity,
+            direction,
+            ticks,
+        });
+    }
+
     /// Start sprinting in the given direction. To stop moving, call
     /// [`Client::walk(WalkDirection::None)`]
     ///
@@ -393,6 +554,7 @@ pub struct StartWalkEvent {
 /// [`Client::sprint`]. To stop walking, call walk with
 /// `WalkDirection::None`.
 pub fn walk_listener(
+    mut commands: Commands,
     mut events: EventReader<StartWalkEvent>,
     mut query: Query<(&mut PhysicsState, &mut Sprinting, &mut Attributes)>,
 ) {
@@ -402,10 +564,104 @@ pub fn walk_listener(
             physics_state.move_direction = event.direction;
             physics_state.trying_to_sprint = false;
             set_sprinting(false, &mut sprinting, &mut attributes);
+            // walking normally overrides a timed walk
+            commands.entity(event.entity).remove::<TimedWalk>();
         }
     }
 }
 
+/// An event sent to make the client walk in a direction for a number of ticks
+/// and then stop. When it stops, a [`TimedWalkFinishedEvent`] is sent.
+///
+/// Sending a [`StartWalkEvent`], [`StartSprintEvent`], or another one of these
+/// before the walk is done replaces it.
+#[derive(Event, Debug)]
+pub struct StartTimedWalkEvent {
+    pub entity: Entity,
+    pub direction: WalkDirection,
+    pub ticks: u32,
+}
+
+/// An event sent when a walk that was started with [`StartTimedWalkEvent`]
+/// finishes. This isn't sent if the walk was replaced before it finished.
+#[derive(Event, Debug, Clone)]
+pub struct TimedWalkFinishedEvent {
+    pub entity: Entity,
+}
+
+/// A component present on clients that are walking for a set number of ticks
+/// because of a [`StartTimedWalkEvent`].
+#[derive(Component, Clone, Debug)]
+pub struct TimedWalk {
+    pub direction: WalkDirection,
+    /// How many more ticks we're going to walk for.
+    pub remaining_ticks: u32,
+}
+
+pub fn timed_walk_listener(
+    mut commands: Commands,
+    mut events: EventReader<StartTimedWalkEvent>,
+    mut query: Query<(&mut PhysicsState, &mut Sprinting, &mut Attributes)>,
+) {
+    for event in events.iter() {
+        if let Ok((mut physics_state, mut sprinting, mut attributes)) = query.get_mut(event.entity)
+        {
+            physics_state.move_direction = event.direction;
+            physics_state.trying_to_sprint = false;
+            set_sprinting(false, &mut sprinting, &mut attributes);
+            commands.entity(event.entity).insert(TimedWalk {
+                direction: event.direction,
+                remaining_ticks: event.ticks,
+            });
+        }
+    }
+}
+
+/// Count down the ticks left in every [`TimedWalk`], and stop walking once
+/// there's none left.
+pub fn tick_timed_walk(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut TimedWalk, &mut PhysicsState)>,
+    mut timed_walk_finished_events: EventWriter<TimedWalkFinishedEvent>,
+) {
+    for (entity, mut timed_walk, mut physics_state) in &mut query {
+        if timed_walk.remaining_ticks == 0 {
+            physics_state.move_direction = WalkDirection::None;
+            commands.entity(entity).remove::<TimedWalk>();
+            timed_walk_finished_events.send(TimedWalkFinishedEvent { entity });
+        } else {
+            timed_walk.remaining_ticks -= 1;
+        }
+    }
+}
+
+/// An event that adds to the velocity (delta) of a local player.
+#[derive(Event, Debug, Clone)]
+pub struct ApplyImpulseEvent {
+    pub entity: Entity,
+    pub impulse: Vec3,
+}
+pub fn handle_apply_impulse_event(
+    mut events: EventReader<ApplyImpulseEvent>,
+    mut query: Query<&mut Physics, With<LocalEntity>>,
+) {
+    for event in events.iter() {
+        if let Ok(mut physics) = query.get_mut(event.entity) {
+            physics.delta += event.impulse;
+        }
+    }
+}
+
+/// An event sent when the server changes the velocity of a local player, like
+/// when we get hit by a mob or caught in an explosion.
+///
+/// `velocity` is our new velocity after the knockback was applied.
+#[derive(Event, Debug, Clone)]
+pub struct KnockbackEvent {
+    pub entity: Entity,
+    pub velocity: Vec3,
+}
+
 /// An event sent when the client starts sprinting. This does not get sent for
 /// non-local entities.
 #[derive(Event)]
@@ -415,6 +671,7 @@ pub struct StartSprintEvent {
 }
 /// Start sprinting in the given direction.
 pub fn sprint_listener(
+    mut commands: Commands,
     mut query: Query<&mut PhysicsState>,
     mut events: EventReader<StartSprintEvent>,
 ) {
@@ -422,6 +679,7 @@ pub fn sprint_listener(
         if let Ok(mut physics_state) = query.get_mut(event.entity) {
             physics_state.move_direction = WalkDirection::from(event.direction);
             physics_state.trying_to_sprint = true;
+            commands.entity(event.entity).remove::<TimedWalk>();
         }
     }
 }
@@ -429,7 +687,7 @@ pub fn sprint_listener(
 /// Change whether we're sprinting by adding an attribute modifier to the
 /// player. You should use the [`walk`] and [`sprint`] methods instead.
 /// Returns if the operation was successful.
-fn set_sprinting(
+pub(crate) fn set_sprinting(
     sprinting: bool,
     currently_sprinting: &mut Sprinting,
     attributes: &mut Attributes,
@@ -457,7 +715,7 @@ fn has_enough_impulse_to_start_sprinting(physics_state: &PhysicsState) -> bool {
     // }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum WalkDirection {
     #[default]
     None,
@@ -488,3 +746,184 @@ impl From<SprintDirection> for WalkDirection {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use azalea_auth::game_profile::GameProfile;
+    use azalea_entity::attributes::AttributeInstance;
+    use bevy_ecs::{event::Events, world::World};
+    use parking_lot::Mutex;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_look_vector_matches_look_direction() {
+        let mut ecs = World::new();
+        let entity = ecs
+            .spawn((
+                // we called set_direction but haven't sent it yet
+                LookDirection {
+                    x_rot: 0.,
+                    y_rot: 90.,
+                },
+                LastSentLookDirection {
+                    x_rot: 90.,
+                    y_rot: 0.,
+                },
+            ))
+            .id();
+        let client = Client::new(
+            GameProfile::new(Uuid::nil(), "bot".to_string()),
+            entity,
+            Arc::new(Mutex::new(ecs)),
+            mpsc::unbounded_channel().0,
+        );
+
+        assert_eq!(client.look_direction(), (0., 90.));
+        // straight down, since that's what we last sent
+        let look_vector = client.look_vector();
+        assert!(look_vector.x.abs() < 1e-6);
+        assert!((look_vector.y + 1.).abs() < 1e-6);
+        assert!(look_vector.z.abs() < 1e-6);
+    }
+
+    fn timed_walk_app() -> (App, Entity) {
+        let mut app = App::new();
+        app.add_event::<StartWalkEvent>()
+            .add_event::<StartTimedWalkEvent>()
+            .add_event::<TimedWalkFinishedEvent>()
+            .add_event::<StartSprintEvent>()
+            .add_systems(
+                Update,
+                (sprint_listener, walk_listener, timed_walk_listener).chain(),
+            )
+            .add_systems(FixedUpdate, tick_timed_walk);
+        let entity = app
+            .world
+            .spawn((
+                PhysicsState::default(),
+                Sprinting(false),
+                Attributes {
+                    speed: AttributeInstance::new(0.1),
+                    attack_speed: AttributeInstance::new(4.),
+                },
+            ))
+            .id();
+        (app, entity)
+    }
+
+    /// Run a tick and return the direction that we'd move in for it.
+    fn tick(app: &mut App, entity: Entity) -> WalkDirection {
+        app.world.run_schedule(FixedUpdate);
+        app.world
+            .get::<PhysicsState>(entity)
+            .unwrap()
+            .move_direction
+    }
+
+    fn finished_walks(app: &mut App) -> usize {
+        app.world
+            .resource_mut::<Events<TimedWalkFinishedEvent>>()
+            .drain()
+            .count()
+    }
+
+    #[test]
+    fn test_timed_walk_stops_after_ticks() {
+        let (mut app, entity) = timed_walk_app();
+        app.world.send_event(StartTimedWalkEvent {
+            entity,
+            direction: WalkDirection::Forward,
+            ticks: 3,
+        });
+        app.update();
+
+        for _ in 0..3 {
+            assert_eq!(tick(&mut app, entity), WalkDirection::Forward);
+        }
+        assert_eq!(finished_walks(&mut app), 0);
+        assert_eq!(tick(&mut app, entity), WalkDirection::None);
+        assert!(app.world.get::<TimedWalk>(entity).is_none());
+        assert_eq!(finished_walks(&mut app), 1);
+
+        // and it only finishes once
+        tick(&mut app, entity);
+        assert_eq!(finished_walks(&mut app), 0);
+    }
+
+    #[test]
+    fn test_walk_replaces_timed_walk() {
+        let (mut app, entity) = timed_walk_app();
+        app.world.send_event(StartTimedWalkEvent {
+            entity,
+            direction: WalkDirection::Forward,
+            ticks: 2,
+        });
+        app.update();
+        tick(&mut app, entity);
+
+        app.world.send_event(StartWalkEvent {
+            entity,
+            direction: WalkDirection::Left,
+        });
+        app.update();
+        for _ in 0..5 {
+            assert_eq!(tick(&mut app, entity), WalkDirection::Left);
+        }
+        assert_eq!(finished_walks(&mut app), 0);
+    }
+
+    #[test]
+    fn test_sprint_replaces_timed_walk() {
+        let (mut app, entity) = timed_walk_app();
+        app.world.send_event(StartTimedWalkEvent {
+            entity,
+            direction: WalkDirection::Backward,
+            ticks: 2,
+        });
+        app.update();
+
+        app.world.send_event(StartSprintEvent {
+            entity,
+            direction: SprintDirection::Forward,
+        });
+        app.update();
+        for _ in 0..5 {
+            assert_eq!(tick(&mut app, entity), WalkDirection::Forward);
+        }
+        assert!(
+            app.world
+                .get::<PhysicsState>(entity)
+                .unwrap()
+                .trying_to_sprint
+        );
+        assert_eq!(finished_walks(&mut app), 0);
+    }
+
+    #[test]
+    fn test_timed_walk_replaces_timed_walk() {
+        let (mut app, entity) = timed_walk_app();
+        app.world.send_event(StartTimedWalkEvent {
+            entity,
+            direction: WalkDirection::Forward,
+            ticks: 1,
+        });
+        app.update();
+        app.world.send_event(StartTimedWalkEvent {
+            entity,
+            direction: WalkDirection::Right,
+            ticks: 4,
+        });
+        app.update();
+
+        for _ in 0..4 {
+            assert_eq!(tick(&mut app, entity), WalkDirection::Right);
+        }
+        assert_eq!(tick(&mut app, entity), WalkDirection::None);
+        assert_eq!(finished_walks(&mut app), 1);
+    }
+}
@@ -2,6 +2,7 @@ use std::io::Cursor;
 use std::sync::Arc;
 
 use azalea_entity::indexing::EntityIdIndex;
+use azalea_entity::LoadedBy;
 use azalea_protocol::packets::configuration::serverbound_finish_configuration_packet::ServerboundFinishConfigurationPacket;
 use azalea_protocol::packets::configuration::serverbound_keep_alive_packet::ServerboundKeepAlivePacket;
 use azalea_protocol::packets::configuration::serverbound_pong_packet::ServerboundPongPacket;
@@ -15,11 +16,15 @@ use bevy_ecs::system::SystemState;
 use log::{debug, error, warn};
 use parking_lot::RwLock;
 
+use crate::brand::{read_brand_payload, ServerBrand};
 use crate::client::InConfigurationState;
 use crate::disconnect::DisconnectEvent;
-use crate::local_player::Hunger;
-use crate::packet_handling::game::KeepAliveEvent;
+use crate::local_player::{BossBars, Experience, Hunger, TabList, TabListHeaderFooter};
+use crate::packet_handling::game::{KeepAliveEvent, ResourcePackEvent};
+use crate::packet_handling::ProtocolStateChangedEvent;
 use crate::raw_connection::RawConnection;
+use crate::resource_pack::ResourcePackPolicy;
+use crate::scoreboard::Scoreboard;
 use crate::ReceivedRegistries;
 
 #[derive(Event, Debug, Clone)]
@@ -42,7 +47,9 @@ pub fn send_packet_events(
         let packets_lock = raw_connection.incoming_packet_queue();
         let mut packets = packets_lock.lock();
         if !packets.is_empty() {
+            let mut packets_read = 0;
             for raw_packet in packets.iter() {
+                packets_read += 1;
                 let packet = match deserialize_packet::<ClientboundConfigurationPacket>(
                     &mut Cursor::new(raw_packet),
                 ) {
@@ -52,13 +59,22 @@ pub fn send_packet_events(
                         continue;
                     }
                 };
+                let is_finish_configuration = matches!(
+                    packet,
+                    ClientboundConfigurationPacket::FinishConfiguration(_)
+                );
                 packet_events.send(PacketEvent {
                     entity: player_entity,
                     packet: packet.clone(),
                 });
+                if is_finish_configuration {
+                    // the packets after this are in the game state, so they're read by
+                    // game::send_packet_events after we switch states
+                    break;
+                }
             }
-            // clear the packets right after we read them
-            packets.clear();
+            // remove the packets right after we read them
+            packets.drain(..packets_read);
         }
     }
 }
@@ -95,6 +111,10 @@ pub fn process_packet_events(ecs: &mut World) {
 
             ClientboundConfigurationPacket::CustomPayload(p) => {
                 debug!("Got custom payload packet {p:?}");
+
+                if let Some(brand) = read_brand_payload(&p.identifier, &p.data) {
+                    ecs.entity_mut(player_entity).insert(ServerBrand(brand));
+                }
             }
             ClientboundConfigurationPacket::Disconnect(p) => {
                 warn!("Got disconnect packet {p:?}");
@@ -103,6 +123,7 @@ pub fn process_packet_events(ecs: &mut World) {
                 let mut disconnect_events = system_state.get_mut(ecs);
                 disconnect_events.send(DisconnectEvent {
                     entity: player_entity,
+                    reason: Some(p.reason.clone()),
                 });
             }
             ClientboundConfigurationPacket::FinishConfiguration(p) => {
@@ -113,43 +134,19 @@ pub fn process_packet_events(ecs: &mut World) {
                 let mut query = system_state.get_mut(ecs);
                 let mut raw_connection = query.get_mut(player_entity).unwrap();
 
-                let instance_holder = crate::local_player::InstanceHolder::new(
-                    player_entity,
-                    // default to an empty world, it'll be set correctly later when we
-                    // get the login packet
-                    Arc::new(RwLock::new(Instance::default())),
-                );
-
                 raw_connection
                     .write_packet(ServerboundFinishConfigurationPacket {}.get())
                     .expect(
                         "we should be in the right state and encoding this packet shouldn't fail",
                     );
                 raw_connection.set_state(ConnectionProtocol::Game);
+                ecs.send_event(ProtocolStateChangedEvent {
+                    entity: player_entity,
+                    from: ConnectionProtocol::Configuration,
+                    to: ConnectionProtocol::Game,
+                });
 
-                // these components are added now that we're going to be in the Game state
-                ecs.entity_mut(player_entity)
-                    .remove::<InConfigurationState>()
-                    .insert(crate::JoinedClientBundle {
-                        instance_holder,
-                        physics_state: crate::PhysicsState::default(),
-                        inventory: crate::inventory::InventoryComponent::default(),
-                        client_information: crate::ClientInformation::default(),
-                        tab_list: crate::local_player::TabList::default(),
-                        current_sequence_number: crate::interact::CurrentSequenceNumber::default(),
-                        last_sent_direction: crate::movement::LastSentLookDirection::default(),
-                        abilities: crate::local_player::PlayerAbilities::default(),
-                        permission_level: crate::local_player::PermissionLevel::default(),
-                        hunger: Hunger::default(),
-                        chunk_batch_info: crate::chunk_batching::ChunkBatchInfo::default(),
-
-                        entity_id_index: EntityIdIndex::default(),
-
-                        mining: crate::mining::MineBundle::default(),
-                        attack: crate::attack::AttackBundle::default(),
-
-                        _local_entity: azalea_entity::LocalEntity,
-                    });
+                enter_game_state(ecs, player_entity);
             }
             ClientboundConfigurationPacket::KeepAlive(p) => {
                 debug!("Got keep alive packet (in configuration) {p:?} for {player_entity:?}");
@@ -183,14 +180,28 @@ pub fn process_packet_events(ecs: &mut World) {
             ClientboundConfigurationPacket::ResourcePack(p) => {
                 debug!("Got resource pack packet {p:?}");
 
-                let mut system_state: SystemState<Query<&RawConnection>> = SystemState::new(ecs);
-                let mut query = system_state.get_mut(ecs);
-                let raw_connection = query.get_mut(player_entity).unwrap();
+                let mut system_state: SystemState<(
+                    Query<&RawConnection>,
+                    Res<ResourcePackPolicy>,
+                    EventWriter<ResourcePackEvent>,
+                )> = SystemState::new(ecs);
+                let (query, policy, mut resource_pack_events) = system_state.get_mut(ecs);
+                let raw_connection = query.get(player_entity).unwrap();
 
-                // always accept resource pack
-                raw_connection.write_packet(
-                    ServerboundResourcePackPacket { action: azalea_protocol::packets::configuration::serverbound_resource_pack_packet::Action::Accepted }.get()
-                ).unwrap();
+                for action in policy.configuration_actions() {
+                    raw_connection
+                        .write_packet(ServerboundResourcePackPacket { action }.get())
+                        .unwrap();
+                }
+                resource_pack_events.send(ResourcePackEvent {
+                    entity: player_entity,
+                    url: p.url,
+                    hash: p.hash,
+                    required: p.required,
+                    prompt: p.prompt,
+                });
+
+                system_state.apply(ecs);
             }
             ClientboundConfigurationPacket::UpdateEnabledFeatures(p) => {
                 debug!("Got update enabled features packet {p:?}");
@@ -201,3 +212,160 @@ pub fn process_packet_events(ecs: &mut World) {
         }
     }
 }
+
+/// Go back to the configuration state after the server sent us a
+/// `StartConfiguration` packet while we were in the game.
+///
+/// We keep our entity and its components, but anything that's specific to the
+/// server that we were on is reset, since a proxy might be moving us to another
+/// server. This is the same state that vanilla clears.
+pub(crate) fn enter_configuration_state(ecs: &mut World, player_entity: Entity) {
+    // the entities from the old server aren't loaded by us anymore, and their
+    // ids could belong to different entities on the new server
+    let mut loaded_by_query = ecs.query::<(Entity, &mut LoadedBy)>();
+    for (entity, mut loaded_by) in loaded_by_query.iter_mut(ecs) {
+        if entity != player_entity && loaded_by.contains(&player_entity) {
+            loaded_by.remove(&player_entity);
+        }
+    }
+
+    ecs.entity_mut(player_entity).insert((
+        InConfigurationState,
+        TabList::default(),
+        TabListHeaderFooter::default(),
+        BossBars::default(),
+        Scoreboard::default(),
+        EntityIdIndex::default(),
+    ));
+}
+
+/// Add the components that local players have in the game state, now that the
+/// server is done configuring us.
+fn enter_game_state(ecs: &mut World, player_entity: Entity) {
+    let mut player = ecs.entity_mut(player_entity);
+    player.remove::<InConfigurationState>();
+    // these components are added the first time that we go into the Game state.
+    // if the server reconfigured us while we were in the game (like when a proxy
+    // moves us to another server), we already have them, and the ones that were
+    // specific to the old server were reset by `enter_configuration_state`
+    if !player.contains::<azalea_entity::LocalEntity>() {
+        let instance_holder = crate::local_player::InstanceHolder::new(
+            player_entity,
+            // default to an empty world, it'll be set correctly later when we
+            // get the login packet
+            Arc::new(RwLock::new(Instance::default())),
+        );
+        player.insert(crate::JoinedClientBundle {
+            instance_holder,
+            physics_state: crate::PhysicsState::default(),
+            inventory: crate::inventory::InventoryComponent::default(),
+            tab_list: TabList::default(),
+            tab_list_header_footer: TabListHeaderFooter::default(),
+            boss_bars: BossBars::default(),
+            scoreboard: Scoreboard::default(),
+            current_sequence_number: crate::interact::CurrentSequenceNumber::default(),
+            reach: crate::interact::Reach::default(),
+            last_sent_direction: crate::movement::LastSentLookDirection::default(),
+            abilities: crate::local_player::PlayerAbilities::default(),
+            permission_level: crate::local_player::PermissionLevel::default(),
+            hunger: Hunger::default(),
+            experience: Experience::default(),
+            active_effects: azalea_entity::effects::ActiveEffects::default(),
+            server_tps: crate::local_player::ServerTps::default(),
+            world_time: crate::world_time::WorldTime::default(),
+            weather: crate::world_time::Weather::default(),
+            chunk_batch_info: crate::chunk_batching::ChunkBatchInfo::default(),
+
+            entity_id_index: EntityIdIndex::default(),
+
+            mining: crate::mining::MineBundle::default(),
+            attack: crate::attack::AttackBundle::default(),
+
+            _local_entity: azalea_entity::LocalEntity,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use azalea_auth::game_profile::GameProfile;
+    use azalea_core::game_type::GameMode;
+    use azalea_protocol::packets::game::clientbound_set_display_objective_packet::DisplaySlot;
+    use azalea_world::MinecraftEntityId;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::PlayerInfo;
+
+    #[test]
+    fn test_reconfiguration_keeps_components() {
+        let mut ecs = World::new();
+        let player_entity = ecs.spawn(InConfigurationState).id();
+
+        enter_game_state(&mut ecs, player_entity);
+        assert!(!ecs.entity(player_entity).contains::<InConfigurationState>());
+        ecs.get_mut::<Experience>(player_entity).unwrap().level = 30;
+
+        // the server sends us back to the configuration state and then to the game
+        // state again
+        enter_configuration_state(&mut ecs, player_entity);
+        assert!(ecs.entity(player_entity).contains::<InConfigurationState>());
+        enter_game_state(&mut ecs, player_entity);
+        assert!(!ecs.entity(player_entity).contains::<InConfigurationState>());
+        assert_eq!(ecs.get::<Experience>(player_entity).unwrap().level, 30);
+    }
+
+    #[test]
+    fn test_reconfiguration_clears_server_state() {
+        let mut ecs = World::new();
+        let player_entity = ecs.spawn(InConfigurationState).id();
+        enter_game_state(&mut ecs, player_entity);
+
+        // the old server told us about another player
+        let other_entity = ecs.spawn(LoadedBy(HashSet::from([player_entity]))).id();
+        ecs.get_mut::<EntityIdIndex>(player_entity)
+            .unwrap()
+            .insert(MinecraftEntityId(5), other_entity);
+        let uuid = Uuid::from_u128(1);
+        ecs.get_mut::<TabList>(player_entity).unwrap().insert(
+            uuid,
+            PlayerInfo {
+                profile: GameProfile::new(uuid, "other".to_string()),
+                uuid,
+                gamemode: GameMode::Survival,
+                latency: 0,
+                display_name: None,
+            },
+        );
+        ecs.get_mut::<TabListHeaderFooter>(player_entity)
+            .unwrap()
+            .header = "old server".into();
+        ecs.get_mut::<Scoreboard>(player_entity)
+            .unwrap()
+            .display_slots
+            .insert(DisplaySlot::Sidebar, "kills".to_string());
+
+        enter_configuration_state(&mut ecs, player_entity);
+        enter_game_state(&mut ecs, player_entity);
+
+        let player = ecs.entity(player_entity);
+        assert!(player.get::<TabList>().unwrap().is_empty());
+        assert_eq!(
+            player
+                .get::<TabListHeaderFooter>()
+                .unwrap()
+                .header
+                .to_string(),
+            ""
+        );
+        assert!(player.get::<BossBars>().unwrap().is_empty());
+        assert!(player.get::<Scoreboard>().unwrap().display_slots.is_empty());
+        assert!(!player
+            .get::<EntityIdIndex>()
+            .unwrap()
+            .contains_key(&MinecraftEntityId(5)));
+        assert!(ecs.get::<LoadedBy>(other_entity).unwrap().is_empty());
+    }
+}
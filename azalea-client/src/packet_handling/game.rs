@@ -7,44 +7,73 @@ use std::{
 use azalea_chat::FormattedText;
 use azalea_core::{
     game_type::GameMode,
-    position::{ChunkPos, Vec3},
+    position::{BlockPos, ChunkPos, Vec3},
     resource_location::ResourceLocation,
 };
 use azalea_entity::{
+    effects::{ActiveEffects, MobEffectData},
     indexing::{EntityIdIndex, EntityUuidIndex},
-    metadata::{apply_metadata, Health, PlayerMetadataBundle},
-    Dead, EntityBundle, EntityKind, LastSentPosition, LoadedBy, LocalEntity, LookDirection,
-    Physics, PlayerBundle, Position, RelativeEntityUpdate,
+    metadata::{apply_metadata, AbstractLiving, Health, ItemItem, PlayerMetadataBundle},
+    Dead, EntityBundle, EntityDataItem, EntityDataValue, EntityKind, LastSentPosition, LoadedBy,
+    LocalEntity, LookDirection, Physics, PlayerBundle, Pose, Position, RelativeEntityUpdate,
+    Vehicle,
 };
+use azalea_inventory::ItemSlot;
 use azalea_nbt::NbtCompound;
 use azalea_protocol::{
-    packets::game::{
-        clientbound_player_combat_kill_packet::ClientboundPlayerCombatKillPacket,
-        serverbound_accept_teleportation_packet::ServerboundAcceptTeleportationPacket,
-        serverbound_keep_alive_packet::ServerboundKeepAlivePacket,
-        serverbound_move_player_pos_rot_packet::ServerboundMovePlayerPosRotPacket,
-        serverbound_pong_packet::ServerboundPongPacket, ClientboundGamePacket,
+    packets::{
+        game::{
+            clientbound_animate_packet::AnimationAction,
+            clientbound_light_update_packet::ClientboundLightUpdatePacketData,
+            clientbound_player_combat_kill_packet::ClientboundPlayerCombatKillPacket,
+            clientbound_player_position_packet::ClientboundPlayerPositionPacket,
+            serverbound_accept_teleportation_packet::ServerboundAcceptTeleportationPacket,
+            serverbound_configuration_acknowledged_packet::ServerboundConfigurationAcknowledgedPacket,
+            serverbound_keep_alive_packet::ServerboundKeepAlivePacket,
+            serverbound_move_player_pos_rot_packet::ServerboundMovePlayerPosRotPacket,
+            serverbound_pong_packet::ServerboundPongPacket,
+            serverbound_resource_pack_packet::ServerboundResourcePackPacket, ClientboundGamePacket,
+        },
+        ConnectionProtocol,
     },
     read::deserialize_packet,
 };
-use azalea_world::{Instance, InstanceContainer, InstanceName, MinecraftEntityId, PartialInstance};
+use azalea_world::{
+    BlockEntityData, Chunk, Instance, InstanceContainer, InstanceName, MinecraftEntityId,
+    PartialInstance,
+};
 use bevy_ecs::{prelude::*, system::SystemState};
 use log::{debug, error, trace, warn};
 use parking_lot::RwLock;
 
 use crate::{
+    brand::{read_brand_payload, ServerBrand},
     chat::{ChatPacket, ChatReceivedEvent},
     chunk_batching,
+    chunk_cache::{ChunkCache, UpdateChunkCacheEvent},
+    client::InConfigurationState,
+    commands::CommandTree,
+    crafting::KnownRecipes,
     disconnect::DisconnectEvent,
+    eat::Eating,
+    interact::PlacingBlock,
     inventory::{
         ClientSideCloseContainerEvent, InventoryComponent, MenuOpenedEvent,
         SetContainerContentEvent,
     },
     local_player::{
-        GameProfileComponent, Hunger, InstanceHolder, LocalGameMode, PlayerAbilities,
-        SendPacketEvent, TabList,
+        BossBars, Experience, GameProfileComponent, Hardcore, Hunger, InstanceHolder,
+        LocalGameMode, PlayerAbilities, SendPacketEvent, ServerTps, TabList, TabListHeaderFooter,
     },
+    movement::{KnockbackEvent, LastSentLookDirection, PhysicsState},
+    packet_handling::{configuration::enter_configuration_state, ProtocolStateChangedEvent},
     raw_connection::RawConnection,
+    ready::ServerViewDistance,
+    resource_pack::ResourcePackPolicy,
+    respawn::SpawnPoint,
+    scoreboard::Scoreboard,
+    trading::MerchantOffers,
+    world_time::{Weather, WorldTime},
     ClientInformation, PlayerInfo, ReceivedRegistries,
 };
 
@@ -101,13 +130,15 @@ pub struct UpdatePlayerEvent {
     pub info: PlayerInfo,
 }
 
-/// Event for when an entity dies. dies. If it's a local player and there's a
+/// Event for when an entity dies. If it's a local player and there's a
 /// reason in the death screen, the [`ClientboundPlayerCombatKillPacket`] will
 /// be included.
 #[derive(Event, Debug, Clone)]
 pub struct DeathEvent {
     pub entity: Entity,
     pub packet: Option<ClientboundPlayerCombatKillPacket>,
+    /// Where the entity was when it died, if it has a position.
+    pub position: Option<Vec3>,
 }
 
 /// A KeepAlive packet is sent from the server to verify that the client is
@@ -120,6 +151,9 @@ pub struct KeepAliveEvent {
     pub id: u64,
 }
 
+/// Sent when the server asks us to use a resource pack, in either the
+/// configuration or game state. We respond to it automatically depending on
+/// the [`ResourcePackPolicy`].
 #[derive(Event, Debug, Clone)]
 pub struct ResourcePackEvent {
     pub entity: Entity,
@@ -140,8 +174,139 @@ pub struct InstanceLoadedEvent {
     pub instance: Weak<RwLock<Instance>>,
 }
 
+/// A local player moved to a different instance (aka world, dimension), like
+/// by going through a portal or respawning. This is also sent when we first
+/// join a world, in which case `from` is `None`.
+///
+/// You should clear any state you're keeping about the old world when you get
+/// this.
+#[derive(Event, Debug, Clone)]
+pub struct DimensionChangedEvent {
+    pub entity: Entity,
+    pub from: Option<ResourceLocation>,
+    pub to: ResourceLocation,
+}
+
+/// An item (or arrow, or experience orb) was picked up by an entity. This is
+/// sent when any entity near us picks something up, not just when we do.
+#[derive(Event, Debug, Clone)]
+pub struct ItemPickupEvent {
+    /// The client that received the packet.
+    pub entity: Entity,
+    /// The entity that was picked up, if we know about it.
+    pub item_entity: Option<Entity>,
+    /// The entity that picked up the item, if we know about it.
+    pub collector: Option<Entity>,
+    /// The items that were picked up, which may be fewer than the whole stack.
+    /// This is empty if the entity that was picked up isn't an item.
+    pub item: ItemSlot,
+}
+
+/// An entity near us swung its arm, got hurt, woke up, or was hit with a
+/// critical hit. This is never sent for our own arm swings.
+#[derive(Event, Debug, Clone)]
+pub struct EntityAnimationEvent {
+    /// The client that received the packet.
+    pub entity: Entity,
+    /// The entity that's doing the animation, if we know about it.
+    pub animated_entity: Option<Entity>,
+    pub entity_id: MinecraftEntityId,
+    pub action: AnimationAction,
+}
+
+/// The server updated some of an entity's metadata, like its health, pose, or
+/// custom name.
+///
+/// This is sent after the new values are applied, so the entity's components
+/// (like [`Health`], [`Pose`], and [`CustomName`]) already have them. If more
+/// than one of our clients can see the entity, it's only sent for the client
+/// whose update was applied.
+///
+/// [`CustomName`]: azalea_entity::metadata::CustomName
+#[derive(Event, Debug, Clone)]
+pub struct EntityMetadataChangedEvent {
+    /// The client that received the packet.
+    pub entity: Entity,
+    /// The entity whose metadata changed.
+    pub changed_entity: Entity,
+    pub entity_id: MinecraftEntityId,
+    pub kind: azalea_registry::EntityKind,
+    /// The metadata that the server sent. What each index means depends on the
+    /// kind of entity, but the methods on this event can be used to get the
+    /// fields that every entity has.
+    pub items: Vec<EntityDataItem>,
+    /// The entity's new health, if it's a living entity and its health was
+    /// updated.
+    pub health: Option<f32>,
+}
+impl EntityMetadataChangedEvent {
+    // these are the indices of the fields that every entity has (see
+    // `AbstractEntity::apply_metadata`)
+    const FLAGS_INDEX: u8 = 0;
+    const CUSTOM_NAME_INDEX: u8 = 2;
+    const POSE_INDEX: u8 = 6;
+    /// The index of the health for living entities.
+    const HEALTH_INDEX: u8 = 9;
+
+    /// Whether the metadata item with the given index was updated.
+    pub fn changed(&self, index: u8) -> bool {
+        self.items.iter().any(|item| item.index == index)
+    }
+
+    fn get(&self, index: u8) -> Option<&EntityDataValue> {
+        self.items
+            .iter()
+            .find(|item| item.index == index)
+            .map(|item| &item.value)
+    }
+
+    /// The entity's new pose, if it was updated.
+    pub fn pose(&self) -> Option<Pose> {
+        self.get(Self::POSE_INDEX)?.as_pose().copied()
+    }
+
+    /// The entity's new custom name, if it was updated. This is `Some(None)` if
+    /// the custom name was removed.
+    pub fn custom_name(&self) -> Option<Option<FormattedText>> {
+        self.get(Self::CUSTOM_NAME_INDEX)?
+            .as_optional_formatted_text()
+            .cloned()
+    }
+
+    /// Whether the entity is sneaking now, if its flags were updated.
+    pub fn sneaking(&self) -> Option<bool> {
+        let flags = self.get(Self::FLAGS_INDEX)?.as_byte()?;
+        Some(flags & 0x2 != 0)
+    }
+}
+
+/// The server opened the screen for writing on a sign, which usually happens
+/// right after we place one. See [`Client::edit_sign`].
+///
+/// [`Client::edit_sign`]: crate::Client::edit_sign
+#[derive(Event, Debug, Clone)]
+pub struct OpenSignEditorEvent {
+    pub entity: Entity,
+    pub pos: BlockPos,
+    /// Whether the editor is for the front of the sign, as opposed to the
+    /// back.
+    pub is_front_text: bool,
+}
+
+/// The server forcibly moved a local player, like with the `/tp` command or
+/// because it didn't accept a movement (rubber-banding).
+///
+/// This is sent after the teleport is confirmed and our position is updated.
+#[derive(Event, Debug, Clone)]
+pub struct TeleportedEvent {
+    pub entity: Entity,
+    pub old_position: Vec3,
+    pub new_position: Vec3,
+}
+
+#[allow(clippy::type_complexity)]
 pub fn send_packet_events(
-    query: Query<(Entity, &RawConnection), With<LocalEntity>>,
+    query: Query<(Entity, &RawConnection), (With<LocalEntity>, Without<InConfigurationState>)>,
     mut packet_events: ResMut<Events<PacketEvent>>,
 ) {
     // we manually clear and send the events at the beginning of each update
@@ -152,7 +317,9 @@ pub fn send_packet_events(
         let packets_lock = raw_connection.incoming_packet_queue();
         let mut packets = packets_lock.lock();
         if !packets.is_empty() {
+            let mut packets_read = 0;
             for raw_packet in packets.iter() {
+                packets_read += 1;
                 let packet =
                     match deserialize_packet::<ClientboundGamePacket>(&mut Cursor::new(raw_packet))
                     {
@@ -162,13 +329,20 @@ pub fn send_packet_events(
                             continue;
                         }
                     };
+                let is_start_configuration =
+                    matches!(packet, ClientboundGamePacket::StartConfiguration(_));
                 packet_events.send(PacketEvent {
                     entity: player_entity,
                     packet: packet.clone(),
                 });
+                if is_start_configuration {
+                    // the packets after this are in the configuration state, so they're read by
+                    // configuration::send_packet_events after we switch states
+                    break;
+                }
             }
-            // clear the packets right after we read them
-            packets.clear();
+            // remove the packets right after we read them
+            packets.drain(..packets_read);
         }
     }
 }
@@ -203,6 +377,7 @@ pub fn process_packet_events(ecs: &mut World) {
                         &mut InstanceHolder,
                     )>,
                     EventWriter<InstanceLoadedEvent>,
+                    EventWriter<DimensionChangedEvent>,
                     ResMut<InstanceContainer>,
                     ResMut<EntityUuidIndex>,
                     EventWriter<SendPacketEvent>,
@@ -211,6 +386,7 @@ pub fn process_packet_events(ecs: &mut World) {
                     mut commands,
                     mut query,
                     mut instance_loaded_events,
+                    mut dimension_changed_events,
                     mut instance_container,
                     mut entity_uuid_index,
                     mut send_packet_events,
@@ -228,8 +404,17 @@ pub fn process_packet_events(ecs: &mut World) {
                 {
                     let new_instance_name = p.common.dimension.clone();
 
+                    let old_instance_name = instance_name.as_ref().map(|name| name.0.clone());
+                    if old_instance_name.as_ref() != Some(&new_instance_name) {
+                        dimension_changed_events.send(DimensionChangedEvent {
+                            entity: player_entity,
+                            from: old_instance_name,
+                            to: new_instance_name.clone(),
+                        });
+                    }
+
                     if let Some(mut instance_name) = instance_name {
-                        *instance_name = instance_name.clone();
+                        **instance_name = new_instance_name.clone();
                     } else {
                         commands
                             .entity(player_entity)
@@ -293,10 +478,18 @@ pub fn process_packet_events(ecs: &mut World) {
                             current: p.common.game_type,
                             previous: p.common.previous_game_type.into(),
                         },
+                        Hardcore(p.hardcore),
                         // this gets overwritten later by the SetHealth packet
                         received_registries.clone(),
                         player_bundle,
+                        // respawning gives us a new player, so our effects are gone (the
+                        // server will resend any that we should still have)
+                        ActiveEffects::default(),
+                        ServerViewDistance(p.chunk_radius),
                     ));
+                    // we might be on a different server than before, so our bed isn't our
+                    // spawn point anymore (the server sends the world spawn right after this)
+                    commands.entity(player_entity).remove::<SpawnPoint>();
 
                     azalea_entity::indexing::add_entity_to_indexes(
                         entity_id,
@@ -331,6 +524,12 @@ pub fn process_packet_events(ecs: &mut World) {
             }
             ClientboundGamePacket::SetChunkCacheRadius(p) => {
                 debug!("Got set chunk cache radius packet {p:?}");
+
+                ecs.entity_mut(player_entity)
+                    .insert(ServerViewDistance(p.radius));
+                ecs.send_event(UpdateChunkCacheEvent {
+                    entity: player_entity,
+                });
             }
 
             ClientboundGamePacket::ChunkBatchStart(_p) => {
@@ -361,12 +560,19 @@ pub fn process_packet_events(ecs: &mut World) {
 
             ClientboundGamePacket::CustomPayload(p) => {
                 debug!("Got custom payload packet {p:?}");
+
+                // proxies like bungeecord send the brand again when switching servers
+                if let Some(brand) = read_brand_payload(&p.identifier, &p.data) {
+                    ecs.entity_mut(player_entity).insert(ServerBrand(brand));
+                }
             }
             ClientboundGamePacket::ChangeDifficulty(p) => {
                 debug!("Got difficulty packet {p:?}");
             }
-            ClientboundGamePacket::Commands(_p) => {
+            ClientboundGamePacket::Commands(p) => {
                 debug!("Got declare commands packet");
+
+                ecs.entity_mut(player_entity).insert(CommandTree::new(&p));
             }
             ClientboundGamePacket::PlayerAbilities(p) => {
                 debug!("Got player abilities packet {p:?}");
@@ -379,6 +585,17 @@ pub fn process_packet_events(ecs: &mut World) {
             }
             ClientboundGamePacket::SetCarriedItem(p) => {
                 debug!("Got set carried item packet {p:?}");
+
+                let mut system_state: SystemState<Query<&mut InventoryComponent>> =
+                    SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                let mut inventory = query.get_mut(player_entity).unwrap();
+
+                if p.slot < 9 {
+                    inventory.selected_hotbar_slot = p.slot;
+                } else {
+                    warn!("Server tried to select invalid hotbar slot {}", p.slot);
+                }
             }
             ClientboundGamePacket::UpdateTags(_p) => {
                 debug!("Got update tags packet");
@@ -390,19 +607,39 @@ pub fn process_packet_events(ecs: &mut World) {
                 let mut disconnect_events = system_state.get_mut(ecs);
                 disconnect_events.send(DisconnectEvent {
                     entity: player_entity,
+                    reason: Some(p.reason.clone()),
                 });
             }
-            ClientboundGamePacket::UpdateRecipes(_p) => {
+            ClientboundGamePacket::UpdateRecipes(p) => {
                 debug!("Got update recipes packet");
+
+                ecs.entity_mut(player_entity).insert(KnownRecipes::from(&p));
             }
-            ClientboundGamePacket::EntityEvent(_p) => {
+            ClientboundGamePacket::MerchantOffers(p) => {
+                debug!("Got merchant offers packet {p:?}");
+
+                ecs.entity_mut(player_entity)
+                    .insert(MerchantOffers::from(&p));
+            }
+            ClientboundGamePacket::EntityEvent(p) => {
                 // debug!("Got entity event packet {p:?}");
+
+                let mut system_state: SystemState<Query<&MinecraftEntityId>> =
+                    SystemState::new(ecs);
+                let query = system_state.get(ecs);
+                let Ok(player_entity_id) = query.get(player_entity) else {
+                    continue;
+                };
+
+                // 9 means that we finished using an item, like eating food
+                if p.entity_id == **player_entity_id && p.event_id == 9 {
+                    ecs.entity_mut(player_entity).remove::<Eating>();
+                }
             }
             ClientboundGamePacket::Recipe(_p) => {
                 debug!("Got recipe packet");
             }
             ClientboundGamePacket::PlayerPosition(p) => {
-                // TODO: reply with teleport confirm
                 debug!("Got player position packet {p:?}");
 
                 #[allow(clippy::type_complexity)]
@@ -412,74 +649,38 @@ pub fn process_packet_events(ecs: &mut World) {
                         &mut LookDirection,
                         &mut Position,
                         &mut LastSentPosition,
+                        Option<&mut LastSentLookDirection>,
                     )>,
                     EventWriter<SendPacketEvent>,
+                    EventWriter<TeleportedEvent>,
                 )> = SystemState::new(ecs);
-                let (mut query, mut send_packet_events) = system_state.get_mut(ecs);
-                let Ok((mut physics, mut direction, mut position, mut last_sent_position)) =
-                    query.get_mut(player_entity)
+                let (mut query, mut send_packet_events, mut teleported_events) =
+                    system_state.get_mut(ecs);
+                let Ok((
+                    mut physics,
+                    mut direction,
+                    mut position,
+                    mut last_sent_position,
+                    last_sent_direction,
+                )) = query.get_mut(player_entity)
                 else {
                     continue;
                 };
 
-                let delta_movement = physics.delta;
+                let old_position = **position;
+                let (new_pos, new_delta, new_direction) =
+                    apply_teleport(&p, **position, physics.delta, &direction);
+                let (y_rot, x_rot) = (new_direction.y_rot, new_direction.x_rot);
 
-                let is_x_relative = p.relative_arguments.x;
-                let is_y_relative = p.relative_arguments.y;
-                let is_z_relative = p.relative_arguments.z;
-
-                let (delta_x, new_pos_x) = if is_x_relative {
-                    last_sent_position.x += p.x;
-                    (delta_movement.x, position.x + p.x)
-                } else {
-                    last_sent_position.x = p.x;
-                    (0.0, p.x)
-                };
-                let (delta_y, new_pos_y) = if is_y_relative {
-                    last_sent_position.y += p.y;
-                    (delta_movement.y, position.y + p.y)
-                } else {
-                    last_sent_position.y = p.y;
-                    (0.0, p.y)
-                };
-                let (delta_z, new_pos_z) = if is_z_relative {
-                    last_sent_position.z += p.z;
-                    (delta_movement.z, position.z + p.z)
-                } else {
-                    last_sent_position.z = p.z;
-                    (0.0, p.z)
-                };
-
-                let mut y_rot = p.y_rot;
-                let mut x_rot = p.x_rot;
-                if p.relative_arguments.x_rot {
-                    x_rot += direction.x_rot;
-                }
-                if p.relative_arguments.y_rot {
-                    y_rot += direction.y_rot;
-                }
-
-                physics.delta = Vec3 {
-                    x: delta_x,
-                    y: delta_y,
-                    z: delta_z,
-                };
-                // we call a function instead of setting the fields ourself since the
-                // function makes sure the rotations stay in their
-                // ranges
-                (direction.y_rot, direction.x_rot) = (y_rot, x_rot);
-                // TODO: minecraft sets "xo", "yo", and "zo" here but idk what that means
-                // so investigate that ig
-                let new_pos = Vec3 {
-                    x: new_pos_x,
-                    y: new_pos_y,
-                    z: new_pos_z,
-                };
+                physics.delta = new_delta;
+                *direction = new_direction;
 
                 if new_pos != **position {
                     **position = new_pos;
                 }
 
+                // the teleport has to be confirmed before we send our new position, or the
+                // server ignores it and teleports us back again
                 send_packet_events.send(SendPacketEvent {
                     entity: player_entity,
                     packet: ServerboundAcceptTeleportationPacket { id: p.id }.get(),
@@ -497,6 +698,18 @@ pub fn process_packet_events(ecs: &mut World) {
                     }
                     .get(),
                 });
+                // we just sent our position, so don't send it again next tick
+                **last_sent_position = new_pos;
+                if let Some(mut last_sent_direction) = last_sent_direction {
+                    last_sent_direction.y_rot = y_rot;
+                    last_sent_direction.x_rot = x_rot;
+                }
+
+                teleported_events.send(TeleportedEvent {
+                    entity: player_entity,
+                    old_position,
+                    new_position: new_pos,
+                });
             }
             ClientboundGamePacket::PlayerInfoUpdate(p) => {
                 debug!("Got player info packet {p:?}");
@@ -589,24 +802,59 @@ pub fn process_packet_events(ecs: &mut World) {
                 partial_world.chunks.view_center = ChunkPos::new(p.x, p.z);
             }
             ClientboundGamePacket::ChunksBiomes(_) => {}
-            ClientboundGamePacket::LightUpdate(_p) => {
-                // debug!("Got light update packet {p:?}");
+            ClientboundGamePacket::LightUpdate(p) => {
+                trace!("Got light update packet {} {}", p.x, p.z);
+
+                let mut system_state: SystemState<Query<&InstanceHolder>> = SystemState::new(ecs);
+                let query = system_state.get(ecs);
+                let local_player = query.get(player_entity).unwrap();
+
+                let world = local_player.instance.read();
+                // vanilla keeps light updates for chunks that it doesn't have yet, but the
+                // server always sends the light with the chunk anyways
+                let Some(chunk) = world.chunks.get(&ChunkPos::new(p.x, p.z)) else {
+                    continue;
+                };
+                update_chunk_light(&mut chunk.write(), world.chunks.height, &p.light_data);
             }
             ClientboundGamePacket::LevelChunkWithLight(p) => {
                 debug!("Got chunk with light packet {} {}", p.x, p.z);
                 let pos = ChunkPos::new(p.x, p.z);
 
-                let mut system_state: SystemState<Query<&mut InstanceHolder>> =
-                    SystemState::new(ecs);
-                let mut query = system_state.get_mut(ecs);
-                let local_player = query.get_mut(player_entity).unwrap();
+                #[allow(clippy::type_complexity)]
+                let mut system_state: SystemState<(
+                    Query<(&mut InstanceHolder, Option<&InstanceName>)>,
+                    Option<Res<ChunkCache>>,
+                    EventWriter<UpdateChunkCacheEvent>,
+                )> = SystemState::new(ecs);
+                let (mut query, chunk_cache, mut update_chunk_cache_events) =
+                    system_state.get_mut(ecs);
+                let (local_player, instance_name) = query.get_mut(player_entity).unwrap();
+
+                // loading a chunk can replace an outdated one that's in the chunk cache
+                update_chunk_cache_events.send(UpdateChunkCacheEvent {
+                    entity: player_entity,
+                });
 
                 // OPTIMIZATION: if we already know about the chunk from the
                 // shared world (and not ourselves), then we don't need to
                 // parse it again. This is only used when we have a shared
                 // world, since we check that the chunk isn't currently owned
                 // by this client.
-                let shared_chunk = local_player.instance.read().chunks.get(&pos);
+                let shared_chunk = local_player
+                    .instance
+                    .read()
+                    .chunks
+                    .get(&pos)
+                    .filter(|chunk| {
+                        // chunks that only the chunk cache is keeping in memory might be outdated
+                        let is_only_cached = chunk_cache.as_ref().zip(instance_name).is_some_and(
+                            |(chunk_cache, instance_name)| {
+                                chunk_cache.is_only_cached(instance_name, &pos, chunk)
+                            },
+                        );
+                        !is_only_cached
+                    });
                 let this_client_has_chunk = local_player
                     .partial_instance
                     .read()
@@ -641,6 +889,25 @@ pub fn process_packet_events(ecs: &mut World) {
                     &mut world.chunks,
                 ) {
                     error!("Couldn't set chunk data: {e}");
+                    continue;
+                }
+                if let Some(chunk) = world.chunks.get(&pos) {
+                    update_chunk_light(&mut chunk.write(), world.chunks.height, &p.light_data);
+                }
+
+                for block_entity in &p.chunk_data.block_entities {
+                    let block_pos = BlockPos::new(
+                        pos.x * 16 + (block_entity.packed_xz >> 4) as i32,
+                        block_entity.y as i16 as i32,
+                        pos.z * 16 + (block_entity.packed_xz & 15) as i32,
+                    );
+                    world.chunks.set_block_entity(
+                        &block_pos,
+                        BlockEntityData {
+                            kind: block_entity.kind,
+                            nbt: block_entity.data.as_compound().cloned().unwrap_or_default(),
+                        },
+                    );
                 }
             }
             ClientboundGamePacket::AddEntity(p) => {
@@ -751,6 +1018,7 @@ pub fn process_packet_events(ecs: &mut World) {
                     continue;
                 };
                 let entity_kind = *entity_kind_query.get(entity).unwrap();
+                let minecraft_entity_id = MinecraftEntityId(p.id);
 
                 // we use RelativeEntityUpdate because it makes sure changes aren't made
                 // multiple times
@@ -759,16 +1027,35 @@ pub fn process_packet_events(ecs: &mut World) {
                     update: Box::new(move |entity| {
                         let entity_id = entity.id();
                         entity.world_scope(|world| {
+                            let items = (*p.packed_items).clone();
+
                             let mut commands_system_state = SystemState::<Commands>::new(world);
                             let mut commands = commands_system_state.get_mut(world);
                             let mut entity_comands = commands.entity(entity_id);
-                            if let Err(e) = apply_metadata(
-                                &mut entity_comands,
-                                *entity_kind,
-                                (*p.packed_items).clone(),
-                            ) {
+                            if let Err(e) =
+                                apply_metadata(&mut entity_comands, *entity_kind, items.clone())
+                            {
                                 warn!("{e}");
                             }
+                            commands_system_state.apply(world);
+
+                            let is_living = world.entity(entity_id).contains::<AbstractLiving>();
+                            let health = if is_living
+                                && items.iter().any(|item| {
+                                    item.index == EntityMetadataChangedEvent::HEALTH_INDEX
+                                }) {
+                                world.get::<Health>(entity_id).map(|health| **health)
+                            } else {
+                                None
+                            };
+                            world.send_event(EntityMetadataChangedEvent {
+                                entity: player_entity,
+                                changed_entity: entity_id,
+                                entity_id: minecraft_entity_id,
+                                kind: *entity_kind,
+                                items,
+                                health,
+                            });
                         });
                     }),
                 });
@@ -778,20 +1065,97 @@ pub fn process_packet_events(ecs: &mut World) {
             ClientboundGamePacket::UpdateAttributes(_p) => {
                 // debug!("Got update attributes packet {p:?}");
             }
-            ClientboundGamePacket::SetEntityMotion(_p) => {
+            ClientboundGamePacket::SetEntityMotion(p) => {
                 // debug!("Got entity velocity packet {p:?}");
+
+                #[allow(clippy::type_complexity)]
+                let mut system_state: SystemState<(
+                    Commands,
+                    Query<(&EntityIdIndex, &InstanceHolder)>,
+                    EventWriter<KnockbackEvent>,
+                )> = SystemState::new(ecs);
+                let (mut commands, mut query, mut knockback_events) = system_state.get_mut(ecs);
+                let (entity_id_index, instance_holder) = query.get_mut(player_entity).unwrap();
+
+                let Some(entity) = entity_id_index.get(&MinecraftEntityId(p.id)) else {
+                    // this is common for entities that were just despawned, so we don't warn
+                    continue;
+                };
+
+                // the velocity is sent in units of 1/8000 of a block per tick
+                let new_delta = Vec3 {
+                    x: p.xa as f64 / 8000.,
+                    y: p.ya as f64 / 8000.,
+                    z: p.za as f64 / 8000.,
+                };
+                commands.entity(entity).add(RelativeEntityUpdate {
+                    partial_world: instance_holder.partial_instance.clone(),
+                    update: Box::new(move |entity_mut| {
+                        let mut physics = entity_mut.get_mut::<Physics>().unwrap();
+                        physics.delta = new_delta;
+                    }),
+                });
+                if entity == player_entity {
+                    knockback_events.send(KnockbackEvent {
+                        entity: player_entity,
+                        velocity: new_delta,
+                    });
+                }
+
+                system_state.apply(ecs);
             }
             ClientboundGamePacket::SetEntityLink(p) => {
                 debug!("Got set entity link packet {p:?}");
             }
             ClientboundGamePacket::InitializeBorder(p) => {
                 debug!("Got initialize border packet {p:?}");
+
+                let mut system_state: SystemState<Query<&InstanceHolder>> = SystemState::new(ecs);
+                let query = system_state.get(ecs);
+                let instance_holder = query.get(player_entity).unwrap();
+                let world_border = &mut instance_holder.instance.write().world_border;
+                (world_border.center_x, world_border.center_z) = (p.new_center_x, p.new_center_z);
+                world_border.lerp_size_between(p.old_size, p.new_size, p.lerp_time);
+                world_border.absolute_max_size = p.new_absolute_max_size;
+                world_border.warning_blocks = p.warning_blocks;
+                world_border.warning_time = p.warning_time;
             }
-            ClientboundGamePacket::SetTime(_p) => {
+            ClientboundGamePacket::SetTime(p) => {
                 // debug!("Got set time packet {p:?}");
+
+                #[allow(clippy::type_complexity)]
+                let mut system_state: SystemState<(
+                    Query<(&mut ServerTps, &mut WorldTime)>,
+                    ResMut<ServerTps>,
+                )> = SystemState::new(ecs);
+                let (mut query, mut server_tps_resource) = system_state.get_mut(ecs);
+                let (mut server_tps, mut world_time) = query.get_mut(player_entity).unwrap();
+
+                server_tps.update(p.game_time);
+                *server_tps_resource = server_tps.clone();
+                // the day time is negative if the daylight cycle is off
+                world_time.update(p.game_time, p.day_time as i64);
             }
             ClientboundGamePacket::SetDefaultSpawnPosition(p) => {
                 debug!("Got set default spawn position packet {p:?}");
+
+                let mut system_state: SystemState<(Commands, Query<Option<&mut SpawnPoint>>)> =
+                    SystemState::new(ecs);
+                let (mut commands, mut query) = system_state.get_mut(ecs);
+                let spawn_point = query.get_mut(player_entity).unwrap();
+
+                if let Some(mut spawn_point) = spawn_point {
+                    spawn_point.pos = p.pos;
+                    spawn_point.angle = p.angle;
+                } else {
+                    commands.entity(player_entity).insert(SpawnPoint {
+                        pos: p.pos,
+                        angle: p.angle,
+                        bed: None,
+                    });
+                }
+
+                system_state.apply(ecs);
             }
             ClientboundGamePacket::SetHealth(p) => {
                 debug!("Got set health packet {p:?}");
@@ -810,6 +1174,14 @@ pub fn process_packet_events(ecs: &mut World) {
             }
             ClientboundGamePacket::SetExperience(p) => {
                 debug!("Got set experience packet {p:?}");
+
+                let mut system_state: SystemState<Query<&mut Experience>> = SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                let mut experience = query.get_mut(player_entity).unwrap();
+
+                experience.level = p.experience_level;
+                experience.progress = p.experience_progress;
+                experience.total = p.total_experience;
             }
             ClientboundGamePacket::TeleportEntity(p) => {
                 let mut system_state: SystemState<(
@@ -1007,6 +1379,21 @@ pub fn process_packet_events(ecs: &mut World) {
             }
             ClientboundGamePacket::Animate(p) => {
                 debug!("Got animate packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Query<&EntityIdIndex>,
+                    EventWriter<EntityAnimationEvent>,
+                )> = SystemState::new(ecs);
+                let (query, mut entity_animation_events) = system_state.get_mut(ecs);
+                let entity_id_index = query.get(player_entity).unwrap();
+
+                let entity_id = MinecraftEntityId(p.id);
+                entity_animation_events.send(EntityAnimationEvent {
+                    entity: player_entity,
+                    animated_entity: entity_id_index.get(&entity_id),
+                    entity_id,
+                    action: p.action,
+                });
             }
             ClientboundGamePacket::SectionBlocksUpdate(p) => {
                 debug!("Got section blocks update packet {p:?}");
@@ -1028,7 +1415,6 @@ pub fn process_packet_events(ecs: &mut World) {
 
                 debug!("Got game event packet {p:?}");
 
-                #[allow(clippy::single_match)]
                 match p.event {
                     EventType::ChangeGameMode => {
                         let mut system_state: SystemState<Query<&mut LocalGameMode>> =
@@ -1039,6 +1425,24 @@ pub fn process_packet_events(ecs: &mut World) {
                             local_game_mode.current = new_game_mode;
                         }
                     }
+                    EventType::StartRaining
+                    | EventType::StopRaining
+                    | EventType::RainLevelChange
+                    | EventType::ThunderLevelChange => {
+                        let mut system_state: SystemState<Query<&mut Weather>> =
+                            SystemState::new(ecs);
+                        let mut query = system_state.get_mut(ecs);
+                        let mut weather = query.get_mut(player_entity).unwrap();
+                        match p.event {
+                            // these are the same as vanilla, the rain level is
+                            // then faded with RainLevelChange
+                            EventType::StartRaining => weather.rain_level = 0.,
+                            EventType::StopRaining => weather.rain_level = 1.,
+                            EventType::RainLevelChange => weather.rain_level = p.param,
+                            EventType::ThunderLevelChange => weather.thunder_level = p.param,
+                            _ => unreachable!(),
+                        }
+                    }
                     _ => {}
                 }
             }
@@ -1053,16 +1457,95 @@ pub fn process_packet_events(ecs: &mut World) {
             }
             ClientboundGamePacket::UpdateMobEffect(p) => {
                 debug!("Got update mob effect packet {p:?}");
+
+                #[allow(clippy::type_complexity)]
+                let mut system_state: SystemState<(
+                    Commands,
+                    Query<&EntityIdIndex>,
+                    Query<Option<&mut ActiveEffects>>,
+                )> = SystemState::new(ecs);
+                let (mut commands, query, mut effects_query) = system_state.get_mut(ecs);
+                let entity_id_index = query.get(player_entity).unwrap();
+
+                let Some(entity) = entity_id_index.get(&MinecraftEntityId(p.entity_id)) else {
+                    warn!(
+                        "Server sent an update mob effect packet for an entity id ({}) that we don't know about",
+                        p.entity_id
+                    );
+                    continue;
+                };
+
+                let data = MobEffectData {
+                    amplifier: p.effect_amplifier as u32,
+                    // vanilla sends -1 for effects that never run out
+                    duration: if p.effect_duration_ticks == u32::MAX {
+                        None
+                    } else {
+                        Some(p.effect_duration_ticks)
+                    },
+                    ambient: p.flags & 0b1 != 0,
+                };
+                match effects_query.get_mut(entity) {
+                    Ok(Some(mut active_effects)) => {
+                        active_effects.insert(p.effect, data);
+                    }
+                    _ => {
+                        let mut active_effects = ActiveEffects::default();
+                        active_effects.insert(p.effect, data);
+                        commands.entity(entity).insert(active_effects);
+                    }
+                }
+
+                system_state.apply(ecs);
             }
             ClientboundGamePacket::AddExperienceOrb(_) => {}
             ClientboundGamePacket::AwardStats(_) => {}
-            ClientboundGamePacket::BlockChangedAck(_) => {}
+            ClientboundGamePacket::BlockChangedAck(p) => {
+                debug!("Got block changed ack packet {p:?}");
+
+                let mut system_state: SystemState<Query<&PlacingBlock>> = SystemState::new(ecs);
+                let query = system_state.get(ecs);
+                let Ok(placing_block) = query.get(player_entity) else {
+                    continue;
+                };
+
+                if p.sequence as u32 >= placing_block.sequence {
+                    ecs.entity_mut(player_entity).remove::<PlacingBlock>();
+                }
+            }
             ClientboundGamePacket::BlockDestruction(_) => {}
-            ClientboundGamePacket::BlockEntityData(_) => {}
+            ClientboundGamePacket::BlockEntityData(p) => {
+                debug!("Got block entity data packet {p:?}");
+
+                let mut system_state: SystemState<Query<&InstanceHolder>> = SystemState::new(ecs);
+                let query = system_state.get(ecs);
+                let local_player = query.get(player_entity).unwrap();
+
+                let world = local_player.instance.read();
+
+                world.chunks.set_block_entity(
+                    &p.pos,
+                    BlockEntityData {
+                        kind: p.block_entity_type,
+                        nbt: p.tag.as_compound().cloned().unwrap_or_default(),
+                    },
+                );
+            }
             ClientboundGamePacket::BlockEvent(p) => {
                 debug!("Got block event packet {p:?}");
             }
-            ClientboundGamePacket::BossEvent(_) => {}
+            ClientboundGamePacket::BossEvent(p) => {
+                debug!("Got boss event packet {p:?}");
+
+                let mut system_state: SystemState<(Query<&mut BossBars>, ResMut<BossBars>)> =
+                    SystemState::new(ecs);
+                let (mut query, mut boss_bars_resource) = system_state.get_mut(ecs);
+                let mut boss_bars = query.get_mut(player_entity).unwrap();
+
+                boss_bars.handle_operation(p.id, &p.operation);
+
+                *boss_bars_resource = boss_bars.clone();
+            }
             ClientboundGamePacket::CommandSuggestions(_) => {}
             ClientboundGamePacket::ContainerSetContent(p) => {
                 debug!("Got container set content packet {p:?}");
@@ -1154,11 +1637,59 @@ pub fn process_packet_events(ecs: &mut World) {
             ClientboundGamePacket::Cooldown(_) => {}
             ClientboundGamePacket::CustomChatCompletions(_) => {}
             ClientboundGamePacket::DeleteChat(_) => {}
-            ClientboundGamePacket::Explode(_) => {}
-            ClientboundGamePacket::ForgetLevelChunk(_) => {}
+            ClientboundGamePacket::Explode(p) => {
+                debug!("Got explode packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Query<&mut Physics>,
+                    EventWriter<KnockbackEvent>,
+                )> = SystemState::new(ecs);
+                let (mut query, mut knockback_events) = system_state.get_mut(ecs);
+                let mut physics = query.get_mut(player_entity).unwrap();
+
+                // the knockback is only for us, other entities get it from SetEntityMotion
+                let knockback = Vec3 {
+                    x: p.knockback_x as f64,
+                    y: p.knockback_y as f64,
+                    z: p.knockback_z as f64,
+                };
+                if knockback != Vec3::default() {
+                    physics.delta += knockback;
+                    knockback_events.send(KnockbackEvent {
+                        entity: player_entity,
+                        velocity: physics.delta,
+                    });
+                }
+            }
+            ClientboundGamePacket::ForgetLevelChunk(p) => {
+                debug!("Got forget level chunk packet {p:?}");
+
+                #[allow(clippy::type_complexity)]
+                let mut system_state: SystemState<(
+                    Query<(&InstanceHolder, Option<&InstanceName>)>,
+                    Option<ResMut<ChunkCache>>,
+                    EventWriter<UpdateChunkCacheEvent>,
+                )> = SystemState::new(ecs);
+                let (query, chunk_cache, mut update_chunk_cache_events) = system_state.get_mut(ecs);
+                let (local_player, instance_name) = query.get(player_entity).unwrap();
+
+                let mut world = local_player.instance.write();
+                let mut partial_world = local_player.partial_instance.write();
+
+                // the chunk cache has to get its own reference before we drop ours, and then
+                // it decides whether to keep it around when it's updated
+                if let (Some(mut chunk_cache), Some(instance_name)) = (chunk_cache, instance_name) {
+                    if let Some(chunk) = world.chunks.get(&p.pos) {
+                        chunk_cache.keep_unloaded(instance_name, p.pos, chunk);
+                    }
+                }
+                partial_world.chunks.set(&p.pos, None, &mut world.chunks);
+                update_chunk_cache_events.send(UpdateChunkCacheEvent {
+                    entity: player_entity,
+                });
+            }
             ClientboundGamePacket::HorseScreenOpen(_) => {}
             ClientboundGamePacket::MapItemData(_) => {}
-            ClientboundGamePacket::MerchantOffers(_) => {}
             ClientboundGamePacket::MoveVehicle(_) => {}
             ClientboundGamePacket::OpenBook(_) => {}
             ClientboundGamePacket::OpenScreen(p) => {
@@ -1173,7 +1704,19 @@ pub fn process_packet_events(ecs: &mut World) {
                     title: p.title,
                 })
             }
-            ClientboundGamePacket::OpenSignEditor(_) => {}
+            ClientboundGamePacket::OpenSignEditor(p) => {
+                debug!("Got open sign editor packet {p:?}");
+
+                let mut system_state: SystemState<EventWriter<OpenSignEditorEvent>> =
+                    SystemState::new(ecs);
+                let mut open_sign_editor_events = system_state.get_mut(ecs);
+
+                open_sign_editor_events.send(OpenSignEditorEvent {
+                    entity: player_entity,
+                    pos: p.pos,
+                    is_front_text: p.is_front_text,
+                });
+            }
             ClientboundGamePacket::Ping(p) => {
                 debug!("Got ping packet {p:?}");
 
@@ -1198,31 +1741,63 @@ pub fn process_packet_events(ecs: &mut World) {
                 #[allow(clippy::type_complexity)]
                 let mut system_state: SystemState<(
                     Commands,
-                    Query<(&MinecraftEntityId, Option<&Dead>)>,
+                    Query<(&MinecraftEntityId, &Position, Option<&Dead>)>,
                     EventWriter<DeathEvent>,
                 )> = SystemState::new(ecs);
                 let (mut commands, mut query, mut death_events) = system_state.get_mut(ecs);
-                let (entity_id, dead) = query.get_mut(player_entity).unwrap();
+                let (entity_id, position, dead) = query.get_mut(player_entity).unwrap();
 
                 if **entity_id == p.player_id && dead.is_none() {
                     commands.entity(player_entity).insert(Dead);
                     death_events.send(DeathEvent {
                         entity: player_entity,
                         packet: Some(p.clone()),
+                        position: Some(**position),
                     });
                 }
 
                 system_state.apply(ecs);
             }
             ClientboundGamePacket::PlayerLookAt(_) => {}
-            ClientboundGamePacket::RemoveMobEffect(_) => {}
+            ClientboundGamePacket::RemoveMobEffect(p) => {
+                debug!("Got remove mob effect packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Query<&EntityIdIndex>,
+                    Query<&mut ActiveEffects>,
+                )> = SystemState::new(ecs);
+                let (query, mut effects_query) = system_state.get_mut(ecs);
+                let entity_id_index = query.get(player_entity).unwrap();
+
+                let Some(entity) = entity_id_index.get(&MinecraftEntityId(p.entity_id)) else {
+                    warn!(
+                        "Server sent a remove mob effect packet for an entity id ({}) that we don't know about",
+                        p.entity_id
+                    );
+                    continue;
+                };
+
+                if let Ok(mut active_effects) = effects_query.get_mut(entity) {
+                    active_effects.remove(&p.effect);
+                }
+            }
             ClientboundGamePacket::ResourcePack(p) => {
                 debug!("Got resource pack packet {p:?}");
 
-                let mut system_state: SystemState<EventWriter<ResourcePackEvent>> =
-                    SystemState::new(ecs);
-                let mut resource_pack_events = system_state.get_mut(ecs);
+                let mut system_state: SystemState<(
+                    Res<ResourcePackPolicy>,
+                    EventWriter<ResourcePackEvent>,
+                    EventWriter<SendPacketEvent>,
+                )> = SystemState::new(ecs);
+                let (policy, mut resource_pack_events, mut send_packet_events) =
+                    system_state.get_mut(ecs);
 
+                for action in policy.game_actions() {
+                    send_packet_events.send(SendPacketEvent {
+                        entity: player_entity,
+                        packet: ServerboundResourcePackPacket { action }.get(),
+                    });
+                }
                 resource_pack_events.send(ResourcePackEvent {
                     entity: player_entity,
                     url: p.url,
@@ -1244,18 +1819,41 @@ pub fn process_packet_events(ecs: &mut World) {
                         &GameProfileComponent,
                         &ClientInformation,
                         &ReceivedRegistries,
+                        &mut PhysicsState,
+                        Option<&InstanceName>,
                     )>,
                     EventWriter<InstanceLoadedEvent>,
+                    EventWriter<DimensionChangedEvent>,
                     ResMut<InstanceContainer>,
                 )> = SystemState::new(ecs);
-                let (mut commands, mut query, mut instance_loaded_events, mut instance_container) =
-                    system_state.get_mut(ecs);
-                let (mut instance_holder, game_profile, client_information, received_registries) =
-                    query.get_mut(player_entity).unwrap();
+                let (
+                    mut commands,
+                    mut query,
+                    mut instance_loaded_events,
+                    mut dimension_changed_events,
+                    mut instance_container,
+                ) = system_state.get_mut(ecs);
+                let (
+                    mut instance_holder,
+                    game_profile,
+                    client_information,
+                    received_registries,
+                    mut physics_state,
+                    instance_name,
+                ) = query.get_mut(player_entity).unwrap();
 
                 {
                     let new_instance_name = p.common.dimension.clone();
 
+                    let old_instance_name = instance_name.map(|name| name.0.clone());
+                    if old_instance_name.as_ref() != Some(&new_instance_name) {
+                        dimension_changed_events.send(DimensionChangedEvent {
+                            entity: player_entity,
+                            from: old_instance_name,
+                            to: new_instance_name.clone(),
+                        });
+                    }
+
                     let Some(dimension_type) = received_registries.dimension_type() else {
                         error!("Server didn't send dimension type registry, can't log in");
                         continue;
@@ -1311,28 +1909,148 @@ pub fn process_packet_events(ecs: &mut World) {
                             previous: p.common.previous_game_type.into(),
                         },
                         player_bundle,
+                        // respawning gives us a new player, so our effects are gone (the
+                        // server will resend any that we should still have)
+                        ActiveEffects::default(),
                     ));
+                    // the metadata bundle resets ShiftKeyDown, and the server also forgets
+                    // that we were sneaking
+                    physics_state.was_sneaking = false;
                 }
 
                 // Remove the Dead marker component from the player.
                 commands.entity(player_entity).remove::<Dead>();
+                // and we're not riding anything anymore
+                commands.entity(player_entity).remove::<Vehicle>();
 
                 system_state.apply(ecs);
             }
 
             ClientboundGamePacket::SelectAdvancementsTab(_) => {}
             ClientboundGamePacket::SetActionBarText(_) => {}
-            ClientboundGamePacket::SetBorderCenter(_) => {}
-            ClientboundGamePacket::SetBorderLerpSize(_) => {}
-            ClientboundGamePacket::SetBorderSize(_) => {}
-            ClientboundGamePacket::SetBorderWarningDelay(_) => {}
-            ClientboundGamePacket::SetBorderWarningDistance(_) => {}
+            ClientboundGamePacket::SetBorderCenter(p) => {
+                debug!("Got set border center packet {p:?}");
+
+                let mut system_state: SystemState<Query<&InstanceHolder>> = SystemState::new(ecs);
+                let query = system_state.get(ecs);
+                let instance_holder = query.get(player_entity).unwrap();
+                let world_border = &mut instance_holder.instance.write().world_border;
+                (world_border.center_x, world_border.center_z) = (p.new_center_x, p.new_center_z);
+            }
+            ClientboundGamePacket::SetBorderLerpSize(p) => {
+                debug!("Got set border lerp size packet {p:?}");
+
+                let mut system_state: SystemState<Query<&InstanceHolder>> = SystemState::new(ecs);
+                let query = system_state.get(ecs);
+                let instance_holder = query.get(player_entity).unwrap();
+                let world_border = &mut instance_holder.instance.write().world_border;
+                world_border.lerp_size_between(p.old_size, p.new_size, p.lerp_time);
+            }
+            ClientboundGamePacket::SetBorderSize(p) => {
+                debug!("Got set border size packet {p:?}");
+
+                let mut system_state: SystemState<Query<&InstanceHolder>> = SystemState::new(ecs);
+                let query = system_state.get(ecs);
+                let instance_holder = query.get(player_entity).unwrap();
+                let world_border = &mut instance_holder.instance.write().world_border;
+                world_border.set_size(p.size);
+            }
+            ClientboundGamePacket::SetBorderWarningDelay(p) => {
+                debug!("Got set border warning delay packet {p:?}");
+
+                let mut system_state: SystemState<Query<&InstanceHolder>> = SystemState::new(ecs);
+                let query = system_state.get(ecs);
+                let instance_holder = query.get(player_entity).unwrap();
+                let world_border = &mut instance_holder.instance.write().world_border;
+                world_border.warning_time = p.warning_delay;
+            }
+            ClientboundGamePacket::SetBorderWarningDistance(p) => {
+                debug!("Got set border warning distance packet {p:?}");
+
+                let mut system_state: SystemState<Query<&InstanceHolder>> = SystemState::new(ecs);
+                let query = system_state.get(ecs);
+                let instance_holder = query.get(player_entity).unwrap();
+                let world_border = &mut instance_holder.instance.write().world_border;
+                world_border.warning_blocks = p.warning_blocks;
+            }
             ClientboundGamePacket::SetCamera(_) => {}
-            ClientboundGamePacket::SetDisplayObjective(_) => {}
-            ClientboundGamePacket::SetObjective(_) => {}
-            ClientboundGamePacket::SetPassengers(_) => {}
-            ClientboundGamePacket::SetPlayerTeam(_) => {}
-            ClientboundGamePacket::SetScore(_) => {}
+            ClientboundGamePacket::SetDisplayObjective(p) => {
+                debug!("Got set display objective packet {p:?}");
+
+                let mut system_state: SystemState<(Query<&mut Scoreboard>, ResMut<Scoreboard>)> =
+                    SystemState::new(ecs);
+                let (mut query, mut scoreboard_resource) = system_state.get_mut(ecs);
+                let mut scoreboard = query.get_mut(player_entity).unwrap();
+
+                scoreboard.handle_set_display_objective(&p);
+
+                *scoreboard_resource = scoreboard.clone();
+            }
+            ClientboundGamePacket::SetObjective(p) => {
+                debug!("Got set objective packet {p:?}");
+
+                let mut system_state: SystemState<(Query<&mut Scoreboard>, ResMut<Scoreboard>)> =
+                    SystemState::new(ecs);
+                let (mut query, mut scoreboard_resource) = system_state.get_mut(ecs);
+                let mut scoreboard = query.get_mut(player_entity).unwrap();
+
+                scoreboard.handle_set_objective(&p);
+
+                *scoreboard_resource = scoreboard.clone();
+            }
+            ClientboundGamePacket::SetPassengers(p) => {
+                debug!("Got set passengers packet {p:?}");
+
+                #[allow(clippy::type_complexity)]
+                let mut system_state: SystemState<(
+                    Commands,
+                    Query<(&EntityIdIndex, &MinecraftEntityId, Option<&Vehicle>)>,
+                )> = SystemState::new(ecs);
+                let (mut commands, query) = system_state.get_mut(ecs);
+                let (entity_id_index, our_entity_id, current_vehicle) =
+                    query.get(player_entity).unwrap();
+
+                let Some(vehicle) = entity_id_index.get(&MinecraftEntityId(p.vehicle)) else {
+                    warn!(
+                        "Server sent a set passengers packet for an entity id ({}) that we don't know about",
+                        p.vehicle
+                    );
+                    continue;
+                };
+
+                // we only keep track of what local players are riding
+                if p.passengers.contains(&our_entity_id.0) {
+                    commands.entity(player_entity).insert(Vehicle(vehicle));
+                } else if current_vehicle == Some(&Vehicle(vehicle)) {
+                    commands.entity(player_entity).remove::<Vehicle>();
+                }
+
+                system_state.apply(ecs);
+            }
+            ClientboundGamePacket::SetPlayerTeam(p) => {
+                debug!("Got set player team packet {p:?}");
+
+                let mut system_state: SystemState<(Query<&mut Scoreboard>, ResMut<Scoreboard>)> =
+                    SystemState::new(ecs);
+                let (mut query, mut scoreboard_resource) = system_state.get_mut(ecs);
+                let mut scoreboard = query.get_mut(player_entity).unwrap();
+
+                scoreboard.handle_set_player_team(&p);
+
+                *scoreboard_resource = scoreboard.clone();
+            }
+            ClientboundGamePacket::SetScore(p) => {
+                debug!("Got set score packet {p:?}");
+
+                let mut system_state: SystemState<(Query<&mut Scoreboard>, ResMut<Scoreboard>)> =
+                    SystemState::new(ecs);
+                let (mut query, mut scoreboard_resource) = system_state.get_mut(ecs);
+                let mut scoreboard = query.get_mut(player_entity).unwrap();
+
+                scoreboard.handle_set_score(&p);
+
+                *scoreboard_resource = scoreboard.clone();
+            }
             ClientboundGamePacket::SetSimulationDistance(_) => {}
             ClientboundGamePacket::SetSubtitleText(_) => {}
             ClientboundGamePacket::SetTitleText(_) => {}
@@ -1340,15 +2058,211 @@ pub fn process_packet_events(ecs: &mut World) {
             ClientboundGamePacket::ClearTitles(_) => {}
             ClientboundGamePacket::SoundEntity(_) => {}
             ClientboundGamePacket::StopSound(_) => {}
-            ClientboundGamePacket::TabList(_) => {}
+            ClientboundGamePacket::TabList(p) => {
+                debug!("Got tab list packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Query<&mut TabListHeaderFooter>,
+                    ResMut<TabListHeaderFooter>,
+                )> = SystemState::new(ecs);
+                let (mut query, mut header_footer_resource) = system_state.get_mut(ecs);
+                let mut header_footer = query.get_mut(player_entity).unwrap();
+
+                header_footer.header = p.header.clone();
+                header_footer.footer = p.footer.clone();
+
+                *header_footer_resource = header_footer.clone();
+            }
             ClientboundGamePacket::TagQuery(_) => {}
-            ClientboundGamePacket::TakeItemEntity(_) => {}
+            ClientboundGamePacket::TakeItemEntity(p) => {
+                debug!("Got take item entity packet {p:?}");
+
+                let mut system_state: SystemState<(
+                    Query<&EntityIdIndex>,
+                    Query<&ItemItem>,
+                    EventWriter<ItemPickupEvent>,
+                )> = SystemState::new(ecs);
+                let (query, item_query, mut item_pickup_events) = system_state.get_mut(ecs);
+                let entity_id_index = query.get(player_entity).unwrap();
+
+                let item_entity = entity_id_index.get(&MinecraftEntityId(p.item_id));
+                let collector = entity_id_index.get(&MinecraftEntityId(p.player_id));
+                let mut item = item_entity
+                    .and_then(|item_entity| item_query.get(item_entity).ok())
+                    .map(|item| item.0.clone())
+                    .unwrap_or_default();
+                if let ItemSlot::Present(item) = &mut item {
+                    item.count = p.amount as i8;
+                }
+
+                item_pickup_events.send(ItemPickupEvent {
+                    entity: player_entity,
+                    item_entity,
+                    collector,
+                    item,
+                });
+            }
             ClientboundGamePacket::DisguisedChat(_) => {}
             ClientboundGamePacket::Bundle(_) => {}
             ClientboundGamePacket::DamageEvent(_) => {}
             ClientboundGamePacket::HurtAnimation(_) => {}
 
-            ClientboundGamePacket::StartConfiguration(_) => todo!(),
+            ClientboundGamePacket::StartConfiguration(p) => {
+                debug!("Got start configuration packet {p:?}");
+
+                let mut system_state: SystemState<Query<&mut RawConnection>> =
+                    SystemState::new(ecs);
+                let mut query = system_state.get_mut(ecs);
+                let mut raw_connection = query.get_mut(player_entity).unwrap();
+
+                raw_connection
+                    .write_packet(ServerboundConfigurationAcknowledgedPacket {}.get())
+                    .expect(
+                        "we should be in the right state and encoding this packet shouldn't fail",
+                    );
+                raw_connection.set_state(ConnectionProtocol::Configuration);
+                ecs.send_event(ProtocolStateChangedEvent {
+                    entity: player_entity,
+                    from: ConnectionProtocol::Game,
+                    to: ConnectionProtocol::Configuration,
+                });
+
+                // we keep our entity while we're being reconfigured, but the state from
+                // the server that we were on is reset
+                enter_configuration_state(ecs, player_entity);
+            }
         }
     }
 }
+
+/// Update the light in a chunk from the data in a light update or chunk packet.
+fn update_chunk_light(
+    chunk: &mut Chunk,
+    dimension_height: u32,
+    light_data: &ClientboundLightUpdatePacketData,
+) {
+    let section_count = (dimension_height / 16) as usize;
+    chunk.light.update_sky(
+        section_count,
+        &light_data.sky_y_mask,
+        &light_data.empty_sky_y_mask,
+        &light_data.sky_updates,
+    );
+    chunk.light.update_block(
+        section_count,
+        &light_data.block_y_mask,
+        &light_data.empty_block_y_mask,
+        &light_data.block_updates,
+    );
+}
+
+/// Where a [`ClientboundPlayerPositionPacket`] teleports us to, and what our
+/// velocity and look direction are after it. Returns `(position, delta,
+/// direction)`.
+fn apply_teleport(
+    p: &ClientboundPlayerPositionPacket,
+    position: Vec3,
+    delta: Vec3,
+    direction: &LookDirection,
+) -> (Vec3, Vec3, LookDirection) {
+    // each axis is handled separately since the server can make only some of them
+    // relative. relative axes are offsets from where we are now and keep our
+    // momentum, and absolute ones reset it.
+    fn axis(relative: bool, position: f64, delta: f64, value: f64) -> (f64, f64) {
+        if relative {
+            (position + value, delta)
+        } else {
+            (value, 0.)
+        }
+    }
+    let relative = &p.relative_arguments;
+    let (x, delta_x) = axis(relative.x, position.x, delta.x, p.x);
+    let (y, delta_y) = axis(relative.y, position.y, delta.y, p.y);
+    let (z, delta_z) = axis(relative.z, position.z, delta.z, p.z);
+
+    let mut new_direction = LookDirection {
+        x_rot: p.x_rot,
+        y_rot: p.y_rot,
+    };
+    if relative.x_rot {
+        new_direction.x_rot += direction.x_rot;
+    }
+    if relative.y_rot {
+        new_direction.y_rot += direction.y_rot;
+    }
+
+    (
+        Vec3::new(x, y, z),
+        Vec3::new(delta_x, delta_y, delta_z),
+        new_direction,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_protocol::packets::game::clientbound_player_position_packet::RelativeMovements;
+
+    use super::*;
+
+    fn teleport(relative_arguments: RelativeMovements) -> ClientboundPlayerPositionPacket {
+        ClientboundPlayerPositionPacket {
+            x: 10.,
+            y: 20.,
+            z: 30.,
+            y_rot: 45.,
+            x_rot: 10.,
+            relative_arguments,
+            id: 0,
+        }
+    }
+
+    #[test]
+    fn test_partially_relative_teleport() {
+        // like `/tp @s ~ 100 ~5`, with our rotation kept
+        let p = teleport(RelativeMovements {
+            x: true,
+            y: false,
+            z: true,
+            y_rot: true,
+            x_rot: false,
+        });
+        let (position, delta, direction) = apply_teleport(
+            &p,
+            Vec3::new(1., 2., 3.),
+            Vec3::new(0.1, -0.5, 0.2),
+            &LookDirection {
+                x_rot: 30.,
+                y_rot: 90.,
+            },
+        );
+        assert_eq!(position, Vec3::new(11., 20., 33.));
+        // only the relative axes keep our momentum
+        assert_eq!(delta, Vec3::new(0.1, 0., 0.2));
+        assert_eq!(direction.y_rot, 135.);
+        assert_eq!(direction.x_rot, 10.);
+    }
+
+    #[test]
+    fn test_absolute_teleport() {
+        let p = teleport(RelativeMovements {
+            x: false,
+            y: false,
+            z: false,
+            y_rot: false,
+            x_rot: false,
+        });
+        let (position, delta, direction) = apply_teleport(
+            &p,
+            Vec3::new(1., 2., 3.),
+            Vec3::new(0.1, -0.5, 0.2),
+            &LookDirection {
+                x_rot: 30.,
+                y_rot: 90.,
+            },
+        );
+        assert_eq!(position, Vec3::new(10., 20., 30.));
+        assert_eq!(delta, Vec3::default());
+        assert_eq!(direction.y_rot, 45.);
+        assert_eq!(direction.x_rot, 10.);
+    }
+}
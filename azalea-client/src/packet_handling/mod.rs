@@ -1,28 +1,59 @@
-use azalea_entity::{metadata::Health, EntityUpdateSet};
+use azalea_entity::{metadata::Health, EntityUpdateSet, Position};
+use azalea_protocol::packets::ConnectionProtocol;
 use bevy_app::{App, First, Plugin, PreUpdate, Update};
 use bevy_ecs::prelude::*;
 
-use crate::{chat::ChatReceivedEvent, events::death_listener};
+use crate::{
+    chat::ChatReceivedEvent,
+    chunk_batching::{ChunkBatchFinishedEvent, ChunkBatchStartEvent},
+    chunk_cache::UpdateChunkCacheEvent,
+    disconnect::DisconnectEvent,
+    events::death_listener,
+    inventory::{ClientSideCloseContainerEvent, MenuOpenedEvent, SetContainerContentEvent},
+    movement::KnockbackEvent,
+};
 
 use self::game::{
-    AddPlayerEvent, DeathEvent, InstanceLoadedEvent, KeepAliveEvent, RemovePlayerEvent,
-    ResourcePackEvent, UpdatePlayerEvent,
+    AddPlayerEvent, DeathEvent, DimensionChangedEvent, EntityAnimationEvent,
+    EntityMetadataChangedEvent, InstanceLoadedEvent, ItemPickupEvent, KeepAliveEvent,
+    OpenSignEditorEvent, RemovePlayerEvent, ResourcePackEvent, TeleportedEvent, UpdatePlayerEvent,
 };
 
 pub mod configuration;
 pub mod game;
+pub mod raw;
 
 pub struct PacketHandlerPlugin;
 
+/// An event that's sent when a local player's connection switches to a
+/// different protocol state.
+///
+/// The handshake and login states happen before the client is added to the
+/// ECS, so the first of these that gets sent is from
+/// [`ConnectionProtocol::Login`] to [`ConnectionProtocol::Configuration`].
+/// Servers (usually proxies switching us to a different backend) can also send
+/// us from the game state back to the configuration state, in which case the
+/// entity keeps its components until we're in the game state again.
+/// It's sent from the packet handler in the same update that the state
+/// changes, so systems that read it can send packets in the new state right
+/// away.
+#[derive(Event, Debug, Clone)]
+pub struct ProtocolStateChangedEvent {
+    pub entity: Entity,
+    pub from: ConnectionProtocol,
+    pub to: ConnectionProtocol,
+}
+
 pub fn death_event_on_0_health(
-    query: Query<(Entity, &Health), Changed<Health>>,
+    query: Query<(Entity, &Health, Option<&Position>), Changed<Health>>,
     mut death_events: EventWriter<DeathEvent>,
 ) {
-    for (entity, health) in query.iter() {
+    for (entity, health, position) in query.iter() {
         if **health == 0. {
             death_events.send(DeathEvent {
                 entity,
                 packet: None,
+                position: position.map(|position| **position),
             });
         }
     }
@@ -54,6 +85,23 @@ impl Plugin for PacketHandlerPlugin {
         .add_event::<DeathEvent>()
         .add_event::<KeepAliveEvent>()
         .add_event::<ResourcePackEvent>()
-        .add_event::<InstanceLoadedEvent>();
+        .add_event::<InstanceLoadedEvent>()
+        .add_event::<DimensionChangedEvent>()
+        .add_event::<ItemPickupEvent>()
+        .add_event::<TeleportedEvent>()
+        .add_event::<EntityAnimationEvent>()
+        .add_event::<EntityMetadataChangedEvent>()
+        .add_event::<OpenSignEditorEvent>()
+        .add_event::<ProtocolStateChangedEvent>()
+        // these are also added by the plugins that handle them, but we send them so
+        // they have to exist even if those plugins are disabled
+        .add_event::<DisconnectEvent>()
+        .add_event::<UpdateChunkCacheEvent>()
+        .add_event::<KnockbackEvent>()
+        .add_event::<ClientSideCloseContainerEvent>()
+        .add_event::<MenuOpenedEvent>()
+        .add_event::<SetContainerContentEvent>()
+        .add_event::<ChunkBatchStartEvent>()
+        .add_event::<ChunkBatchFinishedEvent>();
     }
 }
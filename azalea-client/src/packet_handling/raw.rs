@@ -0,0 +1,95 @@
+//! Observe every packet that the server sends us, including ones that azalea
+//! doesn't have a dedicated event for.
+
+use std::sync::Arc;
+
+use azalea_protocol::packets::{
+    configuration::ClientboundConfigurationPacket, game::ClientboundGamePacket, ConnectionProtocol,
+};
+use bevy_app::{App, First, Plugin};
+use bevy_ecs::prelude::*;
+
+use super::{configuration, game};
+
+/// A plugin that sends a [`RawPacketEvent`] for every packet we receive.
+///
+/// This isn't included in the `DefaultPlugins` since it has to clone every
+/// packet, so you have to add it yourself if you want to use it.
+///
+/// ```
+/// # use azalea_client::packet_handling::raw::{RawPacket, RawPacketEvent, RawPacketPlugin};
+/// # use bevy_ecs::event::EventReader;
+/// # fn example(app: &mut bevy_app::App) {
+/// app.add_plugins(RawPacketPlugin);
+/// # }
+/// fn log_packets(mut events: EventReader<RawPacketEvent>) {
+///     for event in events.iter() {
+///         if let RawPacket::Game(packet) = &event.packet {
+///             println!("{:?} got {packet:?}", event.entity);
+///         }
+///     }
+/// }
+/// ```
+pub struct RawPacketPlugin;
+impl Plugin for RawPacketPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RawPacketEvent>().add_systems(
+            First,
+            (
+                send_raw_configuration_packet_events.after(configuration::send_packet_events),
+                send_raw_game_packet_events.after(game::send_packet_events),
+            ),
+        );
+    }
+}
+
+/// A packet was received by a local player. These are sent in the same order
+/// that the packets were received in.
+#[derive(Event, Debug, Clone)]
+pub struct RawPacketEvent {
+    /// The client entity that received the packet.
+    pub entity: Entity,
+    pub packet: RawPacket,
+}
+
+/// A clientbound packet, along with the state the connection was in when it
+/// was received.
+#[derive(Debug, Clone)]
+pub enum RawPacket {
+    Configuration(Arc<ClientboundConfigurationPacket>),
+    Game(Arc<ClientboundGamePacket>),
+}
+
+impl RawPacket {
+    /// The state that the connection was in when this packet was received.
+    pub fn state(&self) -> ConnectionProtocol {
+        match self {
+            RawPacket::Configuration(_) => ConnectionProtocol::Configuration,
+            RawPacket::Game(_) => ConnectionProtocol::Game,
+        }
+    }
+}
+
+fn send_raw_configuration_packet_events(
+    mut events: EventReader<configuration::PacketEvent>,
+    mut raw_packet_events: EventWriter<RawPacketEvent>,
+) {
+    for event in events.iter() {
+        raw_packet_events.send(RawPacketEvent {
+            entity: event.entity,
+            packet: RawPacket::Configuration(Arc::new(event.packet.clone())),
+        });
+    }
+}
+
+fn send_raw_game_packet_events(
+    mut events: EventReader<game::PacketEvent>,
+    mut raw_packet_events: EventWriter<RawPacketEvent>,
+) {
+    for event in events.iter() {
+        raw_packet_events.send(RawPacketEvent {
+            entity: event.entity,
+            packet: RawPacket::Game(Arc::new(event.packet.clone())),
+        });
+    }
+}
@@ -0,0 +1,235 @@
+//! Limit how quickly we send packets to the server, so we don't get kicked by
+//! anti-cheats or the server's packet limits.
+
+use std::{collections::VecDeque, time::Instant};
+
+use azalea_protocol::packets::{game::ServerboundGamePacket, ConnectionProtocol};
+use bevy_app::{App, Plugin, Update};
+use bevy_ecs::prelude::*;
+use log::error;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    local_player::handle_send_packet_event, raw_connection::RawConnection, Client, TickBroadcast,
+};
+
+/// A plugin that sends the packets queued by a [`PacketRateLimiter`].
+pub struct PacketRateLimitPlugin;
+impl Plugin for PacketRateLimitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            send_rate_limited_packets.after(handle_send_packet_event),
+        );
+    }
+}
+
+/// How quickly packets are allowed to be sent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    /// The average number of packets that can be sent every second.
+    pub packets_per_second: f32,
+    /// How many packets can be sent at once if we haven't sent any in a while.
+    pub burst: u32,
+}
+impl RateLimit {
+    pub fn per_second(packets_per_second: f32) -> Self {
+        Self {
+            packets_per_second,
+            burst: 1,
+        }
+    }
+
+    #[must_use]
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+}
+
+/// A function that returns whether a packet should use a certain limit.
+pub type PacketFilter = fn(&ServerboundGamePacket) -> bool;
+
+struct TokenBucket {
+    limit: RateLimit,
+    tokens: f32,
+    last_refill: Instant,
+}
+impl TokenBucket {
+    fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f32,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn try_take(&mut self, now: Instant) -> bool {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f32();
+        self.tokens =
+            (self.tokens + elapsed * self.limit.packets_per_second).min(self.limit.burst as f32);
+        self.last_refill = now;
+
+        if self.tokens >= 1. {
+            self.tokens -= 1.;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// A component that makes a client queue the packets it sends with
+/// [`SendPacketEvent`] and send them no faster than the configured limits.
+///
+/// Queued packets are always sent in the order they were queued in, but exempt
+/// packets (keep-alives and pongs by default) skip the queue.
+///
+/// ```
+/// # use azalea_client::packet_rate_limit::{PacketRateLimiter, RateLimit};
+/// # use azalea_protocol::packets::game::ServerboundGamePacket;
+/// # fn example(client: &azalea_client::Client) {
+/// let rate_limiter = PacketRateLimiter::new(Some(RateLimit::per_second(40.).with_burst(10)))
+///     .with_limit(
+///         |packet| matches!(packet, ServerboundGamePacket::ContainerClick(_)),
+///         RateLimit::per_second(5.),
+///     );
+/// client
+///     .ecs
+///     .lock()
+///     .entity_mut(client.entity)
+///     .insert(rate_limiter);
+/// # }
+/// ```
+///
+/// [`SendPacketEvent`]: crate::local_player::SendPacketEvent
+#[derive(Component)]
+pub struct PacketRateLimiter {
+    default_bucket: Option<TokenBucket>,
+    buckets: Vec<(PacketFilter, TokenBucket)>,
+    exempt: Vec<PacketFilter>,
+    queue: VecDeque<ServerboundGamePacket>,
+    /// The number of queued packets at which
+    /// [`Client::wait_for_packet_queue_room`] starts waiting.
+    pub max_queued: usize,
+}
+
+impl PacketRateLimiter {
+    /// Make a rate limiter that uses the given limit for every packet that
+    /// doesn't have its own limit. If it's `None`, those packets aren't
+    /// limited (but they still wait behind other queued packets).
+    pub fn new(default_limit: Option<RateLimit>) -> Self {
+        Self {
+            default_bucket: default_limit.map(TokenBucket::new),
+            buckets: Vec::new(),
+            exempt: vec![|packet| {
+                matches!(
+                    packet,
+                    ServerboundGamePacket::KeepAlive(_) | ServerboundGamePacket::Pong(_)
+                )
+            }],
+            queue: VecDeque::new(),
+            max_queued: 64,
+        }
+    }
+
+    /// Use a different limit for the packets that match the filter. If a
+    /// packet matches more than one filter, the one that was added first is
+    /// used.
+    #[must_use]
+    pub fn with_limit(mut self, filter: PacketFilter, limit: RateLimit) -> Self {
+        self.buckets.push((filter, TokenBucket::new(limit)));
+        self
+    }
+
+    /// Make the packets that match the filter skip the queue and always be
+    /// sent immediately.
+    #[must_use]
+    pub fn with_exempt(mut self, filter: PacketFilter) -> Self {
+        self.exempt.push(filter);
+        self
+    }
+
+    /// Whether this packet skips the queue.
+    pub fn is_exempt(&self, packet: &ServerboundGamePacket) -> bool {
+        self.exempt.iter().any(|filter| filter(packet))
+    }
+
+    /// The number of packets that are waiting to be sent.
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Whether there's less than [`Self::max_queued`] packets waiting to be
+    /// sent.
+    pub fn has_room(&self) -> bool {
+        self.queue.len() < self.max_queued
+    }
+
+    pub(crate) fn enqueue(&mut self, packet: ServerboundGamePacket) {
+        self.queue.push_back(packet);
+    }
+
+    /// Take every queued packet without waiting for their limits, which is
+    /// used when we're disconnecting and the packets would be lost otherwise.
+    pub(crate) fn take_queued(&mut self) -> VecDeque<ServerboundGamePacket> {
+        std::mem::take(&mut self.queue)
+    }
+
+    /// Take the next packet from the queue if its limit allows it to be sent
+    /// now.
+    fn pop(&mut self, now: Instant) -> Option<ServerboundGamePacket> {
+        let packet = self.queue.front()?;
+        let bucket = match self.buckets.iter_mut().find(|(filter, _)| filter(packet)) {
+            Some((_, bucket)) => Some(bucket),
+            None => self.default_bucket.as_mut(),
+        };
+        if let Some(bucket) = bucket {
+            if !bucket.try_take(now) {
+                return None;
+            }
+        }
+        self.queue.pop_front()
+    }
+}
+
+pub fn send_rate_limited_packets(mut query: Query<(&RawConnection, &mut PacketRateLimiter)>) {
+    let now = Instant::now();
+    for (raw_connection, mut rate_limiter) in &mut query {
+        if raw_connection.state() != ConnectionProtocol::Game {
+            // keep the packets queued until we're back in the game state
+            continue;
+        }
+        while let Some(packet) = rate_limiter.pop(now) {
+            if let Err(e) = raw_connection.write_packet(packet) {
+                error!("Failed to send packet: {e}");
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Wait until our [`PacketRateLimiter`] has room for more packets. This
+    /// returns immediately if we don't have a rate limiter.
+    ///
+    /// Use this before sending lots of packets so they don't pile up in the
+    /// queue.
+    pub async fn wait_for_packet_queue_room(&self) {
+        let mut receiver = self.ecs.lock().resource::<TickBroadcast>().subscribe();
+        loop {
+            let has_room = self
+                .ecs
+                .lock()
+                .get::<PacketRateLimiter>(self.entity)
+                .is_none_or(PacketRateLimiter::has_room);
+            if has_room {
+                return;
+            }
+            if let Err(RecvError::Closed) = receiver.recv().await {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Record the physics state of local players every tick.
+//!
+//! This isn't added by default since most bots don't need it. Add the
+//! [`PhysicsSnapshotPlugin`] to start receiving [`PhysicsSnapshotEvent`]s.
+
+use azalea_core::position::Vec3;
+use azalea_entity::{
+    metadata::ShiftKeyDown, Jumping, LocalEntity, LookDirection, Physics, Position,
+};
+use azalea_physics::PhysicsSet;
+use bevy_app::{App, FixedUpdate, Plugin};
+use bevy_ecs::prelude::*;
+
+use crate::movement::{PhysicsState, WalkDirection};
+
+/// A plugin that sends a [`PhysicsSnapshotEvent`] for every local player after
+/// the physics step each tick.
+///
+/// ```
+/// # use azalea_client::physics_snapshot::{PhysicsSnapshotEvent, PhysicsSnapshotPlugin};
+/// # use bevy_ecs::event::EventReader;
+/// # fn example(app: &mut bevy_app::App) {
+/// app.add_plugins(PhysicsSnapshotPlugin);
+/// # }
+/// fn record_movement(mut events: EventReader<PhysicsSnapshotEvent>) {
+///     for event in events.iter() {
+///         println!("{:?}", event.snapshot);
+///     }
+/// }
+/// ```
+pub struct PhysicsSnapshotPlugin;
+impl Plugin for PhysicsSnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PhysicsSnapshotEvent>()
+            .add_systems(FixedUpdate, send_physics_snapshot_events.after(PhysicsSet));
+    }
+}
+
+/// The movement-related state of a player at the end of a tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhysicsSnapshot {
+    pub position: Vec3,
+    /// Our velocity (the `delta` in [`Physics`]), in blocks per tick.
+    pub velocity: Vec3,
+    pub on_ground: bool,
+    pub y_rot: f32,
+    pub x_rot: f32,
+
+    /// The direction we were trying to walk in.
+    pub move_direction: WalkDirection,
+    pub trying_to_sprint: bool,
+    pub jumping: bool,
+    pub sneaking: bool,
+}
+
+/// An event sent every tick with the [`PhysicsSnapshot`] of a local player.
+/// This is only sent if the [`PhysicsSnapshotPlugin`] was added.
+#[derive(Event, Debug, Clone)]
+pub struct PhysicsSnapshotEvent {
+    pub entity: Entity,
+    pub snapshot: PhysicsSnapshot,
+}
+
+#[allow(clippy::type_complexity)]
+pub fn send_physics_snapshot_events(
+    query: Query<
+        (
+            Entity,
+            &Position,
+            &Physics,
+            &LookDirection,
+            &PhysicsState,
+            &Jumping,
+            &ShiftKeyDown,
+        ),
+        With<LocalEntity>,
+    >,
+    mut events: EventWriter<PhysicsSnapshotEvent>,
+) {
+    for (entity, position, physics, look_direction, physics_state, jumping, shift_key_down) in
+        &query
+    {
+        events.send(PhysicsSnapshotEvent {
+            entity,
+            snapshot: PhysicsSnapshot {
+                position: **position,
+                velocity: physics.delta,
+                on_ground: physics.on_ground,
+                y_rot: look_direction.y_rot,
+                x_rot: look_direction.x_rot,
+                move_direction: physics_state.move_direction,
+                trying_to_sprint: physics_state.trying_to_sprint,
+                jumping: **jumping,
+                sneaking: **shift_key_down,
+            },
+        });
+    }
+}
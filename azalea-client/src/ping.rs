@@ -1,20 +1,37 @@
 //! Ping Minecraft servers.
 
+use azalea_chat::{text_component::legacy_color_code_to_text_component, FormattedText};
 use azalea_protocol::{
     connect::{Connection, ConnectionError},
     packets::{
         handshaking::client_intention_packet::ClientIntentionPacket,
         status::{
-            clientbound_status_response_packet::ClientboundStatusResponsePacket,
+            clientbound_status_response_packet::{
+                ClientboundStatusResponsePacket, Players, Version,
+            },
+            serverbound_ping_request_packet::ServerboundPingRequestPacket,
             serverbound_status_request_packet::ServerboundStatusRequestPacket,
-            ClientboundStatusPacket,
+            ClientboundStatusPacket, ServerboundStatusPacket,
         },
         ConnectionProtocol, PROTOCOL_VERSION,
     },
     resolver, ServerAddress,
 };
-use std::io;
+use derive_more::Deref;
+use log::debug;
+use std::{
+    io,
+    net::SocketAddr,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// How long [`ping_server`] waits for the server to respond before giving up.
+pub const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(10);
 
 #[derive(Error, Debug)]
 pub enum PingError {
@@ -28,9 +45,37 @@ pub enum PingError {
     WritePacket(#[from] io::Error),
     #[error("The given address could not be parsed into a ServerAddress")]
     InvalidAddress,
+    #[error("The server didn't respond in time")]
+    Timeout,
+    #[error("The server sent a legacy ping response that couldn't be parsed")]
+    InvalidLegacyResponse,
+}
+
+/// The response from pinging a server with [`ping_server`].
+///
+/// This derefs to the [`ClientboundStatusResponsePacket`], so you can do
+/// things like `status.players.online` directly.
+#[derive(Debug, Clone, Deref)]
+pub struct ServerStatus {
+    /// The MOTD, player count, version, and favicon that the server sent us.
+    #[deref]
+    pub status: ClientboundStatusResponsePacket,
+    /// How long it took for the server to respond to a ping, or `None` if it
+    /// sent its status but didn't respond to the ping in time.
+    pub latency: Option<Duration>,
+    /// Whether the server only understood the ping from before 1.7, so the
+    /// status doesn't have a favicon or sample players.
+    pub is_legacy: bool,
 }
 
-/// Ping a Minecraft server.
+/// Ping a Minecraft server to get its MOTD, player count, version, and
+/// favicon without joining it.
+///
+/// Servers older than 1.7 are pinged the way they expect, and this gives up
+/// with [`PingError::Timeout`] if the server doesn't respond within
+/// [`DEFAULT_PING_TIMEOUT`]. If the server sends its status but doesn't
+/// respond to the ping afterwards, the status is still returned without a
+/// [`ServerStatus::latency`].
 ///
 /// # Examples
 ///
@@ -41,16 +86,74 @@ pub enum PingError {
 /// async fn main() {
 ///     let response = ping::ping_server("play.hypixel.net").await.unwrap();
 ///     println!("{}", response.description.to_ansi());
+///     println!("{} players online", response.players.online);
+///     if let Some(latency) = response.latency {
+///         println!("{}ms", latency.as_millis());
+///     }
 /// }
 /// ```
-pub async fn ping_server(
+pub async fn ping_server(address: impl TryInto<ServerAddress>) -> Result<ServerStatus, PingError> {
+    ping_server_with_timeout(address, DEFAULT_PING_TIMEOUT).await
+}
+
+/// Same as [`ping_server`], but with a custom timeout.
+pub async fn ping_server_with_timeout(
     address: impl TryInto<ServerAddress>,
-) -> Result<ClientboundStatusResponsePacket, PingError> {
+    timeout: Duration,
+) -> Result<ServerStatus, PingError> {
     let address: ServerAddress = address.try_into().map_err(|_| PingError::InvalidAddress)?;
+    let deadline = tokio::time::Instant::now() + timeout;
 
-    let resolved_address = resolver::resolve_address(&address).await?;
+    let (status, conn) = tokio::time::timeout_at(deadline, async {
+        let resolved_address = resolver::resolve_address(&address).await?;
 
-    let mut conn = Connection::new(&resolved_address).await?;
+        match request_status(&address, &resolved_address).await {
+            Err(PingError::ReadPacket(err)) => {
+                // old servers don't understand the handshake and just disconnect us (or send
+                // a legacy kick packet), so try the old way of pinging before giving up
+                debug!("Couldn't read status response ({err}), trying a legacy ping");
+                legacy_ping(&resolved_address)
+                    .await
+                    .map(|status| (status, None))
+                    .map_err(|_| PingError::ReadPacket(err))
+            }
+            result => result.map(|(status, conn)| (status, Some(conn))),
+        }
+    })
+    .await
+    .map_err(|_| PingError::Timeout)??;
+
+    // legacy pings already measured the latency
+    let Some(conn) = conn else {
+        return Ok(status);
+    };
+    // the status is still useful if the server doesn't answer the ping
+    let latency = match tokio::time::timeout_at(deadline, measure_latency(conn)).await {
+        Ok(Ok(latency)) => Some(latency),
+        Ok(Err(err)) => {
+            debug!("Couldn't measure latency: {err}");
+            None
+        }
+        Err(_) => {
+            debug!("The server didn't respond to the ping in time");
+            None
+        }
+    };
+    Ok(ServerStatus { latency, ..status })
+}
+
+/// Connect to the server and get its status, without measuring the latency.
+async fn request_status(
+    address: &ServerAddress,
+    resolved_address: &SocketAddr,
+) -> Result<
+    (
+        ServerStatus,
+        Connection<ClientboundStatusPacket, ServerboundStatusPacket>,
+    ),
+    PingError,
+> {
+    let mut conn = Connection::new(resolved_address).await?;
 
     // send the client intention packet and switch to the status state
     conn.write(
@@ -68,14 +171,185 @@ pub async fn ping_server(
     // send the empty status request packet
     conn.write(ServerboundStatusRequestPacket {}.get()).await?;
 
-    let packet = conn.read().await?;
-
-    loop {
-        match packet {
-            ClientboundStatusPacket::StatusResponse(p) => return Ok(p),
+    let status = loop {
+        match conn.read().await? {
+            ClientboundStatusPacket::StatusResponse(p) => break p,
             ClientboundStatusPacket::PongResponse(_) => {
                 // we should never get this packet since we didn't send a ping
             }
         }
+    };
+
+    Ok((
+        ServerStatus {
+            status,
+            latency: None,
+            is_legacy: false,
+        },
+        conn,
+    ))
+}
+
+/// Send a ping to the server and wait for it to send it back.
+async fn measure_latency(
+    mut conn: Connection<ClientboundStatusPacket, ServerboundStatusPacket>,
+) -> Result<Duration, PingError> {
+    // the server sends back whatever number we send, but vanilla uses the time
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_millis() as u64);
+    let ping_sent_at = Instant::now();
+    conn.write(ServerboundPingRequestPacket { time }.get())
+        .await?;
+    loop {
+        if let ClientboundStatusPacket::PongResponse(p) = conn.read().await? {
+            if p.time == time {
+                return Ok(ping_sent_at.elapsed());
+            }
+        }
+    }
+}
+
+/// Ping a server the way that clients before 1.7 did.
+async fn legacy_ping(resolved_address: &SocketAddr) -> Result<ServerStatus, PingError> {
+    let mut stream = TcpStream::connect(resolved_address)
+        .await
+        .map_err(ConnectionError::from)?;
+    let ping_sent_at = Instant::now();
+    // this is what 1.6 clients sent, and older servers ignore the 0x01
+    stream
+        .write_all(&[0xfe, 0x01])
+        .await
+        .map_err(ConnectionError::from)?;
+
+    // the response is a kick packet, which is 0xff followed by a string
+    let mut header = [0; 3];
+    stream
+        .read_exact(&mut header)
+        .await
+        .map_err(ConnectionError::from)?;
+    let latency = ping_sent_at.elapsed();
+    if header[0] != 0xff {
+        return Err(PingError::InvalidLegacyResponse);
+    }
+    // the length is in characters, which are two bytes each
+    let length = u16::from_be_bytes([header[1], header[2]]) as usize;
+    let mut data = vec![0; length * 2];
+    stream
+        .read_exact(&mut data)
+        .await
+        .map_err(ConnectionError::from)?;
+    let utf16 = data
+        .chunks_exact(2)
+        .map(|c| u16::from_be_bytes([c[0], c[1]]))
+        .collect::<Vec<_>>();
+    let text = String::from_utf16(&utf16).map_err(|_| PingError::InvalidLegacyResponse)?;
+
+    let status = parse_legacy_status(&text).ok_or(PingError::InvalidLegacyResponse)?;
+    Ok(ServerStatus {
+        status,
+        latency: Some(latency),
+        is_legacy: true,
+    })
+}
+
+/// Parse the string in a legacy ping response.
+///
+/// Servers from 1.4 to 1.6 send `§1\0protocol\0version\0motd\0online\0max`,
+/// and older ones send `motd§online§max`.
+fn parse_legacy_status(text: &str) -> Option<ClientboundStatusResponsePacket> {
+    let (protocol, version_name, motd, online, max) = if let Some(rest) = text.strip_prefix("§1\0")
+    {
+        let mut parts = rest.split('\0');
+        (
+            parts.next()?.parse().ok()?,
+            parts.next()?.to_string(),
+            parts.next()?,
+            parts.next()?,
+            parts.next()?,
+        )
+    } else {
+        // the motd can have color codes in it, so we split from the end
+        let mut parts = text.rsplitn(3, '§');
+        let max = parts.next()?;
+        let online = parts.next()?;
+        let motd = parts.next()?;
+        // these versions didn't send a version, so we don't know it
+        (-1, String::new(), motd, online, max)
+    };
+
+    Some(ClientboundStatusResponsePacket {
+        description: FormattedText::Text(legacy_color_code_to_text_component(motd)),
+        favicon: None,
+        players: Players {
+            max: max.parse().ok()?,
+            online: online.parse().ok()?,
+            sample: Vec::new(),
+        },
+        version: Version {
+            name: version_name,
+            protocol,
+        },
+        enforces_secure_chat: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Join the parts of a 1.4 to 1.6 legacy response with null characters.
+    fn legacy_response(parts: &[&str]) -> String {
+        parts.join("\0")
+    }
+
+    #[test]
+    fn test_parse_legacy_status() {
+        let status = parse_legacy_status(&legacy_response(&[
+            "§1",
+            "74",
+            "1.6.4",
+            "A Minecraft Server",
+            "5",
+            "20",
+        ]))
+        .unwrap();
+        assert_eq!(status.version.protocol, 74);
+        assert_eq!(status.version.name, "1.6.4");
+        assert_eq!(status.description.to_string(), "A Minecraft Server");
+        assert_eq!(status.players.online, 5);
+        assert_eq!(status.players.max, 20);
+        assert!(status.favicon.is_none());
+    }
+
+    #[test]
+    fn test_parse_old_legacy_status() {
+        // the motd can have color codes, which use the same character as the separator
+        let status = parse_legacy_status("§aGreen §lserver§3§10").unwrap();
+        assert_eq!(status.version.protocol, -1);
+        assert_eq!(status.version.name, "");
+        assert_eq!(status.description.to_string(), "Green server");
+        assert_eq!(status.players.online, 3);
+        assert_eq!(status.players.max, 10);
+    }
+
+    #[test]
+    fn test_parse_invalid_legacy_status() {
+        // missing the player counts
+        assert!(parse_legacy_status(&legacy_response(&[
+            "§1",
+            "74",
+            "1.6.4",
+            "A Minecraft Server"
+        ]))
+        .is_none());
+        // the protocol version isn't a number
+        assert!(
+            parse_legacy_status(&legacy_response(&["§1", "abc", "1.6.4", "motd", "5", "20"]))
+                .is_none()
+        );
+        assert!(parse_legacy_status("motd§five§20").is_none());
+        assert!(parse_legacy_status("just a motd").is_none());
+        assert!(parse_legacy_status("").is_none());
     }
 }
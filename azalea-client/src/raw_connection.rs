@@ -11,7 +11,9 @@ use bevy_ecs::prelude::*;
 use log::error;
 use parking_lot::Mutex;
 use thiserror::Error;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::connection_stats::ConnectionStats;
 
 /// A component for clients that can read and write packets to the server. This
 /// works with raw bytes, so you'll have to serialize/deserialize packets
@@ -30,27 +32,63 @@ pub struct RawConnection {
     write_packets_task: tokio::task::JoinHandle<()>,
 
     connection_protocol: ConnectionProtocol,
+
+    /// The compression threshold and whether the connection was encrypted
+    /// when it was made. These can't change after the login state.
+    compression_threshold: Option<u32>,
+    is_encrypted: bool,
 }
 
 #[derive(Clone)]
 struct RawConnectionReader {
     pub incoming_packet_queue: Arc<Mutex<Vec<Vec<u8>>>>,
     pub run_schedule_sender: mpsc::UnboundedSender<()>,
+    pub stats: ConnectionStats,
 }
 #[derive(Clone)]
 struct RawConnectionWriter {
-    pub outgoing_packets_sender: mpsc::UnboundedSender<Vec<u8>>,
+    pub outgoing_packets_sender: mpsc::UnboundedSender<OutgoingMessage>,
+    pub stats: ConnectionStats,
 }
 
+/// Something for the write task to do. These are handled in the order they
+/// were sent.
+enum OutgoingMessage {
+    Packet(Vec<u8>),
+    /// Shut down the connection after writing every packet before this, and
+    /// then tell the sender that we're done.
+    Close(oneshot::Sender<()>),
+}
+
+/// An error from writing a packet to the server.
+///
+/// If this is [`WritePacketError::ConnectionClosed`] then writing again won't
+/// work until we reconnect, but the other variants mean that there's something
+/// wrong with the packet.
 #[derive(Error, Debug)]
 pub enum WritePacketError {
+    /// The packet is for a different protocol state than the one that the
+    /// connection is in, like a game packet while we're still in the
+    /// configuration state.
     #[error("Wrong protocol state: expected {expected:?}, got {got:?}")]
     WrongState {
         expected: ConnectionProtocol,
         got: ConnectionProtocol,
     },
+    /// The packet couldn't be serialized, usually because it's too big.
     #[error(transparent)]
     Encoding(#[from] azalea_protocol::write::PacketEncodeError),
+    /// We're not connected to the server anymore, so the packet can't be sent.
+    #[error("The connection to the server is closed")]
+    ConnectionClosed,
+}
+
+impl WritePacketError {
+    /// Whether this error happened because we're not connected anymore, in
+    /// which case we'd have to reconnect to send packets again.
+    pub fn is_connection_closed(&self) -> bool {
+        matches!(self, Self::ConnectionClosed)
+    }
 }
 
 impl RawConnection {
@@ -59,17 +97,23 @@ impl RawConnection {
         connection_protocol: ConnectionProtocol,
         raw_read_connection: RawReadConnection,
         raw_write_connection: RawWriteConnection,
+        stats: ConnectionStats,
     ) -> Self {
         let (outgoing_packets_sender, outgoing_packets_receiver) = mpsc::unbounded_channel();
 
         let incoming_packet_queue = Arc::new(Mutex::new(Vec::new()));
 
+        let compression_threshold = raw_read_connection.compression_threshold;
+        let is_encrypted = raw_read_connection.dec_cipher.is_some();
+
         let reader = RawConnectionReader {
             incoming_packet_queue: incoming_packet_queue.clone(),
             run_schedule_sender,
+            stats: stats.clone(),
         };
         let writer = RawConnectionWriter {
             outgoing_packets_sender,
+            stats,
         };
 
         let read_packets_task = tokio::spawn(reader.clone().read_task(raw_read_connection));
@@ -85,43 +129,90 @@ impl RawConnection {
             read_packets_task,
             write_packets_task,
             connection_protocol,
+            compression_threshold,
+            is_encrypted,
         }
     }
 
-    pub fn write_raw_packet(&self, raw_packet: Vec<u8>) {
+    /// Write a packet that was already serialized to the server.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WritePacketError::ConnectionClosed`] if the task that writes
+    /// packets has stopped, which happens when the connection is closed.
+    pub fn write_raw_packet(&self, raw_packet: Vec<u8>) -> Result<(), WritePacketError> {
         self.writer
             .outgoing_packets_sender
-            .send(raw_packet)
-            .unwrap();
+            .send(OutgoingMessage::Packet(raw_packet))
+            .map_err(|_| WritePacketError::ConnectionClosed)
+    }
+
+    /// Close the connection after every packet that was already written has
+    /// been sent to the server.
+    ///
+    /// `closed_sender` is notified once the connection is shut down. If the
+    /// write task had already stopped, it's dropped instead.
+    pub fn close_after_flush(&self, closed_sender: oneshot::Sender<()>) {
+        let _ = self
+            .writer
+            .outgoing_packets_sender
+            .send(OutgoingMessage::Close(closed_sender));
+    }
+
+    /// Close the connection immediately, without waiting for packets that are
+    /// still being written.
+    ///
+    /// Use [`Self::close_after_flush`] instead if the connection is still
+    /// working.
+    pub fn close(&self) {
+        self.read_packets_task.abort();
+        self.write_packets_task.abort();
     }
 
     /// Write the packet with the given state to the server.
     ///
     /// # Errors
     ///
-    /// Returns an error if the packet is not valid for the current state, or if
-    /// encoding it failed somehow (like it's too big or something).
+    /// Returns an error if encoding the packet failed somehow (like it's too
+    /// big), or if the connection is closed.
     pub fn write_packet<P: ProtocolPacket + Debug>(
         &self,
         packet: P,
     ) -> Result<(), WritePacketError> {
         let raw_packet = serialize_packet(&packet)?;
-        self.write_raw_packet(raw_packet);
-        Ok(())
+        self.write_raw_packet(raw_packet)
     }
 
-    /// Returns whether the connection is still alive.
+    /// Returns whether the connection is still alive, which is until either
+    /// reading or writing packets stops.
     pub fn is_alive(&self) -> bool {
-        !self.read_packets_task.is_finished()
+        !self.read_packets_task.is_finished() && !self.write_packets_task.is_finished()
     }
 
     pub fn incoming_packet_queue(&self) -> Arc<Mutex<Vec<Vec<u8>>>> {
         self.reader.incoming_packet_queue.clone()
     }
 
+    /// The protocol state that the connection is currently in.
+    pub fn state(&self) -> ConnectionProtocol {
+        self.connection_protocol
+    }
+
     pub fn set_state(&mut self, connection_protocol: ConnectionProtocol) {
         self.connection_protocol = connection_protocol;
     }
+
+    /// The size in bytes that packets have to be before they're compressed,
+    /// or `None` if compression is disabled.
+    pub fn compression_threshold(&self) -> Option<u32> {
+        self.compression_threshold
+    }
+
+    /// Whether the connection is encrypted, which is only the case for
+    /// online-mode servers.
+    pub fn is_encrypted(&self) -> bool {
+        self.is_encrypted
+    }
 }
 
 impl RawConnectionReader {
@@ -131,6 +222,7 @@ impl RawConnectionReader {
         loop {
             match read_conn.read().await {
                 Ok(raw_packet) => {
+                    self.stats.record_received(raw_packet.len());
                     self.incoming_packet_queue.lock().push(raw_packet);
                     // tell the client to run all the systems
                     self.run_schedule_sender.send(()).unwrap();
@@ -153,13 +245,25 @@ impl RawConnectionWriter {
     pub async fn write_task(
         self,
         mut write_conn: RawWriteConnection,
-        mut outgoing_packets_receiver: mpsc::UnboundedReceiver<Vec<u8>>,
+        mut outgoing_packets_receiver: mpsc::UnboundedReceiver<OutgoingMessage>,
     ) {
-        while let Some(raw_packet) = outgoing_packets_receiver.recv().await {
-            if let Err(err) = write_conn.write(&raw_packet).await {
-                error!("Disconnecting because we couldn't write a packet: {err}.");
-                break;
-            };
+        while let Some(message) = outgoing_packets_receiver.recv().await {
+            match message {
+                OutgoingMessage::Packet(raw_packet) => {
+                    if let Err(err) = write_conn.write(&raw_packet).await {
+                        error!("Disconnecting because we couldn't write a packet: {err}.");
+                        break;
+                    };
+                    self.stats.record_sent(raw_packet.len());
+                }
+                OutgoingMessage::Close(closed_sender) => {
+                    if let Err(err) = write_conn.shutdown().await {
+                        error!("Error shutting down connection: {err}.");
+                    }
+                    let _ = closed_sender.send(());
+                    break;
+                }
+            }
         }
         // receiver is automatically closed when it's dropped
     }
@@ -168,7 +272,6 @@ impl RawConnectionWriter {
 impl Drop for RawConnection {
     /// Stop every active task when this `RawConnection` is dropped.
     fn drop(&mut self) {
-        self.read_packets_task.abort();
-        self.write_packets_task.abort();
+        self.close();
     }
 }
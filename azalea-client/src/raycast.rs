@@ -0,0 +1,325 @@
+//! Find the block or entity that we're looking at, like vanilla does for
+//! targeting.
+
+use azalea_core::{
+    aabb::AABB, block_hit_result::BlockHitResult, game_type::GameMode, position::Vec3,
+};
+use azalea_entity::{
+    view_vector, Dead, EntityKind, EntityUuid, EyeHeight, LookDirection, Physics, Position,
+};
+use azalea_world::{Instance, InstanceContainer, InstanceName};
+use bevy_ecs::prelude::*;
+
+use crate::{interact::pick, local_player::TabList, Client};
+
+/// The block or entity that a raycast hit. See [`Client::raycast`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HitResult {
+    Block(BlockHitResult),
+    Entity(EntityHitResult),
+}
+
+impl HitResult {
+    /// The exact point where the ray hit the block or entity.
+    pub fn location(&self) -> Vec3 {
+        match self {
+            HitResult::Block(block) => block.location,
+            HitResult::Entity(entity) => entity.location,
+        }
+    }
+}
+
+/// The entity that a raycast hit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntityHitResult {
+    pub entity: Entity,
+    /// The point where the ray went into the entity's bounding box.
+    pub location: Vec3,
+}
+
+/// Whether vanilla lets players target this kind of entity, so raycasts go
+/// through entities like dropped items and arrows.
+pub fn is_pickable(kind: azalea_registry::EntityKind) -> bool {
+    use azalea_registry::EntityKind::*;
+    !matches!(
+        kind,
+        AreaEffectCloud
+            | Arrow
+            | BlockDisplay
+            | Egg
+            | EnderPearl
+            | EvokerFangs
+            | ExperienceBottle
+            | ExperienceOrb
+            | EyeOfEnder
+            | FireworkRocket
+            | FishingBobber
+            | Item
+            | ItemDisplay
+            | LightningBolt
+            | LlamaSpit
+            | Marker
+            | Potion
+            | Snowball
+            | SpectralArrow
+            | TextDisplay
+            | Trident
+    )
+}
+
+/// Whether a raycast can hit this entity. Entities that aren't pickable and
+/// players in spectator mode are skipped, like in vanilla.
+///
+/// `game_mode` is the entity's game mode from the tab list, or `None` if it
+/// isn't a player that's in the tab list.
+pub fn is_targetable(kind: azalea_registry::EntityKind, game_mode: Option<GameMode>) -> bool {
+    is_pickable(kind) && game_mode != Some(GameMode::Spectator)
+}
+
+/// Get the closest entity whose bounding box is on the line between `from`
+/// and `to`. If `from` is inside of an entity, that entity is returned.
+pub fn clip_entities<'a>(
+    from: Vec3,
+    to: Vec3,
+    entities: impl IntoIterator<Item = (Entity, &'a AABB)>,
+) -> Option<EntityHitResult> {
+    let mut closest: Option<(f64, EntityHitResult)> = None;
+    for (entity, bounding_box) in entities {
+        let (distance, location) = if bounding_box.contains(from.x, from.y, from.z) {
+            (0., from)
+        } else if let Some(location) = bounding_box.clip(&from, &to) {
+            (from.distance_to_sqr(&location), location)
+        } else {
+            continue;
+        };
+        let is_closer = match closest {
+            Some((closest_distance, _)) => distance < closest_distance,
+            None => true,
+        };
+        if is_closer {
+            closest = Some((distance, EntityHitResult { entity, location }));
+        }
+    }
+    closest.map(|(_, hit)| hit)
+}
+
+/// Cast a ray from `eye_position` in the given direction and return the first
+/// block or entity that it hits.
+///
+/// Like vanilla, entities are only hit if they're closer than the first block
+/// in the way.
+pub fn raycast<'a>(
+    look_direction: &LookDirection,
+    eye_position: &Vec3,
+    instance: &Instance,
+    entities: impl IntoIterator<Item = (Entity, &'a AABB)>,
+    max_distance: f64,
+) -> Option<HitResult> {
+    let block_hit = pick(look_direction, eye_position, instance, max_distance);
+    let entity_distance = if block_hit.miss {
+        max_distance
+    } else {
+        eye_position.distance_to(&block_hit.location)
+    };
+
+    let end_position = eye_position + &(view_vector(look_direction) * entity_distance);
+    if let Some(entity_hit) = clip_entities(*eye_position, end_position, entities) {
+        return Some(HitResult::Entity(entity_hit));
+    }
+    if block_hit.miss {
+        None
+    } else {
+        Some(HitResult::Block(block_hit))
+    }
+}
+
+impl Client {
+    /// Get the block or entity that we're looking at, or `None` if there's
+    /// nothing within `max_distance` blocks of our eyes.
+    ///
+    /// Our reach for blocks and entities is in the
+    /// [`Reach`](crate::interact::Reach) component, which has vanilla's
+    /// defaults. The block that we're looking at is also always available in
+    /// the [`HitResultComponent`](crate::interact::HitResultComponent).
+    pub fn raycast(&self, max_distance: f64) -> Option<HitResult> {
+        let mut ecs = self.ecs.lock();
+        let (position, eye_height, look_direction, instance_name) =
+            self.query::<(&Position, &EyeHeight, &LookDirection, &InstanceName)>(&mut ecs);
+        let eye_position = Vec3::from(position).up(**eye_height as f64);
+        let look_direction = look_direction.clone();
+        let instance_name = instance_name.clone();
+
+        let instance_lock = ecs.resource::<InstanceContainer>().get(&instance_name)?;
+        let instance = instance_lock.read();
+
+        let mut query = ecs.query_filtered::<(
+            Entity,
+            &Physics,
+            &InstanceName,
+            &EntityKind,
+            Option<&EntityUuid>,
+        ), Without<Dead>>();
+        let tab_list = ecs.get::<TabList>(self.entity);
+        let entities = query
+            .iter(&ecs)
+            .filter(|(entity, _, entity_instance_name, kind, uuid)| {
+                let game_mode = uuid
+                    .zip(tab_list)
+                    .and_then(|(uuid, tab_list)| tab_list.get(&**uuid))
+                    .map(|player_info| player_info.gamemode);
+                *entity != self.entity
+                    && **entity_instance_name == instance_name
+                    && is_targetable(***kind, game_mode)
+            })
+            .map(|(entity, physics, _, _, _)| (entity, &physics.bounding_box));
+
+        raycast(
+            &look_direction,
+            &eye_position,
+            &instance,
+            entities,
+            max_distance,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use azalea_core::position::{BlockPos, ChunkPos};
+    use azalea_world::Chunk;
+    use parking_lot::RwLock;
+
+    use super::*;
+
+    /// An instance with a stone block 3 blocks in front of where we look from
+    /// in the tests. The chunk has to be kept around for as long as the
+    /// instance is used.
+    fn instance_with_wall() -> (Instance, Arc<RwLock<Chunk>>) {
+        let mut instance = Instance::default();
+        let chunk = Arc::new(RwLock::new(Chunk::default()));
+        instance
+            .chunks
+            .map
+            .insert(ChunkPos::new(0, 0), Arc::downgrade(&chunk));
+        instance.chunks.set_block_state(
+            &BlockPos::new(0, 70, 3),
+            azalea_registry::Block::Stone.into(),
+        );
+        (instance, chunk)
+    }
+
+    fn entity_box_at_z(z: f64) -> AABB {
+        AABB {
+            min_x: 0.2,
+            min_y: 70.,
+            min_z: z - 0.3,
+            max_x: 0.8,
+            max_y: 71.8,
+            max_z: z + 0.3,
+        }
+    }
+
+    const EYE_POSITION: Vec3 = Vec3 {
+        x: 0.5,
+        y: 70.5,
+        z: 0.5,
+    };
+    // looking straight towards positive z
+    const LOOK_DIRECTION: LookDirection = LookDirection {
+        x_rot: 0.,
+        y_rot: 0.,
+    };
+
+    #[test]
+    fn test_raycast_hits_block() {
+        let (instance, _chunk) = instance_with_wall();
+        let hit = raycast(&LOOK_DIRECTION, &EYE_POSITION, &instance, [], 5.);
+        let Some(HitResult::Block(block_hit)) = hit else {
+            panic!("expected to hit a block, got {hit:?}");
+        };
+        assert_eq!(block_hit.block_pos, BlockPos::new(0, 70, 3));
+        assert_eq!(block_hit.location.z, 3.);
+
+        // the block is out of reach
+        assert_eq!(
+            raycast(&LOOK_DIRECTION, &EYE_POSITION, &instance, [], 2.),
+            None
+        );
+    }
+
+    #[test]
+    fn test_raycast_hits_entity_in_front_of_block() {
+        let (instance, _chunk) = instance_with_wall();
+        let entity = Entity::from_raw(1);
+        let bounding_box = entity_box_at_z(2.);
+        let hit = raycast(
+            &LOOK_DIRECTION,
+            &EYE_POSITION,
+            &instance,
+            [(entity, &bounding_box)],
+            5.,
+        );
+        let Some(HitResult::Entity(entity_hit)) = hit else {
+            panic!("expected to hit an entity, got {hit:?}");
+        };
+        assert_eq!(entity_hit.entity, entity);
+        assert!((entity_hit.location.z - 1.7).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_raycast_doesnt_hit_entity_behind_block() {
+        let (instance, _chunk) = instance_with_wall();
+        let bounding_box = entity_box_at_z(4.5);
+        let hit = raycast(
+            &LOOK_DIRECTION,
+            &EYE_POSITION,
+            &instance,
+            [(Entity::from_raw(1), &bounding_box)],
+            5.,
+        );
+        assert!(matches!(hit, Some(HitResult::Block(_))), "got {hit:?}");
+    }
+
+    #[test]
+    fn test_clip_entities_picks_closest() {
+        let near = entity_box_at_z(2.);
+        let far = entity_box_at_z(4.);
+        let to = Vec3::new(0.5, 70.5, 10.);
+        let hit = clip_entities(
+            EYE_POSITION,
+            to,
+            [(Entity::from_raw(2), &far), (Entity::from_raw(1), &near)],
+        )
+        .unwrap();
+        assert_eq!(hit.entity, Entity::from_raw(1));
+
+        // we're inside of the far one, so that's the one we hit
+        let from = Vec3::new(0.5, 70.5, 4.);
+        let hit = clip_entities(
+            from,
+            to,
+            [(Entity::from_raw(1), &near), (Entity::from_raw(2), &far)],
+        )
+        .unwrap();
+        assert_eq!(hit.entity, Entity::from_raw(2));
+        assert_eq!(hit.location, from);
+    }
+
+    #[test]
+    fn test_targetable_entities() {
+        use azalea_registry::EntityKind;
+
+        assert!(is_targetable(EntityKind::Zombie, None));
+        assert!(is_targetable(EntityKind::Player, Some(GameMode::Survival)));
+        // dropped items and projectiles aren't pickable
+        assert!(!is_targetable(EntityKind::Item, None));
+        assert!(!is_targetable(EntityKind::Arrow, None));
+        // players in spectator mode can't be hit
+        assert!(!is_targetable(
+            EntityKind::Player,
+            Some(GameMode::Spectator)
+        ));
+    }
+}
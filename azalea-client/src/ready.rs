@@ -0,0 +1,173 @@
+//! Waiting for the chunks around us to load after we join, so scripts don't
+//! start pathfinding or scanning in a world that looks empty.
+
+use std::time::Duration;
+
+use azalea_core::position::ChunkPos;
+use azalea_entity::Position;
+use bevy_ecs::{component::Component, system::Resource};
+use derive_more::Deref;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{local_player::InstanceHolder, Client, ClientInformation, TickBroadcast};
+
+/// How many chunks around us have to be loaded before
+/// [`Client::wait_until_ready`] returns, and how long it waits for them. You
+/// can insert this resource yourself to change them.
+///
+/// ```
+/// # use azalea_client::ready::ReadySettings;
+/// # use std::time::Duration;
+/// # let mut app = bevy_app::App::new();
+/// app.insert_resource(ReadySettings {
+///     chunk_radius: 4,
+///     timeout: Duration::from_secs(30),
+/// });
+/// ```
+#[derive(Resource, Clone, Debug)]
+pub struct ReadySettings {
+    /// The number of chunks in every direction from the one we're in that
+    /// have to be loaded. This is 0 to only wait for our own chunk.
+    ///
+    /// It's limited to our view distance and the server's, since chunks
+    /// farther away than that are never sent to us.
+    pub chunk_radius: u32,
+    /// How long to wait for the chunks before giving up.
+    pub timeout: Duration,
+}
+impl Default for ReadySettings {
+    fn default() -> Self {
+        Self {
+            chunk_radius: 2,
+            timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+/// The view distance that the server told us it's using, in chunks. This is
+/// inserted when we join and updated when the server changes it.
+#[derive(Component, Clone, Copy, Debug, Deref)]
+pub struct ServerViewDistance(pub u32);
+
+impl Client {
+    /// Wait until the chunk that we're in and the ones around it (see
+    /// [`ReadySettings`]) are loaded, or until the timeout passes. Returns
+    /// whether they all loaded.
+    ///
+    /// Call this after joining so the world isn't still empty when your code
+    /// starts looking at it.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # async fn example(bot: &Client) {
+    /// if !bot.wait_until_ready().await {
+    ///     println!("the chunks around us didn't load in time");
+    /// }
+    /// # }
+    /// ```
+    pub async fn wait_until_ready(&self) -> bool {
+        let (mut receiver, settings) = {
+            let ecs = self.ecs.lock();
+            (
+                ecs.resource::<TickBroadcast>().subscribe(),
+                ecs.resource::<ReadySettings>().clone(),
+            )
+        };
+
+        // the timeout can't only be checked after ticks, since they might never come
+        // (like in the manual tick mode)
+        let deadline = tokio::time::Instant::now() + settings.timeout;
+        tokio::time::timeout_at(deadline, async {
+            loop {
+                if self.chunks_around_us_loaded(settings.chunk_radius) {
+                    return true;
+                }
+                if let Err(RecvError::Closed) = receiver.recv().await {
+                    return false;
+                }
+            }
+        })
+        .await
+        .unwrap_or(false)
+    }
+
+    /// Whether every chunk within `chunk_radius` of the one we're in is
+    /// loaded. This is checked every tick since we might've moved into a
+    /// different chunk or dimension.
+    fn chunks_around_us_loaded(&self, chunk_radius: u32) -> bool {
+        let mut ecs = self.ecs.lock();
+        let mut query = ecs.query::<(
+            &InstanceHolder,
+            &Position,
+            &ClientInformation,
+            Option<&ServerViewDistance>,
+        )>();
+        let Ok((instance_holder, position, client_information, server_view_distance)) =
+            query.get(&ecs, self.entity)
+        else {
+            // we haven't gotten to the game state yet
+            return false;
+        };
+
+        // the server won't send chunks past either view distance, so we'd wait
+        // forever for them
+        let mut chunk_radius = chunk_radius.min(client_information.view_distance.into());
+        if let Some(server_view_distance) = server_view_distance {
+            chunk_radius = chunk_radius.min(**server_view_distance);
+        }
+        let chunk_radius = chunk_radius as i32;
+
+        let center = ChunkPos::from(**position);
+        let partial_instance = instance_holder.partial_instance.read();
+        for x in -chunk_radius..=chunk_radius {
+            for z in -chunk_radius..=chunk_radius {
+                // the server sends chunks in a circle, so the corners might never come
+                if x * x + z * z > chunk_radius * chunk_radius {
+                    continue;
+                }
+                let pos = ChunkPos::new(center.x + x, center.z + z);
+                if partial_instance.chunks.limited_get(&pos).is_none() {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use azalea_auth::game_profile::GameProfile;
+    use bevy_app::App;
+    use parking_lot::Mutex;
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::client::TickBroadcastPlugin;
+
+    #[tokio::test]
+    async fn test_wait_until_ready_times_out_without_ticks() {
+        let mut app = App::new();
+        app.add_plugins(TickBroadcastPlugin)
+            .insert_resource(ReadySettings {
+                chunk_radius: 0,
+                timeout: Duration::from_millis(50),
+            });
+        let entity = app.world.spawn_empty().id();
+        let ecs = Arc::new(Mutex::new(std::mem::take(&mut app.world)));
+        let client = Client::new(
+            GameProfile::new(Uuid::nil(), "bot".to_string()),
+            entity,
+            ecs,
+            mpsc::unbounded_channel().0,
+        );
+
+        // nothing ever ticks, like in the manual tick mode, so this would hang if the
+        // timeout was only checked after a tick
+        let result = tokio::time::timeout(Duration::from_secs(5), client.wait_until_ready()).await;
+        assert_eq!(result, Ok(false));
+    }
+}
@@ -0,0 +1,66 @@
+//! Responding to the resource packs that servers ask us to use.
+//!
+//! We never actually download the packs, but some servers kick clients that
+//! don't say that they loaded them.
+
+use azalea_protocol::packets::{configuration, game};
+use bevy_ecs::system::Resource;
+
+/// How we respond when a server asks us to use a resource pack. A
+/// [`ResourcePackEvent`] is sent either way, so you can see the pack's URL and
+/// hash.
+///
+/// This defaults to [`ResourcePackPolicy::Accept`], since servers that
+/// require a pack kick clients that decline it. You can replace this resource
+/// to respond differently.
+///
+/// ```
+/// # use azalea_client::resource_pack::ResourcePackPolicy;
+/// # fn example(app: &mut bevy_app::App) {
+/// app.insert_resource(ResourcePackPolicy::Decline);
+/// # }
+/// ```
+///
+/// [`ResourcePackEvent`]: crate::packet_handling::game::ResourcePackEvent
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResourcePackPolicy {
+    /// Say that we accepted the pack and then that it loaded successfully.
+    #[default]
+    Accept,
+    /// Say that we declined the pack. Servers that require the pack will kick
+    /// us for this.
+    Decline,
+    /// Say that we accepted the pack but then failed to download it.
+    FailDownload,
+    /// Don't respond at all, so you can send your own response when you get a
+    /// [`ResourcePackEvent`].
+    ///
+    /// [`ResourcePackEvent`]: crate::packet_handling::game::ResourcePackEvent
+    Ignore,
+}
+
+impl ResourcePackPolicy {
+    /// The actions that we send in the game state, in order.
+    pub fn game_actions(&self) -> Vec<game::serverbound_resource_pack_packet::Action> {
+        use game::serverbound_resource_pack_packet::Action;
+        match self {
+            Self::Accept => vec![Action::Accepted, Action::SuccessfullyLoaded],
+            Self::Decline => vec![Action::Declined],
+            Self::FailDownload => vec![Action::Accepted, Action::FailedDownload],
+            Self::Ignore => vec![],
+        }
+    }
+
+    /// The actions that we send in the configuration state, in order.
+    pub fn configuration_actions(
+        &self,
+    ) -> Vec<configuration::serverbound_resource_pack_packet::Action> {
+        use configuration::serverbound_resource_pack_packet::Action;
+        match self {
+            Self::Accept => vec![Action::Accepted, Action::SuccessfullyLoaded],
+            Self::Decline => vec![Action::Declined],
+            Self::FailDownload => vec![Action::Accepted, Action::FailedDownload],
+            Self::Ignore => vec![],
+        }
+    }
+}
@@ -1,10 +1,14 @@
+use azalea_core::position::{BlockPos, GlobalPos};
 use azalea_protocol::packets::game::serverbound_client_command_packet::{
     self, ServerboundClientCommandPacket,
 };
 use bevy_app::{App, Plugin, Update};
 use bevy_ecs::prelude::*;
 
-use crate::local_player::{handle_send_packet_event, SendPacketEvent};
+use crate::{
+    local_player::{handle_send_packet_event, SendPacketEvent},
+    Client,
+};
 
 /// Tell the server that we're respawning.
 #[derive(Event, Debug, Clone)]
@@ -12,6 +16,31 @@ pub struct PerformRespawnEvent {
     pub entity: Entity,
 }
 
+/// A component that contains where we'll appear when we respawn.
+///
+/// This is inserted when the server tells us the world spawn after we join
+/// (or when we sleep in a bed), and the server tells us again when we change
+/// dimensions.
+#[derive(Component, Clone, Debug, PartialEq)]
+pub struct SpawnPoint {
+    /// The world spawn, which is where we respawn if we don't have a bed.
+    /// Compasses point here.
+    ///
+    /// These are overworld coordinates, even if we're in another dimension,
+    /// since the other dimensions share the overworld's spawn. Like vanilla,
+    /// this is the origin until the server tells us the real one.
+    pub pos: BlockPos,
+    /// The direction that we face when we spawn at the world spawn.
+    pub angle: f32,
+    /// The last bed that we slept in, which is where we respawn instead of the
+    /// world spawn.
+    ///
+    /// The server doesn't tell us if the bed was broken (or if the spawn point
+    /// was changed with a command), in which case we'll actually respawn at
+    /// the world spawn in the overworld.
+    pub bed: Option<GlobalPos>,
+}
+
 /// A plugin that makes [`PerformRespawnEvent`] send the packet to respawn.
 pub struct RespawnPlugin;
 impl Plugin for RespawnPlugin {
@@ -35,3 +64,28 @@ pub fn perform_respawn(
         });
     }
 }
+
+impl Client {
+    /// Tell the server that we want to respawn after dying.
+    ///
+    /// On hardcore servers (see [`Hardcore`]), this puts us in spectator mode
+    /// instead, since we can't respawn.
+    ///
+    /// [`Hardcore`]: crate::Hardcore
+    pub fn respawn(&mut self) {
+        self.ecs.lock().send_event(PerformRespawnEvent {
+            entity: self.entity,
+        });
+    }
+
+    /// Get the spawn point of the world that we're in, or `None` if the server
+    /// hasn't told us it yet. This doesn't include beds, see
+    /// [`SpawnPoint::bed`] for that.
+    ///
+    /// This is a shortcut for
+    /// `bot.get_component::<SpawnPoint>().map(|spawn_point| spawn_point.pos)`.
+    pub fn spawn_point(&self) -> Option<BlockPos> {
+        self.get_component::<SpawnPoint>()
+            .map(|spawn_point| spawn_point.pos)
+    }
+}
@@ -0,0 +1,368 @@
+//! The objectives, scores, and teams that the server sends us. Minigame
+//! servers often use the sidebar to show things like timers and kill counts.
+
+use std::collections::{HashMap, HashSet};
+
+use azalea_chat::{style::ChatFormatting, FormattedText};
+use azalea_protocol::packets::game::{
+    clientbound_set_display_objective_packet::{ClientboundSetDisplayObjectivePacket, DisplaySlot},
+    clientbound_set_objective_packet::{self, ClientboundSetObjectivePacket, RenderType},
+    clientbound_set_player_team_packet::{self, ClientboundSetPlayerTeamPacket},
+    clientbound_set_score_packet::{self, ClientboundSetScorePacket},
+};
+use bevy_ecs::{component::Component, system::Resource};
+use log::warn;
+
+use crate::Client;
+
+/// The most lines that the vanilla client shows in the sidebar.
+pub const MAX_SIDEBAR_LINES: usize = 15;
+
+/// A component that contains the scoreboard objectives and teams that the
+/// server has sent us.
+///
+/// ```
+/// # fn example(client: &azalea_client::Client) {
+/// let scoreboard = client.scoreboard();
+/// if let Some(sidebar) = scoreboard.sidebar() {
+///     println!("{}", sidebar.display_name);
+/// }
+/// for line in scoreboard.sidebar_lines() {
+///     println!("{} {}", line.text, line.score);
+/// }
+/// # }
+/// ```
+#[derive(Component, Resource, Clone, Debug, Default)]
+pub struct Scoreboard {
+    /// The objectives, keyed by their name.
+    pub objectives: HashMap<String, Objective>,
+    /// The names of the objectives that are being shown in each display slot.
+    pub display_slots: HashMap<DisplaySlot, String>,
+    /// The teams, keyed by their name.
+    pub teams: HashMap<String, Team>,
+}
+
+/// Something that scores are tracked for, like kills or the time left in a
+/// game.
+#[derive(Clone, Debug)]
+pub struct Objective {
+    pub display_name: FormattedText,
+    pub render_type: RenderType,
+    /// The scores for this objective, keyed by the owner's name. The owner is
+    /// usually a player's username, but servers often use fake names to show
+    /// arbitrary text in the sidebar.
+    pub scores: HashMap<String, i32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Team {
+    pub display_name: FormattedText,
+    /// Shown before the name of every member of the team.
+    pub prefix: FormattedText,
+    /// Shown after the name of every member of the team.
+    pub suffix: FormattedText,
+    pub color: ChatFormatting,
+    pub friendly_fire: bool,
+    pub see_friendly_invisibles: bool,
+    pub nametag_visibility: String,
+    pub collision_rule: String,
+    /// The names of the players (or other score owners) in this team.
+    pub members: HashSet<String>,
+}
+impl Team {
+    fn new(parameters: clientbound_set_player_team_packet::Parameters) -> Self {
+        let mut team = Self {
+            display_name: FormattedText::default(),
+            prefix: FormattedText::default(),
+            suffix: FormattedText::default(),
+            color: ChatFormatting::Reset,
+            friendly_fire: false,
+            see_friendly_invisibles: false,
+            nametag_visibility: String::new(),
+            collision_rule: String::new(),
+            members: HashSet::new(),
+        };
+        team.update(parameters);
+        team
+    }
+
+    fn update(&mut self, parameters: clientbound_set_player_team_packet::Parameters) {
+        self.display_name = parameters.display_name;
+        self.prefix = parameters.player_prefix;
+        self.suffix = parameters.player_suffix;
+        self.color = parameters.color;
+        self.friendly_fire = parameters.options & 0x01 != 0;
+        self.see_friendly_invisibles = parameters.options & 0x02 != 0;
+        self.nametag_visibility = parameters.nametag_visibility;
+        self.collision_rule = parameters.collision_rule;
+    }
+}
+
+/// A line in the sidebar, in the order that the vanilla client shows them.
+#[derive(Clone, Debug)]
+pub struct SidebarLine {
+    pub owner: String,
+    /// The owner's name with their team's prefix and suffix.
+    pub text: String,
+    pub score: i32,
+}
+
+impl Scoreboard {
+    /// The objective that's being shown in the sidebar, if any.
+    pub fn sidebar(&self) -> Option<&Objective> {
+        self.displayed_objective(DisplaySlot::Sidebar)
+    }
+
+    /// The objective that's being shown in the given display slot, if any.
+    pub fn displayed_objective(&self, slot: DisplaySlot) -> Option<&Objective> {
+        self.objectives.get(self.display_slots.get(&slot)?)
+    }
+
+    /// The lines in the sidebar, sorted from top to bottom. This is empty if
+    /// the sidebar isn't being shown.
+    pub fn sidebar_lines(&self) -> Vec<SidebarLine> {
+        let Some(objective) = self.sidebar() else {
+            return Vec::new();
+        };
+
+        let mut scores = objective.scores.iter().collect::<Vec<_>>();
+        // highest score first, and ties are sorted by name
+        scores.sort_by(|(a_owner, a_score), (b_owner, b_score)| {
+            b_score.cmp(a_score).then_with(|| a_owner.cmp(b_owner))
+        });
+        scores
+            .into_iter()
+            .take(MAX_SIDEBAR_LINES)
+            .map(|(owner, &score)| {
+                let text = match self.team_for(owner) {
+                    Some(team) => format!("{}{owner}{}", team.prefix, team.suffix),
+                    None => owner.clone(),
+                };
+                SidebarLine {
+                    owner: owner.clone(),
+                    text,
+                    score,
+                }
+            })
+            .collect()
+    }
+
+    /// The team that the given player (or other score owner) is in.
+    pub fn team_for(&self, owner: &str) -> Option<&Team> {
+        self.teams
+            .values()
+            .find(|team| team.members.contains(owner))
+    }
+
+    pub fn handle_set_objective(&mut self, packet: &ClientboundSetObjectivePacket) {
+        let name = &packet.objective_name;
+        match &packet.method {
+            clientbound_set_objective_packet::Method::Add(info) => {
+                // servers sometimes re-add objectives without removing them first, in which
+                // case the old scores are discarded
+                self.objectives.insert(
+                    name.clone(),
+                    Objective {
+                        display_name: info.display_name.clone(),
+                        render_type: info.render_type,
+                        scores: HashMap::new(),
+                    },
+                );
+            }
+            clientbound_set_objective_packet::Method::Change(info) => {
+                let Some(objective) = self.objectives.get_mut(name) else {
+                    warn!("Tried to change unknown objective {name}");
+                    return;
+                };
+                objective.display_name = info.display_name.clone();
+                objective.render_type = info.render_type;
+            }
+            clientbound_set_objective_packet::Method::Remove => {
+                self.objectives.remove(name);
+                // re-adding the objective doesn't make it displayed again
+                self.display_slots.retain(|_, displayed| displayed != name);
+            }
+        }
+    }
+
+    pub fn handle_set_score(&mut self, packet: &ClientboundSetScorePacket) {
+        match packet.method {
+            clientbound_set_score_packet::Method::Change { score } => {
+                let Some(objective_name) = &packet.objective_name else {
+                    warn!(
+                        "Tried to set a score for {} without an objective",
+                        packet.owner
+                    );
+                    return;
+                };
+                let Some(objective) = self.objectives.get_mut(objective_name) else {
+                    warn!("Tried to set a score for unknown objective {objective_name}");
+                    return;
+                };
+                // scores are signed but they're read as unsigned
+                objective.scores.insert(packet.owner.clone(), score as i32);
+            }
+            clientbound_set_score_packet::Method::Remove => match &packet.objective_name {
+                Some(objective_name) => {
+                    if let Some(objective) = self.objectives.get_mut(objective_name) {
+                        objective.scores.remove(&packet.owner);
+                    }
+                }
+                // no objective means the owner is removed from all of them
+                None => {
+                    for objective in self.objectives.values_mut() {
+                        objective.scores.remove(&packet.owner);
+                    }
+                }
+            },
+        }
+    }
+
+    pub fn handle_set_display_objective(&mut self, packet: &ClientboundSetDisplayObjectivePacket) {
+        if packet.objective_name.is_empty() {
+            self.display_slots.remove(&packet.slot);
+        } else {
+            self.display_slots
+                .insert(packet.slot, packet.objective_name.clone());
+        }
+    }
+
+    pub fn handle_set_player_team(&mut self, packet: &ClientboundSetPlayerTeamPacket) {
+        let name = &packet.name;
+        match &packet.method {
+            clientbound_set_player_team_packet::Method::Add((parameters, members)) => {
+                let mut team = Team::new(parameters.clone());
+                self.remove_from_teams(members);
+                team.members.extend(members.iter().cloned());
+                self.teams.insert(name.clone(), team);
+            }
+            clientbound_set_player_team_packet::Method::Remove => {
+                self.teams.remove(name);
+            }
+            clientbound_set_player_team_packet::Method::Change(parameters) => {
+                let Some(team) = self.teams.get_mut(name) else {
+                    warn!("Tried to change unknown team {name}");
+                    return;
+                };
+                team.update(parameters.clone());
+            }
+            clientbound_set_player_team_packet::Method::Join(members) => {
+                // players can only be in one team at a time
+                self.remove_from_teams(members);
+                let Some(team) = self.teams.get_mut(name) else {
+                    warn!("Tried to join unknown team {name}");
+                    return;
+                };
+                team.members.extend(members.iter().cloned());
+            }
+            clientbound_set_player_team_packet::Method::Leave(members) => {
+                let Some(team) = self.teams.get_mut(name) else {
+                    warn!("Tried to leave unknown team {name}");
+                    return;
+                };
+                for member in members {
+                    team.members.remove(member);
+                }
+            }
+        }
+    }
+
+    fn remove_from_teams(&mut self, members: &[String]) {
+        for team in self.teams.values_mut() {
+            for member in members {
+                team.members.remove(member);
+            }
+        }
+    }
+}
+
+impl Client {
+    /// Get the scoreboard objectives and teams that the server has sent us.
+    ///
+    /// This is a shortcut for `bot.component::<Scoreboard>()`.
+    pub fn scoreboard(&self) -> Scoreboard {
+        self.component::<Scoreboard>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_protocol::packets::game::clientbound_set_objective_packet::DisplayInfo;
+
+    use super::*;
+
+    fn add_objective(scoreboard: &mut Scoreboard, name: &str) {
+        scoreboard.handle_set_objective(&ClientboundSetObjectivePacket {
+            objective_name: name.to_string(),
+            method: clientbound_set_objective_packet::Method::Add(DisplayInfo {
+                display_name: FormattedText::from(name.to_string()),
+                render_type: RenderType::Integer,
+            }),
+        });
+    }
+
+    fn set_score(scoreboard: &mut Scoreboard, objective: Option<&str>, owner: &str, score: i32) {
+        scoreboard.handle_set_score(&ClientboundSetScorePacket {
+            owner: owner.to_string(),
+            method: clientbound_set_score_packet::Method::Change {
+                score: score as u32,
+            },
+            objective_name: objective.map(str::to_string),
+        });
+    }
+
+    fn remove_score(scoreboard: &mut Scoreboard, objective: Option<&str>, owner: &str) {
+        scoreboard.handle_set_score(&ClientboundSetScorePacket {
+            owner: owner.to_string(),
+            method: clientbound_set_score_packet::Method::Remove,
+            objective_name: objective.map(str::to_string),
+        });
+    }
+
+    #[test]
+    fn test_remove_score() {
+        let mut scoreboard = Scoreboard::default();
+        add_objective(&mut scoreboard, "kills");
+        add_objective(&mut scoreboard, "deaths");
+        set_score(&mut scoreboard, Some("kills"), "alice", 3);
+        set_score(&mut scoreboard, Some("kills"), "bob", -1);
+        set_score(&mut scoreboard, Some("deaths"), "alice", 2);
+
+        remove_score(&mut scoreboard, Some("kills"), "alice");
+        assert_eq!(scoreboard.objectives["kills"].scores.get("alice"), None);
+        assert_eq!(scoreboard.objectives["kills"].scores.get("bob"), Some(&-1));
+        assert_eq!(
+            scoreboard.objectives["deaths"].scores.get("alice"),
+            Some(&2)
+        );
+
+        // no objective means it's removed from every objective
+        remove_score(&mut scoreboard, None, "alice");
+        assert_eq!(scoreboard.objectives["deaths"].scores.get("alice"), None);
+    }
+
+    #[test]
+    fn test_readd_objective_with_same_name() {
+        let mut scoreboard = Scoreboard::default();
+        add_objective(&mut scoreboard, "kills");
+        set_score(&mut scoreboard, Some("kills"), "alice", 3);
+        scoreboard.handle_set_display_objective(&ClientboundSetDisplayObjectivePacket {
+            slot: DisplaySlot::Sidebar,
+            objective_name: "kills".to_string(),
+        });
+
+        // re-adding without removing discards the old scores but keeps it displayed
+        add_objective(&mut scoreboard, "kills");
+        assert!(scoreboard.objectives["kills"].scores.is_empty());
+        assert!(scoreboard.sidebar().is_some());
+
+        // removing it and adding it back doesn't display it again
+        scoreboard.handle_set_objective(&ClientboundSetObjectivePacket {
+            objective_name: "kills".to_string(),
+            method: clientbound_set_objective_packet::Method::Remove,
+        });
+        add_objective(&mut scoreboard, "kills");
+        assert!(scoreboard.sidebar().is_none());
+        assert!(scoreboard.sidebar_lines().is_empty());
+    }
+}
@@ -0,0 +1,281 @@
+//! Writing text on signs.
+
+use azalea_core::position::BlockPos;
+use azalea_protocol::packets::game::serverbound_sign_update_packet::ServerboundSignUpdatePacket;
+use bevy_app::{App, FixedUpdate, Plugin, Update};
+use bevy_ecs::prelude::*;
+use log::warn;
+
+use crate::{
+    local_player::{handle_send_packet_event, SendPacketEvent},
+    packet_handling::game::OpenSignEditorEvent,
+    Client,
+};
+
+/// How many ticks we wait for the server to open a sign editor after
+/// [`Client::edit_sign`] is called, and how long a sign editor that the server
+/// opened stays open before we stop trying to answer it.
+///
+/// The server stops letting us edit a sign if we walk away from it, so we
+/// don't want to write on a sign long after we were allowed to.
+pub const SIGN_EDIT_TIMEOUT_TICKS: u32 = 20 * 5;
+
+/// A plugin that lets clients write text on signs. See [`Client::edit_sign`].
+pub struct SignPlugin;
+impl Plugin for SignPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<EditSignEvent>()
+            .add_systems(
+                Update,
+                (
+                    handle_open_sign_editor_event,
+                    // so the edit handler sees the SignEditor that was just inserted
+                    apply_deferred,
+                    handle_edit_sign_event,
+                )
+                    .chain()
+                    .before(handle_send_packet_event),
+            )
+            .add_systems(FixedUpdate, tick_sign_edit_timeouts);
+    }
+}
+
+impl Client {
+    /// Write text on the sign at the given position, like you would in the
+    /// screen that opens after placing a sign.
+    ///
+    /// The server only lets us edit signs that it opened the editor for, which
+    /// it usually does right after we place one (or when we right-click a sign
+    /// that we're allowed to edit). If it hasn't done that yet, the text is
+    /// sent as soon as it does. If the editor doesn't open within
+    /// [`SIGN_EDIT_TIMEOUT_TICKS`] (or it was opened longer ago than that), the
+    /// edit is dropped.
+    ///
+    /// Since 1.20, signs have text on both sides. `front` is which side we
+    /// write on, which doesn't have to be the side that the server opened the
+    /// editor for. See [`Client::sign_editor`] to get that side.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # use azalea_core::position::BlockPos;
+    /// # fn example(bot: &mut Client, pos: BlockPos) {
+    /// bot.edit_sign(
+    ///     pos,
+    ///     [
+    ///         "".to_string(),
+    ///         "diamonds".to_string(),
+    ///         "for sale".to_string(),
+    ///         "".to_string(),
+    ///     ],
+    ///     true,
+    /// );
+    /// # }
+    /// ```
+    pub fn edit_sign(&mut self, pos: BlockPos, lines: [String; 4], front: bool) {
+        self.ecs.lock().send_event(EditSignEvent {
+            entity: self.entity,
+            pos,
+            lines,
+            is_front_text: front,
+        });
+    }
+
+    /// Get the sign editor that the server opened for us, if there is one that
+    /// we haven't answered yet.
+    pub fn sign_editor(&self) -> Option<SignEditor> {
+        self.get_component::<SignEditor>()
+    }
+}
+
+/// Write text on a sign. See [`Client::edit_sign`].
+#[derive(Event, Debug, Clone)]
+pub struct EditSignEvent {
+    pub entity: Entity,
+    pub pos: BlockPos,
+    pub lines: [String; 4],
+    pub is_front_text: bool,
+}
+
+/// A component that's present on clients that the server opened a sign editor
+/// for, until we answer it or it times out.
+#[derive(Component, Debug, Clone)]
+pub struct SignEditor {
+    pub pos: BlockPos,
+    /// The side of the sign that the server opened the editor for, which is
+    /// the side that we were looking at.
+    pub is_front_text: bool,
+    /// How many ticks ago the server opened the editor.
+    pub ticks_open: u32,
+}
+
+/// A component that's present on clients that called [`Client::edit_sign`]
+/// before the server opened the editor for that sign.
+#[derive(Component, Debug, Clone)]
+pub struct PendingSignEdit {
+    pub pos: BlockPos,
+    pub lines: [String; 4],
+    pub is_front_text: bool,
+    /// How many ticks we've been waiting for the server to open the editor.
+    pub ticks_waited: u32,
+}
+
+pub fn handle_open_sign_editor_event(
+    mut commands: Commands,
+    mut events: EventReader<OpenSignEditorEvent>,
+    query: Query<Option<&PendingSignEdit>>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.iter() {
+        let Ok(pending) = query.get(event.entity) else {
+            continue;
+        };
+
+        if let Some(pending) = pending.filter(|pending| pending.pos == event.pos) {
+            send_sign_update(
+                event.entity,
+                pending.pos,
+                pending.lines.clone(),
+                pending.is_front_text,
+                &mut send_packet_events,
+            );
+            commands.entity(event.entity).remove::<PendingSignEdit>();
+            continue;
+        }
+
+        commands.entity(event.entity).insert(SignEditor {
+            pos: event.pos,
+            is_front_text: event.is_front_text,
+            ticks_open: 0,
+        });
+    }
+}
+
+pub fn handle_edit_sign_event(
+    mut commands: Commands,
+    mut events: EventReader<EditSignEvent>,
+    query: Query<Option<&SignEditor>>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.iter() {
+        let Ok(sign_editor) = query.get(event.entity) else {
+            continue;
+        };
+
+        if sign_editor.is_some_and(|sign_editor| sign_editor.pos == event.pos) {
+            send_sign_update(
+                event.entity,
+                event.pos,
+                event.lines.clone(),
+                event.is_front_text,
+                &mut send_packet_events,
+            );
+            commands.entity(event.entity).remove::<SignEditor>();
+        } else {
+            // the server hasn't opened the editor yet, which usually means we just placed
+            // the sign and the packet hasn't arrived
+            commands.entity(event.entity).insert(PendingSignEdit {
+                pos: event.pos,
+                lines: event.lines.clone(),
+                is_front_text: event.is_front_text,
+                ticks_waited: 0,
+            });
+        }
+    }
+}
+
+fn send_sign_update(
+    entity: Entity,
+    pos: BlockPos,
+    lines: [String; 4],
+    is_front_text: bool,
+    send_packet_events: &mut EventWriter<SendPacketEvent>,
+) {
+    send_packet_events.send(SendPacketEvent {
+        entity,
+        packet: ServerboundSignUpdatePacket {
+            pos,
+            is_front_text,
+            lines,
+        }
+        .get(),
+    });
+}
+
+/// Forget about sign editors that we didn't answer and edits that the server
+/// didn't open an editor for in time.
+#[allow(clippy::type_complexity)]
+pub fn tick_sign_edit_timeouts(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            Option<&mut SignEditor>,
+            Option<&mut PendingSignEdit>,
+        ),
+        Or<(With<SignEditor>, With<PendingSignEdit>)>,
+    >,
+) {
+    for (entity, sign_editor, pending) in &mut query {
+        if let Some(mut sign_editor) = sign_editor {
+            sign_editor.ticks_open += 1;
+            if sign_editor.ticks_open > SIGN_EDIT_TIMEOUT_TICKS {
+                commands.entity(entity).remove::<SignEditor>();
+            }
+        }
+        if let Some(mut pending) = pending {
+            pending.ticks_waited += 1;
+            if pending.ticks_waited > SIGN_EDIT_TIMEOUT_TICKS {
+                warn!(
+                    "The server didn't open the sign editor at {:?} in time, so the text wasn't written",
+                    pending.pos
+                );
+                commands.entity(entity).remove::<PendingSignEdit>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_protocol::packets::game::ServerboundGamePacket;
+
+    use super::*;
+
+    #[test]
+    fn test_edit_sign_in_same_frame_as_editor_opens() {
+        let mut app = App::new();
+        app.add_event::<OpenSignEditorEvent>()
+            .add_event::<SendPacketEvent>()
+            .add_plugins(SignPlugin);
+        let entity = app.world.spawn_empty().id();
+        let pos = BlockPos::new(1, 64, 2);
+
+        app.world.send_event(OpenSignEditorEvent {
+            entity,
+            pos,
+            is_front_text: true,
+        });
+        app.world.send_event(EditSignEvent {
+            entity,
+            pos,
+            lines: Default::default(),
+            is_front_text: true,
+        });
+        app.update();
+
+        let sent = app
+            .world
+            .resource_mut::<Events<SendPacketEvent>>()
+            .drain()
+            .collect::<Vec<_>>();
+        assert_eq!(sent.len(), 1);
+        assert!(matches!(
+            sent[0].packet,
+            ServerboundGamePacket::SignUpdate(ServerboundSignUpdatePacket { pos: sent_pos, .. })
+                if sent_pos == pos
+        ));
+        let entity = app.world.entity(entity);
+        assert!(!entity.contains::<SignEditor>());
+        assert!(!entity.contains::<PendingSignEdit>());
+    }
+}
@@ -0,0 +1,337 @@
+//! Sleeping in beds and waking up.
+
+use azalea_chat::FormattedText;
+use azalea_core::position::{BlockPos, GlobalPos};
+use azalea_entity::{metadata::SleepingPos, LocalEntity};
+use azalea_physics::PhysicsSet;
+use azalea_protocol::packets::game::serverbound_player_command_packet::{
+    self, ServerboundPlayerCommandPacket,
+};
+use azalea_world::{InstanceName, MinecraftEntityId};
+use bevy_app::{App, FixedUpdate, Plugin, Update};
+use bevy_ecs::prelude::*;
+use thiserror::Error;
+
+use crate::{
+    chat::{ChatPacket, ChatReceivedEvent},
+    interact::{handle_block_interact_event, BlockInteractEvent},
+    local_player::{handle_send_packet_event, SendPacketEvent},
+    respawn::SpawnPoint,
+    Client,
+};
+
+/// A plugin that lets clients sleep in beds. See [`Client::start_sleeping`].
+pub struct SleepPlugin;
+impl Plugin for SleepPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<StartSleepingEvent>()
+            .add_event::<WakeUpEvent>()
+            .add_event::<SleepFailedEvent>()
+            .add_systems(FixedUpdate, tick_trying_to_sleep.before(PhysicsSet))
+            .add_systems(
+                Update,
+                (
+                    (
+                        handle_start_sleeping_event.before(handle_block_interact_event),
+                        handle_wake_up_event.before(handle_send_packet_event),
+                    ),
+                    update_sleeping,
+                    detect_sleep_failure,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// How many ticks we wait for the server to put us in a bed or tell us why it
+/// couldn't before we give up.
+const SLEEP_TIMEOUT_TICKS: u32 = 20;
+
+/// The reason that the server didn't let us sleep in a bed.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SleepError {
+    #[error("You can only sleep at night or during thunderstorms")]
+    NotNight,
+    #[error("This bed is occupied")]
+    Occupied,
+    #[error("The bed is too far away")]
+    TooFarAway,
+    #[error("The bed is obstructed")]
+    Obstructed,
+    #[error("There are monsters nearby")]
+    NotSafe,
+    /// The server didn't put us in the bed and didn't tell us why. This
+    /// happens when the block isn't a bed, or when the bed explodes because
+    /// it's in the nether or the end.
+    #[error("The server didn't respond")]
+    NoResponse,
+}
+
+impl SleepError {
+    /// Get the error from the translation key of the message that the server
+    /// sends us when we can't sleep.
+    pub fn from_translation_key(key: &str) -> Option<Self> {
+        Some(match key {
+            "block.minecraft.bed.no_sleep" => SleepError::NotNight,
+            "block.minecraft.bed.occupied" => SleepError::Occupied,
+            "block.minecraft.bed.too_far_away" => SleepError::TooFarAway,
+            "block.minecraft.bed.obstructed" => SleepError::Obstructed,
+            "block.minecraft.bed.not_safe" => SleepError::NotSafe,
+            _ => return None,
+        })
+    }
+}
+
+/// A component that's present while we're sleeping in a bed. It contains the
+/// position of the bed.
+#[derive(Component, Clone, Debug)]
+pub struct Sleeping(pub BlockPos);
+
+/// A component that's present after we right-clicked a bed and while we're
+/// waiting for the server to put us in it.
+#[derive(Component, Clone, Debug)]
+pub struct TryingToSleep {
+    pub bed: BlockPos,
+    /// The number of ticks since we right-clicked the bed.
+    pub ticks: u32,
+}
+
+/// A component that contains the reason we couldn't sleep the last time we
+/// tried. It's removed when we try to sleep again.
+#[derive(Component, Clone, Debug)]
+pub struct LastSleepError(pub SleepError);
+
+impl Client {
+    /// Right-click a bed to try to sleep in it. This doesn't wait for the
+    /// server to respond, so check for the [`Sleeping`] or [`LastSleepError`]
+    /// components after.
+    ///
+    /// You should probably use `bot.sleep(bed).await` from the `azalea` crate
+    /// instead, which waits until we're in the bed or returns why we couldn't
+    /// sleep.
+    pub fn start_sleeping(&mut self, bed: BlockPos) {
+        let mut ecs = self.ecs.lock();
+        // removed now so the error from the last attempt can't be mistaken for
+        // the result of this one
+        ecs.entity_mut(self.entity).remove::<LastSleepError>();
+        ecs.send_event(StartSleepingEvent {
+            entity: self.entity,
+            bed,
+        });
+    }
+
+    /// Get out of the bed that we're sleeping in. This doesn't do anything if
+    /// we're not sleeping.
+    pub fn wake_up(&mut self) {
+        self.ecs.lock().send_event(WakeUpEvent {
+            entity: self.entity,
+        });
+    }
+
+    /// Whether we're currently sleeping in a bed.
+    pub fn is_sleeping(&self) -> bool {
+        self.get_component::<Sleeping>().is_some()
+    }
+}
+
+/// Right-click a bed to try to sleep in it.
+#[derive(Event)]
+pub struct StartSleepingEvent {
+    pub entity: Entity,
+    pub bed: BlockPos,
+}
+
+pub fn handle_start_sleeping_event(
+    mut commands: Commands,
+    mut events: EventReader<StartSleepingEvent>,
+    mut block_interact_events: EventWriter<BlockInteractEvent>,
+) {
+    for event in events.iter() {
+        block_interact_events.send(BlockInteractEvent {
+            entity: event.entity,
+            position: event.bed,
+        });
+        commands.entity(event.entity).insert(TryingToSleep {
+            bed: event.bed,
+            ticks: 0,
+        });
+    }
+}
+
+/// Get out of the bed that we're in, like clicking "Leave Bed" in vanilla.
+#[derive(Event)]
+pub struct WakeUpEvent {
+    pub entity: Entity,
+}
+
+pub fn handle_wake_up_event(
+    mut events: EventReader<WakeUpEvent>,
+    query: Query<&MinecraftEntityId, With<Sleeping>>,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for event in events.iter() {
+        let Ok(entity_id) = query.get(event.entity) else {
+            continue;
+        };
+        send_packet_events.send(SendPacketEvent {
+            entity: event.entity,
+            packet: ServerboundPlayerCommandPacket {
+                id: **entity_id,
+                action: serverbound_player_command_packet::Action::StopSleeping,
+                data: 0,
+            }
+            .get(),
+        });
+    }
+}
+
+/// An event that's sent when the server didn't let us sleep in a bed that we
+/// tried to sleep in.
+#[derive(Event, Debug, Clone)]
+pub struct SleepFailedEvent {
+    pub entity: Entity,
+    pub bed: BlockPos,
+    pub reason: SleepError,
+}
+
+/// Add or remove the [`Sleeping`] component when the server tells us that our
+/// sleeping position changed.
+///
+/// Sleeping in a bed also makes it our [`SpawnPoint`].
+#[allow(clippy::type_complexity)]
+pub fn update_sleeping(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &SleepingPos,
+            Option<&InstanceName>,
+            Option<&mut SpawnPoint>,
+        ),
+        (Changed<SleepingPos>, With<LocalEntity>),
+    >,
+) {
+    for (entity, sleeping_pos, instance_name, spawn_point) in &mut query {
+        if let Some(bed) = **sleeping_pos {
+            if let Some(instance_name) = instance_name {
+                let bed = GlobalPos {
+                    pos: bed,
+                    world: (**instance_name).clone(),
+                };
+                if let Some(mut spawn_point) = spawn_point {
+                    spawn_point.bed = Some(bed);
+                } else {
+                    // the server hasn't told us the world spawn yet, it'll get filled in
+                    // when it does
+                    commands.entity(entity).insert(SpawnPoint {
+                        pos: BlockPos::default(),
+                        angle: 0.,
+                        bed: Some(bed),
+                    });
+                }
+            }
+            commands
+                .entity(entity)
+                .insert(Sleeping(bed))
+                .remove::<TryingToSleep>();
+        } else {
+            commands.entity(entity).remove::<Sleeping>();
+        }
+    }
+}
+
+/// Vanilla tells us why we can't sleep with a message in the action bar, so
+/// look for those while we're trying to sleep.
+pub fn detect_sleep_failure(
+    mut commands: Commands,
+    mut chat_events: EventReader<ChatReceivedEvent>,
+    query: Query<&TryingToSleep>,
+    mut sleep_failed_events: EventWriter<SleepFailedEvent>,
+) {
+    for event in chat_events.iter() {
+        let ChatPacket::System(packet) = &event.packet else {
+            continue;
+        };
+        let FormattedText::Translatable(text) = &packet.content else {
+            continue;
+        };
+        let Some(reason) = SleepError::from_translation_key(&text.key) else {
+            continue;
+        };
+        let Ok(trying_to_sleep) = query.get(event.entity) else {
+            continue;
+        };
+        fail_to_sleep(
+            &mut commands,
+            &mut sleep_failed_events,
+            event.entity,
+            trying_to_sleep.bed,
+            reason,
+        );
+    }
+}
+
+/// Give up on sleeping if the server never responded to us clicking the bed.
+pub fn tick_trying_to_sleep(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut TryingToSleep)>,
+    mut sleep_failed_events: EventWriter<SleepFailedEvent>,
+) {
+    for (entity, mut trying_to_sleep) in &mut query {
+        trying_to_sleep.ticks += 1;
+        if trying_to_sleep.ticks > SLEEP_TIMEOUT_TICKS {
+            fail_to_sleep(
+                &mut commands,
+                &mut sleep_failed_events,
+                entity,
+                trying_to_sleep.bed,
+                SleepError::NoResponse,
+            );
+        }
+    }
+}
+
+fn fail_to_sleep(
+    commands: &mut Commands,
+    sleep_failed_events: &mut EventWriter<SleepFailedEvent>,
+    entity: Entity,
+    bed: BlockPos,
+    reason: SleepError,
+) {
+    commands
+        .entity(entity)
+        .remove::<TryingToSleep>()
+        .insert(LastSleepError(reason.clone()));
+    sleep_failed_events.send(SleepFailedEvent {
+        entity,
+        bed,
+        reason,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_core::resource_location::ResourceLocation;
+
+    use super::*;
+
+    #[test]
+    fn test_sleeping_without_spawn_point_inserts_one() {
+        let mut app = App::new();
+        app.add_systems(Update, update_sleeping);
+        let bed = BlockPos::new(1, 64, 2);
+        let entity = app
+            .world
+            .spawn((
+                SleepingPos(Some(bed)),
+                InstanceName(ResourceLocation::new("minecraft:overworld")),
+                LocalEntity,
+            ))
+            .id();
+        app.update();
+
+        let spawn_point = app.world.get::<SpawnPoint>(entity).unwrap();
+        assert_eq!(spawn_point.bed.as_ref().map(|bed| bed.pos), Some(bed));
+        assert_eq!(app.world.get::<Sleeping>(entity).map(|s| s.0), Some(bed));
+    }
+}
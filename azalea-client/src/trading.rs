@@ -0,0 +1,277 @@
+//! Trading with villagers and wandering traders.
+//!
+//! To see what a merchant is offering, interact with it using
+//! [`Client::interact_entity`] and wait for the server to send its trades.
+
+use azalea_inventory::{operations::QuickMoveClick, ItemSlot, ItemSlotData, Menu};
+pub use azalea_protocol::packets::game::clientbound_merchant_offers_packet::MerchantOffer;
+use azalea_protocol::packets::game::{
+    clientbound_merchant_offers_packet::ClientboundMerchantOffersPacket,
+    serverbound_select_trade_packet::ServerboundSelectTradePacket,
+};
+use bevy_ecs::component::Component;
+use thiserror::Error;
+
+use crate::{
+    inventory::{ContainerClickEvent, InventoryComponent},
+    local_player::SendPacketEvent,
+    Client,
+};
+
+/// The trades that a merchant is offering us, which the server sends when we
+/// open its menu.
+///
+/// The server sends these again whenever they change, like when the villager
+/// levels up and unlocks more trades or when a trade runs out, so this is
+/// always the latest list.
+#[derive(Component, Clone, Debug)]
+pub struct MerchantOffers {
+    /// The id of the merchant menu that these trades are for.
+    pub container_id: u8,
+    pub offers: Vec<MerchantOffer>,
+    /// The villager's level from 1 (novice) to 5 (master).
+    pub villager_level: u32,
+    pub villager_xp: u32,
+    /// Whether the villager's experience bar is shown, which is false for
+    /// wandering traders.
+    pub show_progress: bool,
+    pub can_restock: bool,
+}
+impl From<&ClientboundMerchantOffersPacket> for MerchantOffers {
+    fn from(packet: &ClientboundMerchantOffersPacket) -> Self {
+        Self {
+            container_id: packet.container_id as u8,
+            offers: packet.offers.clone(),
+            villager_level: packet.villager_level,
+            villager_xp: packet.villager_xp,
+            show_progress: packet.show_progress,
+            can_restock: packet.can_restock,
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum TradeError {
+    #[error("We don't have a merchant's menu open")]
+    NoMerchantOpen,
+    #[error("The merchant doesn't have a trade at index {0}")]
+    UnknownTrade(usize),
+    #[error("The trade at index {0} is out of stock")]
+    OutOfStock(usize),
+    /// We don't have enough of one of the items that the trade costs.
+    #[error("We need {missing} more {item} to make the trade")]
+    MissingPayment {
+        item: azalea_registry::Item,
+        missing: i32,
+    },
+}
+
+impl Client {
+    /// Get the trades that the merchant we have open is offering, or an empty
+    /// list if we don't have a merchant's menu open.
+    ///
+    /// The trades are in the same order as they're shown in vanilla, and new
+    /// ones that the villager unlocks by leveling up are added to the end, so
+    /// the index of a trade doesn't change while the menu is open.
+    pub fn villager_trades(&self) -> Vec<MerchantOffer> {
+        self.merchant_offers()
+            .map(|merchant_offers| merchant_offers.offers)
+            .unwrap_or_default()
+    }
+
+    /// Get the trades that the merchant we have open is offering along with
+    /// its level and experience, or `None` if we don't have a merchant's menu
+    /// open.
+    pub fn merchant_offers(&self) -> Option<MerchantOffers> {
+        let mut ecs = self.ecs.lock();
+        let (inventory, merchant_offers) =
+            self.query::<(&InventoryComponent, Option<&MerchantOffers>)>(&mut ecs);
+        let merchant_offers = merchant_offers?;
+        // the trades are left over from the last merchant if we closed its menu
+        if !is_merchant_open(inventory, merchant_offers) {
+            return None;
+        }
+        Some(merchant_offers.clone())
+    }
+
+    /// Make the trade at the given index in [`Client::villager_trades`].
+    ///
+    /// Like in vanilla, the server moves the items that the trade costs from
+    /// our inventory into the payment slots, and then the result is
+    /// shift-clicked into our inventory. This makes the trade as many times as
+    /// the items in the payment slots allow (up to a stack of each), or until
+    /// it runs out of stock or our inventory is full.
+    ///
+    /// Nothing is sent if the trade is out of stock or we don't have enough
+    /// of the items that it costs.
+    ///
+    /// ```
+    /// # use azalea_client::Client;
+    /// # use azalea_registry::Item;
+    /// # fn example(bot: &Client) {
+    /// // sell anything that gets us emeralds
+    /// for (index, offer) in bot.villager_trades().iter().enumerate() {
+    ///     if offer.result.kind() == Item::Emerald && !offer.is_out_of_stock() {
+    ///         if let Err(err) = bot.trade(index) {
+    ///             println!("couldn't trade: {err}");
+    ///         }
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn trade(&self, index: usize) -> Result<(), TradeError> {
+        let mut ecs = self.ecs.lock();
+        let window_id = {
+            let (inventory, merchant_offers) =
+                self.query::<(&InventoryComponent, Option<&MerchantOffers>)>(&mut ecs);
+            let merchant_offers = merchant_offers
+                .filter(|merchant_offers| is_merchant_open(inventory, merchant_offers))
+                .ok_or(TradeError::NoMerchantOpen)?;
+            let offer = merchant_offers
+                .offers
+                .get(index)
+                .ok_or(TradeError::UnknownTrade(index))?;
+            if offer.is_out_of_stock() {
+                return Err(TradeError::OutOfStock(index));
+            }
+            check_payment(inventory.menu(), offer)?;
+            inventory.id
+        };
+
+        // the server fills the payment slots when we select the trade, so this has
+        // to be sent before the click
+        ecs.send_event(SendPacketEvent {
+            entity: self.entity,
+            packet: ServerboundSelectTradePacket { item: index as u32 }.get(),
+        });
+        ecs.send_event(ContainerClickEvent {
+            entity: self.entity,
+            window_id,
+            operation: QuickMoveClick::Left {
+                slot: Menu::MERCHANT_RESULT_SLOT as u16,
+            }
+            .into(),
+        });
+        Ok(())
+    }
+}
+
+/// Whether the menu that we have open is the one that the trades are for.
+fn is_merchant_open(inventory: &InventoryComponent, merchant_offers: &MerchantOffers) -> bool {
+    inventory.id == merchant_offers.container_id
+        && matches!(inventory.container_menu, Some(Menu::Merchant { .. }))
+}
+
+/// Check that we have enough items in our inventory and the payment slots to
+/// pay for the trade once.
+fn check_payment(menu: &Menu, offer: &MerchantOffer) -> Result<(), TradeError> {
+    let costs = [offer.cost_a(), offer.cost_b.clone()]
+        .into_iter()
+        .filter_map(|cost| match cost {
+            ItemSlot::Present(cost) => Some(cost),
+            ItemSlot::Empty => None,
+        })
+        .collect::<Vec<ItemSlotData>>();
+
+    // the server moves whatever is in the payment slots back into our inventory
+    // before filling them, so those count too
+    let slots = Menu::MERCHANT_PAYMENTS_SLOTS.chain(menu.player_slots_range());
+    for cost in &costs {
+        // both costs can be the same item
+        let needed: i32 = costs
+            .iter()
+            .filter(|other| other.is_same_item_and_nbt(cost))
+            .map(|other| other.count as i32)
+            .sum();
+        let have: i32 = slots
+            .clone()
+            .filter_map(|slot| match menu.slot(slot) {
+                Some(ItemSlot::Present(item)) if item.is_same_item_and_nbt(cost) => {
+                    Some(item.count as i32)
+                }
+                _ => None,
+            })
+            .sum();
+        if have < needed {
+            return Err(TradeError::MissingPayment {
+                item: cost.kind,
+                missing: needed - have,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_registry::{Item, MenuKind};
+
+    use super::*;
+
+    fn offer(cost_a: ItemSlot, cost_b: ItemSlot) -> MerchantOffer {
+        MerchantOffer {
+            base_cost_a: cost_a,
+            result: ItemSlot::Present(ItemSlotData {
+                kind: Item::Emerald,
+                count: 1,
+                nbt: Default::default(),
+            }),
+            cost_b,
+            out_of_stock: false,
+            uses: 0,
+            max_uses: 12,
+            xp: 1,
+            special_price_diff: 0,
+            price_multiplier: 0.05,
+            demand: 0,
+        }
+    }
+
+    fn stack(kind: Item, count: i8) -> ItemSlot {
+        ItemSlot::Present(ItemSlotData {
+            kind,
+            count,
+            nbt: Default::default(),
+        })
+    }
+
+    #[test]
+    fn test_check_payment_counts_both_costs() {
+        let mut menu = Menu::from_kind(MenuKind::Merchant);
+        let first_player_slot = *menu.player_slots_range().start();
+        *menu.slot_mut(first_player_slot).unwrap() = stack(Item::Wheat, 20);
+
+        // 20 wheat isn't enough when both costs are wheat
+        let offer = offer(stack(Item::Wheat, 12), stack(Item::Wheat, 12));
+        assert_eq!(
+            check_payment(&menu, &offer),
+            Err(TradeError::MissingPayment {
+                item: Item::Wheat,
+                missing: 4,
+            })
+        );
+
+        // but it is if the rest is already in the payment slots
+        *menu
+            .slot_mut(Menu::MERCHANT_PAYMENTS_SLOTS.start() + 1)
+            .unwrap() = stack(Item::Wheat, 4);
+        assert_eq!(check_payment(&menu, &offer), Ok(()));
+    }
+
+    #[test]
+    fn test_check_payment_missing_second_cost() {
+        let mut menu = Menu::from_kind(MenuKind::Merchant);
+        *menu
+            .slot_mut(*Menu::MERCHANT_PAYMENTS_SLOTS.start())
+            .unwrap() = stack(Item::Emerald, 5);
+
+        let offer = offer(stack(Item::Emerald, 5), stack(Item::Book, 1));
+        assert_eq!(
+            check_payment(&menu, &offer),
+            Err(TradeError::MissingPayment {
+                item: Item::Book,
+                missing: 1,
+            })
+        );
+    }
+}
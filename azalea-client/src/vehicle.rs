@@ -0,0 +1,153 @@
+//! Ride and steer vehicles like boats and horses.
+//!
+//! To get on a vehicle, interact with it using [`Client::interact_entity`].
+//! While we're riding something, [`Client::walk`] and [`Client::set_jumping`]
+//! are sent to the server as steering input instead of moving us.
+//!
+//! Our own physics are paused while we're riding, and we're kept on the
+//! vehicle wherever it goes (see [`azalea_physics::riding`]). They start again
+//! when we get off.
+
+pub use azalea_entity::Vehicle;
+use azalea_entity::{metadata::ShiftKeyDown, Jumping, LoadedBy, LookDirection, Physics};
+use azalea_physics::PhysicsSet;
+use azalea_protocol::packets::game::{
+    serverbound_move_player_rot_packet::ServerboundMovePlayerRotPacket,
+    serverbound_player_input_packet::ServerboundPlayerInputPacket,
+};
+use bevy_app::{App, FixedUpdate, Plugin, Update};
+use bevy_ecs::prelude::*;
+
+use crate::{local_player::SendPacketEvent, movement::PhysicsState, Client};
+
+/// A plugin that sends our steering input to the server while we're riding a
+/// [`Vehicle`], and handles [`DismountEvent`].
+pub struct VehiclePlugin;
+impl Plugin for VehiclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DismountEvent>()
+            .add_systems(
+                FixedUpdate,
+                (send_vehicle_input, release_dismount_shift)
+                    .chain()
+                    .after(PhysicsSet),
+            )
+            .add_systems(Update, (handle_dismount_event, remove_unloaded_vehicles));
+    }
+}
+
+/// Send the packets that vanilla sends every tick while riding something, in
+/// place of the normal movement packets.
+///
+/// Note that vehicles that the client is supposed to control (like boats and
+/// horses) aren't simulated yet, so we only send our input and the server
+/// decides what to do with it.
+#[allow(clippy::type_complexity)]
+pub fn send_vehicle_input(
+    query: Query<
+        (
+            Entity,
+            &LookDirection,
+            &Physics,
+            &PhysicsState,
+            &Jumping,
+            &ShiftKeyDown,
+        ),
+        With<Vehicle>,
+    >,
+    mut send_packet_events: EventWriter<SendPacketEvent>,
+) {
+    for (entity, direction, physics, physics_state, jumping, shift_key_down) in &query {
+        send_packet_events.send(SendPacketEvent {
+            entity,
+            packet: ServerboundMovePlayerRotPacket {
+                y_rot: direction.y_rot,
+                x_rot: direction.x_rot,
+                on_ground: physics.on_ground,
+            }
+            .get(),
+        });
+        send_packet_events.send(SendPacketEvent {
+            entity,
+            packet: ServerboundPlayerInputPacket {
+                xxa: physics_state.left_impulse,
+                zza: physics_state.forward_impulse,
+                is_jumping: **jumping,
+                is_shift_key_down: **shift_key_down,
+            }
+            .get(),
+        });
+    }
+}
+
+/// Get off the vehicle that we're riding, like pressing shift in vanilla.
+#[derive(Event, Debug, Clone)]
+pub struct DismountEvent {
+    pub entity: Entity,
+}
+/// A component that's present for the tick that we're holding shift to get off
+/// our vehicle. See [`DismountEvent`].
+#[derive(Component, Clone, Debug)]
+pub struct Dismounting;
+
+pub fn handle_dismount_event(
+    mut commands: Commands,
+    mut events: EventReader<DismountEvent>,
+    mut query: Query<&mut ShiftKeyDown, With<Vehicle>>,
+) {
+    for event in events.iter() {
+        let Ok(mut shift_key_down) = query.get_mut(event.entity) else {
+            continue;
+        };
+        // this is sent with the rest of our input next tick, so it can't get lost
+        // between two input packets
+        **shift_key_down = true;
+        commands.entity(event.entity).insert(Dismounting);
+    }
+}
+
+/// Let go of shift after we've sent it to the server to get off our vehicle.
+pub fn release_dismount_shift(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ShiftKeyDown), With<Dismounting>>,
+) {
+    for (entity, mut shift_key_down) in &mut query {
+        **shift_key_down = false;
+        commands.entity(entity).remove::<Dismounting>();
+    }
+}
+
+/// Stop riding vehicles that the server stopped telling us about, since it
+/// doesn't send a set passengers packet when the vehicle is removed.
+pub fn remove_unloaded_vehicles(
+    mut commands: Commands,
+    query: Query<(Entity, &Vehicle)>,
+    loaded_by_query: Query<&LoadedBy>,
+) {
+    for (entity, vehicle) in &query {
+        let is_loaded = loaded_by_query
+            .get(**vehicle)
+            .is_ok_and(|loaded_by| loaded_by.contains(&entity));
+        if !is_loaded {
+            commands.entity(entity).remove::<Vehicle>();
+        }
+    }
+}
+
+impl Client {
+    /// Get the entity that we're riding, or `None` if we're not riding
+    /// anything.
+    ///
+    /// This is a shortcut for `bot.get_component::<Vehicle>()`.
+    pub fn vehicle(&self) -> Option<Entity> {
+        self.get_component::<Vehicle>().map(|vehicle| *vehicle)
+    }
+
+    /// Get off the vehicle that we're riding. This doesn't do anything if
+    /// we're not riding anything.
+    pub fn dismount(&mut self) {
+        self.ecs.lock().send_event(DismountEvent {
+            entity: self.entity,
+        });
+    }
+}
@@ -0,0 +1,166 @@
+//! Keep track of the time of day and the weather in the world that we're in.
+
+use azalea_physics::PhysicsSet;
+use bevy_app::{App, FixedUpdate, Plugin};
+use bevy_ecs::prelude::*;
+
+use crate::Client;
+
+/// How many ticks a full day-night cycle takes.
+pub const TICKS_PER_DAY: u64 = 24000;
+
+/// A plugin that moves [`WorldTime`] forward every tick, so it stays accurate
+/// between updates from the server.
+///
+/// The time is set by the `SetTime` packet, and the [`Weather`] is set by the
+/// `GameEvent` packet.
+pub struct WorldTimePlugin;
+impl Plugin for WorldTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(FixedUpdate, tick_world_time.before(PhysicsSet));
+    }
+}
+
+/// The age of the world and the time of day in it.
+#[derive(Component, Clone, Debug, Default)]
+pub struct WorldTime {
+    /// How many ticks the world has existed for. This goes up every tick even
+    /// if the daylight cycle is off.
+    pub game_time: u64,
+    /// The time of day, which is the number of ticks since the first day
+    /// started. Use [`Self::time_of_day`] if you don't care about what day
+    /// it is.
+    pub day_time: u64,
+    /// Whether the time of day changes, which is false if the
+    /// `doDaylightCycle` gamerule is off.
+    pub daylight_cycle: bool,
+}
+
+impl WorldTime {
+    /// Set the time from the values in the `SetTime` packet. The server sends
+    /// a negative day time when the daylight cycle is off.
+    pub fn update(&mut self, game_time: u64, day_time: i64) {
+        self.game_time = game_time;
+        self.day_time = day_time.unsigned_abs();
+        self.daylight_cycle = day_time >= 0;
+    }
+
+    /// The number of ticks since the start of the current day, from 0 to
+    /// 23999. 0 is sunrise, 6000 is noon, 12000 is sunset, and 18000 is
+    /// midnight.
+    pub fn time_of_day(&self) -> u64 {
+        self.day_time % TICKS_PER_DAY
+    }
+
+    /// Whether it's late enough that players can sleep in beds (ignoring
+    /// thunderstorms, which also let players sleep).
+    pub fn is_night(&self) -> bool {
+        (12542..=23459).contains(&self.time_of_day())
+    }
+}
+
+/// How much it's raining and thundering in the world.
+#[derive(Component, Clone, Debug, Default)]
+pub struct Weather {
+    /// How heavy the rain is, from 0 to 1. This changes gradually when rain
+    /// starts or stops.
+    pub rain_level: f32,
+    /// How heavy the thunder is, from 0 to 1.
+    pub thunder_level: f32,
+}
+
+impl Weather {
+    /// Whether it's raining (or snowing, in cold biomes). This uses the same
+    /// threshold as vanilla.
+    pub fn is_raining(&self) -> bool {
+        self.rain_level > 0.2
+    }
+
+    /// Whether there's a thunderstorm, which also lets players sleep.
+    pub fn is_thundering(&self) -> bool {
+        self.is_raining() && self.thunder_level > 0.9
+    }
+}
+
+pub fn tick_world_time(mut query: Query<&mut WorldTime>) {
+    for mut world_time in query.iter_mut() {
+        world_time.game_time += 1;
+        if world_time.daylight_cycle {
+            world_time.day_time += 1;
+        }
+    }
+}
+
+impl Client {
+    /// Get the number of ticks since the start of the current day, from 0 to
+    /// 23999. See [`WorldTime::time_of_day`].
+    ///
+    /// This is a shortcut for `bot.component::<WorldTime>().time_of_day()`.
+    pub fn time_of_day(&self) -> u64 {
+        self.component::<WorldTime>().time_of_day()
+    }
+
+    /// Whether it's raining where we are (or snowing, in cold biomes).
+    ///
+    /// This is a shortcut for `bot.component::<Weather>().is_raining()`.
+    pub fn is_raining(&self) -> bool {
+        self.component::<Weather>().is_raining()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::Update;
+
+    use super::*;
+
+    #[test]
+    fn test_update_with_daylight_cycle() {
+        let mut world_time = WorldTime::default();
+        world_time.update(100_000, 30_000);
+        assert_eq!(world_time.game_time, 100_000);
+        assert_eq!(world_time.day_time, 30_000);
+        assert!(world_time.daylight_cycle);
+        assert_eq!(world_time.time_of_day(), 6000);
+    }
+
+    #[test]
+    fn test_update_with_frozen_time() {
+        let mut world_time = WorldTime::default();
+        // the server negates the day time when the daylight cycle is off
+        world_time.update(100_000, -18_000);
+        assert_eq!(world_time.day_time, 18_000);
+        assert!(!world_time.daylight_cycle);
+        assert!(world_time.is_night());
+    }
+
+    fn tick(world_time: WorldTime, ticks: u32) -> WorldTime {
+        let mut app = App::new();
+        app.add_systems(Update, tick_world_time);
+        let entity = app.world.spawn(world_time).id();
+        for _ in 0..ticks {
+            app.update();
+        }
+        app.world.get::<WorldTime>(entity).unwrap().clone()
+    }
+
+    #[test]
+    fn test_tick_advancing_time() {
+        let mut world_time = WorldTime::default();
+        world_time.update(100, TICKS_PER_DAY as i64 - 1);
+        let world_time = tick(world_time, 2);
+        assert_eq!(world_time.game_time, 102);
+        assert_eq!(world_time.day_time, TICKS_PER_DAY + 1);
+        assert_eq!(world_time.time_of_day(), 1);
+    }
+
+    #[test]
+    fn test_tick_frozen_time() {
+        let mut world_time = WorldTime::default();
+        world_time.update(100, -6000);
+        let world_time = tick(world_time, 20);
+        // the world still gets older, but the time of day stays the same
+        assert_eq!(world_time.game_time, 120);
+        assert_eq!(world_time.day_time, 6000);
+    }
+}
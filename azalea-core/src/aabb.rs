@@ -435,6 +435,15 @@ impl AABB {
         }
     }
 
+    /// The squared distance from the point to the closest point in this box,
+    /// or 0 if the point is inside it.
+    pub fn distance_to_sqr(&self, point: &Vec3) -> f64 {
+        let x = (self.min_x - point.x).max(point.x - self.max_x).max(0.);
+        let y = (self.min_y - point.y).max(point.y - self.max_y).max(0.);
+        let z = (self.min_z - point.z).max(point.z - self.max_z).max(0.);
+        x * x + y * y + z * z
+    }
+
     pub fn max(&self, axis: &Axis) -> f64 {
         axis.choose(self.max_x, self.max_y, self.max_z)
     }
@@ -466,4 +475,19 @@ mod tests {
             None
         );
     }
+
+    #[test]
+    fn test_aabb_distance_to_sqr() {
+        let aabb = AABB {
+            min_x: 0.,
+            min_y: 0.,
+            min_z: 0.,
+            max_x: 1.,
+            max_y: 1.,
+            max_z: 1.,
+        };
+        assert_eq!(aabb.distance_to_sqr(&Vec3::new(0.5, 0.5, 0.5)), 0.);
+        assert_eq!(aabb.distance_to_sqr(&Vec3::new(0.5, 3., 0.5)), 4.);
+        assert_eq!(aabb.distance_to_sqr(&Vec3::new(-1., 0.5, 2.)), 2.);
+    }
 }
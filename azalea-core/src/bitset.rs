@@ -18,8 +18,12 @@ impl BitSet {
         }
     }
 
+    /// Whether the bit at the index is set. Like Java's `BitSet.get`, bits past
+    /// the end are treated as unset.
     pub fn index(&self, index: usize) -> bool {
-        (self.data[index / 64] & (1u64 << (index % 64))) != 0
+        self.data
+            .get(index / 64)
+            .is_some_and(|word| (word & (1u64 << (index % 64))) != 0)
     }
 
     fn check_range(&self, from_index: usize, to_index: usize) {
@@ -198,6 +202,8 @@ mod tests {
         assert!(!bitset.index(0));
         assert!(bitset.index(1));
         assert!(!bitset.index(2));
+        // past the end
+        assert!(!bitset.index(100));
     }
 
     #[test]
@@ -229,7 +229,7 @@ impl ChunkSectionPos {
     }
 }
 /// The coordinates of a block inside a chunk.
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
 pub struct ChunkBlockPos {
     pub x: u8,
     pub y: i32,
@@ -265,7 +265,7 @@ impl Add<ChunkSectionBlockPos> for ChunkSectionPos {
 }
 
 /// A block pos with an attached world
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GlobalPos {
     pub pos: BlockPos,
     // this is actually a ResourceKey in Minecraft, but i don't think it matters?
@@ -7,9 +7,11 @@ use aes::{
     cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit},
     Aes128,
 };
+use md5::Md5;
 use rand::{rngs::OsRng, RngCore};
 use sha1::{Digest, Sha1};
 pub use signing::*;
+use uuid::Uuid;
 
 fn generate_secret_key() -> [u8; 16] {
     let mut key = [0u8; 16];
@@ -35,6 +37,16 @@ pub fn hex_digest(digest: &[u8]) -> String {
     num_bigint::BigInt::from_signed_bytes_be(digest).to_str_radix(16)
 }
 
+/// Get the UUID that offline-mode servers give to a player with the given
+/// username.
+///
+/// This is the same as Java's `UUID.nameUUIDFromBytes` on
+/// `OfflinePlayer:<username>`.
+pub fn offline_uuid(username: &str) -> Uuid {
+    let hash = Md5::digest(format!("OfflinePlayer:{username}").as_bytes());
+    uuid::Builder::from_md5_bytes(hash.into()).into_uuid()
+}
+
 #[derive(Debug)]
 pub struct EncryptResult {
     pub secret_key: [u8; 16],
@@ -105,6 +117,14 @@ mod tests {
         assert_eq!(digest, "88e16a1019277b15d58faf0541e11910eb756f6");
     }
 
+    #[test]
+    fn test_offline_uuid() {
+        assert_eq!(
+            offline_uuid("Notch").to_string(),
+            "b50ad385-829d-3141-a216-7e7d7539ba7f"
+        );
+    }
+
     #[test]
     fn encode_packet_twice() {
         let mut packet = vec![0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
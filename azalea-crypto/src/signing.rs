@@ -30,6 +30,12 @@ pub fn make_salt() -> u64 {
     rand::random()
 }
 
+/// Generates a random UUID to identify a chat session. A new one is made every
+/// time we join a server.
+pub fn make_chat_session_id() -> Uuid {
+    uuid::Builder::from_random_bytes(rand::random()).into_uuid()
+}
+
 pub struct SignChatMessageOptions {
     pub account_uuid: Uuid,
     pub chat_session_uuid: Uuid,
@@ -1,6 +1,60 @@
-// TODO
+use std::collections::HashMap;
 
-// pub struct ActiveEffects(HashMap<azalea_registry::MobEffect, MobEffectData>);
+use bevy_ecs::component::Component;
+use derive_more::{Deref, DerefMut};
+
+/// The status effects (like speed or poison) that an entity currently has.
+///
+/// The durations here are ticked down locally every game tick, so they stay
+/// roughly accurate between updates from the server.
+#[derive(Component, Clone, Debug, Default, Deref, DerefMut)]
+pub struct ActiveEffects(pub HashMap<azalea_registry::MobEffect, MobEffectData>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MobEffectData {
+    /// The level of the effect, where the lowest level is 0 (so Speed II has
+    /// an amplifier of 1).
+    pub amplifier: u32,
+    /// The number of ticks until this effect expires, or `None` if it lasts
+    /// forever.
+    pub duration: Option<u32>,
+    /// Whether the effect came from a beacon or conduit.
+    pub ambient: bool,
+}
+
+impl MobEffectData {
+    /// Whether this effect will run out on its own.
+    pub fn is_infinite(&self) -> bool {
+        self.duration.is_none()
+    }
+}
+
+impl ActiveEffects {
+    /// Returns the level of the given effect, or `None` if the effect is not
+    /// active. The lowest level is 0.
+    pub fn get_level(&self, effect: azalea_registry::MobEffect) -> Option<u32> {
+        self.0.get(&effect).map(|data| data.amplifier)
+    }
+
+    /// Whether the given effect is currently active.
+    pub fn has(&self, effect: azalea_registry::MobEffect) -> bool {
+        self.0.contains_key(&effect)
+    }
+
+    /// Count down the durations of every effect by one tick and remove the
+    /// ones that ran out.
+    pub fn tick(&mut self) {
+        self.0.retain(|_, data| match &mut data.duration {
+            Some(duration) => {
+                *duration = duration.saturating_sub(1);
+                *duration > 0
+            }
+            None => true,
+        });
+    }
+}
+
+// TODO: these should take the player's ActiveEffects
 
 /// Returns the level of the given effect, or `None` if the effect is not
 /// active. The lowest level is 0.
@@ -24,3 +78,38 @@ pub fn get_dig_speed_amplifier() -> Option<u32> {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effects_expire() {
+        let mut effects = ActiveEffects::default();
+        effects.insert(
+            azalea_registry::MobEffect::Speed,
+            MobEffectData {
+                amplifier: 1,
+                duration: Some(2),
+                ambient: false,
+            },
+        );
+        effects.insert(
+            azalea_registry::MobEffect::NightVision,
+            MobEffectData {
+                amplifier: 0,
+                duration: None,
+                ambient: false,
+            },
+        );
+
+        effects.tick();
+        assert_eq!(
+            effects.get_level(azalea_registry::MobEffect::Speed),
+            Some(1)
+        );
+        effects.tick();
+        assert!(!effects.has(azalea_registry::MobEffect::Speed));
+        assert!(effects.has(azalea_registry::MobEffect::NightVision));
+    }
+}
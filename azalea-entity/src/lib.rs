@@ -3,7 +3,7 @@
 pub mod attributes;
 mod data;
 mod dimensions;
-mod effects;
+pub mod effects;
 mod enchantments;
 pub mod metadata;
 pub mod mining;
@@ -19,11 +19,11 @@ use azalea_core::{
     resource_location::ResourceLocation,
 };
 use azalea_world::{ChunkStorage, InstanceName};
-use bevy_ecs::{bundle::Bundle, component::Component};
+use bevy_ecs::{bundle::Bundle, component::Component, entity::Entity};
 pub use data::*;
 use derive_more::{Deref, DerefMut};
 pub use dimensions::EntityDimensions;
-use std::fmt::Debug;
+use std::{f64::consts::PI, fmt::Debug};
 use uuid::Uuid;
 
 pub use crate::plugin::*;
@@ -72,6 +72,17 @@ pub fn view_vector(look_direction: &LookDirection) -> Vec3 {
     }
 }
 
+/// Return the (`y_rot`, `x_rot`) that would make a client at `current` be
+/// looking at `target`.
+pub fn direction_looking_at(current: &Vec3, target: &Vec3) -> (f32, f32) {
+    // borrowed from mineflayer's Bot.lookAt because i didn't want to do math
+    let delta = target - current;
+    let y_rot = (PI - f64::atan2(-delta.x, -delta.z)) * (180.0 / PI);
+    let ground_distance = f64::sqrt(delta.x * delta.x + delta.z * delta.z);
+    let x_rot = f64::atan2(delta.y, ground_distance) * -(180.0 / PI);
+    (y_rot as f32, x_rot as f32)
+}
+
 /// Get the position of the block below the entity, but a little lower.
 pub fn on_pos_legacy(chunk_storage: &ChunkStorage, position: &Position) -> BlockPos {
     on_pos(0.2, chunk_storage, position)
@@ -197,6 +208,23 @@ impl From<&LastSentPosition> for BlockPos {
 #[derive(Debug, Component, Clone, Deref, DerefMut, Default)]
 pub struct Jumping(bool);
 
+/// A component for local players that are flying like they can in creative
+/// mode, so they don't fall and move up and down when they jump or sneak.
+///
+/// azalea-client keeps this in sync with the player's abilities, so you
+/// shouldn't add or remove it yourself.
+#[derive(Debug, Component, Clone, Copy)]
+pub struct Flying {
+    /// How fast we fly horizontally, which is 0.05 by default. Flying up and
+    /// down is three times as fast as this.
+    pub speed: f32,
+}
+
+/// A component for entities that are riding another entity, like a player in a
+/// boat or on a horse. The entity in this is the one being ridden.
+#[derive(Debug, Component, Clone, Copy, Deref, PartialEq, Eq)]
+pub struct Vehicle(pub Entity);
+
 /// A component that contains the direction an entity is looking.
 #[derive(Debug, Component, Clone, Default)]
 pub struct LookDirection {
@@ -219,6 +247,9 @@ pub struct Physics {
 
     pub on_ground: bool,
     pub last_on_ground: bool,
+    /// Whether the entity ran into a wall on the x or z axis during the last
+    /// tick.
+    pub horizontal_collision: bool,
 
     /// The width and height of the entity.
     pub dimensions: EntityDimensions,
@@ -240,6 +271,7 @@ impl Physics {
 
             on_ground: false,
             last_on_ground: false,
+            horizontal_collision: false,
 
             bounding_box: dimensions.make_bounding_box(pos),
             dimensions,
@@ -366,6 +398,12 @@ pub struct LocalEntity;
 #[derive(Component, Clone, Debug, PartialEq, Deref, DerefMut)]
 pub struct FluidOnEyes(azalea_registry::Fluid);
 
+impl FluidOnEyes {
+    pub fn new(fluid: azalea_registry::Fluid) -> Self {
+        Self(fluid)
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
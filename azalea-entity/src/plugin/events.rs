@@ -0,0 +1,105 @@
+//! Events for keeping track of the entities in the world without having to
+//! query the ECS every tick.
+
+use azalea_core::{
+    position::{BlockPos, Vec3},
+    resource_location::ResourceLocation,
+};
+use azalea_world::{InstanceName, MinecraftEntityId};
+use bevy_ecs::prelude::*;
+use uuid::Uuid;
+
+use crate::{EntityKind, EntityUuid, Position};
+
+/// An entity was added to the world. This is sent the first update after
+/// the entity is spawned, and only once even if it's loaded by multiple
+/// clients.
+#[derive(Event, Debug, Clone)]
+pub struct EntitySpawnEvent {
+    pub entity: Entity,
+    pub uuid: Uuid,
+    pub kind: azalea_registry::EntityKind,
+    pub position: Vec3,
+    /// The name of the instance that the entity was spawned in.
+    pub instance_name: ResourceLocation,
+}
+
+/// An entity was removed from the world because no clients have it loaded
+/// anymore. The entity doesn't exist in the ECS when this is received.
+#[derive(Event, Debug, Clone)]
+pub struct EntityDespawnEvent {
+    pub entity: Entity,
+    pub uuid: Uuid,
+    pub kind: azalea_registry::EntityKind,
+}
+
+/// An entity moved into a different block.
+///
+/// This isn't sent for smaller movements so it doesn't fire every tick for
+/// every entity that's wandering around.
+#[derive(Event, Debug, Clone)]
+pub struct EntityMoveEvent {
+    pub entity: Entity,
+    pub uuid: Uuid,
+    pub kind: azalea_registry::EntityKind,
+    pub old_block_pos: BlockPos,
+    pub new_block_pos: BlockPos,
+    pub position: Vec3,
+}
+
+/// The block position that we last sent an [`EntityMoveEvent`] for.
+#[derive(Component)]
+pub struct LastMoveEventBlockPos(BlockPos);
+
+#[allow(clippy::type_complexity)]
+pub fn send_entity_spawn_events(
+    mut commands: Commands,
+    query: Query<
+        (Entity, &EntityUuid, &EntityKind, &Position, &InstanceName),
+        Added<MinecraftEntityId>,
+    >,
+    mut spawn_events: EventWriter<EntitySpawnEvent>,
+) {
+    for (entity, uuid, kind, position, instance_name) in &query {
+        spawn_events.send(EntitySpawnEvent {
+            entity,
+            uuid: **uuid,
+            kind: **kind,
+            position: **position,
+            instance_name: (**instance_name).clone(),
+        });
+        commands
+            .entity(entity)
+            .insert(LastMoveEventBlockPos(BlockPos::from(position)));
+    }
+}
+
+pub fn send_entity_move_events(
+    mut query: Query<
+        (
+            Entity,
+            &EntityUuid,
+            &EntityKind,
+            &Position,
+            &mut LastMoveEventBlockPos,
+        ),
+        Changed<Position>,
+    >,
+    mut move_events: EventWriter<EntityMoveEvent>,
+) {
+    for (entity, uuid, kind, position, mut last_block_pos) in &mut query {
+        let new_block_pos = BlockPos::from(position);
+        if new_block_pos == last_block_pos.0 {
+            continue;
+        }
+        move_events.send(EntityMoveEvent {
+            entity,
+            uuid: **uuid,
+            kind: **kind,
+            old_block_pos: last_block_pos.0,
+            new_block_pos,
+            position: **position,
+        });
+        last_block_pos.0 = new_block_pos;
+    }
+}
@@ -5,6 +5,7 @@ use azalea_world::{Instance, InstanceContainer, InstanceName, MinecraftEntityId}
 use bevy_ecs::{
     component::Component,
     entity::Entity,
+    event::EventWriter,
     query::Changed,
     system::{Commands, Query, Res, ResMut, Resource},
 };
@@ -13,9 +14,9 @@ use nohash_hasher::IntMap;
 use std::{collections::HashMap, fmt::Debug};
 use uuid::Uuid;
 
-use crate::{EntityUuid, LastSentPosition, Position};
+use crate::{EntityKind, EntityUuid, LastSentPosition, Position};
 
-use super::LoadedBy;
+use super::{EntityDespawnEvent, LoadedBy};
 
 #[derive(Resource, Default)]
 pub struct EntityUuidIndex {
@@ -122,14 +123,16 @@ pub fn remove_despawned_entities_from_indexes(
             Entity,
             &EntityUuid,
             &MinecraftEntityId,
+            &EntityKind,
             &Position,
             &InstanceName,
             &LoadedBy,
         ),
         Changed<LoadedBy>,
     >,
+    mut despawn_events: EventWriter<EntityDespawnEvent>,
 ) {
-    for (entity, uuid, minecraft_id, position, world_name, loaded_by) in &query {
+    for (entity, uuid, minecraft_id, kind, position, world_name, loaded_by) in &query {
         let Some(instance_lock) = instance_container.get(world_name) else {
             // the instance isn't even loaded by us, so we can safely delete the entity
             debug!(
@@ -142,6 +145,11 @@ pub fn remove_despawned_entities_from_indexes(
             }
             // and now remove the entity from the ecs
             commands.entity(entity).despawn();
+            despawn_events.send(EntityDespawnEvent {
+                entity,
+                uuid: **uuid,
+                kind: **kind,
+            });
 
             continue;
         };
@@ -178,6 +186,11 @@ pub fn remove_despawned_entities_from_indexes(
         }
         // and now remove the entity from the ecs
         commands.entity(entity).despawn();
+        despawn_events.send(EntityDespawnEvent {
+            entity,
+            uuid: **uuid,
+            kind: **kind,
+        });
         debug!("Despawned entity {entity:?} because it was not loaded by anything.");
     }
 }
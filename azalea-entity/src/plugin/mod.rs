@@ -1,3 +1,4 @@
+pub mod events;
 pub mod indexing;
 mod relative_updates;
 
@@ -14,6 +15,7 @@ use crate::{
     metadata::Health, Dead, EyeHeight, FluidOnEyes, LocalEntity, LookDirection, Physics, Position,
 };
 
+pub use events::{EntityDespawnEvent, EntityMoveEvent, EntitySpawnEvent};
 use indexing::EntityUuidIndex;
 pub use relative_updates::RelativeEntityUpdate;
 
@@ -34,26 +36,34 @@ impl Plugin for EntityPlugin {
         // added to indexes during update (done by this plugin)
         // modified during update
         // despawned post-update (done by this plugin)
-        app.add_systems(
-            PreUpdate,
-            indexing::remove_despawned_entities_from_indexes.in_set(EntityUpdateSet::Deindex),
-        )
-        .add_systems(
-            Update,
-            (
-                (indexing::update_entity_chunk_positions).in_set(EntityUpdateSet::Index),
+        app.add_event::<EntitySpawnEvent>()
+            .add_event::<EntityDespawnEvent>()
+            .add_event::<EntityMoveEvent>()
+            .add_systems(
+                PreUpdate,
+                indexing::remove_despawned_entities_from_indexes.in_set(EntityUpdateSet::Deindex),
+            )
+            .add_systems(
+                Update,
                 (
-                    relative_updates::debug_detect_updates_received_on_local_entities,
-                    debug_new_entity,
-                    add_dead,
-                    clamp_look_direction,
-                    update_fluid_on_eyes,
+                    (indexing::update_entity_chunk_positions).in_set(EntityUpdateSet::Index),
+                    (
+                        relative_updates::debug_detect_updates_received_on_local_entities,
+                        debug_new_entity,
+                        add_dead,
+                        clamp_look_direction,
+                        update_fluid_on_eyes,
+                    ),
+                    (
+                        events::send_entity_spawn_events,
+                        events::send_entity_move_events,
+                    )
+                        .chain(),
                 ),
-            ),
-        )
-        .add_systems(Update, update_bounding_box)
-        .add_systems(PreUpdate, update_in_loaded_chunk)
-        .init_resource::<EntityUuidIndex>();
+            )
+            .add_systems(Update, update_bounding_box)
+            .add_systems(PreUpdate, update_in_loaded_chunk)
+            .init_resource::<EntityUuidIndex>();
     }
 }
 
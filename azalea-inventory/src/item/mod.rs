@@ -21,3 +21,89 @@ impl MaxStackSizeExt for azalea_registry::Item {
         64
     }
 }
+
+pub trait MaxDamageExt {
+    /// Get how much damage this item can take before it breaks, or 0 if it
+    /// can't be damaged.
+    fn max_damage(&self) -> i32;
+
+    /// Whether this item loses durability when it's used.
+    ///
+    /// This is equivalent to `self.max_damage() > 0`.
+    fn damageable(&self) -> bool {
+        self.max_damage() > 0
+    }
+}
+
+impl MaxDamageExt for azalea_registry::Item {
+    fn max_damage(&self) -> i32 {
+        use azalea_registry::Item;
+        match self {
+            Item::WoodenSword
+            | Item::WoodenShovel
+            | Item::WoodenPickaxe
+            | Item::WoodenAxe
+            | Item::WoodenHoe => 59,
+            Item::StoneSword
+            | Item::StoneShovel
+            | Item::StonePickaxe
+            | Item::StoneAxe
+            | Item::StoneHoe => 131,
+            Item::IronSword
+            | Item::IronShovel
+            | Item::IronPickaxe
+            | Item::IronAxe
+            | Item::IronHoe => 250,
+            Item::GoldenSword
+            | Item::GoldenShovel
+            | Item::GoldenPickaxe
+            | Item::GoldenAxe
+            | Item::GoldenHoe => 32,
+            Item::DiamondSword
+            | Item::DiamondShovel
+            | Item::DiamondPickaxe
+            | Item::DiamondAxe
+            | Item::DiamondHoe => 1561,
+            Item::NetheriteSword
+            | Item::NetheriteShovel
+            | Item::NetheritePickaxe
+            | Item::NetheriteAxe
+            | Item::NetheriteHoe => 2031,
+
+            Item::LeatherHelmet => 55,
+            Item::LeatherChestplate => 80,
+            Item::LeatherLeggings => 75,
+            Item::LeatherBoots => 65,
+            Item::ChainmailHelmet | Item::IronHelmet => 165,
+            Item::ChainmailChestplate | Item::IronChestplate => 240,
+            Item::ChainmailLeggings | Item::IronLeggings => 225,
+            Item::ChainmailBoots | Item::IronBoots => 195,
+            Item::GoldenHelmet => 77,
+            Item::GoldenChestplate => 112,
+            Item::GoldenLeggings => 105,
+            Item::GoldenBoots => 91,
+            Item::DiamondHelmet => 363,
+            Item::DiamondChestplate => 528,
+            Item::DiamondLeggings => 495,
+            Item::DiamondBoots => 429,
+            Item::NetheriteHelmet => 407,
+            Item::NetheriteChestplate => 592,
+            Item::NetheriteLeggings => 555,
+            Item::NetheriteBoots => 481,
+            Item::TurtleHelmet => 275,
+
+            Item::Bow => 384,
+            Item::Crossbow => 465,
+            Item::Trident => 250,
+            Item::Shield => 336,
+            Item::Elytra => 432,
+            Item::FishingRod => 64,
+            Item::Shears => 238,
+            Item::FlintAndSteel => 64,
+            Item::CarrotOnAStick => 25,
+            Item::WarpedFungusOnAStick => 100,
+            Item::Brush => 64,
+            _ => 0,
+        }
+    }
+}
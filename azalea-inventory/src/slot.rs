@@ -1,6 +1,13 @@
 use azalea_buf::{BufReadError, McBuf, McBufReadable, McBufWritable};
-use azalea_nbt::Nbt;
-use std::io::{Cursor, Write};
+use azalea_chat::FormattedText;
+use azalea_nbt::{Nbt, NbtCompound, NbtList};
+use azalea_registry::Enchantment;
+use std::{
+    io::{Cursor, Write},
+    str::FromStr,
+};
+
+use crate::item::MaxDamageExt;
 
 /// Either an item in an inventory or nothing.
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -123,6 +130,232 @@ impl ItemSlotData {
     pub fn is_same_item_and_nbt(&self, other: &ItemSlotData) -> bool {
         self.kind == other.kind && self.nbt == other.nbt
     }
+
+    /// Get a tag from the item's NBT, if it has one.
+    fn tag(&self, key: &str) -> Option<&Nbt> {
+        self.nbt.as_compound().and_then(|nbt| nbt.get(key))
+    }
+
+    /// The enchantments on this item and their levels, including ones that
+    /// vanilla doesn't have.
+    ///
+    /// Enchanted books store their enchantments separately, so use
+    /// [`Self::stored_enchantments`] for those.
+    ///
+    /// ```
+    /// # use azalea_inventory::ItemSlotData;
+    /// # use azalea_nbt::{Nbt, NbtCompound, NbtList};
+    /// # use azalea_registry::{Enchantment, Item};
+    /// let pickaxe = ItemSlotData {
+    ///     kind: Item::DiamondPickaxe,
+    ///     count: 1,
+    ///     nbt: Nbt::Compound(NbtCompound::from(vec![(
+    ///         "Enchantments".into(),
+    ///         Nbt::List(NbtList::Compound(vec![NbtCompound::from(vec![
+    ///             ("id".into(), Nbt::String("minecraft:fortune".into())),
+    ///             ("lvl".into(), Nbt::Short(3)),
+    ///         ])])),
+    ///     )])),
+    /// };
+    /// assert_eq!(pickaxe.enchantments(), vec![(Enchantment::Fortune.into(), 3)]);
+    /// assert_eq!(pickaxe.enchantment_level(Enchantment::Fortune), 3);
+    /// assert_eq!(pickaxe.enchantment_level(Enchantment::SilkTouch), 0);
+    /// ```
+    pub fn enchantments(&self) -> Vec<(EnchantmentId, i32)> {
+        self.enchantments_in_tag("Enchantments")
+    }
+
+    /// The enchantments stored in an enchanted book, which get applied to
+    /// another item when they're combined in an anvil.
+    pub fn stored_enchantments(&self) -> Vec<(EnchantmentId, i32)> {
+        self.enchantments_in_tag("StoredEnchantments")
+    }
+
+    fn enchantments_in_tag(&self, key: &str) -> Vec<(EnchantmentId, i32)> {
+        let Some(Nbt::List(NbtList::Compound(enchantments))) = self.tag(key) else {
+            return Vec::new();
+        };
+        enchantments
+            .iter()
+            .filter_map(|enchantment| {
+                let id = EnchantmentId::from_id(enchantment.get("id")?.as_string()?);
+                Some((id, nbt_as_int(enchantment.get("lvl")?)?))
+            })
+            .collect()
+    }
+
+    /// The level of the given enchantment on this item, or 0 if it doesn't
+    /// have it.
+    pub fn enchantment_level(&self, enchantment: Enchantment) -> i32 {
+        self.enchantments()
+            .into_iter()
+            .find(|(kind, _)| *kind == enchantment)
+            .map_or(0, |(_, level)| level)
+    }
+
+    /// The name that this item was given in an anvil, if any.
+    pub fn custom_name(&self) -> Option<FormattedText> {
+        let display = self.tag("display")?.as_compound()?;
+        let name = display.get("Name")?.as_string()?;
+        serde_json::from_str(name).ok()
+    }
+
+    /// How much durability this item has lost. This is 0 for items that can't
+    /// be damaged.
+    pub fn damage(&self) -> i32 {
+        self.tag("Damage").and_then(nbt_as_int).unwrap_or(0).max(0)
+    }
+
+    /// How much damage this item can take before it breaks, or 0 if it can't
+    /// be damaged.
+    pub fn max_damage(&self) -> i32 {
+        self.kind.max_damage()
+    }
+
+    /// Whether this item has the `Unbreakable` tag, which stops it from losing
+    /// durability.
+    pub fn is_unbreakable(&self) -> bool {
+        self.tag("Unbreakable")
+            .and_then(nbt_as_int)
+            .is_some_and(|unbreakable| unbreakable != 0)
+    }
+
+    /// The items inside of this shulker box, in the order of the slots they're
+    /// in. Empty slots are skipped.
+    ///
+    /// This also works for other containers that were picked up with their
+    /// contents, like chests that were middle-clicked in creative mode.
+    ///
+    /// ```
+    /// # use azalea_inventory::ItemSlotData;
+    /// # use azalea_nbt::{Nbt, NbtCompound, NbtList};
+    /// # use azalea_registry::Item;
+    /// let shulker_box = ItemSlotData {
+    ///     kind: Item::ShulkerBox,
+    ///     count: 1,
+    ///     nbt: Nbt::Compound(NbtCompound::from(vec![(
+    ///         "BlockEntityTag".into(),
+    ///         Nbt::Compound(NbtCompound::from(vec![(
+    ///             "Items".into(),
+    ///             Nbt::List(NbtList::Compound(vec![NbtCompound::from(vec![
+    ///                 ("Slot".into(), Nbt::Byte(3)),
+    ///                 ("id".into(), Nbt::String("minecraft:diamond".into())),
+    ///                 ("Count".into(), Nbt::Byte(64)),
+    ///             ])])),
+    ///         )])),
+    ///     )])),
+    /// };
+    /// let contents = shulker_box.shulker_box_contents();
+    /// assert_eq!(contents.len(), 1);
+    /// assert_eq!(contents[0].kind, Item::Diamond);
+    /// assert_eq!(contents[0].count, 64);
+    /// ```
+    pub fn shulker_box_contents(&self) -> Vec<ItemSlotData> {
+        let Some(Nbt::List(NbtList::Compound(items))) = self
+            .tag("BlockEntityTag")
+            .and_then(|block_entity| block_entity.as_compound()?.get("Items"))
+        else {
+            return Vec::new();
+        };
+        let mut items = items
+            .iter()
+            .filter_map(|item| {
+                let slot = item.get("Slot").and_then(nbt_as_int).unwrap_or(0);
+                Some((slot, item_from_nbt(item)?))
+            })
+            .collect::<Vec<_>>();
+        items.sort_by_key(|(slot, _)| *slot);
+        items.into_iter().map(|(_, item)| item).collect()
+    }
+
+    /// The items inside of this bundle, starting with the one that comes out
+    /// first when it's right-clicked.
+    pub fn bundle_contents(&self) -> Vec<ItemSlotData> {
+        let Some(Nbt::List(NbtList::Compound(items))) = self.tag("Items") else {
+            return Vec::new();
+        };
+        items.iter().filter_map(item_from_nbt).collect()
+    }
+}
+
+/// Read an item that's stored in NBT, like the ones inside of shulker boxes
+/// and bundles.
+fn item_from_nbt(nbt: &NbtCompound) -> Option<ItemSlotData> {
+    Some(ItemSlotData {
+        kind: parse_id(nbt.get("id")?.as_string()?)?,
+        count: nbt_as_int(nbt.get("Count")?)? as i8,
+        nbt: nbt.get("tag").cloned().unwrap_or_default(),
+    })
+}
+
+/// Parse a resource location from NBT, like `minecraft:stone`. Old items might
+/// not have a namespace, so it defaults to `minecraft`.
+fn parse_id<T: FromStr>(id: &str) -> Option<T> {
+    if id.contains(':') {
+        id.parse().ok()
+    } else {
+        format!("minecraft:{id}").parse().ok()
+    }
+}
+
+/// The id of an enchantment on an item. Servers with mods or plugins can add
+/// enchantments that vanilla doesn't have, so those are kept as
+/// [`Self::Other`].
+///
+/// ```
+/// # use azalea_inventory::EnchantmentId;
+/// # use azalea_registry::Enchantment;
+/// assert_eq!(
+///     EnchantmentId::from_id("minecraft:mending"),
+///     EnchantmentId::Vanilla(Enchantment::Mending)
+/// );
+/// assert_eq!(
+///     EnchantmentId::from_id("mymod:lifesteal"),
+///     EnchantmentId::Other("mymod:lifesteal".to_string())
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EnchantmentId {
+    Vanilla(Enchantment),
+    /// An enchantment that isn't in vanilla, with its full id (like
+    /// `mymod:lifesteal`).
+    Other(String),
+}
+impl EnchantmentId {
+    /// Get the enchantment from the id that's stored in an item's NBT. Ids
+    /// without a namespace are in the `minecraft` namespace.
+    pub fn from_id(id: &str) -> Self {
+        let id = if id.contains(':') {
+            id.to_string()
+        } else {
+            format!("minecraft:{id}")
+        };
+        match id.parse() {
+            Ok(enchantment) => Self::Vanilla(enchantment),
+            Err(_) => Self::Other(id),
+        }
+    }
+}
+impl From<Enchantment> for EnchantmentId {
+    fn from(enchantment: Enchantment) -> Self {
+        Self::Vanilla(enchantment)
+    }
+}
+impl PartialEq<Enchantment> for EnchantmentId {
+    fn eq(&self, other: &Enchantment) -> bool {
+        *self == Self::Vanilla(*other)
+    }
+}
+
+/// Read an integer from NBT, regardless of what size it was stored as.
+fn nbt_as_int(nbt: &Nbt) -> Option<i32> {
+    match nbt {
+        Nbt::Byte(n) => Some(*n as i32),
+        Nbt::Short(n) => Some(*n as i32),
+        Nbt::Int(n) => Some(*n),
+        Nbt::Long(n) => Some(*n as i32),
+        _ => None,
+    }
 }
 
 impl McBufReadable for ItemSlot {
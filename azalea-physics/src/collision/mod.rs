@@ -133,11 +133,12 @@ fn collide(movement: &Vec3, world: &Instance, physics: &azalea_entity::Physics)
 
 /// Move an entity by a given delta, checking for collisions.
 pub fn move_colliding(
-    _mover_type: &MoverType,
+    mover_type: &MoverType,
     movement: &Vec3,
     world: &Instance,
     mut position: Mut<azalea_entity::Position>,
     physics: &mut azalea_entity::Physics,
+    is_staying_on_ground_surface: bool,
 ) -> Result<(), MoveEntityError> {
     // TODO: do all these
 
@@ -158,7 +159,13 @@ pub fn move_colliding(
     //     this.setDeltaMovement(Vec3.ZERO);
     // }
 
-    // movement = this.maybeBackOffFromEdge(movement, moverType);
+    let movement = &maybe_back_off_from_edge(
+        movement,
+        mover_type,
+        world,
+        physics,
+        is_staying_on_ground_surface,
+    );
 
     let collide_result = collide(movement, world, physics);
 
@@ -186,6 +193,7 @@ pub fn move_colliding(
     let vertical_collision = movement.y != collide_result.y;
     let on_ground = vertical_collision && movement.y < 0.;
     physics.on_ground = on_ground;
+    physics.horizontal_collision = horizontal_collision;
 
     // TODO: minecraft checks for a "minor" horizontal collision here
 
@@ -247,6 +255,60 @@ pub fn move_colliding(
     Ok(())
 }
 
+/// Stop the movement before it would take the entity off the edge of the
+/// block it's standing on. This is what stops players from falling while
+/// they're sneaking.
+fn maybe_back_off_from_edge(
+    movement: &Vec3,
+    mover_type: &MoverType,
+    world: &Instance,
+    physics: &azalea_entity::Physics,
+    is_staying_on_ground_surface: bool,
+) -> Vec3 {
+    // TODO: check that we're not flying, and use the fall distance to check if
+    // we're above ground instead of just on_ground
+    if movement.y > 0.
+        || !matches!(mover_type, MoverType::Own | MoverType::Player)
+        || !is_staying_on_ground_surface
+        || !physics.on_ground
+    {
+        return *movement;
+    }
+
+    let max_up_step = 0.6;
+    let step = 0.05;
+    let no_collision = |x: f64, z: f64| {
+        let aabb = physics
+            .bounding_box
+            .move_relative(&Vec3::new(x, -max_up_step, z));
+        get_block_collisions(world, aabb).next().is_none()
+    };
+    let step_towards_zero = |n: f64| {
+        if (-step..step).contains(&n) {
+            0.
+        } else if n > 0. {
+            n - step
+        } else {
+            n + step
+        }
+    };
+
+    let mut x = movement.x;
+    let mut z = movement.z;
+    while x != 0. && no_collision(x, 0.) {
+        x = step_towards_zero(x);
+    }
+    while z != 0. && no_collision(0., z) {
+        z = step_towards_zero(z);
+    }
+    while x != 0. && z != 0. && no_collision(x, z) {
+        x = step_towards_zero(x);
+        z = step_towards_zero(z);
+    }
+
+    Vec3::new(x, movement.y, z)
+}
+
 fn collide_bounding_box(
     movement: &Vec3,
     entity_bounding_box: &AABB,
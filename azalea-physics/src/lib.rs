@@ -3,6 +3,8 @@
 
 pub mod clip;
 pub mod collision;
+pub mod riding;
+pub mod simulation;
 
 use azalea_block::{Block, BlockState};
 use azalea_core::{
@@ -10,13 +12,15 @@ use azalea_core::{
     position::{BlockPos, Vec3},
 };
 use azalea_entity::{
-    metadata::Sprinting, move_relative, Attributes, InLoadedChunk, Jumping, LocalEntity,
-    LookDirection, Physics, Position,
+    metadata::{ShiftKeyDown, Sprinting},
+    move_relative, Attributes, Flying, InLoadedChunk, Jumping, LocalEntity, LookDirection, Physics,
+    Position, Vehicle,
 };
+use azalea_registry::tags;
 use azalea_world::{Instance, InstanceContainer, InstanceName};
 use bevy_app::{App, FixedUpdate, Plugin};
 use bevy_ecs::{
-    query::With,
+    query::{With, Without},
     schedule::{IntoSystemConfigs, SystemSet},
     system::{Query, Res},
     world::Mut,
@@ -32,7 +36,7 @@ impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             FixedUpdate,
-            (ai_step, travel)
+            (ai_step, travel, riding::position_passengers)
                 .chain()
                 .in_set(PhysicsSet)
                 .after(azalea_entity::update_in_loaded_chunk),
@@ -50,14 +54,29 @@ fn travel(
             &mut LookDirection,
             &mut Position,
             Option<&Sprinting>,
+            Option<&Jumping>,
+            Option<&ShiftKeyDown>,
+            Option<&Flying>,
             &Attributes,
             &InstanceName,
         ),
-        (With<LocalEntity>, With<InLoadedChunk>),
+        // the vehicle moves entities that are riding it instead
+        (With<LocalEntity>, With<InLoadedChunk>, Without<Vehicle>),
     >,
     instance_container: Res<InstanceContainer>,
 ) {
-    for (mut physics, direction, position, sprinting, attributes, world_name) in &mut query {
+    for (
+        mut physics,
+        direction,
+        position,
+        sprinting,
+        jumping,
+        shift_key_down,
+        flying,
+        attributes,
+        world_name,
+    ) in &mut query
+    {
         let world_lock = instance_container
             .get(world_name)
             .expect("All entities should be in a valid world");
@@ -68,6 +87,8 @@ fn travel(
         // }
 
         let gravity: f64 = 0.08;
+        // flying ignores gravity and slows down our vertical movement instead
+        let delta_y_before_travel = physics.delta.y;
 
         // TODO: slow falling effect
         // let is_falling = self.delta.y <= 0.;
@@ -100,6 +121,10 @@ fn travel(
             position,
             attributes,
             sprinting.map(|s| **s).unwrap_or(false),
+            jumping.map(|j| **j).unwrap_or(false),
+            // sneaking while flying makes us go down instead of stopping at edges
+            shift_key_down.map(|s| **s).unwrap_or(false) && flying.is_none(),
+            flying.map(|f| f.speed),
         );
 
         movement.y -= gravity;
@@ -120,6 +145,10 @@ fn travel(
                 z: movement.z * inertia as f64,
             };
         }
+
+        if flying.is_some() {
+            physics.delta.y = delta_y_before_travel * 0.6;
+        }
     }
 }
 
@@ -134,13 +163,40 @@ pub fn ai_step(
             &Position,
             &LookDirection,
             &Sprinting,
+            Option<&ShiftKeyDown>,
+            Option<&Flying>,
             &InstanceName,
         ),
-        (With<LocalEntity>, With<InLoadedChunk>),
+        // the vehicle moves entities that are riding it instead
+        (With<LocalEntity>, With<InLoadedChunk>, Without<Vehicle>),
     >,
     instance_container: Res<InstanceContainer>,
 ) {
-    for (mut physics, jumping, position, look_direction, sprinting, instance_name) in &mut query {
+    for (
+        mut physics,
+        jumping,
+        position,
+        look_direction,
+        sprinting,
+        shift_key_down,
+        flying,
+        instance_name,
+    ) in &mut query
+    {
+        let is_jumping = jumping.is_some_and(|j| **j);
+
+        if let Some(flying) = flying {
+            // sneaking and jumping make us fly down and up
+            let mut vertical_input = 0.;
+            if shift_key_down.is_some_and(|s| **s) {
+                vertical_input -= 1.;
+            }
+            if is_jumping {
+                vertical_input += 1.;
+            }
+            physics.delta.y += vertical_input * flying.speed as f64 * 3.;
+        }
+
         // vanilla does movement interpolation here, doesn't really matter much for a
         // bot though
 
@@ -154,21 +210,16 @@ pub fn ai_step(
             physics.delta.z = 0.;
         }
 
-        if let Some(jumping) = jumping {
-            if **jumping {
-                // TODO: jumping in liquids and jump delay
-
-                if physics.on_ground {
-                    jump_from_ground(
-                        &mut physics,
-                        position,
-                        look_direction,
-                        sprinting,
-                        instance_name,
-                        &instance_container,
-                    )
-                }
-            }
+        // TODO: jumping in liquids and jump delay
+        if is_jumping && flying.is_none() && physics.on_ground {
+            jump_from_ground(
+                &mut physics,
+                position,
+                look_direction,
+                sprinting,
+                instance_name,
+                &instance_container,
+            )
         }
 
         physics.xxa *= 0.98;
@@ -200,18 +251,28 @@ pub fn jump_from_ground(
         z: old_delta_movement.z,
     };
     if **sprinting {
-        // sprint jumping gives some extra velocity
-        let y_rot = look_direction.y_rot * 0.017453292;
-        physics.delta += Vec3 {
-            x: (-math::sin(y_rot) * 0.2) as f64,
-            y: 0.,
-            z: (math::cos(y_rot) * 0.2) as f64,
-        };
+        physics.delta += sprint_jump_boost(look_direction);
     }
 
     physics.has_impulse = true;
 }
 
+/// How much horizontal velocity jumping while sprinting adds, in blocks per
+/// tick.
+pub const SPRINT_JUMP_BOOST: f32 = 0.2;
+
+/// The extra velocity that jumping while sprinting gives us. It's
+/// [`SPRINT_JUMP_BOOST`] blocks per tick in the direction we're looking,
+/// ignoring pitch.
+pub fn sprint_jump_boost(look_direction: &LookDirection) -> Vec3 {
+    let y_rot = look_direction.y_rot * 0.017453292;
+    Vec3 {
+        x: (-math::sin(y_rot) * SPRINT_JUMP_BOOST) as f64,
+        y: 0.,
+        z: (math::cos(y_rot) * SPRINT_JUMP_BOOST) as f64,
+    }
+}
+
 fn get_block_pos_below_that_affects_movement(position: &Position) -> BlockPos {
     BlockPos::new(
         position.x.floor() as i32,
@@ -221,46 +282,97 @@ fn get_block_pos_below_that_affects_movement(position: &Position) -> BlockPos {
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_relative_friction_and_calculate_movement(
     block_friction: f32,
     world: &Instance,
     physics: &mut Physics,
     direction: &LookDirection,
     // this is kept as a Mut for bevy change tracking
-    position: Mut<Position>,
+    mut position: Mut<Position>,
     attributes: &Attributes,
     is_sprinting: bool,
+    is_jumping: bool,
+    is_shift_key_down: bool,
+    flying_speed: Option<f32>,
 ) -> Vec3 {
     move_relative(
         physics,
         direction,
-        get_friction_influenced_speed(physics, attributes, block_friction, is_sprinting),
+        get_friction_influenced_speed(
+            physics,
+            attributes,
+            block_friction,
+            is_sprinting,
+            flying_speed,
+        ),
         &Vec3 {
             x: physics.xxa as f64,
             y: physics.yya as f64,
             z: physics.zza as f64,
         },
     );
-    // entity.delta = entity.handle_on_climbable(entity.delta);
+    physics.delta = handle_on_climbable(physics.delta, world, &position, is_shift_key_down);
+
     move_colliding(
         &MoverType::Own,
         &physics.delta.clone(),
         world,
-        position,
+        position.reborrow(),
         physics,
+        is_shift_key_down,
     )
     .expect("Entity should exist.");
-    // let delta_movement = entity.delta;
+
     // ladders
     //   if ((entity.horizontalCollision || entity.jumping) && (entity.onClimbable()
     // || entity.getFeetBlockState().is(Blocks.POWDER_SNOW) &&
     // PowderSnowBlock.canEntityWalkOnPowderSnow(entity))) {      var3 = new
     // Vec3(var3.x, 0.2D, var3.z);   }
     // TODO: powdered snow
+    if (physics.horizontal_collision || is_jumping) && on_climbable(world, &position) {
+        physics.delta.y = 0.2;
+    }
 
     physics.delta
 }
 
+/// Whether the block at the entity's feet is something that can be climbed,
+/// like a ladder or vines.
+pub fn on_climbable(world: &Instance, position: &Position) -> bool {
+    // TODO: trapdoors on top of ladders are also climbable
+    let Some(block_state) = world.chunks.get_block_state(&position.into()) else {
+        return false;
+    };
+    let block = Box::<dyn Block>::from(block_state).as_registry_block();
+    tags::blocks::CLIMBABLE.contains(&block)
+}
+
+/// Limit the entity's movement while it's on a ladder, so it doesn't slide off
+/// or fall too fast.
+fn handle_on_climbable(
+    delta: Vec3,
+    world: &Instance,
+    position: &Position,
+    is_shift_key_down: bool,
+) -> Vec3 {
+    if !on_climbable(world, position) {
+        return delta;
+    }
+
+    // TODO: reset fall distance when we have fall damage
+    let x = delta.x.clamp(-0.15, 0.15);
+    let z = delta.z.clamp(-0.15, 0.15);
+    let mut y = delta.y.max(-0.15);
+    // sneaking on a ladder makes you stop sliding down
+    // TODO: scaffolding works differently
+    if y < 0. && is_shift_key_down {
+        y = 0.;
+    }
+
+    Vec3 { x, y, z }
+}
+
 // private float getFrictionInfluencedSpeed(float friction) {
 //     return this.onGround ? this.getSpeed() * (0.21600002F / (friction *
 // friction * friction)) : this.flyingSpeed; }
@@ -269,11 +381,17 @@ fn get_friction_influenced_speed(
     attributes: &Attributes,
     friction: f32,
     is_sprinting: bool,
+    flying_speed: Option<f32>,
 ) -> f32 {
-    // TODO: have speed & flying_speed fields in entity
     if physics.on_ground {
         let speed: f32 = attributes.speed.calculate() as f32;
         speed * (0.216f32 / (friction * friction * friction))
+    } else if let Some(flying_speed) = flying_speed {
+        if is_sprinting {
+            flying_speed * 2.
+        } else {
+            flying_speed
+        }
     } else {
         // entity.flying_speed
         if is_sprinting {
@@ -699,4 +817,148 @@ mod tests {
         let entity_pos = app.world.get::<Position>(entity).unwrap();
         assert_eq!(entity_pos.y, 70.5);
     }
+
+    #[test]
+    fn test_flying() {
+        let mut app = make_test_app();
+        let world_lock = app.world.resource_mut::<InstanceContainer>().insert(
+            ResourceLocation::new("minecraft:overworld"),
+            384,
+            -64,
+        );
+        let mut partial_world = PartialInstance::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut world_lock.write().chunks,
+        );
+
+        let entity = app
+            .world
+            .spawn((
+                EntityBundle::new(
+                    Uuid::nil(),
+                    Vec3 {
+                        x: 0.5,
+                        y: 70.,
+                        z: 0.5,
+                    },
+                    azalea_registry::EntityKind::Player,
+                    ResourceLocation::new("minecraft:overworld"),
+                ),
+                MinecraftEntityId(0),
+                LocalEntity,
+                Flying { speed: 0.05 },
+                // ai_step only runs for entities with metadata
+                Sprinting(false),
+            ))
+            .id();
+        for _ in 0..20 {
+            app.world.run_schedule(FixedUpdate);
+            app.update();
+        }
+        // flying entities don't fall
+        assert_eq!(app.world.get::<Position>(entity).unwrap().y, 70.);
+
+        **app.world.get_mut::<Jumping>(entity).unwrap() = true;
+        for _ in 0..20 {
+            app.world.run_schedule(FixedUpdate);
+            app.update();
+        }
+        let y_after_ascending = app.world.get::<Position>(entity).unwrap().y;
+        assert!(y_after_ascending > 72.);
+
+        // and we hover again when we stop jumping
+        **app.world.get_mut::<Jumping>(entity).unwrap() = false;
+        for _ in 0..20 {
+            app.world.run_schedule(FixedUpdate);
+            app.update();
+        }
+        let hover_y = app.world.get::<Position>(entity).unwrap().y;
+        assert!(hover_y >= y_after_ascending);
+        for _ in 0..20 {
+            app.world.run_schedule(FixedUpdate);
+            app.update();
+        }
+        assert_eq!(app.world.get::<Position>(entity).unwrap().y, hover_y);
+    }
+
+    #[test]
+    fn test_riding() {
+        let mut app = make_test_app();
+        let world_lock = app.world.resource_mut::<InstanceContainer>().insert(
+            ResourceLocation::new("minecraft:overworld"),
+            384,
+            -64,
+        );
+        let mut partial_world = PartialInstance::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut world_lock.write().chunks,
+        );
+
+        let boat = app
+            .world
+            .spawn((
+                EntityBundle::new(
+                    Uuid::from_u128(1),
+                    Vec3 {
+                        x: 0.5,
+                        y: 70.,
+                        z: 0.5,
+                    },
+                    azalea_registry::EntityKind::Boat,
+                    ResourceLocation::new("minecraft:overworld"),
+                ),
+                MinecraftEntityId(1),
+            ))
+            .id();
+        let entity = app
+            .world
+            .spawn((
+                EntityBundle::new(
+                    Uuid::nil(),
+                    Vec3 {
+                        x: 2.5,
+                        y: 70.,
+                        z: 0.5,
+                    },
+                    azalea_registry::EntityKind::Player,
+                    ResourceLocation::new("minecraft:overworld"),
+                ),
+                MinecraftEntityId(0),
+                LocalEntity,
+                Vehicle(boat),
+            ))
+            .id();
+        for _ in 0..20 {
+            app.world.run_schedule(FixedUpdate);
+            app.update();
+        }
+        // we sit in the boat instead of falling
+        assert_eq!(
+            *app.world.get::<Position>(entity).unwrap(),
+            Position::new(Vec3 {
+                x: 0.5,
+                y: 70. - 0.1 + riding::PLAYER_RIDING_OFFSET,
+                z: 0.5,
+            })
+        );
+
+        // and we follow it when it moves
+        app.world.get_mut::<Position>(boat).unwrap().x = 1.5;
+        app.world.run_schedule(FixedUpdate);
+        app.update();
+        assert_eq!(app.world.get::<Position>(entity).unwrap().x, 1.5);
+
+        // we stop riding it if it's removed, and our physics start again
+        app.world.despawn(boat);
+        for _ in 0..10 {
+            app.world.run_schedule(FixedUpdate);
+            app.update();
+        }
+        assert!(app.world.get::<Vehicle>(entity).is_none());
+        assert!(app.world.get::<Position>(entity).unwrap().y < 69.);
+    }
 }
@@ -0,0 +1,73 @@
+//! Moving entities along with the vehicle that they're riding.
+//!
+//! Our normal physics don't run while we're riding something, since the
+//! vehicle is what moves us. Instead, we put ourselves on the vehicle every
+//! tick so we don't fight with the server about where we are.
+
+use azalea_core::position::Vec3;
+use azalea_entity::{EntityKind, LocalEntity, Physics, Position, Vehicle};
+use azalea_registry::EntityKind as Kind;
+use bevy_ecs::{
+    entity::Entity,
+    query::{With, Without},
+    system::{Commands, Query},
+};
+
+/// How far below the seat of a vehicle a player's position is.
+pub const PLAYER_RIDING_OFFSET: f64 = -0.35;
+
+/// How far above a vehicle's position its seat is, or 0 for vehicles that we
+/// don't know about.
+///
+/// Animations (like horses rearing or striders bobbing) aren't simulated, so
+/// this is only the height that the seat is at when the vehicle is still.
+pub fn passengers_riding_offset(kind: Kind) -> f64 {
+    match kind {
+        Kind::Boat | Kind::ChestBoat => -0.1,
+        Kind::Minecart
+        | Kind::ChestMinecart
+        | Kind::CommandBlockMinecart
+        | Kind::FurnaceMinecart
+        | Kind::HopperMinecart
+        | Kind::SpawnerMinecart
+        | Kind::TntMinecart => 0.,
+        Kind::Horse | Kind::SkeletonHorse | Kind::ZombieHorse => 1.2,
+        Kind::Donkey | Kind::Mule => 0.875,
+        Kind::Llama | Kind::TraderLlama => 1.122,
+        Kind::Pig => 0.675,
+        Kind::Strider => 1.51,
+        Kind::Camel => 1.775,
+        _ => 0.,
+    }
+}
+
+/// Move local entities that are riding something to where they sit on their
+/// vehicle, and stop them from keeping any velocity that they had before they
+/// got on.
+///
+/// Vehicles that we're riding are moved by the server (or by the vehicle's
+/// own movement packets), so we just follow them. If the vehicle doesn't exist
+/// anymore, we stop riding it so our physics start again.
+#[allow(clippy::type_complexity)]
+pub fn position_passengers(
+    mut commands: Commands,
+    mut passengers: Query<(Entity, &Vehicle, &mut Position, &mut Physics), With<LocalEntity>>,
+    vehicles: Query<(&Position, &EntityKind), Without<Vehicle>>,
+) {
+    for (entity, vehicle, mut position, mut physics) in &mut passengers {
+        let Ok((vehicle_position, vehicle_kind)) = vehicles.get(**vehicle) else {
+            commands.entity(entity).remove::<Vehicle>();
+            continue;
+        };
+        physics.delta = Vec3::default();
+
+        let new_position = Vec3 {
+            y: vehicle_position.y + passengers_riding_offset(**vehicle_kind) + PLAYER_RIDING_OFFSET,
+            ..**vehicle_position
+        };
+        // only trigger change detection if we actually moved
+        if **position != new_position {
+            **position = new_position;
+        }
+    }
+}
@@ -0,0 +1,296 @@
+//! Run physics on a single entity without a server, which is useful for
+//! testing movement code.
+//!
+//! ```
+//! # use azalea_core::position::{BlockPos, Vec3};
+//! # use azalea_physics::simulation::{PhysicsInput, PhysicsSimulation};
+//! let mut simulation = PhysicsSimulation::new(Vec3::new(0.5, 71., 0.5));
+//! simulation.set_block_state(&BlockPos::new(0, 70, 0), azalea_registry::Block::Stone.into());
+//!
+//! let positions = simulation.run(vec![PhysicsInput::default(); 20]);
+//! assert_eq!(positions.last().unwrap().y, 71.);
+//! ```
+
+use std::{collections::HashMap, sync::Arc};
+
+use azalea_block::BlockState;
+use azalea_core::{
+    position::{BlockPos, ChunkPos, Vec3},
+    resource_location::ResourceLocation,
+};
+use azalea_entity::{
+    attributes::AttributeInstance,
+    metadata::{ShiftKeyDown, Sprinting},
+    Attributes, EntityDimensions, Jumping, LocalEntity, LookDirection, Physics, Position,
+};
+use azalea_world::{Chunk, Instance, InstanceContainer, InstanceName, MinecraftEntityId};
+use bevy_app::{App, FixedUpdate};
+use bevy_ecs::prelude::*;
+use parking_lot::RwLock;
+
+/// The inputs that the simulated entity has for one tick. This is roughly
+/// equivalent to the keys a player would be pressing.
+#[derive(Clone, Debug, Default)]
+pub struct PhysicsInput {
+    /// How much the entity is trying to move forward, from -1 (backward) to 1
+    /// (forward).
+    pub forward: f32,
+    /// How much the entity is trying to move sideways, from -1 (right) to 1
+    /// (left).
+    pub left: f32,
+    pub jumping: bool,
+    pub sprinting: bool,
+    /// Whether the entity is holding shift, which stops it from walking off
+    /// edges. Note that this doesn't make [`Self::forward`] and [`Self::left`]
+    /// slower like it does for real players.
+    pub sneaking: bool,
+    /// The direction the entity is looking in, which determines which way
+    /// "forward" is.
+    pub look_direction: LookDirection,
+}
+
+/// A headless world with a single player-sized entity in it that only runs
+/// physics.
+///
+/// Ticks are only run when you call [`Self::tick`], so the result only depends
+/// on the world and the inputs you give it.
+pub struct PhysicsSimulation {
+    pub app: App,
+    pub entity: Entity,
+    instance: Arc<RwLock<Instance>>,
+    /// The instance only has weak references to its chunks, so we have to keep
+    /// them alive here.
+    chunks: HashMap<ChunkPos, Arc<RwLock<Chunk>>>,
+}
+
+impl PhysicsSimulation {
+    /// Create an empty world with the entity at the given position. The chunk
+    /// that the entity is in will be loaded, but you'll have to place any
+    /// blocks with [`Self::set_block_state`].
+    pub fn new(position: Vec3) -> Self {
+        let instance_name = ResourceLocation::new("azalea:simulation");
+
+        let instance = Arc::new(RwLock::new(Instance::default()));
+
+        let mut app = App::new();
+        app.add_plugins((crate::PhysicsPlugin, azalea_entity::EntityPlugin))
+            .insert_resource(InstanceContainer {
+                instances: [(instance_name.clone(), Arc::downgrade(&instance))]
+                    .into_iter()
+                    .collect(),
+            });
+
+        // the multithreaded executor doesn't always run systems in the same order
+        app.edit_schedule(bevy_app::Main, |schedule| {
+            schedule.set_executor_kind(bevy_ecs::schedule::ExecutorKind::SingleThreaded);
+        });
+
+        let dimensions = EntityDimensions {
+            width: 0.6,
+            height: 1.8,
+        };
+        let entity = app
+            .world
+            .spawn((
+                MinecraftEntityId(0),
+                InstanceName(instance_name),
+                LocalEntity,
+                Position::new(position),
+                Physics::new(dimensions, &position),
+                Attributes {
+                    speed: AttributeInstance::new(0.1),
+                    attack_speed: AttributeInstance::new(4.0),
+                },
+                Jumping::default(),
+                LookDirection::default(),
+                Sprinting(false),
+                ShiftKeyDown(false),
+            ))
+            .id();
+
+        let mut simulation = Self {
+            app,
+            entity,
+            instance,
+            chunks: HashMap::new(),
+        };
+        simulation.load_chunk(ChunkPos::from(&position));
+        // run an update so the entity gets marked as being in a loaded chunk
+        simulation.app.update();
+
+        simulation
+    }
+
+    fn load_chunk(&mut self, chunk_pos: ChunkPos) {
+        let chunk = self
+            .chunks
+            .entry(chunk_pos)
+            .or_insert_with(|| Arc::new(RwLock::new(Chunk::default())));
+        self.instance
+            .write()
+            .chunks
+            .map
+            .insert(chunk_pos, Arc::downgrade(chunk));
+    }
+
+    /// Set a block in the world, loading the chunk it's in if necessary.
+    pub fn set_block_state(&mut self, pos: &BlockPos, state: BlockState) {
+        self.load_chunk(ChunkPos::from(pos));
+        self.instance.write().chunks.set_block_state(pos, state);
+    }
+
+    pub fn instance(&self) -> &Arc<RwLock<Instance>> {
+        &self.instance
+    }
+
+    /// Apply the inputs and run a single physics tick.
+    pub fn tick(&mut self, input: &PhysicsInput) {
+        let mut entity = self.app.world.entity_mut(self.entity);
+        {
+            let mut physics = entity.get_mut::<Physics>().unwrap();
+            physics.zza = input.forward;
+            physics.xxa = input.left;
+        }
+        **entity.get_mut::<Jumping>().unwrap() = input.jumping;
+        **entity.get_mut::<Sprinting>().unwrap() = input.sprinting;
+        **entity.get_mut::<ShiftKeyDown>().unwrap() = input.sneaking;
+        *entity.get_mut::<LookDirection>().unwrap() = input.look_direction.clone();
+
+        self.app.world.run_schedule(FixedUpdate);
+        self.app.update();
+    }
+
+    /// Run a tick for every input and return the position of the entity after
+    /// each one.
+    pub fn run(&mut self, inputs: impl IntoIterator<Item = PhysicsInput>) -> Vec<Vec3> {
+        inputs
+            .into_iter()
+            .map(|input| {
+                self.tick(&input);
+                self.position()
+            })
+            .collect()
+    }
+
+    pub fn position(&self) -> Vec3 {
+        **self.app.world.get::<Position>(self.entity).unwrap()
+    }
+
+    pub fn physics(&self) -> &Physics {
+        self.app.world.get::<Physics>(self.entity).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SPRINT_JUMP_BOOST;
+
+    fn make_simulation(position: Vec3, solid_blocks: &[BlockPos]) -> PhysicsSimulation {
+        let mut simulation = PhysicsSimulation::new(position);
+        for block_pos in solid_blocks {
+            simulation.set_block_state(block_pos, azalea_registry::Block::Stone.into());
+        }
+        simulation
+    }
+
+    #[test]
+    fn test_walk_forward() {
+        let floor = (0..5).map(|z| BlockPos::new(0, 70, z)).collect::<Vec<_>>();
+        let mut simulation = make_simulation(Vec3::new(0.5, 71., 0.5), &floor);
+
+        let positions = simulation.run(vec![
+            PhysicsInput {
+                forward: 1.,
+                ..Default::default()
+            };
+            10
+        ]);
+
+        let last_position = positions.last().unwrap();
+        assert_eq!(last_position.y, 71.);
+        // a yaw of 0 is south, which is +z
+        assert!(last_position.z > 1.5, "{last_position:?}");
+    }
+
+    #[test]
+    fn test_sprint_jump_boost() {
+        let floor = (0..5).map(|z| BlockPos::new(0, 70, z)).collect::<Vec<_>>();
+        let sprinting = PhysicsInput {
+            forward: 1.,
+            sprinting: true,
+            ..Default::default()
+        };
+
+        let mut jump_simulation = make_simulation(Vec3::new(0.5, 71., 0.5), &floor);
+        let mut walk_simulation = make_simulation(Vec3::new(0.5, 71., 0.5), &floor);
+        // wait until they're on the ground
+        for _ in 0..2 {
+            jump_simulation.tick(&PhysicsInput::default());
+            walk_simulation.tick(&PhysicsInput::default());
+        }
+
+        jump_simulation.tick(&PhysicsInput {
+            jumping: true,
+            ..sprinting.clone()
+        });
+        walk_simulation.tick(&sprinting);
+
+        // the boost is applied before moving, and then slowed down by the
+        // friction of the stone we jumped off of
+        let ground_friction = 0.6 * 0.91;
+        let extra_z = jump_simulation.physics().delta.z - walk_simulation.physics().delta.z;
+        assert!(
+            (extra_z - SPRINT_JUMP_BOOST as f64 * ground_friction).abs() < 1e-6,
+            "{extra_z}"
+        );
+        assert!(jump_simulation.position().z > walk_simulation.position().z);
+    }
+
+    #[test]
+    fn test_sneaking_stops_at_edge() {
+        let floor = (0..2).map(|z| BlockPos::new(0, 70, z)).collect::<Vec<_>>();
+        let walking = PhysicsInput {
+            forward: 1.,
+            ..Default::default()
+        };
+
+        let mut sneak_simulation = make_simulation(Vec3::new(0.5, 71., 0.5), &floor);
+        let sneak_positions = sneak_simulation.run(vec![
+            PhysicsInput {
+                sneaking: true,
+                ..walking.clone()
+            };
+            30
+        ]);
+        let last_position = sneak_positions.last().unwrap();
+        assert_eq!(last_position.y, 71.);
+        // we can hang off the edge by up to half of our width
+        assert!(last_position.z > 1.5, "{last_position:?}");
+        assert!(last_position.z < 2.3, "{last_position:?}");
+
+        let mut walk_simulation = make_simulation(Vec3::new(0.5, 71., 0.5), &floor);
+        let walk_positions = walk_simulation.run(vec![walking; 30]);
+        assert!(walk_positions.last().unwrap().y < 71.);
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let floor = (0..5).map(|z| BlockPos::new(0, 70, z)).collect::<Vec<_>>();
+        let inputs = (0..20)
+            .map(|i| PhysicsInput {
+                forward: 1.,
+                jumping: i % 5 == 0,
+                sprinting: true,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut simulation = make_simulation(Vec3::new(0.5, 71., 0.5), &floor);
+        let first_positions = simulation.run(inputs.clone());
+        let mut simulation = make_simulation(Vec3::new(0.5, 71., 0.5), &floor);
+        let second_positions = simulation.run(inputs);
+
+        assert_eq!(first_positions, second_positions);
+    }
+}
@@ -1,5 +1,5 @@
 use azalea_buf::McBuf;
-use azalea_inventory::ItemSlot;
+use azalea_inventory::{item::MaxStackSizeExt, ItemSlot, ItemSlotData};
 use azalea_protocol_macros::ClientboundGamePacket;
 
 #[derive(Clone, Debug, McBuf, ClientboundGamePacket)]
@@ -28,3 +28,88 @@ pub struct MerchantOffer {
     pub price_multiplier: f32,
     pub demand: u32,
 }
+
+impl MerchantOffer {
+    /// The first item that we have to pay, with its count adjusted for how in
+    /// demand the trade is and any discounts we have (like from curing the
+    /// villager or having Hero of the Village).
+    pub fn cost_a(&self) -> ItemSlot {
+        let ItemSlot::Present(base_cost_a) = &self.base_cost_a else {
+            return ItemSlot::Empty;
+        };
+        let base_count = base_cost_a.count as i32;
+        // the demand is sent as an int, so it can be negative
+        let demand = self.demand as i32;
+        let demand_diff = ((base_count * demand) as f32 * self.price_multiplier)
+            .floor()
+            .max(0.) as i32;
+        let count = (base_count + demand_diff + self.special_price_diff)
+            .clamp(1, base_cost_a.kind.max_stack_size() as i32);
+        ItemSlot::Present(ItemSlotData {
+            count: count as i8,
+            ..base_cost_a.clone()
+        })
+    }
+
+    /// Whether the trade has been used too many times and can't be used again
+    /// until the villager restocks.
+    pub fn is_out_of_stock(&self) -> bool {
+        self.out_of_stock || self.uses >= self.max_uses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_registry::Item;
+
+    use super::*;
+
+    fn offer(base_count: i8, demand: u32, special_price_diff: i32) -> MerchantOffer {
+        MerchantOffer {
+            base_cost_a: ItemSlot::Present(ItemSlotData {
+                kind: Item::Emerald,
+                count: base_count,
+                nbt: Default::default(),
+            }),
+            result: ItemSlot::Empty,
+            cost_b: ItemSlot::Empty,
+            out_of_stock: false,
+            uses: 0,
+            max_uses: 12,
+            xp: 1,
+            special_price_diff,
+            price_multiplier: 0.2,
+            demand,
+        }
+    }
+
+    fn count(slot: ItemSlot) -> i8 {
+        match slot {
+            ItemSlot::Present(item) => item.count,
+            ItemSlot::Empty => 0,
+        }
+    }
+
+    #[test]
+    fn test_cost_a_demand() {
+        // 10 + floor(10 * 5 * 0.2)
+        assert_eq!(count(offer(10, 5, 0).cost_a()), 20);
+        // negative demand never makes the trade cheaper
+        assert_eq!(count(offer(10, -5i32 as u32, 0).cost_a()), 10);
+    }
+
+    #[test]
+    fn test_cost_a_clamped() {
+        // discounts can't make it free
+        assert_eq!(count(offer(10, 0, -20).cost_a()), 1);
+        // and demand can't make it cost more than a stack
+        assert_eq!(count(offer(60, 10, 0).cost_a()), 64);
+    }
+
+    #[test]
+    fn test_cost_a_empty() {
+        let mut offer = offer(10, 0, 0);
+        offer.base_cost_a = ItemSlot::Empty;
+        assert_eq!(offer.cost_a(), ItemSlot::Empty);
+    }
+}
@@ -7,7 +7,7 @@ pub struct ClientboundSetDisplayObjectivePacket {
     pub objective_name: String,
 }
 
-#[derive(Clone, Debug, Copy, McBuf)]
+#[derive(Clone, Debug, Copy, PartialEq, Eq, Hash, McBuf)]
 pub enum DisplaySlot {
     List = 0,
     Sidebar,
@@ -52,7 +52,7 @@ pub struct DisplayInfo {
     pub render_type: RenderType,
 }
 
-#[derive(McBuf, Copy, Clone, Debug)]
+#[derive(McBuf, Copy, Clone, Debug, PartialEq, Eq)]
 pub enum RenderType {
     Integer,
     Hearts,
@@ -0,0 +1,12 @@
+use azalea_nbt::NbtCompound;
+
+/// The extra data that some blocks have, like the text on a sign or the
+/// patterns on a banner.
+///
+/// The server doesn't send the contents of containers like chests, so you have
+/// to open them to see what's inside.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockEntityData {
+    pub kind: azalea_registry::BlockEntityKind,
+    pub nbt: NbtCompound,
+}
@@ -1,8 +1,10 @@
+use crate::block_entity::BlockEntityData;
 use crate::heightmap::Heightmap;
 use crate::heightmap::HeightmapKind;
+use crate::light::{ChunkLight, LightLevel};
 use crate::palette::PalettedContainer;
 use crate::palette::PalettedContainerKind;
-use azalea_block::BlockState;
+use azalea_block::{Block, BlockState};
 use azalea_buf::{BufReadError, McBufReadable, McBufWritable};
 use azalea_core::position::{BlockPos, ChunkBlockPos, ChunkPos, ChunkSectionBlockPos};
 use azalea_nbt::NbtCompound;
@@ -40,10 +42,10 @@ pub struct ChunkStorage {
     pub map: HashMap<ChunkPos, Weak<RwLock<Chunk>>>,
 }
 
-/// A single chunk in a world (16*?*16 blocks). This only contains the blocks
-/// and biomes. You can derive the height of the chunk from the number of
-/// sections, but you need a [`ChunkStorage`] to get the minimum Y
-/// coordinate.
+/// A single chunk in a world (16*?*16 blocks). This only contains the blocks,
+/// biomes, block entities, and light. You can derive the height of the chunk
+/// from the number of sections, but you need a [`ChunkStorage`] to get the
+/// minimum Y coordinate.
 #[derive(Debug)]
 pub struct Chunk {
     pub sections: Vec<Section>,
@@ -51,6 +53,11 @@ pub struct Chunk {
     /// Usually for clients only `WorldSurface` and `MotionBlocking` are
     /// present.
     pub heightmaps: HashMap<HeightmapKind, Heightmap>,
+    /// The extra data for blocks like signs and chests. This is removed when
+    /// the block is replaced with a different one.
+    pub block_entities: HashMap<ChunkBlockPos, BlockEntityData>,
+    /// The block and sky light that the server sent us for this chunk.
+    pub light: ChunkLight,
 }
 
 /// A section of a chunk, i.e. a 16*16*16 block area.
@@ -82,6 +89,8 @@ impl Default for Chunk {
         Chunk {
             sections: vec![Section::default(); (384 / 16) as usize],
             heightmaps: HashMap::new(),
+            block_entities: HashMap::new(),
+            light: ChunkLight::default(),
         }
     }
 }
@@ -222,6 +231,12 @@ impl ChunkStorage {
         self.map.get(pos).and_then(|chunk| chunk.upgrade())
     }
 
+    /// Forget about the chunks that were dropped because nothing had them
+    /// loaded anymore, so the map doesn't keep growing.
+    pub fn remove_dropped_chunks(&mut self) {
+        self.map.retain(|_, chunk| chunk.strong_count() > 0);
+    }
+
     pub fn get_block_state(&self, pos: &BlockPos) -> Option<BlockState> {
         let chunk_pos = ChunkPos::from(pos);
         let chunk = self.get(&chunk_pos)?;
@@ -238,6 +253,33 @@ impl ChunkStorage {
         let mut chunk = chunk.write();
         Some(chunk.get_and_set(&ChunkBlockPos::from(pos), state, self.min_y))
     }
+
+    pub fn get_block_entity(&self, pos: &BlockPos) -> Option<BlockEntityData> {
+        let chunk = self.get(&ChunkPos::from(pos))?;
+        let chunk = chunk.read();
+        chunk.get_block_entity(&ChunkBlockPos::from(pos)).cloned()
+    }
+
+    /// Set the block entity at the position, replacing the one that was there
+    /// before. Returns `None` if the chunk isn't loaded.
+    pub fn set_block_entity(&self, pos: &BlockPos, block_entity: BlockEntityData) -> Option<()> {
+        let chunk = self.get(&ChunkPos::from(pos))?;
+        let mut chunk = chunk.write();
+        chunk.set_block_entity(ChunkBlockPos::from(pos), block_entity);
+        Some(())
+    }
+
+    /// Get the block and sky light at the position. Returns `None` if the
+    /// chunk isn't loaded.
+    pub fn get_light(&self, pos: &BlockPos) -> Option<LightLevel> {
+        let chunk = self.get(&ChunkPos::from(pos))?;
+        let chunk = chunk.read();
+        let chunk_pos = ChunkBlockPos::from(pos);
+        Some(LightLevel {
+            block: chunk.light.get_block_light(&chunk_pos, self.min_y),
+            sky: chunk.light.get_sky_light(&chunk_pos, self.min_y),
+        })
+    }
 }
 
 impl Chunk {
@@ -272,6 +314,8 @@ impl Chunk {
         Ok(Chunk {
             sections,
             heightmaps,
+            block_entities: HashMap::new(),
+            light: ChunkLight::default(),
         })
     }
 
@@ -294,6 +338,7 @@ impl Chunk {
         for heightmap in self.heightmaps.values_mut() {
             heightmap.update(pos, state, &self.sections);
         }
+        self.remove_replaced_block_entity(pos, previous_state, state);
 
         previous_state
     }
@@ -303,11 +348,42 @@ impl Chunk {
         // TODO: make sure the section exists
         let section = &mut self.sections[section_index as usize];
         let chunk_section_pos = ChunkSectionBlockPos::from(pos);
+        let previous_state = section.get(chunk_section_pos);
         section.set(chunk_section_pos, state);
 
         for heightmap in self.heightmaps.values_mut() {
             heightmap.update(pos, state, &self.sections);
         }
+        self.remove_replaced_block_entity(pos, previous_state, state);
+    }
+
+    /// Remove the block entity at the position if the block was replaced with a
+    /// different block. Changing a block's properties (like which way a chest
+    /// is facing) keeps its block entity.
+    fn remove_replaced_block_entity(
+        &mut self,
+        pos: &ChunkBlockPos,
+        previous_state: BlockState,
+        state: BlockState,
+    ) {
+        if previous_state == state || !self.block_entities.contains_key(pos) {
+            return;
+        }
+        let previous_block = Box::<dyn Block>::from(previous_state).as_registry_block();
+        let block = Box::<dyn Block>::from(state).as_registry_block();
+        if previous_block != block {
+            self.block_entities.remove(pos);
+        }
+    }
+
+    pub fn get_block_entity(&self, pos: &ChunkBlockPos) -> Option<&BlockEntityData> {
+        self.block_entities.get(pos)
+    }
+
+    /// Set the block entity at the position, replacing the one that was there
+    /// before.
+    pub fn set_block_entity(&mut self, pos: ChunkBlockPos, block_entity: BlockEntityData) {
+        self.block_entities.insert(pos, block_entity);
     }
 }
 
@@ -478,4 +554,37 @@ mod tests {
             .get_block_state(&BlockPos { x: 0, y: -65, z: 0 })
             .is_none());
     }
+
+    #[test]
+    fn test_block_entity_removed_when_block_replaced() {
+        let mut chunk_storage = ChunkStorage::default();
+        let mut partial_chunk_storage = PartialChunkStorage::default();
+        partial_chunk_storage.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        let pos = BlockPos::new(1, 64, 1);
+        let sign = BlockEntityData {
+            kind: azalea_registry::BlockEntityKind::Sign,
+            nbt: NbtCompound::default(),
+        };
+        chunk_storage.set_block_state(&pos, azalea_registry::Block::OakSign.into());
+        chunk_storage.set_block_entity(&pos, sign.clone());
+        assert_eq!(chunk_storage.get_block_entity(&pos), Some(sign.clone()));
+
+        // changing the properties of the block keeps the block entity
+        chunk_storage.set_block_state(
+            &pos,
+            azalea_block::blocks::OakSign {
+                rotation: azalea_block::properties::OakSignRotation::_4,
+                waterlogged: true,
+            }
+            .into(),
+        );
+        assert_eq!(chunk_storage.get_block_entity(&pos), Some(sign));
+
+        chunk_storage.set_block_state(&pos, BlockState::AIR);
+        assert_eq!(chunk_storage.get_block_entity(&pos), None);
+    }
 }
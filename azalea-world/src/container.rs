@@ -9,7 +9,7 @@ use std::{
     sync::{Arc, Weak},
 };
 
-use crate::{ChunkStorage, Instance};
+use crate::{ChunkStorage, Instance, WorldBorder};
 
 /// A container of [`Instance`]s (aka worlds). Instances are stored as a Weak
 /// pointer here, so if no clients are using an instance it will be forgotten.
@@ -70,6 +70,7 @@ impl InstanceContainer {
                 chunks: ChunkStorage::new(height, min_y),
                 entities_by_chunk: HashMap::new(),
                 entity_by_id: IntMap::default(),
+                world_border: WorldBorder::default(),
             }));
             self.instances.insert(name, Arc::downgrade(&world));
             world
@@ -3,22 +3,27 @@
 #![feature(error_generic_member_access)]
 
 mod bit_storage;
+mod block_entity;
 mod chunk_storage;
 mod container;
 pub mod heightmap;
 pub mod iterators;
+pub mod light;
 pub mod palette;
 mod world;
+mod world_border;
 
 use std::backtrace::Backtrace;
 
 pub use bit_storage::BitStorage;
+pub use block_entity::BlockEntityData;
 pub use chunk_storage::{
     calculate_chunk_storage_range, Chunk, ChunkStorage, PartialChunkStorage, Section,
 };
 pub use container::*;
 use thiserror::Error;
 pub use world::*;
+pub use world_border::*;
 
 #[derive(Error, Debug)]
 pub enum MoveEntityError {
@@ -0,0 +1,221 @@
+//! Block light and sky light, which the server calculates and sends to us
+//! along with chunks.
+
+use azalea_core::{
+    bitset::BitSet,
+    position::{ChunkBlockPos, ChunkSectionBlockPos},
+};
+
+/// The highest light level.
+pub const MAX_LIGHT_LEVEL: u8 = 15;
+
+/// The light levels at a position in the world.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LightLevel {
+    /// The light that comes from blocks like torches and glowstone.
+    pub block: u8,
+    /// The light that comes from the sky. This doesn't change at night, the
+    /// sky just gives off less light then.
+    pub sky: u8,
+}
+
+/// The light levels for every block in a 16x16x16 section, stored as 4 bits
+/// per block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataLayer(Box<[u8; DataLayer::SIZE]>);
+impl DataLayer {
+    /// The number of bytes in a layer.
+    pub const SIZE: usize = 2048;
+
+    /// A layer where every block has a light level of 0.
+    pub fn empty() -> Self {
+        Self(Box::new([0; Self::SIZE]))
+    }
+
+    /// Make a layer from the bytes that the server sent us, or return `None`
+    /// if there's the wrong number of them.
+    pub fn new(data: &[u8]) -> Option<Self> {
+        Some(Self(Box::new(data.try_into().ok()?)))
+    }
+
+    pub fn get(&self, pos: &ChunkSectionBlockPos) -> u8 {
+        let index = ((pos.y as usize) << 8) | ((pos.z as usize) << 4) | pos.x as usize;
+        let byte = self.0[index >> 1];
+        if index & 1 == 0 {
+            byte & 0xf
+        } else {
+            byte >> 4
+        }
+    }
+}
+
+/// The light data for a chunk.
+///
+/// There's a layer for every section in the chunk plus one below and one above
+/// it, since light can come from outside of the world. Layers that are `None`
+/// are ones that the server didn't send us.
+#[derive(Clone, Debug, Default)]
+pub struct ChunkLight {
+    pub sky: Vec<Option<DataLayer>>,
+    pub block: Vec<Option<DataLayer>>,
+}
+
+impl ChunkLight {
+    /// Update the sky light from the masks and data in a light update or chunk
+    /// packet. `section_count` is the number of sections in the chunk, not
+    /// including the ones below and above it.
+    pub fn update_sky(
+        &mut self,
+        section_count: usize,
+        mask: &BitSet,
+        empty_mask: &BitSet,
+        updates: &[Vec<u8>],
+    ) {
+        update_layers(&mut self.sky, section_count, mask, empty_mask, updates);
+    }
+
+    /// Update the block light from the masks and data in a light update or
+    /// chunk packet. `section_count` is the number of sections in the chunk,
+    /// not including the ones below and above it.
+    pub fn update_block(
+        &mut self,
+        section_count: usize,
+        mask: &BitSet,
+        empty_mask: &BitSet,
+        updates: &[Vec<u8>],
+    ) {
+        update_layers(&mut self.block, section_count, mask, empty_mask, updates);
+    }
+
+    /// Get the block light at the position, or 0 if the server didn't send it
+    /// to us.
+    pub fn get_block_light(&self, pos: &ChunkBlockPos, min_y: i32) -> u8 {
+        let Some(index) = layer_index(pos.y, min_y) else {
+            return 0;
+        };
+        match self.block.get(index) {
+            Some(Some(layer)) => layer.get(&ChunkSectionBlockPos::from(pos)),
+            _ => 0,
+        }
+    }
+
+    /// Get the sky light at the position.
+    ///
+    /// Like in vanilla, sections that the server didn't send sky light for get
+    /// it from the closest section above them that it did send, since nothing
+    /// in between is blocking the sky. If the server didn't send any sky light
+    /// for this chunk then we're in a dimension without a sky (like the
+    /// nether), so this is always 0.
+    pub fn get_sky_light(&self, pos: &ChunkBlockPos, min_y: i32) -> u8 {
+        if self.sky.iter().all(Option::is_none) {
+            return 0;
+        }
+        let Some(index) = layer_index(pos.y, min_y) else {
+            // below the bottom layer is dark and above the top is in the open sky
+            return if pos.y < min_y { 0 } else { MAX_LIGHT_LEVEL };
+        };
+        let section_pos = ChunkSectionBlockPos::from(pos);
+        if let Some(Some(layer)) = self.sky.get(index) {
+            return layer.get(&section_pos);
+        }
+        // use the bottom of the closest layer above us
+        self.sky
+            .iter()
+            .skip(index + 1)
+            .flatten()
+            .next()
+            .map(|layer| {
+                layer.get(&ChunkSectionBlockPos {
+                    y: 0,
+                    ..section_pos
+                })
+            })
+            .unwrap_or(MAX_LIGHT_LEVEL)
+    }
+}
+
+/// The index of the layer that has the light for the given y coordinate, or
+/// `None` if it's below the layer under the world.
+fn layer_index(y: i32, min_y: i32) -> Option<usize> {
+    // the first layer is the one below the world
+    let index = y.div_floor(16) - min_y.div_floor(16) + 1;
+    usize::try_from(index).ok()
+}
+
+fn update_layers(
+    layers: &mut Vec<Option<DataLayer>>,
+    section_count: usize,
+    mask: &BitSet,
+    empty_mask: &BitSet,
+    updates: &[Vec<u8>],
+) {
+    layers.resize(section_count + 2, None);
+    let mut updates = updates.iter();
+    for (i, layer) in layers.iter_mut().enumerate() {
+        if mask.index(i) {
+            // the updates are only sent for the layers in the mask, in order
+            *layer = updates.next().and_then(|data| DataLayer::new(data));
+        } else if empty_mask.index(i) {
+            *layer = Some(DataLayer::empty());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer_with(pos: &ChunkSectionBlockPos, level: u8) -> Vec<u8> {
+        let mut data = vec![0; DataLayer::SIZE];
+        let index = ((pos.y as usize) << 8) | ((pos.z as usize) << 4) | pos.x as usize;
+        data[index >> 1] = if index & 1 == 0 { level } else { level << 4 };
+        data
+    }
+
+    #[test]
+    fn test_data_layer_get() {
+        let pos = ChunkSectionBlockPos::new(3, 5, 7);
+        let layer = DataLayer::new(&layer_with(&pos, 13)).unwrap();
+        assert_eq!(layer.get(&pos), 13);
+        assert_eq!(layer.get(&ChunkSectionBlockPos::new(2, 5, 7)), 0);
+        assert_eq!(layer.get(&ChunkSectionBlockPos::new(4, 5, 7)), 0);
+    }
+
+    #[test]
+    fn test_block_light() {
+        let mut light = ChunkLight::default();
+        let mut mask = BitSet::new(64);
+        // the section from y=0 to y=15, since the world starts at y=-64
+        mask.set(5);
+        let update = layer_with(&ChunkSectionBlockPos::new(1, 2, 3), 14);
+        light.update_block(24, &mask, &BitSet::new(64), &[update]);
+
+        assert_eq!(light.get_block_light(&ChunkBlockPos::new(1, 2, 3), -64), 14);
+        assert_eq!(light.get_block_light(&ChunkBlockPos::new(1, 18, 3), -64), 0);
+        assert_eq!(
+            light.get_block_light(&ChunkBlockPos::new(1, -500, 3), -64),
+            0
+        );
+    }
+
+    #[test]
+    fn test_sky_light_from_above() {
+        let mut light = ChunkLight::default();
+        let mut mask = BitSet::new(64);
+        mask.set(5);
+        let update = layer_with(&ChunkSectionBlockPos::new(1, 0, 3), 9);
+        light.update_sky(24, &mask, &BitSet::new(64), &[update]);
+
+        assert_eq!(light.get_sky_light(&ChunkBlockPos::new(1, 0, 3), -64), 9);
+        // the section below doesn't have data, so it uses the bottom of this one
+        assert_eq!(light.get_sky_light(&ChunkBlockPos::new(1, -10, 3), -64), 9);
+        // nothing above has data, so it's in the open sky
+        assert_eq!(light.get_sky_light(&ChunkBlockPos::new(1, 40, 3), -64), 15);
+    }
+
+    #[test]
+    fn test_no_sky_light() {
+        let light = ChunkLight::default();
+        assert_eq!(light.get_sky_light(&ChunkBlockPos::new(0, 64, 0), 0), 0);
+    }
+}
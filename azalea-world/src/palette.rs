@@ -101,9 +101,11 @@ impl PalettedContainer {
 
     /// Sets the id at the given coordinates and return the previous id
     pub fn get_and_set(&mut self, x: usize, y: usize, z: usize, value: u32) -> u32 {
-        let paletted_value = self.id_for(value);
-        self.storage
-            .get_and_set(self.index_from_coords(x, y, z), paletted_value as u64) as u32
+        // the previous value has to be read before setting since inserting into the
+        // palette might resize it
+        let previous_value = self.get(x, y, z);
+        self.set(x, y, z, value);
+        previous_value
     }
 
     /// Sets the id at the given index and return the previous id. You probably
@@ -1,4 +1,7 @@
-use crate::{iterators::ChunkIterator, palette::Palette, ChunkStorage, PartialChunkStorage};
+use crate::{
+    iterators::ChunkIterator, light::LightLevel, palette::Palette, BlockEntityData, ChunkStorage,
+    PartialChunkStorage, WorldBorder,
+};
 use azalea_block::{BlockState, BlockStates, FluidState};
 use azalea_core::position::{BlockPos, ChunkPos};
 use bevy_ecs::{component::Component, entity::Entity};
@@ -85,6 +88,9 @@ pub struct Instance {
     /// An index of Minecraft entity IDs to Azalea ECS entities. You should
     /// avoid using this and instead use `azalea_entity::EntityIdIndex`
     pub entity_by_id: IntMap<MinecraftEntityId, Entity>,
+
+    /// The border that players in this world can't go past.
+    pub world_border: WorldBorder,
 }
 
 impl Instance {
@@ -100,6 +106,37 @@ impl Instance {
         self.chunks.set_block_state(pos, state)
     }
 
+    /// Get the block entity data at the given position, like the text on a
+    /// sign. Returns `None` if there's no block entity there or the chunk
+    /// isn't loaded.
+    pub fn get_block_entity(&self, pos: &BlockPos) -> Option<BlockEntityData> {
+        self.chunks.get_block_entity(pos)
+    }
+
+    /// Set the block entity data at the given position, replacing the data
+    /// that was there before.
+    pub fn set_block_entity(&self, pos: &BlockPos, block_entity: BlockEntityData) -> Option<()> {
+        self.chunks.set_block_entity(pos, block_entity)
+    }
+
+    /// Get the block and sky light at the given position, or `None` if the
+    /// chunk isn't loaded.
+    pub fn get_light(&self, pos: &BlockPos) -> Option<LightLevel> {
+        self.chunks.get_light(pos)
+    }
+
+    /// Get the light from blocks like torches at the given position, or `None`
+    /// if the chunk isn't loaded.
+    pub fn get_block_light(&self, pos: &BlockPos) -> Option<u8> {
+        self.get_light(pos).map(|light| light.block)
+    }
+
+    /// Get the light from the sky at the given position, or `None` if the
+    /// chunk isn't loaded.
+    pub fn get_sky_light(&self, pos: &BlockPos) -> Option<u8> {
+        self.get_light(pos).map(|light| light.sky)
+    }
+
     /// Find the coordinates of a block in the world.
     ///
     /// Note that this is sorted by `x+y+z` and not `x^2+y^2+z^2` for
@@ -203,6 +240,103 @@ impl Instance {
             None
         }
     }
+
+    /// Find the coordinates of every block within `radius` blocks of
+    /// `nearest_to` that matches the given function, sorted by distance
+    /// (closest first).
+    ///
+    /// Chunks that aren't loaded are skipped.
+    ///
+    /// ```
+    /// # use azalea_block::BlockStates;
+    /// # fn example(client: &azalea_client::Client) {
+    /// let diamond_ore = BlockStates::from(azalea_registry::Block::DiamondOre);
+    /// let ores = client
+    ///     .world()
+    ///     .read()
+    ///     .find_blocks(client.position(), 32, |state| diamond_ore.contains(&state));
+    /// # }
+    /// ```
+    pub fn find_blocks(
+        &self,
+        nearest_to: impl Into<BlockPos>,
+        radius: u32,
+        matcher: impl Fn(BlockState) -> bool,
+    ) -> Vec<BlockPos> {
+        let nearest_to: BlockPos = nearest_to.into();
+        let radius = radius as i32;
+        let radius_sqr = radius * radius;
+
+        let min_chunk = ChunkPos::from(&(nearest_to - BlockPos::new(radius, 0, radius)));
+        let max_chunk = ChunkPos::from(&(nearest_to + BlockPos::new(radius, 0, radius)));
+
+        let mut found = Vec::new();
+
+        for chunk_x in min_chunk.x..=max_chunk.x {
+            for chunk_z in min_chunk.z..=max_chunk.z {
+                let chunk_pos = ChunkPos::new(chunk_x, chunk_z);
+                let Some(chunk) = self.chunks.get(&chunk_pos) else {
+                    continue;
+                };
+
+                for (section_index, section) in chunk.read().sections.iter().enumerate() {
+                    let section_min_y = self.chunks.min_y + (section_index * 16) as i32;
+                    if section_min_y + 15 < nearest_to.y - radius
+                        || section_min_y > nearest_to.y + radius
+                    {
+                        continue;
+                    }
+
+                    // check the palette first so we only have to call the matcher once for
+                    // every distinct state in the section
+                    let matching_ids: Option<Vec<u32>> = match &section.states.palette {
+                        // an empty list (rather than None) so sections of a single
+                        // non-matching state are skipped instead of scanned
+                        Palette::SingleValue(id) => Some(
+                            matcher(BlockState { id: *id })
+                                .then_some(*id)
+                                .into_iter()
+                                .collect(),
+                        ),
+                        Palette::Linear(ids) | Palette::Hashmap(ids) => Some(
+                            ids.iter()
+                                .copied()
+                                .filter(|&id| matcher(BlockState { id }))
+                                .collect(),
+                        ),
+                        Palette::Global => None,
+                    };
+                    if matches!(&matching_ids, Some(ids) if ids.is_empty()) {
+                        continue;
+                    }
+
+                    for i in 0..4096 {
+                        let id = section.states.get_at_index(i);
+                        let is_match = match &matching_ids {
+                            Some(ids) => ids.contains(&id),
+                            None => matcher(BlockState { id }),
+                        };
+                        if !is_match {
+                            continue;
+                        }
+
+                        let (section_x, section_y, section_z) = section.states.coords_from_index(i);
+                        let pos = BlockPos {
+                            x: chunk_pos.x * 16 + section_x as i32,
+                            y: section_min_y + section_y as i32,
+                            z: chunk_pos.z * 16 + section_z as i32,
+                        };
+                        if nearest_to.distance_to_sqr(&pos) <= radius_sqr {
+                            found.push(pos);
+                        }
+                    }
+                }
+            }
+        }
+
+        found.sort_by_key(|pos| nearest_to.distance_to_sqr(pos));
+        found
+    }
 }
 
 impl Debug for PartialInstance {
@@ -235,6 +369,7 @@ impl From<ChunkStorage> for Instance {
             chunks,
             entities_by_chunk: HashMap::new(),
             entity_by_id: IntMap::default(),
+            world_border: WorldBorder::default(),
         }
     }
 }
@@ -273,4 +408,40 @@ mod tests {
         let pos = instance.find_block(BlockPos { x: 0, y: 0, z: 0 }, &Block::Stone.into());
         assert_eq!(pos, Some(BlockPos { x: 17, y: 0, z: 0 }));
     }
+
+    #[test]
+    fn find_blocks() {
+        let mut instance = Instance::default();
+
+        let chunk_storage = &mut instance.chunks;
+        let mut partial_chunk_storage = PartialChunkStorage::default();
+
+        partial_chunk_storage.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            chunk_storage,
+        );
+
+        chunk_storage.set_block_state(&BlockPos { x: 5, y: 0, z: 0 }, Block::Stone.into());
+        chunk_storage.set_block_state(&BlockPos { x: 0, y: 2, z: 0 }, Block::Stone.into());
+        chunk_storage.set_block_state(
+            &BlockPos {
+                x: 15,
+                y: 15,
+                z: 15,
+            },
+            Block::Stone.into(),
+        );
+        // this one's in an unloaded chunk
+        chunk_storage.set_block_state(&BlockPos { x: 17, y: 0, z: 0 }, Block::Stone.into());
+
+        let stone = BlockStates::from(Block::Stone);
+        let found = instance.find_blocks(BlockPos { x: 0, y: 0, z: 0 }, 10, |state| {
+            stone.contains(&state)
+        });
+        assert_eq!(
+            found,
+            vec![BlockPos { x: 0, y: 2, z: 0 }, BlockPos { x: 5, y: 0, z: 0 }]
+        );
+    }
 }
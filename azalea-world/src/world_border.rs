@@ -0,0 +1,192 @@
+use std::time::{Duration, Instant};
+
+use azalea_core::position::BlockPos;
+
+/// The default size of the world border, in blocks.
+pub const DEFAULT_WORLD_BORDER_SIZE: f64 = 5.999_996_8E7;
+/// The furthest that the world border can be from the center of the world.
+pub const DEFAULT_ABSOLUTE_MAX_SIZE: u32 = 29_999_984;
+
+/// The square that players can't leave in a world.
+///
+/// The border can grow or shrink over time, so [`Self::size`] and the bounds
+/// are calculated from the current time.
+#[derive(Clone, Debug)]
+pub struct WorldBorder {
+    pub center_x: f64,
+    pub center_z: f64,
+    /// The border can never be further than this many blocks from (0, 0).
+    pub absolute_max_size: u32,
+    /// How close to the border (in blocks) we have to be for the vanilla client
+    /// to show a warning.
+    pub warning_blocks: u32,
+    /// If the border is shrinking, how many seconds before it reaches us the
+    /// vanilla client shows a warning.
+    pub warning_time: u32,
+    extent: BorderExtent,
+}
+
+#[derive(Clone, Debug)]
+enum BorderExtent {
+    Static(f64),
+    Moving {
+        from: f64,
+        to: f64,
+        start: Instant,
+        duration: Duration,
+    },
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        Self {
+            center_x: 0.,
+            center_z: 0.,
+            absolute_max_size: DEFAULT_ABSOLUTE_MAX_SIZE,
+            warning_blocks: 5,
+            warning_time: 15,
+            extent: BorderExtent::Static(DEFAULT_WORLD_BORDER_SIZE),
+        }
+    }
+}
+
+impl WorldBorder {
+    /// The current length of each side of the border.
+    pub fn size(&self) -> f64 {
+        match self.extent {
+            // this is called a lot by the pathfinder so avoid getting the time if we can
+            BorderExtent::Static(size) => size,
+            BorderExtent::Moving { .. } => self.size_at(Instant::now()),
+        }
+    }
+
+    /// The length of each side of the border at the given time, accounting for
+    /// the border growing or shrinking.
+    pub fn size_at(&self, now: Instant) -> f64 {
+        match self.extent {
+            BorderExtent::Static(size) => size,
+            BorderExtent::Moving {
+                from,
+                to,
+                start,
+                duration,
+            } => {
+                let elapsed = now.saturating_duration_since(start);
+                if elapsed >= duration {
+                    return to;
+                }
+                let progress = elapsed.as_secs_f64() / duration.as_secs_f64();
+                from + (to - from) * progress
+            }
+        }
+    }
+
+    /// The size that the border is going to end up at. This is the same as
+    /// [`Self::size`] if it isn't moving.
+    pub fn target_size(&self) -> f64 {
+        match self.extent {
+            BorderExtent::Static(size) => size,
+            BorderExtent::Moving { to, .. } => to,
+        }
+    }
+
+    /// Whether the border is currently growing or shrinking.
+    pub fn is_moving(&self) -> bool {
+        match self.extent {
+            BorderExtent::Static(_) => false,
+            BorderExtent::Moving {
+                start, duration, ..
+            } => start.elapsed() < duration,
+        }
+    }
+
+    /// Immediately change the size of the border.
+    pub fn set_size(&mut self, size: f64) {
+        self.extent = BorderExtent::Static(size);
+    }
+
+    /// Make the border move from `from` to `to` over the given number of
+    /// milliseconds, starting now.
+    pub fn lerp_size_between(&mut self, from: f64, to: f64, milliseconds: u64) {
+        self.extent = if from == to || milliseconds == 0 {
+            BorderExtent::Static(to)
+        } else {
+            BorderExtent::Moving {
+                from,
+                to,
+                start: Instant::now(),
+                duration: Duration::from_millis(milliseconds),
+            }
+        };
+    }
+
+    /// The (min x, min z, max x, max z) coordinates of the border right now.
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        self.bounds_for_size(self.size())
+    }
+
+    /// The (min x, min z, max x, max z) coordinates of the border at the given
+    /// time.
+    pub fn bounds_at(&self, now: Instant) -> (f64, f64, f64, f64) {
+        self.bounds_for_size(self.size_at(now))
+    }
+
+    fn bounds_for_size(&self, size: f64) -> (f64, f64, f64, f64) {
+        let half_size = size / 2.;
+        let max_size = self.absolute_max_size as f64;
+        (
+            (self.center_x - half_size).clamp(-max_size, max_size),
+            (self.center_z - half_size).clamp(-max_size, max_size),
+            (self.center_x + half_size).clamp(-max_size, max_size),
+            (self.center_z + half_size).clamp(-max_size, max_size),
+        )
+    }
+
+    /// Whether the whole block is inside of the border right now.
+    pub fn is_within_bounds(&self, pos: &BlockPos) -> bool {
+        let (min_x, min_z, max_x, max_z) = self.bounds();
+        pos.x as f64 >= min_x
+            && pos.x as f64 + 1. <= max_x
+            && pos.z as f64 >= min_z
+            && pos.z as f64 + 1. <= max_z
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_contains_spawn() {
+        let border = WorldBorder::default();
+        assert!(border.is_within_bounds(&BlockPos::new(0, 64, 0)));
+        assert!(!border.is_within_bounds(&BlockPos::new(30_000_000, 64, 0)));
+    }
+
+    #[test]
+    fn test_small_border() {
+        let mut border = WorldBorder {
+            center_x: 0.5,
+            center_z: 0.5,
+            ..Default::default()
+        };
+        border.set_size(3.);
+        assert!(border.is_within_bounds(&BlockPos::new(-1, 0, 1)));
+        assert!(!border.is_within_bounds(&BlockPos::new(-2, 0, 0)));
+        assert!(!border.is_within_bounds(&BlockPos::new(0, 0, 2)));
+    }
+
+    #[test]
+    fn test_shrinking_border() {
+        let mut border = WorldBorder::default();
+        border.lerp_size_between(100., 50., 10_000);
+        let BorderExtent::Moving { start, .. } = border.extent else {
+            panic!("border should be moving");
+        };
+
+        assert_eq!(border.size_at(start), 100.);
+        assert_eq!(border.size_at(start + Duration::from_secs(5)), 75.);
+        assert_eq!(border.size_at(start + Duration::from_secs(20)), 50.);
+        assert_eq!(border.target_size(), 50.);
+    }
+}
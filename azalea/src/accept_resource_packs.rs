@@ -1,49 +1,21 @@
+//! Resource packs are accepted by `azalea-client` now, see
+//! [`ResourcePackPolicy`](azalea_client::resource_pack::ResourcePackPolicy).
+
 use crate::app::{App, Plugin};
-use azalea_client::chunk_batching::handle_chunk_batch_finished_event;
-use azalea_client::inventory::InventorySet;
-use azalea_client::packet_handling::{death_event_on_0_health, game::ResourcePackEvent};
-use azalea_client::respawn::perform_respawn;
-use azalea_client::SendPacketEvent;
-use azalea_protocol::packets::game::serverbound_resource_pack_packet::{
-    self, ServerboundResourcePackPacket,
-};
-use bevy_app::Update;
-use bevy_ecs::prelude::*;
 
-/// A plugin that makes it so bots automatically accept resource packs.
+/// This doesn't do anything anymore, since resource packs are accepted by
+/// `azalea-client` in both the configuration and game states.
+///
+/// Disabling this plugin does nothing, so bots will keep accepting resource
+/// packs. To stop that, insert
+/// [`ResourcePackPolicy::Ignore`](azalea_client::resource_pack::ResourcePackPolicy::Ignore)
+/// or
+/// [`ResourcePackPolicy::Decline`](azalea_client::resource_pack::ResourcePackPolicy::Decline)
+/// as a resource.
+#[deprecated = "disabling this does nothing, insert `ResourcePackPolicy::Ignore` or `ResourcePackPolicy::Decline` as a resource instead"]
 #[derive(Clone, Default)]
 pub struct AcceptResourcePacksPlugin;
+#[allow(deprecated)]
 impl Plugin for AcceptResourcePacksPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            accept_resource_pack
-                .before(perform_respawn)
-                .after(death_event_on_0_health)
-                .after(handle_chunk_batch_finished_event)
-                .after(InventorySet),
-        );
-    }
-}
-
-fn accept_resource_pack(
-    mut events: EventReader<ResourcePackEvent>,
-    mut send_packet_events: EventWriter<SendPacketEvent>,
-) {
-    for event in events.iter() {
-        send_packet_events.send(SendPacketEvent {
-            entity: event.entity,
-            packet: ServerboundResourcePackPacket {
-                action: serverbound_resource_pack_packet::Action::Accepted,
-            }
-            .get(),
-        });
-        send_packet_events.send(SendPacketEvent {
-            entity: event.entity,
-            packet: ServerboundResourcePackPacket {
-                action: serverbound_resource_pack_packet::Action::SuccessfullyLoaded,
-            }
-            .get(),
-        });
-    }
+    fn build(&self, _app: &mut App) {}
 }
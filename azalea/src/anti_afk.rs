@@ -0,0 +1,177 @@
+use crate::app::{App, Plugin};
+use crate::pathfinder::Pathfinder;
+use azalea_client::{
+    attack::TicksSinceLastAttack, eat::Eating, interact::SwingArmEvent, mining::Mining,
+    PhysicsState, WalkDirection,
+};
+use azalea_entity::{
+    metadata::{Health, ShiftKeyDown},
+    LocalEntity, LookDirection,
+};
+use azalea_physics::PhysicsSet;
+use azalea_protocol::packets::game::serverbound_interact_packet::InteractionHand;
+use bevy_app::{FixedUpdate, PreUpdate};
+use bevy_ecs::prelude::*;
+
+/// A plugin that makes idle bots do something harmless every so often, so
+/// servers don't kick them for being AFK.
+///
+/// This isn't included in the `DefaultBotPlugins`, so you have to add it
+/// yourself:
+///
+/// ```
+/// # use azalea::{anti_afk::{AntiAfkAction, AntiAfkPlugin}, ClientBuilder};
+/// let builder = ClientBuilder::new().add_plugins(AntiAfkPlugin {
+///     interval_ticks: 20 * 30,
+///     action: AntiAfkAction::SwingArm,
+/// });
+/// ```
+///
+/// Bots that are pathfinding, mining, eating, walking, or attacking aren't
+/// idle, so they're left alone.
+#[derive(Clone)]
+pub struct AntiAfkPlugin {
+    /// How many ticks the bot has to be idle for before it does something.
+    pub interval_ticks: u32,
+    pub action: AntiAfkAction,
+}
+impl Default for AntiAfkPlugin {
+    fn default() -> Self {
+        Self {
+            interval_ticks: 20 * 60,
+            action: AntiAfkAction::LookJitter,
+        }
+    }
+}
+impl Plugin for AntiAfkPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AntiAfkSettings {
+            enabled: true,
+            interval_ticks: self.interval_ticks,
+            action: self.action,
+        })
+        .add_systems(PreUpdate, add_anti_afk_state)
+        .add_systems(FixedUpdate, anti_afk.before(PhysicsSet));
+    }
+}
+
+/// The settings for the [`AntiAfkPlugin`], which can be changed while the bot
+/// is running.
+#[derive(Resource, Clone, Debug)]
+pub struct AntiAfkSettings {
+    pub enabled: bool,
+    pub interval_ticks: u32,
+    pub action: AntiAfkAction,
+}
+
+/// The thing that the [`AntiAfkPlugin`] does to show that we're not AFK.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AntiAfkAction {
+    /// Turn our head slightly, alternating between left and right so we end up
+    /// looking the same way.
+    LookJitter,
+    /// Swing our arm without hitting anything.
+    SwingArm,
+    /// Sneak for one tick.
+    Sneak,
+}
+
+/// How many degrees we turn our head for [`AntiAfkAction::LookJitter`].
+const LOOK_JITTER_DEGREES: f32 = 2.;
+
+/// A component that keeps track of how long a bot has been idle for.
+#[derive(Component, Default)]
+pub struct AntiAfkState {
+    pub idle_ticks: u32,
+    /// Whether the last look jitter was to the left, so the next one goes back
+    /// to the right.
+    jittered_left: bool,
+    /// Whether we started sneaking last tick and have to stop now.
+    sneaking: bool,
+}
+
+fn add_anti_afk_state(
+    mut commands: Commands,
+    query: Query<Entity, (With<LocalEntity>, Without<AntiAfkState>)>,
+) {
+    for entity in &query {
+        commands.entity(entity).insert(AntiAfkState::default());
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn anti_afk(
+    settings: Res<AntiAfkSettings>,
+    mut query: Query<
+        (
+            Entity,
+            &mut AntiAfkState,
+            &Health,
+            &PhysicsState,
+            &mut LookDirection,
+            &mut ShiftKeyDown,
+            Option<&Pathfinder>,
+            Option<&TicksSinceLastAttack>,
+        ),
+        (With<LocalEntity>, Without<Mining>, Without<Eating>),
+    >,
+    mut swing_arm_events: EventWriter<SwingArmEvent>,
+) {
+    for (
+        entity,
+        mut state,
+        health,
+        physics_state,
+        mut look_direction,
+        mut shift_key_down,
+        pathfinder,
+        ticks_since_last_attack,
+    ) in &mut query
+    {
+        if state.sneaking {
+            **shift_key_down = false;
+            state.sneaking = false;
+        }
+
+        let is_pathfinding = pathfinder.is_some_and(|p| p.goal.is_some() || !p.path.is_empty());
+        let attacked_recently =
+            ticks_since_last_attack.is_some_and(|ticks| **ticks < settings.interval_ticks);
+        let is_walking = !matches!(physics_state.move_direction, WalkDirection::None);
+        if !settings.enabled || **health <= 0. || is_pathfinding || attacked_recently || is_walking
+        {
+            state.idle_ticks = 0;
+            continue;
+        }
+
+        state.idle_ticks += 1;
+        if state.idle_ticks < settings.interval_ticks {
+            continue;
+        }
+        state.idle_ticks = 0;
+
+        match settings.action {
+            AntiAfkAction::LookJitter => {
+                let degrees = if state.jittered_left {
+                    LOOK_JITTER_DEGREES
+                } else {
+                    -LOOK_JITTER_DEGREES
+                };
+                look_direction.y_rot += degrees;
+                state.jittered_left = !state.jittered_left;
+            }
+            AntiAfkAction::SwingArm => {
+                swing_arm_events.send(SwingArmEvent {
+                    entity,
+                    hand: InteractionHand::MainHand,
+                });
+            }
+            AntiAfkAction::Sneak => {
+                // don't stop sneaking if something else made us sneak
+                if !**shift_key_down {
+                    **shift_key_down = true;
+                    state.sneaking = true;
+                }
+            }
+        }
+    }
+}
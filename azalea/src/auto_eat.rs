@@ -0,0 +1,92 @@
+use crate::app::{App, Plugin};
+use azalea_client::{
+    eat::{food_properties, handle_start_eating_event, Eating, StartEatingEvent},
+    inventory::InventoryComponent,
+    Hunger, PhysicsState, WalkDirection,
+};
+use azalea_entity::{metadata::Health, LocalEntity};
+use bevy_app::Update;
+use bevy_ecs::prelude::*;
+
+/// A plugin that makes the bot eat the best food in its hotbar when its hunger
+/// bar drops below a threshold.
+///
+/// This isn't included in the `DefaultBotPlugins`, so you have to add it
+/// yourself:
+///
+/// ```
+/// # use azalea::{auto_eat::AutoEatPlugin, ClientBuilder};
+/// let builder = ClientBuilder::new().add_plugins(AutoEatPlugin { food_threshold: 14 });
+/// ```
+///
+/// Harmful foods (like rotten flesh) and golden apples are never eaten, and the
+/// bot won't start eating while it's walking.
+#[derive(Clone)]
+pub struct AutoEatPlugin {
+    /// The bot eats when its food level is below this number. The hunger bar
+    /// goes from 0 to 20.
+    pub food_threshold: u32,
+}
+impl Default for AutoEatPlugin {
+    fn default() -> Self {
+        Self { food_threshold: 14 }
+    }
+}
+impl Plugin for AutoEatPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AutoEatSettings {
+            food_threshold: self.food_threshold,
+        })
+        .add_systems(Update, auto_eat.before(handle_start_eating_event));
+    }
+}
+
+/// The settings for the [`AutoEatPlugin`], which can be changed while the bot
+/// is running.
+#[derive(Resource, Clone, Debug)]
+pub struct AutoEatSettings {
+    pub food_threshold: u32,
+}
+
+#[allow(clippy::type_complexity)]
+fn auto_eat(
+    settings: Res<AutoEatSettings>,
+    query: Query<
+        (Entity, &Hunger, &Health, &InventoryComponent, &PhysicsState),
+        (With<LocalEntity>, Without<Eating>),
+    >,
+    mut start_eating_events: EventWriter<StartEatingEvent>,
+) {
+    for (entity, hunger, health, inventory, physics_state) in &query {
+        if hunger.food >= settings.food_threshold || **health <= 0. {
+            continue;
+        }
+        if !matches!(physics_state.move_direction, WalkDirection::None) {
+            // we'd be interrupted immediately
+            continue;
+        }
+
+        if let Some(slot) = best_food_slot(inventory) {
+            start_eating_events.send(StartEatingEvent { entity, slot });
+        }
+    }
+}
+
+/// Find the hotbar slot with the food that restores the most hunger and
+/// saturation, ignoring food that we shouldn't eat automatically.
+fn best_food_slot(inventory: &InventoryComponent) -> Option<u8> {
+    let hotbar_slots =
+        &inventory.inventory_menu.slots()[inventory.inventory_menu.hotbar_slots_range()];
+    hotbar_slots
+        .iter()
+        .enumerate()
+        .filter_map(|(i, item_slot)| {
+            let food = food_properties(item_slot.kind())?;
+            if food.harmful || food.always_edible {
+                return None;
+            }
+            Some((i as u8, food.nutrition as f32 + food.saturation()))
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(slot, _)| slot)
+}
@@ -2,31 +2,104 @@ use crate::app::{App, Plugin};
 use azalea_client::{
     packet_handling::{death_event_on_0_health, game::DeathEvent},
     respawn::{perform_respawn, PerformRespawnEvent},
+    Hardcore,
 };
-use bevy_app::Update;
+use azalea_entity::Dead;
+use bevy_app::{FixedUpdate, Update};
 use bevy_ecs::prelude::*;
+use log::info;
 
-/// A plugin that makes [`DeathEvent`]s send [`PerformRespawnEvent`]s.
+/// A plugin that makes [`DeathEvent`]s send [`PerformRespawnEvent`]s, depending
+/// on the [`AutoRespawn`] policy.
 #[derive(Clone, Default)]
 pub struct AutoRespawnPlugin;
 impl Plugin for AutoRespawnPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            auto_respawn
-                .before(perform_respawn)
-                .after(death_event_on_0_health),
-        );
+        app.init_resource::<AutoRespawn>()
+            .add_systems(
+                Update,
+                auto_respawn
+                    .before(perform_respawn)
+                    .after(death_event_on_0_health),
+            )
+            .add_systems(FixedUpdate, tick_respawn_delay);
     }
 }
 
+/// Decides what happens when one of our bots dies. This can be changed while
+/// the bots are running.
+///
+/// ```
+/// # use azalea::auto_respawn::AutoRespawn;
+/// # fn example(app: &mut bevy_app::App) {
+/// // wait 5 seconds before respawning
+/// app.insert_resource(AutoRespawn::Delayed(20 * 5));
+/// # }
+/// ```
+///
+/// Bots never respawn automatically on hardcore servers, since respawning
+/// would put them in spectator mode. You can still use
+/// [`Client::respawn`](azalea_client::Client::respawn) to do that yourself.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AutoRespawn {
+    /// Respawn as soon as we die.
+    #[default]
+    Immediate,
+    /// Respawn after waiting this many ticks.
+    Delayed(u32),
+    /// Stay dead until something else respawns us.
+    Never,
+}
+
+/// A component that counts down the ticks until we respawn, for
+/// [`AutoRespawn::Delayed`].
+#[derive(Component, Debug)]
+pub struct RespawnDelay(pub u32);
+
 fn auto_respawn(
+    mut commands: Commands,
     mut events: EventReader<DeathEvent>,
+    policy: Res<AutoRespawn>,
+    query: Query<&Hardcore>,
     mut perform_respawn_events: EventWriter<PerformRespawnEvent>,
 ) {
     for event in events.iter() {
-        perform_respawn_events.send(PerformRespawnEvent {
-            entity: event.entity,
-        });
+        // this also filters out entities that aren't local players
+        let Ok(hardcore) = query.get(event.entity) else {
+            continue;
+        };
+        if **hardcore {
+            info!("Not respawning automatically since the server is in hardcore mode");
+            continue;
+        }
+        match *policy {
+            AutoRespawn::Immediate => perform_respawn_events.send(PerformRespawnEvent {
+                entity: event.entity,
+            }),
+            AutoRespawn::Delayed(ticks) => {
+                commands.entity(event.entity).insert(RespawnDelay(ticks));
+            }
+            AutoRespawn::Never => {}
+        }
+    }
+}
+
+fn tick_respawn_delay(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut RespawnDelay, Option<&Dead>)>,
+    mut perform_respawn_events: EventWriter<PerformRespawnEvent>,
+) {
+    for (entity, mut delay, dead) in &mut query {
+        if dead.is_none() {
+            // we already respawned some other way
+            commands.entity(entity).remove::<RespawnDelay>();
+            continue;
+        }
+        if delay.0 > 0 {
+            delay.0 -= 1;
+            continue;
+        }
+        perform_respawn_events.send(PerformRespawnEvent { entity });
+        commands.entity(entity).remove::<RespawnDelay>();
     }
 }
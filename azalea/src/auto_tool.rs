@@ -0,0 +1,58 @@
+//! Pick the best item in our hotbar for breaking a block.
+
+use azalea_block::{Block, BlockState};
+use azalea_core::position::Vec3;
+use azalea_entity::{mining::get_mine_progress, EntityDimensions, FluidOnEyes, Physics};
+use azalea_inventory::Menu;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BestToolResult {
+    /// The index of the slot in the hotbar, from 0 to 8.
+    pub index: usize,
+    /// How much of the block is broken every tick, from 0 to 1. This is 0 if
+    /// the block can't be broken at all (like bedrock).
+    pub percentage_per_tick: f32,
+}
+
+/// Find the item in our hotbar that breaks the block the fastest, assuming
+/// that we're standing on the ground and not underwater.
+///
+/// If more than one item is equally fast, the first one is picked.
+pub fn best_tool_in_hotbar_for_block(block: BlockState, menu: &Menu) -> BestToolResult {
+    let mut physics = Physics::new(EntityDimensions::default(), &Vec3::default());
+    physics.on_ground = true;
+    accurate_best_tool_in_hotbar_for_block(
+        block,
+        menu,
+        &physics,
+        &FluidOnEyes::new(azalea_registry::Fluid::Empty),
+    )
+}
+
+/// Like [`best_tool_in_hotbar_for_block`], but uses the given physics and
+/// fluid state instead of assuming we're on the ground.
+pub fn accurate_best_tool_in_hotbar_for_block(
+    block: BlockState,
+    menu: &Menu,
+    physics: &Physics,
+    fluid_on_eyes: &FluidOnEyes,
+) -> BestToolResult {
+    let block = Box::<dyn Block>::from(block);
+    let hotbar_slots = &menu.slots()[menu.hotbar_slots_range()];
+
+    let mut best = BestToolResult {
+        index: 0,
+        percentage_per_tick: 0.,
+    };
+    for (index, item) in hotbar_slots.iter().enumerate() {
+        let percentage_per_tick =
+            get_mine_progress(block.as_ref(), item.kind(), menu, fluid_on_eyes, physics);
+        if percentage_per_tick > best.percentage_per_tick {
+            best = BestToolResult {
+                index,
+                percentage_per_tick,
+            };
+        }
+    }
+    best
+}
@@ -1,3 +1,4 @@
+#[allow(deprecated)]
 use crate::accept_resource_packs::AcceptResourcePacksPlugin;
 use crate::app::{App, Plugin, PluginGroup, PluginGroupBuilder};
 use crate::auto_respawn::AutoRespawnPlugin;
@@ -10,19 +11,25 @@ use crate::ecs::{
     system::{Commands, Query},
 };
 use azalea_client::interact::SwingArmEvent;
+use azalea_client::inventory::ItemSlot;
 use azalea_client::mining::Mining;
-use azalea_client::TickBroadcast;
-use azalea_core::position::{BlockPos, Vec3};
+use azalea_client::packet_handling::game::ItemPickupEvent;
+use azalea_client::{InstanceHolder, TickBroadcast};
+use azalea_core::position::{BlockPos, ChunkPos, Vec3};
 use azalea_entity::{
-    clamp_look_direction, metadata::Player, EyeHeight, Jumping, LocalEntity, LookDirection,
-    Position,
+    clamp_look_direction, direction_looking_at, metadata::Player, EntityDespawnEvent,
+    EntitySpawnEvent, EyeHeight, Jumping, LocalEntity, LookDirection, Position,
 };
 use azalea_physics::PhysicsSet;
+use azalea_protocol::packets::game::serverbound_interact_packet::InteractionHand;
+use azalea_world::InstanceName;
 use bevy_app::{FixedUpdate, Update};
 use bevy_ecs::prelude::Event;
 use bevy_ecs::schedule::IntoSystemConfigs;
 use log::trace;
-use std::f64::consts::PI;
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::error::RecvError;
 
 use crate::pathfinder::PathfinderPlugin;
 
@@ -31,16 +38,28 @@ pub struct BotPlugin;
 impl Plugin for BotPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<LookAtEvent>()
+            .add_event::<SmoothLookEvent>()
             .add_event::<JumpEvent>()
+            // this is normally added by the packet handler plugin, but it's also added here so
+            // the bot plugin works without it (like in the pathfinder simulation)
+            .add_event::<ItemPickupEvent>()
             .add_systems(
                 Update,
                 (
                     insert_bot,
                     look_at_listener.before(clamp_look_direction),
+                    smooth_look_listener.after(look_at_listener),
                     jump_listener,
+                    (track_drop_spawns, track_drop_pickups, track_drop_despawns).chain(),
                 ),
             )
-            .add_systems(FixedUpdate, stop_jumping.after(PhysicsSet));
+            .add_systems(
+                FixedUpdate,
+                (
+                    tick_smooth_look.before(PhysicsSet),
+                    stop_jumping.after(PhysicsSet),
+                ),
+            );
     }
 }
 
@@ -75,6 +94,9 @@ pub trait BotClientExt {
     fn jump(&mut self);
     /// Turn the bot's head to look at the coordinate in the world.
     fn look_at(&mut self, pos: Vec3);
+    /// Turn the bot's head towards the coordinate over the next few ticks
+    /// instead of snapping to it. See [`SmoothLookEvent`].
+    fn look_at_smoothly(&mut self, pos: Vec3, settings: SmoothLookSettings);
     /// Get a receiver that will receive a message every tick.
     fn get_tick_broadcaster(&self) -> tokio::sync::broadcast::Receiver<()>;
     /// Mine a block. This won't turn the bot's head towards the block, so if
@@ -82,6 +104,19 @@ pub trait BotClientExt {
     ///
     /// [`look_at`]: crate::prelude::BotClientExt::look_at
     async fn mine(&mut self, position: BlockPos);
+    /// Wait until the chunk at the given position is loaded in our world, or
+    /// until the timeout passes. Returns whether the chunk is loaded.
+    ///
+    /// This is useful after joining or teleporting, since the blocks in
+    /// chunks that haven't been sent to us yet look like air.
+    async fn wait_for_chunk(&self, pos: ChunkPos, timeout: Duration) -> bool;
+    /// Mine a block and wait until we pick up the items that it dropped, or
+    /// until the timeout passes. Returns the items that we picked up.
+    ///
+    /// This doesn't walk to the drops, so items that land out of reach (or
+    /// that another entity picks up first) won't be collected. If the block
+    /// doesn't drop anything then this waits for the whole timeout.
+    async fn mine_and_collect(&mut self, position: BlockPos, timeout: Duration) -> Vec<ItemSlot>;
 }
 
 impl BotClientExt for azalea_client::Client {
@@ -100,6 +135,28 @@ impl BotClientExt for azalea_client::Client {
         });
     }
 
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::{SmoothLookSettings, Vec3};
+    /// # fn example(bot: &mut azalea::Client) {
+    /// bot.look_at_smoothly(
+    ///     Vec3::new(0., 64., 0.),
+    ///     SmoothLookSettings {
+    ///         ticks: 10,
+    ///         ..Default::default()
+    ///     },
+    /// );
+    /// # }
+    /// ```
+    fn look_at_smoothly(&mut self, position: Vec3, settings: SmoothLookSettings) {
+        let mut ecs = self.ecs.lock();
+        ecs.send_event(SmoothLookEvent {
+            entity: self.entity,
+            position,
+            settings,
+        });
+    }
+
     /// ```
     /// # use azalea::prelude::*;
     /// # use azalea::container::WaitingForInventoryOpen;
@@ -124,6 +181,7 @@ impl BotClientExt for azalea_client::Client {
         // vanilla sends an extra swing arm packet when we start mining
         self.ecs.lock().send_event(SwingArmEvent {
             entity: self.entity,
+            hand: InteractionHand::MainHand,
         });
 
         let mut receiver = self.get_tick_broadcaster();
@@ -134,6 +192,186 @@ impl BotClientExt for azalea_client::Client {
             }
         }
     }
+
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use std::time::Duration;
+    /// # async fn example(bot: &mut azalea::Client) {
+    /// let target = azalea::BlockPos::new(1000, 64, 1000);
+    /// if !bot
+    ///     .wait_for_chunk((&target).into(), Duration::from_secs(10))
+    ///     .await
+    /// {
+    ///     println!("the server never sent us the chunk");
+    /// }
+    /// # }
+    /// ```
+    async fn wait_for_chunk(&self, pos: ChunkPos, timeout: Duration) -> bool {
+        let start = Instant::now();
+        let mut receiver = self.get_tick_broadcaster();
+        loop {
+            {
+                let ecs = self.ecs.lock();
+                // checked every tick since the chunk can be unloaded again, and the instance
+                // gets replaced when we change dimensions
+                let Some(instance_holder) = ecs.get::<InstanceHolder>(self.entity) else {
+                    // we're not in the game state (or we disconnected)
+                    return false;
+                };
+                if instance_holder
+                    .partial_instance
+                    .read()
+                    .chunks
+                    .limited_get(&pos)
+                    .is_some()
+                {
+                    return true;
+                }
+            }
+
+            if start.elapsed() >= timeout {
+                return false;
+            }
+            if let Err(RecvError::Closed) = receiver.recv().await {
+                return false;
+            }
+        }
+    }
+
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use std::time::Duration;
+    /// # async fn example(bot: &mut azalea::Client, pos: azalea::BlockPos) {
+    /// let items = bot.mine_and_collect(pos, Duration::from_secs(2)).await;
+    /// for item in items {
+    ///     println!("picked up {} {:?}", item.count(), item.kind());
+    /// }
+    /// # }
+    /// ```
+    async fn mine_and_collect(&mut self, position: BlockPos, timeout: Duration) -> Vec<ItemSlot> {
+        self.ecs
+            .lock()
+            .entity_mut(self.entity)
+            .insert(CollectingDrops::new(position));
+
+        self.mine(position).await;
+
+        let block_was_broken = self
+            .world()
+            .read()
+            .get_block_state(&position)
+            .is_some_and(|state| state.is_air());
+        if block_was_broken {
+            let start = Instant::now();
+            let mut receiver = self.get_tick_broadcaster();
+            let mut saw_drops = false;
+            loop {
+                {
+                    let ecs = self.ecs.lock();
+                    let Some(collecting) = ecs.get::<CollectingDrops>(self.entity) else {
+                        // we disconnected
+                        break;
+                    };
+                    if !collecting.pending.is_empty() {
+                        saw_drops = true;
+                    } else if saw_drops {
+                        // every drop was either picked up or despawned
+                        break;
+                    }
+                }
+
+                if start.elapsed() >= timeout {
+                    break;
+                }
+                if let Err(RecvError::Closed) = receiver.recv().await {
+                    break;
+                }
+            }
+        }
+
+        let mut ecs = self.ecs.lock();
+        let Some(mut entity) = ecs.get_entity_mut(self.entity) else {
+            return Vec::new();
+        };
+        entity
+            .take::<CollectingDrops>()
+            .map(|collecting| collecting.collected)
+            .unwrap_or_default()
+    }
+}
+
+/// How far from the center of a block its drops can spawn for
+/// [`BotClientExt::mine_and_collect`] to count them.
+const DROP_SEARCH_RADIUS: f64 = 1.5;
+
+/// A component that's present while we're running
+/// [`BotClientExt::mine_and_collect`], which keeps track of the items that
+/// dropped from the block.
+#[derive(Component, Debug)]
+pub struct CollectingDrops {
+    pub block: BlockPos,
+    /// The item entities that dropped from the block and haven't despawned
+    /// yet.
+    pub pending: HashSet<Entity>,
+    /// The items that we picked up.
+    pub collected: Vec<ItemSlot>,
+}
+impl CollectingDrops {
+    pub fn new(block: BlockPos) -> Self {
+        Self {
+            block,
+            pending: HashSet::new(),
+            collected: Vec::new(),
+        }
+    }
+}
+
+fn track_drop_spawns(
+    mut events: EventReader<EntitySpawnEvent>,
+    mut query: Query<(&mut CollectingDrops, &InstanceName)>,
+) {
+    for event in events.iter() {
+        if event.kind != azalea_registry::EntityKind::Item {
+            continue;
+        }
+        for (mut collecting, instance_name) in &mut query {
+            if **instance_name == event.instance_name
+                && collecting.block.center().distance_to(&event.position) <= DROP_SEARCH_RADIUS
+            {
+                collecting.pending.insert(event.entity);
+            }
+        }
+    }
+}
+
+fn track_drop_pickups(
+    mut events: EventReader<ItemPickupEvent>,
+    mut query: Query<&mut CollectingDrops>,
+) {
+    for event in events.iter() {
+        let Ok(mut collecting) = query.get_mut(event.entity) else {
+            continue;
+        };
+        let Some(item_entity) = event.item_entity else {
+            continue;
+        };
+        // the item entity stays pending until it despawns, since we might've only
+        // picked up part of the stack
+        if event.collector == Some(event.entity) && collecting.pending.contains(&item_entity) {
+            collecting.collected.push(event.item.clone());
+        }
+    }
+}
+
+fn track_drop_despawns(
+    mut events: EventReader<EntityDespawnEvent>,
+    mut query: Query<&mut CollectingDrops>,
+) {
+    for event in events.iter() {
+        for mut collecting in &mut query {
+            collecting.pending.remove(&event.entity);
+        }
+    }
 }
 
 /// Event to jump once.
@@ -162,11 +400,14 @@ pub struct LookAtEvent {
     pub position: Vec3,
 }
 fn look_at_listener(
+    mut commands: Commands,
     mut events: EventReader<LookAtEvent>,
     mut query: Query<(&Position, &EyeHeight, &mut LookDirection)>,
 ) {
     for event in events.iter() {
         if let Ok((position, eye_height, mut look_direction)) = query.get_mut(event.entity) {
+            // snapping cancels any smooth look that was in progress
+            commands.entity(event.entity).remove::<SmoothLooking>();
             let (y_rot, x_rot) =
                 direction_looking_at(&position.up(eye_height.into()), &event.position);
             trace!(
@@ -179,15 +420,100 @@ fn look_at_listener(
     }
 }
 
-/// Return the (`y_rot`, `x_rot`) that would make a client at `current` be
-/// looking at `target`.
-fn direction_looking_at(current: &Vec3, target: &Vec3) -> (f32, f32) {
-    // borrowed from mineflayer's Bot.lookAt because i didn't want to do math
-    let delta = target - current;
-    let y_rot = (PI - f64::atan2(-delta.x, -delta.z)) * (180.0 / PI);
-    let ground_distance = f64::sqrt(delta.x * delta.x + delta.z * delta.z);
-    let x_rot = f64::atan2(delta.y, ground_distance) * -(180.0 / PI);
-    (y_rot as f32, x_rot as f32)
+/// How quickly a [`SmoothLookEvent`] turns the head.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SmoothLookSettings {
+    /// The number of ticks it should take to look at the target. It'll take
+    /// longer than this if it would mean turning faster than
+    /// `max_degrees_per_tick`.
+    pub ticks: u32,
+    /// The furthest that the head can turn in one tick, in degrees.
+    pub max_degrees_per_tick: f32,
+}
+impl Default for SmoothLookSettings {
+    fn default() -> Self {
+        Self {
+            ticks: 4,
+            max_degrees_per_tick: 40.,
+        }
+    }
+}
+
+/// Make an entity turn towards a position over multiple ticks, like a player
+/// moving their mouse. This is less obvious to anti-cheats than
+/// [`LookAtEvent`], which snaps the head instantly.
+///
+/// The direction to the target is recalculated every tick, so sending this
+/// again with a new position while it's turning is fine. If the target moves
+/// faster than `max_degrees_per_tick`, the head keeps turning at that speed
+/// until it catches up.
+#[derive(Event)]
+pub struct SmoothLookEvent {
+    pub entity: Entity,
+    /// The position we want the entity to be looking at.
+    pub position: Vec3,
+    pub settings: SmoothLookSettings,
+}
+
+/// A component that's present while an entity is turning its head because of
+/// a [`SmoothLookEvent`]. It's removed when the entity is looking at the
+/// target.
+#[derive(Component, Clone, Debug)]
+pub struct SmoothLooking {
+    pub target: Vec3,
+    /// The number of ticks we have left to reach the target. When this is 0 we
+    /// turn as fast as we're allowed to.
+    pub ticks_left: u32,
+    pub max_degrees_per_tick: f32,
+}
+
+fn smooth_look_listener(mut commands: Commands, mut events: EventReader<SmoothLookEvent>) {
+    for event in events.iter() {
+        commands.entity(event.entity).insert(SmoothLooking {
+            target: event.position,
+            ticks_left: event.settings.ticks,
+            max_degrees_per_tick: event.settings.max_degrees_per_tick,
+        });
+    }
+}
+
+fn tick_smooth_look(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &mut SmoothLooking,
+        &Position,
+        &EyeHeight,
+        &mut LookDirection,
+    )>,
+) {
+    for (entity, mut smooth_looking, position, eye_height, mut look_direction) in &mut query {
+        let (target_y_rot, target_x_rot) =
+            direction_looking_at(&position.up(eye_height.into()), &smooth_looking.target);
+        // turn whichever way around is shorter
+        let y_rot_diff = (target_y_rot - look_direction.y_rot + 180.).rem_euclid(360.) - 180.;
+        let x_rot_diff = target_x_rot - look_direction.x_rot;
+
+        // spread the rest of the turn over the ticks we have left, but scale it down
+        // if that's faster than we're allowed to turn
+        let ticks_left = smooth_looking.ticks_left.max(1) as f32;
+        let (y_rot_step, x_rot_step) = (y_rot_diff / ticks_left, x_rot_diff / ticks_left);
+        let step_degrees = (y_rot_step * y_rot_step + x_rot_step * x_rot_step).sqrt();
+        let scale = if step_degrees > smooth_looking.max_degrees_per_tick {
+            smooth_looking.max_degrees_per_tick / step_degrees
+        } else {
+            1.
+        };
+
+        look_direction.y_rot = (look_direction.y_rot + y_rot_step * scale).rem_euclid(360.);
+        look_direction.x_rot = (look_direction.x_rot + x_rot_step * scale).clamp(-90., 90.);
+
+        smooth_looking.ticks_left = smooth_looking.ticks_left.saturating_sub(1);
+        if smooth_looking.ticks_left == 0 && scale == 1. {
+            // the last step took us all the way to the target
+            commands.entity(entity).remove::<SmoothLooking>();
+        }
+    }
 }
 
 /// A [`PluginGroup`] for the plugins that add extra bot functionality to the
@@ -195,12 +521,15 @@ fn direction_looking_at(current: &Vec3, target: &Vec3) -> (f32, f32) {
 pub struct DefaultBotPlugins;
 
 impl PluginGroup for DefaultBotPlugins {
+    #[allow(deprecated)]
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
             .add(BotPlugin)
             .add(PathfinderPlugin)
             .add(ContainerPlugin)
             .add(AutoRespawnPlugin)
+            // kept so code that disables it still compiles, see its docs for what to use
+            // instead
             .add(AcceptResourcePacksPlugin)
     }
 }
@@ -30,6 +30,10 @@ impl ContainerClientExt for Client {
     /// Open a container in the world, like a chest. Use
     /// [`Client::open_inventory`] to open your own inventory.
     ///
+    /// This returns `None` if the server doesn't open the container within
+    /// [`OPEN_CONTAINER_TIMEOUT_TICKS`] ticks, which can happen if the block
+    /// isn't a container or it's too far away.
+    ///
     /// ```
     /// # use azalea::prelude::*;
     /// # async fn example(mut bot: azalea::Client) {
@@ -52,11 +56,19 @@ impl ContainerClientExt for Client {
         self.block_interact(pos);
 
         let mut receiver = self.get_tick_broadcaster();
+        let mut ticks = 0;
         while receiver.recv().await.is_ok() {
-            let ecs = self.ecs.lock();
+            let mut ecs = self.ecs.lock();
             if ecs.get::<WaitingForInventoryOpen>(self.entity).is_none() {
                 break;
             }
+
+            ticks += 1;
+            if ticks >= OPEN_CONTAINER_TIMEOUT_TICKS {
+                ecs.entity_mut(self.entity)
+                    .remove::<WaitingForInventoryOpen>();
+                return None;
+            }
         }
 
         let ecs = self.ecs.lock();
@@ -96,6 +108,10 @@ impl ContainerClientExt for Client {
     }
 }
 
+/// How many ticks [`ContainerClientExt::open_container`] waits for the server
+/// to open the container before giving up.
+pub const OPEN_CONTAINER_TIMEOUT_TICKS: usize = 20 * 5;
+
 /// A handle to the open container. The container will be closed once this is
 /// dropped.
 pub struct ContainerHandle {
@@ -153,6 +169,11 @@ impl ContainerHandle {
         self.menu().map(|menu| menu.contents())
     }
 
+    /// Close the container. This is the same as dropping the handle.
+    pub fn close(self) {
+        drop(self);
+    }
+
     pub fn click(&self, operation: impl Into<ClickOperation>) {
         let operation = operation.into();
         self.client.ecs.lock().send_event(ContainerClickEvent {
@@ -163,6 +184,9 @@ impl ContainerHandle {
     }
 }
 
+/// A component that's present while we're waiting for the server to open a
+/// container. It's removed once we get the container's contents, which the
+/// server sends right after opening it.
 #[derive(Component, Debug)]
 pub struct WaitingForInventoryOpen;
 
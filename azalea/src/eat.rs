@@ -0,0 +1,71 @@
+//! Eating food from the hotbar. See [`EatClientExt::eat`] and
+//! [`AutoEatPlugin`](crate::auto_eat::AutoEatPlugin).
+
+pub use azalea_client::eat::*;
+use azalea_client::{inventory::InventoryComponent, Client};
+
+use crate::bot::BotClientExt;
+
+/// The number of ticks we wait for the server to let us start eating before we
+/// give up.
+const START_EATING_TIMEOUT_TICKS: usize = 5;
+
+pub trait EatClientExt {
+    async fn eat(&mut self, slot: u8) -> bool;
+}
+
+impl EatClientExt for Client {
+    /// Eat the food in the given hotbar slot (0-8) and wait until we're done
+    /// eating it.
+    ///
+    /// This returns `false` if we couldn't start eating, which happens if the
+    /// item isn't food or we're not hungry. Note that it still resolves if
+    /// eating was interrupted by taking damage or moving, so check
+    /// [`Client::hunger`] if you need to know whether it worked.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # async fn example(mut bot: azalea::Client) {
+    /// if bot.hunger().food < 10 {
+    ///     bot.eat(0).await;
+    /// }
+    /// # }
+    /// ```
+    async fn eat(&mut self, slot: u8) -> bool {
+        let item = {
+            let ecs = self.ecs.lock();
+            let inventory = ecs
+                .get::<InventoryComponent>(self.entity)
+                .expect("no inventory");
+            let hotbar_slots =
+                &inventory.inventory_menu.slots()[inventory.inventory_menu.hotbar_slots_range()];
+            let Some(item_slot) = hotbar_slots.get(slot as usize) else {
+                return false;
+            };
+            item_slot.kind()
+        };
+        if food_properties(item).is_none() {
+            return false;
+        }
+
+        let mut receiver = self.get_tick_broadcaster();
+        self.start_eating(slot);
+
+        let mut started_eating = false;
+        let mut ticks = 0;
+        while receiver.recv().await.is_ok() {
+            let is_eating = self.get_component::<Eating>().is_some();
+            if is_eating {
+                started_eating = true;
+            } else if started_eating {
+                return true;
+            } else {
+                ticks += 1;
+                if ticks >= START_EATING_TIMEOUT_TICKS {
+                    return false;
+                }
+            }
+        }
+        false
+    }
+}
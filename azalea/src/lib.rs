@@ -5,13 +5,20 @@
 #![feature(type_changing_struct_update)]
 #![feature(lazy_cell)]
 
+#[deprecated = "resource packs are accepted by azalea-client now, use the `ResourcePackPolicy` resource instead"]
 pub mod accept_resource_packs;
-mod auto_respawn;
+pub mod anti_afk;
+pub mod auto_eat;
+pub mod auto_respawn;
+pub mod auto_tool;
 mod bot;
 pub mod container;
+pub mod eat;
 pub mod nearest_entity;
+pub mod no_fall;
 pub mod pathfinder;
 pub mod prelude;
+pub mod sleep;
 pub mod swarm;
 
 use app::{App, Plugins};
@@ -19,6 +26,7 @@ pub use azalea_auth as auth;
 pub use azalea_block as blocks;
 pub use azalea_brigadier as brigadier;
 pub use azalea_chat::FormattedText;
+use azalea_client::login_query::{LoginQuery, LoginQueryHandlers};
 pub use azalea_client::*;
 pub use azalea_core as core;
 // these are re-exported on this level because they're very common
@@ -83,6 +91,7 @@ where
     /// The function that's called every time a bot receives an [`Event`].
     handler: Option<BoxHandleFn<S>>,
     state: S,
+    tick_mode: TickMode,
 }
 impl ClientBuilder<NoState> {
     /// Start building a client that can join the world.
@@ -124,6 +133,7 @@ impl ClientBuilder<NoState> {
             app: App::new(),
             handler: None,
             state: NoState,
+            tick_mode: TickMode::default(),
         }
     }
 
@@ -175,6 +185,44 @@ where
         self
     }
 
+    /// Set whether game ticks happen every 50 milliseconds or only when
+    /// [`Client::step_tick`] is called. Manual ticks are mostly useful for
+    /// tests that need to be deterministic.
+    #[must_use]
+    pub fn tick_mode(mut self, tick_mode: TickMode) -> Self {
+        self.tick_mode = tick_mode;
+        self
+    }
+
+    /// Answer the login queries that the server sends on the given channel
+    /// with the handler, which is necessary for joining some modded servers
+    /// and proxies. See [`LoginQueryHandlers`].
+    ///
+    /// ```
+    /// # use azalea::{ClientBuilder, ResourceLocation};
+    /// let client_builder = ClientBuilder::new().login_query_handler(
+    ///     ResourceLocation::new("velocity:player_info"),
+    ///     |query| {
+    ///         // sign the player's info with the forwarding secret here
+    /// #       None
+    ///     },
+    /// );
+    /// ```
+    ///
+    /// [`LoginQueryHandlers`]: azalea_client::login_query::LoginQueryHandlers
+    #[must_use]
+    pub fn login_query_handler(
+        mut self,
+        identifier: ResourceLocation,
+        handler: impl Fn(&LoginQuery) -> Option<Vec<u8>> + Send + Sync + 'static,
+    ) -> Self {
+        self.app
+            .world
+            .get_resource_or_insert_with(LoginQueryHandlers::default)
+            .insert(identifier, handler);
+        self
+    }
+
     /// Build this `ClientBuilder` into an actual [`Client`] and join the given
     /// server.
     ///
@@ -193,8 +241,12 @@ where
         // An event that causes the schedule to run. This is only used internally.
         let (run_schedule_sender, run_schedule_receiver) = mpsc::unbounded_channel();
 
-        let ecs_lock =
-            start_ecs_runner(self.app, run_schedule_receiver, run_schedule_sender.clone());
+        let ecs_lock = start_ecs_runner_with_tick_mode(
+            self.app,
+            run_schedule_receiver,
+            run_schedule_sender.clone(),
+            self.tick_mode,
+        );
 
         let (bot, mut rx) = Client::start_client(
             ecs_lock,
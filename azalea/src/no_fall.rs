@@ -0,0 +1,178 @@
+use crate::app::{App, Plugin};
+use azalea_client::movement::{send_position, SpoofOnGround};
+use azalea_entity::{LocalEntity, Physics, Position};
+use azalea_physics::PhysicsSet;
+use bevy_app::{FixedUpdate, PreUpdate};
+use bevy_ecs::prelude::*;
+
+/// A plugin that avoids fall damage by telling the server that we're on the
+/// ground while we're falling.
+///
+/// **This is cheating.** It only works on servers without an anticheat, and
+/// it'll probably get you banned on servers that have one. The server still
+/// counts a little bit of fall distance between our packets, so falling very
+/// fast can deal a tiny amount of damage.
+///
+/// This isn't included in the `DefaultBotPlugins`, so you have to add it
+/// yourself:
+///
+/// ```
+/// # use azalea::{no_fall::NoFallPlugin, ClientBuilder};
+/// let builder = ClientBuilder::new().add_plugins(NoFallPlugin::default());
+/// ```
+///
+/// It can be turned off again by setting [`NoFallSettings::enabled`] to
+/// false, which makes our movement packets the same as vanilla's.
+#[derive(Clone)]
+pub struct NoFallPlugin {
+    /// How many blocks we have to fall before we start claiming to be on the
+    /// ground.
+    pub min_fall_distance: f64,
+}
+impl Default for NoFallPlugin {
+    fn default() -> Self {
+        Self {
+            min_fall_distance: 2.,
+        }
+    }
+}
+impl Plugin for NoFallPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(NoFallSettings {
+            enabled: true,
+            min_fall_distance: self.min_fall_distance,
+        })
+        .add_systems(PreUpdate, add_no_fall_state)
+        .add_systems(
+            FixedUpdate,
+            update_no_fall.after(PhysicsSet).before(send_position),
+        );
+    }
+}
+
+/// The settings for the [`NoFallPlugin`], which can be changed while the bot
+/// is running.
+#[derive(Resource, Clone, Debug)]
+pub struct NoFallSettings {
+    pub enabled: bool,
+    pub min_fall_distance: f64,
+}
+
+/// A component that keeps track of how far we've fallen, since the server
+/// only deals fall damage based on the positions that we send it.
+#[derive(Component, Default)]
+pub struct NoFallState {
+    pub fall_distance: f64,
+    last_y: Option<f64>,
+}
+
+fn add_no_fall_state(
+    mut commands: Commands,
+    query: Query<Entity, (With<LocalEntity>, Without<NoFallState>)>,
+) {
+    for entity in &query {
+        commands
+            .entity(entity)
+            .insert((NoFallState::default(), SpoofOnGround(false)));
+    }
+}
+
+fn update_no_fall(
+    settings: Res<NoFallSettings>,
+    mut query: Query<
+        (&mut NoFallState, &mut SpoofOnGround, &Physics, &Position),
+        With<LocalEntity>,
+    >,
+) {
+    for (mut state, mut spoof_on_ground, physics, position) in &mut query {
+        if physics.on_ground {
+            state.fall_distance = 0.;
+        } else if let Some(last_y) = state.last_y {
+            let y_delta = position.y - last_y;
+            if y_delta < 0. {
+                state.fall_distance -= y_delta;
+            } else {
+                // going up, like from jumping or swimming
+                state.fall_distance = 0.;
+            }
+        }
+        state.last_y = Some(position.y);
+
+        **spoof_on_ground = settings.enabled && state.fall_distance > settings.min_fall_distance;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use azalea_core::position::Vec3;
+    use azalea_entity::EntityDimensions;
+    use bevy_app::Update;
+
+    use super::*;
+
+    fn app(enabled: bool) -> (App, Entity) {
+        let mut app = App::new();
+        app.insert_resource(NoFallSettings {
+            enabled,
+            min_fall_distance: 2.,
+        })
+        .add_systems(Update, update_no_fall);
+        let entity = app
+            .world
+            .spawn((
+                LocalEntity,
+                NoFallState::default(),
+                SpoofOnGround(false),
+                Physics::new(
+                    EntityDimensions {
+                        width: 0.6,
+                        height: 1.8,
+                    },
+                    &Vec3::default(),
+                ),
+                Position::new(Vec3::new(0., 100., 0.)),
+            ))
+            .id();
+        (app, entity)
+    }
+
+    /// Move the entity to the given height and return whether we'd tell the
+    /// server that we're on the ground.
+    fn tick(app: &mut App, entity: Entity, y: f64, on_ground: bool) -> bool {
+        app.world.get_mut::<Position>(entity).unwrap().y = y;
+        app.world.get_mut::<Physics>(entity).unwrap().on_ground = on_ground;
+        app.update();
+        **app.world.get::<SpoofOnGround>(entity).unwrap()
+    }
+
+    #[test]
+    fn test_spoofs_on_ground_after_falling() {
+        let (mut app, entity) = app(true);
+        assert!(!tick(&mut app, entity, 100., true));
+        // short falls don't need to be spoofed since they don't deal damage
+        assert!(!tick(&mut app, entity, 99., false));
+        assert!(!tick(&mut app, entity, 98., false));
+        assert!(tick(&mut app, entity, 97., false));
+        assert!(tick(&mut app, entity, 90., false));
+        // landing resets the fall distance
+        assert!(!tick(&mut app, entity, 90., true));
+        assert!(!tick(&mut app, entity, 89., false));
+    }
+
+    #[test]
+    fn test_going_up_resets_fall_distance() {
+        let (mut app, entity) = app(true);
+        assert!(!tick(&mut app, entity, 100., false));
+        assert!(!tick(&mut app, entity, 98.5, false));
+        // like swimming up for a bit
+        assert!(!tick(&mut app, entity, 99., false));
+        assert!(!tick(&mut app, entity, 97.5, false));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let (mut app, entity) = app(false);
+        assert!(!tick(&mut app, entity, 100., false));
+        assert!(!tick(&mut app, entity, 90., false));
+    }
+}
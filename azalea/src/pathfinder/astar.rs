@@ -15,6 +15,10 @@ where
 {
     pub movements: Vec<Movement<P, M>>,
     pub partial: bool,
+    /// Whether the search was stopped early because it took too long or
+    /// expanded too many nodes, rather than because it ran out of nodes to
+    /// search.
+    pub timed_out: bool,
 }
 
 // used for better results when timing out
@@ -23,12 +27,42 @@ const COEFFICIENTS: [f32; 7] = [1.5, 2., 2.5, 3., 4., 5., 10.];
 
 const MIN_IMPROVEMENT: f32 = 0.01;
 
+/// Limits on how much work [`a_star`] does, which can trade how good the path
+/// is for how quickly it's found.
+#[derive(Clone, Copy, Debug)]
+pub struct SearchOpts {
+    /// How long to search before giving up and returning the best partial path.
+    pub timeout: Duration,
+    /// How much the heuristic is multiplied by. `1.` finds the best path, and
+    /// higher values find a worse path faster (this is called weighted A*).
+    /// This should never be less than `1.`.
+    pub heuristic_weight: f32,
+    /// How many nodes we can expand before giving up and returning the best
+    /// partial path, or `None` for no limit.
+    pub max_nodes: Option<usize>,
+    /// The most that a path is allowed to cost. Nodes that cost more than this
+    /// to get to are never searched, so if the goal is further away then the
+    /// path will be partial.
+    pub max_cost: Option<f32>,
+}
+impl SearchOpts {
+    /// Search for the best path with no node or cost limit.
+    pub fn with_timeout(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            heuristic_weight: 1.,
+            max_nodes: None,
+            max_cost: None,
+        }
+    }
+}
+
 pub fn a_star<P, M, HeuristicFn, SuccessorsFn, SuccessFn>(
     start: P,
     heuristic: HeuristicFn,
     successors: SuccessorsFn,
     success: SuccessFn,
-    timeout: Duration,
+    opts: SearchOpts,
 ) -> Path<P, M>
 where
     P: Eq + Hash + Copy + Debug,
@@ -54,12 +88,15 @@ where
 
     let mut best_paths: [P; 7] = [start; 7];
     let mut best_path_scores: [f32; 7] = [heuristic(start); 7];
+    let mut timed_out = false;
+    let mut expanded_nodes = 0;
 
     while let Some((current_node, _)) = open_set.pop() {
         if success(current_node) {
             return Path {
                 movements: reconstruct_path(nodes, current_node),
                 partial: false,
+                timed_out: false,
             };
         }
 
@@ -70,13 +107,19 @@ where
 
         for neighbor in successors(current_node) {
             let tentative_g_score = current_g_score + neighbor.cost;
+            if opts
+                .max_cost
+                .is_some_and(|max_cost| tentative_g_score > max_cost)
+            {
+                continue;
+            }
             let neighbor_g_score = nodes
                 .get(&neighbor.movement.target)
                 .map(|n| n.g_score)
                 .unwrap_or(f32::MAX);
             if tentative_g_score - neighbor_g_score < MIN_IMPROVEMENT {
                 let heuristic = heuristic(neighbor.movement.target);
-                let f_score = tentative_g_score + heuristic;
+                let f_score = tentative_g_score + heuristic * opts.heuristic_weight;
                 nodes.insert(
                     neighbor.movement.target,
                     Node {
@@ -99,9 +142,19 @@ where
             }
         }
 
-        if start_time.elapsed() > timeout {
+        if start_time.elapsed() > opts.timeout {
             // timeout, just return the best path we have so far
             trace!("A* couldn't find a path in time, returning best path");
+            timed_out = true;
+            break;
+        }
+        expanded_nodes += 1;
+        if opts
+            .max_nodes
+            .is_some_and(|max_nodes| expanded_nodes >= max_nodes)
+        {
+            trace!("A* expanded too many nodes, returning best path");
+            timed_out = true;
             break;
         }
     }
@@ -111,6 +164,7 @@ where
     Path {
         movements: reconstruct_path(nodes, best_path),
         partial: true,
+        timed_out,
     }
 }
 
@@ -169,6 +223,15 @@ pub struct Movement<P: Hash + Copy, M> {
     pub data: M,
 }
 
+impl<P: Hash + Copy + Debug, M: Debug> Debug for Edge<P, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Edge")
+            .field("movement", &self.movement)
+            .field("cost", &self.cost)
+            .finish()
+    }
+}
+
 impl<P: Hash + Copy + Debug, M: Debug> Debug for Movement<P, M> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Movement")
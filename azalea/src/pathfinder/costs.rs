@@ -8,12 +8,76 @@ pub const SPRINT_ONE_BLOCK_COST: f32 = 20. / 5.612;
 pub const FALL_ONE_BLOCK_COST: f32 = 0.5;
 pub const WALK_OFF_BLOCK_COST: f32 = WALK_ONE_BLOCK_COST * 0.8;
 pub const SPRINT_MULTIPLIER: f32 = SPRINT_ONE_BLOCK_COST / WALK_ONE_BLOCK_COST;
+pub const CLIMB_ONE_BLOCK_COST: f32 = 20. / 2.35;
+/// How long it takes to fly one block in a straight line without sprinting.
+pub const FLY_ONE_BLOCK_COST: f32 = 20. / 10.92;
+/// How long it takes to right-click a door, trapdoor, or fence gate and wait
+/// for the server to open it.
+pub const OPEN_DOOR_COST: f32 = 5.;
+/// How much each point of fall damage we'd take adds to the cost of a move.
+pub const FALL_DAMAGE_COST: f32 = 20.;
 
 pub static FALL_1_25_BLOCKS_COST: LazyLock<f32> = LazyLock::new(|| distance_to_ticks(1.25));
 pub static FALL_0_25_BLOCKS_COST: LazyLock<f32> = LazyLock::new(|| distance_to_ticks(0.25));
 pub static JUMP_ONE_BLOCK_COST: LazyLock<f32> =
     LazyLock::new(|| *FALL_1_25_BLOCKS_COST - *FALL_0_25_BLOCKS_COST);
 
+/// The costs that the pathfinder uses for moves, so bots can be made to prefer
+/// some moves over others. These are all in ticks, and the defaults are the
+/// constants in this module.
+///
+/// Costs are only used to choose a path, so changing them doesn't make us
+/// move any differently while we follow it.
+///
+/// ```
+/// # use azalea::pathfinder::{costs::PathfinderCosts, moves::MoveSet};
+/// // avoid jumping and dropping down when there's another way
+/// let move_set = MoveSet::default().costs(PathfinderCosts {
+///     jump_one_block: 20.,
+///     fall_one_block: 5.,
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PathfinderCosts {
+    /// Walking one block, for moves that can't sprint (like going through a
+    /// door).
+    pub walk_one_block: f32,
+    /// Sprinting one block, which is what most moves on the ground do.
+    pub sprint_one_block: f32,
+    /// Jumping up one block, which is added to the cost of walking.
+    pub jump_one_block: f32,
+    /// Falling one block.
+    pub fall_one_block: f32,
+    /// How much each point of fall damage we'd take adds to the cost.
+    pub fall_damage: f32,
+    /// Added to the cost of moving diagonally (which is already
+    /// `sprint_one_block` times the square root of 2), to break ties with
+    /// going straight.
+    pub diagonal_penalty: f32,
+    /// Climbing one block up or down a ladder or vines.
+    pub climb_one_block: f32,
+    /// Flying one block in a straight line.
+    pub fly_one_block: f32,
+    /// Opening a door, trapdoor, or fence gate.
+    pub open_door: f32,
+}
+impl Default for PathfinderCosts {
+    fn default() -> Self {
+        Self {
+            walk_one_block: WALK_ONE_BLOCK_COST,
+            sprint_one_block: SPRINT_ONE_BLOCK_COST,
+            jump_one_block: *JUMP_ONE_BLOCK_COST,
+            fall_one_block: FALL_ONE_BLOCK_COST,
+            fall_damage: FALL_DAMAGE_COST,
+            diagonal_penalty: 0.001,
+            climb_one_block: CLIMB_ONE_BLOCK_COST,
+            fly_one_block: FLY_ONE_BLOCK_COST,
+            open_door: OPEN_DOOR_COST,
+        }
+    }
+}
+
 fn velocity(ticks: usize) -> f32 {
     (0.98.powi(ticks.try_into().unwrap()) - 1.) * -3.92
 }
@@ -43,24 +43,20 @@ impl Goal for YGoal {
     }
 }
 
+/// Get within the given radius of a point. This is useful for things like
+/// following a player, where you don't need to be at an exact position.
 pub struct RadiusGoal {
-    pub pos: Vec3,
-    pub radius: f32,
+    pub center: Vec3,
+    pub radius: f64,
 }
 impl Goal for RadiusGoal {
     fn heuristic(&self, n: BlockPos) -> f32 {
-        let n = n.center();
-        let dx = (self.pos.x - n.x) as f32;
-        let dy = (self.pos.y - n.y) as f32;
-        let dz = (self.pos.z - n.z) as f32;
-        dx * dx + dy * dy + dz * dz
+        // the distance to the edge of the sphere, so it never overestimates the cost
+        let distance = n.center().distance_to(&self.center);
+        (distance - self.radius).max(0.) as f32
     }
     fn success(&self, n: BlockPos) -> bool {
-        let n = n.center();
-        let dx = (self.pos.x - n.x) as f32;
-        let dy = (self.pos.y - n.y) as f32;
-        let dz = (self.pos.z - n.z) as f32;
-        dx * dx + dy * dy + dz * dz <= self.radius * self.radius
+        n.center().distance_to_sqr(&self.center) <= self.radius * self.radius
     }
 }
 
@@ -93,3 +89,89 @@ impl<T: Goal, U: Goal> Goal for AndGoal<T, U> {
         self.0.success(n) && self.1.success(n)
     }
 }
+
+impl Goal for Box<dyn Goal + Send + Sync> {
+    fn heuristic(&self, n: BlockPos) -> f32 {
+        (**self).heuristic(n)
+    }
+    fn success(&self, n: BlockPos) -> bool {
+        (**self).success(n)
+    }
+}
+
+/// Reach any of the given goals. This is like [`OrGoal`] but for any number of
+/// goals, which is useful for things like going to the nearest of several
+/// chests.
+///
+/// Use `Box<dyn Goal + Send + Sync>` as the type if the goals aren't all the
+/// same type. If there are no goals then this can never succeed.
+///
+/// ```
+/// # use azalea::{BlockPos, pathfinder::goals::{BlockPosGoal, OrGoals}};
+/// let goal = OrGoals(vec![
+///     BlockPosGoal(BlockPos::new(0, 70, 0)),
+///     BlockPosGoal(BlockPos::new(10, 70, 0)),
+/// ]);
+/// ```
+pub struct OrGoals<T: Goal>(pub Vec<T>);
+impl<T: Goal> Goal for OrGoals<T> {
+    fn heuristic(&self, n: BlockPos) -> f32 {
+        self.0
+            .iter()
+            .map(|goal| goal.heuristic(n))
+            .fold(f32::INFINITY, f32::min)
+    }
+    fn success(&self, n: BlockPos) -> bool {
+        self.0.iter().any(|goal| goal.success(n))
+    }
+}
+
+/// Reach a position that satisfies all of the given goals. This is like
+/// [`AndGoal`] but for any number of goals.
+///
+/// Use `Box<dyn Goal + Send + Sync>` as the type if the goals aren't all the
+/// same type. If there are no goals then this succeeds immediately.
+pub struct AndGoals<T: Goal>(pub Vec<T>);
+impl<T: Goal> Goal for AndGoals<T> {
+    fn heuristic(&self, n: BlockPos) -> f32 {
+        self.0
+            .iter()
+            .map(|goal| goal.heuristic(n))
+            .fold(0., f32::max)
+    }
+    fn success(&self, n: BlockPos) -> bool {
+        self.0.iter().all(|goal| goal.success(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_or_goals() {
+        let goal = OrGoals(vec![
+            BlockPosGoal(BlockPos::new(0, 0, 0)),
+            BlockPosGoal(BlockPos::new(10, 0, 0)),
+        ]);
+        assert!(goal.success(BlockPos::new(10, 0, 0)));
+        assert!(!goal.success(BlockPos::new(5, 0, 0)));
+        // the heuristic is the distance to the closest goal
+        assert_eq!(goal.heuristic(BlockPos::new(8, 0, 0)), 4.);
+
+        assert!(!OrGoals::<BlockPosGoal>(vec![]).success(BlockPos::new(0, 0, 0)));
+    }
+
+    #[test]
+    fn test_and_goals() {
+        let goal: AndGoals<Box<dyn Goal + Send + Sync>> = AndGoals(vec![
+            Box::new(XZGoal { x: 3, z: 0 }),
+            Box::new(YGoal { y: 70 }),
+        ]);
+        assert!(goal.success(BlockPos::new(3, 70, 0)));
+        assert!(!goal.success(BlockPos::new(3, 71, 0)));
+        assert!(!goal.success(BlockPos::new(0, 70, 0)));
+        // the heuristic is the distance to the furthest goal
+        assert_eq!(goal.heuristic(BlockPos::new(0, 68, 0)), 9.);
+    }
+}
@@ -1,14 +1,15 @@
 //! A pathfinding plugin to make bots navigate the world. A lot of this code is
 //! based on [Baritone](https://github.com/cabaletta/baritone).
 
-mod astar;
+pub mod astar;
 pub mod costs;
 pub mod goals;
-mod moves;
+pub mod moves;
 pub mod simulation;
+pub mod stuck;
 
-use crate::bot::{JumpEvent, LookAtEvent};
-use crate::pathfinder::astar::a_star;
+use crate::bot::{BotClientExt, JumpEvent, LookAtEvent};
+use crate::pathfinder::astar::{a_star, SearchOpts};
 use crate::WalkDirection;
 
 use crate::app::{App, Plugin};
@@ -19,14 +20,21 @@ use crate::ecs::{
     query::{With, Without},
     system::{Commands, Query, Res},
 };
+use azalea_client::flying::{handle_set_flying_event, SetFlyingEvent};
+use azalea_client::interact::{handle_block_interact_event, BlockInteractEvent};
+use azalea_client::inventory::{InventoryComponent, SetSelectedHotbarSlotEvent};
+use azalea_client::mining::{Mining, StartMiningBlockEvent};
 use azalea_client::movement::walk_listener;
+use azalea_client::{LocalGameMode, PlayerAbilities};
 use azalea_client::{StartSprintEvent, StartWalkEvent};
-use azalea_core::position::BlockPos;
-use azalea_entity::metadata::Player;
-use azalea_entity::LocalEntity;
+use azalea_core::game_type::GameMode;
+use azalea_core::position::{BlockPos, ChunkPos};
+use azalea_entity::metadata::{Health, Player};
+use azalea_entity::{Flying, LocalEntity};
 use azalea_entity::{Physics, Position};
+use azalea_inventory::Menu;
 use azalea_physics::PhysicsSet;
-use azalea_world::{InstanceContainer, InstanceName};
+use azalea_world::{Instance, InstanceContainer, InstanceName};
 use bevy_app::{FixedUpdate, PreUpdate, Update};
 use bevy_ecs::prelude::Event;
 use bevy_ecs::query::Changed;
@@ -34,11 +42,16 @@ use bevy_ecs::schedule::IntoSystemConfigs;
 use bevy_tasks::{AsyncComputeTaskPool, Task};
 use futures_lite::future;
 use log::{debug, error, info, trace, warn};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::broadcast::error::RecvError;
 
-use self::moves::{ExecuteCtx, IsReachedCtx, SuccessorsFn};
+use self::moves::door::{handle_open_door_event, OpenDoorEvent};
+use self::moves::fly::{handle_fly_event, stop_flying_down, FlyEvent};
+use self::moves::{ExecuteCtx, IsReachedCtx, MoveSet, PathStep};
+use self::stuck::{PathStuckEvent, StuckDetector, StuckRecovery};
 
 #[derive(Clone, Default)]
 pub struct PathfinderPlugin;
@@ -46,13 +59,28 @@ impl Plugin for PathfinderPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<GotoEvent>()
             .add_event::<PathFoundEvent>()
+            .add_event::<StopPathfindingEvent>()
+            .add_event::<PathProgressEvent>()
+            .add_event::<PathStuckEvent>()
+            // these are sent by moves that break blocks
+            .add_event::<StartMiningBlockEvent>()
+            .add_event::<SetSelectedHotbarSlotEvent>()
+            // and these are sent by moves that go through doors
+            .add_event::<OpenDoorEvent>()
+            .add_event::<BlockInteractEvent>()
+            // and these are sent by moves that fly
+            .add_event::<FlyEvent>()
+            .add_event::<SetFlyingEvent>()
             .add_systems(
                 FixedUpdate,
                 // putting systems in the FixedUpdate schedule makes them run every Minecraft tick
                 // (every 50 milliseconds).
-                tick_execute_path
-                    .after(PhysicsSet)
-                    .after(azalea_client::movement::send_position),
+                (
+                    tick_execute_path
+                        .after(PhysicsSet)
+                        .after(azalea_client::movement::send_position),
+                    stop_flying_down.after(PhysicsSet),
+                ),
             )
             .add_systems(PreUpdate, add_default_pathfinder)
             .add_systems(
@@ -62,8 +90,16 @@ impl Plugin for PathfinderPlugin {
                     handle_tasks,
                     path_found_listener,
                     stop_pathfinding_on_instance_change.before(walk_listener),
+                    handle_stop_pathfinding_event.before(walk_listener),
                 )
                     .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    handle_open_door_event.before(handle_block_interact_event),
+                    handle_fly_event.before(handle_set_flying_event),
+                ),
             );
     }
 }
@@ -78,24 +114,205 @@ pub struct Pathfinder {
     pub last_reached_node: Option<BlockPos>,
     pub last_node_reached_at: Option<Instant>,
     pub goal: Option<Arc<dyn Goal + Send + Sync>>,
-    pub successors_fn: Option<SuccessorsFn>,
+    pub move_set: Option<MoveSet>,
     pub is_calculating: bool,
+    /// The goal that we most recently stopped trying to reach, and whether we
+    /// reached it.
+    pub finished_goal: Option<(Arc<dyn Goal + Send + Sync>, Result<(), PathfindError>)>,
+    /// The edge of the loaded chunks that our path ends at, if we're using
+    /// [`MoveSet::incremental`] and the goal isn't loaded yet.
+    pub frontier: Option<Frontier>,
+    /// The goals that we'll go out of our way for if they're close to our
+    /// path. Goals are removed from this once we take a detour to them.
+    pub opportunistic_goals: Vec<OpportunisticGoal>,
+    /// How many nodes we've reached since we last looked for detours to the
+    /// [`Self::opportunistic_goals`].
+    pub nodes_since_detour_check: usize,
+    /// The last node of the detour that we're taking, if we're going to an
+    /// opportunistic goal. We don't skip ahead past it, since the way back to
+    /// the main goal usually goes through the nodes that we took to get there.
+    pub detour_end: Option<BlockPos>,
+    /// How many nodes we've reached since we started going to the current
+    /// goal. See [`Self::progress`].
+    pub nodes_reached: usize,
+    /// Notices when we stop getting closer to the next node, so we can try to
+    /// get unstuck.
+    pub stuck_detector: StuckDetector,
+    /// The doors, trapdoors, and fence gates that we right-clicked and are
+    /// waiting for the server to open, and when we clicked them.
+    pub pending_door_clicks: HashMap<BlockPos, Instant>,
+}
+impl Pathfinder {
+    /// How far along the path to the current goal we are.
+    pub fn progress(&self) -> PathProgress {
+        PathProgress {
+            nodes_reached: self.nodes_reached,
+            total_nodes: self.nodes_reached + self.path.len(),
+            is_partial: self.is_path_partial || self.is_calculating,
+        }
+    }
+
+    /// Stop trying to reach the current goal (if we have one) and remember
+    /// why in [`Self::finished_goal`].
+    fn finish_goal(&mut self, result: Result<(), PathfindError>) {
+        if let Some(goal) = self.goal.take() {
+            self.finished_goal = Some((goal, result));
+        }
+        self.move_set = None;
+        self.frontier = None;
+        self.opportunistic_goals.clear();
+        self.detour_end = None;
+    }
+}
+
+/// How far along the path to its goal the pathfinder is. See
+/// [`PathfinderClientExt::path_progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathProgress {
+    /// How many nodes we've reached since we started going to the goal.
+    pub nodes_reached: usize,
+    /// The number of nodes we've reached plus the number of nodes that are
+    /// left in the path. This changes when the path is recalculated.
+    pub total_nodes: usize,
+    /// Whether we don't know the whole path to the goal yet, so
+    /// [`Self::total_nodes`] will probably go up.
+    pub is_partial: bool,
+}
+impl PathProgress {
+    /// How many nodes are left in the path that we know about.
+    pub fn nodes_left(&self) -> usize {
+        self.total_nodes - self.nodes_reached
+    }
+
+    /// How much of the path we've walked, from 0 to 1.
+    pub fn fraction(&self) -> f32 {
+        if self.total_nodes == 0 {
+            return 0.;
+        }
+        self.nodes_reached as f32 / self.total_nodes as f32
+    }
+}
+
+/// Sent every tick that the pathfinder reaches at least one new node in its
+/// path.
+#[derive(Event, Debug, Clone)]
+pub struct PathProgressEvent {
+    pub entity: Entity,
+    pub progress: PathProgress,
+}
+
+/// A goal that the pathfinder goes out of its way to reach while it's going to
+/// its main goal, as long as it's close by. See
+/// [`PathfinderClientExt::goto_with_opportunistic_goals`].
+#[derive(Clone)]
+pub struct OpportunisticGoal {
+    pub goal: Arc<dyn Goal + Send + Sync>,
+    /// The most that the path to this goal is allowed to cost. Costs are in
+    /// ticks, so walking one block costs [`costs::WALK_ONE_BLOCK_COST`].
+    ///
+    /// This keeps us from wandering far away from our main goal, since we
+    /// never take a detour that costs more than this.
+    pub max_detour_cost: f32,
+}
+impl OpportunisticGoal {
+    pub fn new(goal: impl Goal + Send + Sync + 'static, max_detour_cost: f32) -> Self {
+        Self {
+            goal: Arc::new(goal),
+            max_detour_cost,
+        }
+    }
+}
+
+/// How many nodes we walk between checks for whether any opportunistic goals
+/// are close enough to take a detour to.
+const DETOUR_CHECK_INTERVAL: usize = 8;
+/// How long we spend looking for a path to each opportunistic goal. The search
+/// is already limited by [`OpportunisticGoal::max_detour_cost`], so this
+/// should rarely be reached.
+const DETOUR_SEARCH_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// A node at the edge of the loaded chunks where a path stops because the rest
+/// of the way to the goal isn't loaded yet.
+#[derive(Debug, Clone)]
+pub struct Frontier {
+    pub pos: BlockPos,
+    /// The goal's heuristic at `pos`. While we're waiting here, new paths
+    /// that don't end closer to the goal than this are ignored, so we don't
+    /// walk back and forth when chunks unload and load again.
+    pub heuristic: f32,
+    /// The chunks near `pos` that weren't loaded. We only search again once
+    /// one of these loads.
+    pub unloaded_chunks: Vec<ChunkPos>,
+    /// When we got to the end of the path and started waiting for chunks.
+    pub reached_at: Option<Instant>,
 }
+
+/// How far (in blocks) from a node we check for unloaded chunks when deciding
+/// whether it's at the edge of the loaded world. This is a bit more than the
+/// longest jump that a move can make.
+const FRONTIER_DISTANCE: i32 = 4;
+/// How much closer to the goal a new frontier has to be before we walk to it.
+const MIN_FRONTIER_IMPROVEMENT: f32 = 1.;
+/// How long we wait at a frontier for more chunks to load before giving up.
+const FRONTIER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Get the chunks within [`FRONTIER_DISTANCE`] blocks of the position that
+/// aren't loaded.
+fn unloaded_chunks_near(world: &Instance, pos: BlockPos) -> Vec<ChunkPos> {
+    let mut unloaded_chunks = Vec::new();
+    for x in [-FRONTIER_DISTANCE, 0, FRONTIER_DISTANCE] {
+        for z in [-FRONTIER_DISTANCE, 0, FRONTIER_DISTANCE] {
+            let chunk_pos = ChunkPos::from(&BlockPos::new(pos.x + x, pos.y, pos.z + z));
+            if world.chunks.get(&chunk_pos).is_none() && !unloaded_chunks.contains(&chunk_pos) {
+                unloaded_chunks.push(chunk_pos);
+            }
+        }
+    }
+    unloaded_chunks
+}
+
+/// The reason that the pathfinder stopped before reaching its goal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum PathfindError {
+    /// There's no way to get any closer to the goal from where we are.
+    #[error("No path to the goal was found")]
+    NoPath,
+    /// The pathfinder ran out of time while searching and couldn't find any
+    /// way to get closer to the goal.
+    #[error("Timed out while calculating a path to the goal")]
+    Timeout,
+    /// We haven't made any progress along the path in a while.
+    #[error("Got stuck while moving to the goal")]
+    Stuck,
+    /// We were told to go somewhere else or to stop pathfinding.
+    #[error("Pathfinding was cancelled")]
+    Cancelled,
+}
+
 #[derive(Event)]
 pub struct GotoEvent {
     pub entity: Entity,
     pub goal: Arc<dyn Goal + Send + Sync>,
-    /// The function that's used for checking what moves are possible. Usually
-    /// `pathfinder::moves::default_move`
-    pub successors_fn: SuccessorsFn,
+    /// The moves that the pathfinder is allowed to use. Usually
+    /// `MoveSet::default()`.
+    pub move_set: MoveSet,
+    /// Goals to take detours to along the way, in order of priority. Usually
+    /// empty.
+    pub opportunistic_goals: Vec<OpportunisticGoal>,
 }
 #[derive(Event)]
 pub struct PathFoundEvent {
     pub entity: Entity,
     pub start: BlockPos,
-    pub path: Option<VecDeque<astar::Movement<BlockPos, moves::MoveData>>>,
+    pub path: Result<VecDeque<astar::Movement<BlockPos, moves::MoveData>>, PathfindError>,
     pub is_partial: bool,
-    pub successors_fn: SuccessorsFn,
+    /// Set if the path stops at the edge of the loaded chunks because we're
+    /// using [`MoveSet::incremental`].
+    pub frontier: Option<Frontier>,
+    pub move_set: MoveSet,
+    /// The opportunistic goal that this path goes to instead of the main goal,
+    /// if we're taking a detour.
+    pub detour: Option<Arc<dyn Goal + Send + Sync>>,
 }
 
 #[allow(clippy::type_complexity)]
@@ -108,46 +325,286 @@ fn add_default_pathfinder(
     }
 }
 
+/// How many ticks [`PathfinderClientExt::goto`] waits without reaching a new
+/// node in the path before it decides that we're stuck.
+const GOTO_STUCK_TICKS: u32 = 20 * 10;
+
 pub trait PathfinderClientExt {
-    fn goto(&self, goal: impl Goal + Send + Sync + 'static);
+    async fn goto(&self, goal: impl Goal + Send + Sync + 'static) -> Result<(), PathfindError>;
+    async fn goto_with_moves(
+        &self,
+        goal: impl Goal + Send + Sync + 'static,
+        move_set: MoveSet,
+    ) -> Result<(), PathfindError>;
+    fn start_goto(&self, goal: impl Goal + Send + Sync + 'static);
+    fn start_goto_with_moves(&self, goal: impl Goal + Send + Sync + 'static, move_set: MoveSet);
+    async fn goto_with_opportunistic_goals(
+        &self,
+        goal: impl Goal + Send + Sync + 'static,
+        opportunistic_goals: Vec<OpportunisticGoal>,
+        move_set: MoveSet,
+    ) -> Result<(), PathfindError>;
+    fn start_goto_with_opportunistic_goals(
+        &self,
+        goal: impl Goal + Send + Sync + 'static,
+        opportunistic_goals: Vec<OpportunisticGoal>,
+        move_set: MoveSet,
+    );
+    fn stop_pathfinding(&self);
+    fn current_path(&self) -> Vec<PathStep>;
+    fn path_progress(&self) -> Option<PathProgress>;
 }
 
 impl PathfinderClientExt for azalea_client::Client {
+    /// Pathfind to the goal and wait until we either reach it or give up.
+    ///
+    /// If you don't want to wait, use [`Self::start_goto`] instead. If the
+    /// server lets us fly, we fly there with [`MoveSet::flying`].
+    ///
     /// ```
     /// # use azalea::prelude::*;
     /// # use azalea::{BlockPos, pathfinder::goals::BlockPosGoal};
+    /// # async fn example(bot: &Client) {
+    /// if let Err(err) = bot.goto(BlockPosGoal(BlockPos::new(0, 70, 0))).await {
+    ///     println!("couldn't get there: {err}");
+    /// }
+    /// # }
+    /// ```
+    async fn goto(&self, goal: impl Goal + Send + Sync + 'static) -> Result<(), PathfindError> {
+        self.goto_with_moves(goal, default_move_set(self)).await
+    }
+
+    /// Same as [`Self::goto`], but with a custom set of moves that the
+    /// pathfinder is allowed to use.
+    async fn goto_with_moves(
+        &self,
+        goal: impl Goal + Send + Sync + 'static,
+        move_set: MoveSet,
+    ) -> Result<(), PathfindError> {
+        self.goto_with_opportunistic_goals(goal, vec![], move_set)
+            .await
+    }
+
+    /// Same as [`Self::goto_with_moves`], but we also take detours to reach
+    /// any of the opportunistic goals that are close to our path. The goals
+    /// earlier in the list have priority, and we only go to each of them once.
+    ///
+    /// This returns when we reach the main goal, whether or not we reached
+    /// the opportunistic goals.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::{BlockPos, pathfinder::{costs, goals::{BlockPosGoal, OrGoals}, moves::MoveSet, OpportunisticGoal}};
+    /// # async fn example(bot: &Client, base: BlockPos, diamond_ores: Vec<BlockPos>) {
+    /// // go to the base, but grab any diamond ore that's about 8 blocks away
+    /// let ores = OrGoals(diamond_ores.into_iter().map(BlockPosGoal).collect());
+    /// bot.goto_with_opportunistic_goals(
+    ///     BlockPosGoal(base),
+    ///     vec![OpportunisticGoal::new(ores, costs::WALK_ONE_BLOCK_COST * 8.)],
+    ///     MoveSet::default(),
+    /// )
+    /// .await;
+    /// # }
+    /// ```
+    async fn goto_with_opportunistic_goals(
+        &self,
+        goal: impl Goal + Send + Sync + 'static,
+        opportunistic_goals: Vec<OpportunisticGoal>,
+        move_set: MoveSet,
+    ) -> Result<(), PathfindError> {
+        let goal: Arc<dyn Goal + Send + Sync> = Arc::new(goal);
+        let is_our_goal = |other: &Arc<dyn Goal + Send + Sync>| Arc::ptr_eq(other, &goal);
+
+        let mut receiver = self.get_tick_broadcaster();
+        self.ecs.lock().send_event(GotoEvent {
+            entity: self.entity,
+            goal: goal.clone(),
+            move_set,
+            opportunistic_goals,
+        });
+
+        let mut last_reached_node = None;
+        let mut ticks_without_progress = 0;
+        loop {
+            if let Err(RecvError::Closed) = receiver.recv().await {
+                return Err(PathfindError::Cancelled);
+            }
+
+            let ecs = self.ecs.lock();
+            let Some(pathfinder) = ecs.get::<Pathfinder>(self.entity) else {
+                return Err(PathfindError::Cancelled);
+            };
+            if let Some((finished_goal, result)) = &pathfinder.finished_goal {
+                if is_our_goal(finished_goal) {
+                    return *result;
+                }
+            }
+
+            if pathfinder.is_calculating || !pathfinder.goal.as_ref().is_some_and(is_our_goal) {
+                // either we're waiting for a path or the goto event hasn't been handled yet
+                continue;
+            }
+            if pathfinder
+                .frontier
+                .as_ref()
+                .is_some_and(|frontier| frontier.reached_at.is_some())
+            {
+                // waiting for chunks to load, which has its own timeout
+                ticks_without_progress = 0;
+                continue;
+            }
+            if pathfinder.last_reached_node != last_reached_node {
+                last_reached_node = pathfinder.last_reached_node;
+                ticks_without_progress = 0;
+                continue;
+            }
+            ticks_without_progress += 1;
+            if ticks_without_progress >= GOTO_STUCK_TICKS {
+                drop(ecs);
+                self.stop_pathfinding();
+                return Err(PathfindError::Stuck);
+            }
+        }
+    }
+
+    /// Start pathfinding to the goal without waiting for us to get there.
+    ///
+    /// Like [`Self::goto`], this flies if the server lets us.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::{BlockPos, pathfinder::goals::BlockPosGoal};
+    /// # fn example(bot: &Client) {
+    /// bot.start_goto(BlockPosGoal(BlockPos::new(0, 70, 0)));
+    /// # }
+    /// ```
+    fn start_goto(&self, goal: impl Goal + Send + Sync + 'static) {
+        self.start_goto_with_moves(goal, default_move_set(self));
+    }
+
+    /// Same as [`Self::start_goto`], but with a custom set of moves that the
+    /// pathfinder is allowed to use.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::{BlockPos, pathfinder::{goals::BlockPosGoal, moves::{basic, MoveSet}}};
     /// # fn example(bot: &Client) {
-    /// bot.goto(BlockPosGoal(BlockPos::new(0, 70, 0)));
+    /// // don't do any parkour
+    /// bot.start_goto_with_moves(
+    ///     BlockPosGoal(BlockPos::new(0, 70, 0)),
+    ///     MoveSet::new(vec![]).with(basic::basic_move),
+    /// );
     /// # }
     /// ```
-    fn goto(&self, goal: impl Goal + Send + Sync + 'static) {
+    fn start_goto_with_moves(&self, goal: impl Goal + Send + Sync + 'static, move_set: MoveSet) {
+        self.start_goto_with_opportunistic_goals(goal, vec![], move_set);
+    }
+
+    /// Same as [`Self::goto_with_opportunistic_goals`], but without waiting
+    /// for us to get there.
+    fn start_goto_with_opportunistic_goals(
+        &self,
+        goal: impl Goal + Send + Sync + 'static,
+        opportunistic_goals: Vec<OpportunisticGoal>,
+        move_set: MoveSet,
+    ) {
         self.ecs.lock().send_event(GotoEvent {
             entity: self.entity,
             goal: Arc::new(goal),
-            successors_fn: moves::default_move,
+            move_set,
+            opportunistic_goals,
+        });
+    }
+
+    /// Stop trying to reach the current goal.
+    fn stop_pathfinding(&self) {
+        self.ecs.lock().send_event(StopPathfindingEvent {
+            entity: self.entity,
         });
     }
+
+    /// Get the moves that are left in the path we're currently following.
+    /// This is empty if we're not pathfinding.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # fn example(bot: &Client) {
+    /// for step in bot.current_path() {
+    ///     // something like "Descend 2 SW"
+    ///     println!("{step}");
+    /// }
+    /// # }
+    /// ```
+    fn current_path(&self) -> Vec<PathStep> {
+        let mut ecs = self.ecs.lock();
+        let (pathfinder, position) = self.query::<(&Pathfinder, &Position)>(&mut ecs);
+        let start = pathfinder
+            .last_reached_node
+            .unwrap_or_else(|| BlockPos::from(position));
+        moves::describe_path(start, &pathfinder.path)
+    }
+
+    /// Get how far along the path to our current goal we are, or `None` if
+    /// we're not pathfinding.
+    ///
+    /// The number of nodes that we've reached stops going up if we get stuck,
+    /// so this can be used to notice that. A [`PathProgressEvent`] is also
+    /// sent every time it goes up.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # fn example(bot: &Client) {
+    /// if let Some(progress) = bot.path_progress() {
+    ///     println!("{:.0}% of the way there", progress.fraction() * 100.);
+    /// }
+    /// # }
+    /// ```
+    fn path_progress(&self) -> Option<PathProgress> {
+        let mut ecs = self.ecs.lock();
+        let pathfinder = self.query::<&Pathfinder>(&mut ecs);
+        pathfinder.goal.as_ref()?;
+        Some(pathfinder.progress())
+    }
 }
 
 #[derive(Component)]
 pub struct ComputePath(Task<Option<PathFoundEvent>>);
 
+#[allow(clippy::type_complexity)]
 fn goto_listener(
     mut commands: Commands,
     mut events: EventReader<GotoEvent>,
-    mut query: Query<(&mut Pathfinder, &Position, &InstanceName)>,
+    mut query: Query<(
+        &mut Pathfinder,
+        &Position,
+        &InstanceName,
+        Option<&Health>,
+        Option<&InventoryComponent>,
+        Option<&LocalGameMode>,
+    )>,
     instance_container: Res<InstanceContainer>,
 ) {
     let thread_pool = AsyncComputeTaskPool::get();
 
     for event in events.iter() {
-        let (mut pathfinder, position, instance_name) = query
+        let (mut pathfinder, position, instance_name, health, inventory, game_mode) = query
             .get_mut(event.entity)
             .expect("Called goto on an entity that's not in the world");
 
+        let is_recalculation = pathfinder
+            .goal
+            .as_ref()
+            .is_some_and(|goal| Arc::ptr_eq(goal, &event.goal));
+        if !is_recalculation {
+            pathfinder.finish_goal(Err(PathfindError::Cancelled));
+            pathfinder.nodes_reached = 0;
+        }
+
         // we store the goal so it can be recalculated later if necessary
         pathfinder.goal = Some(event.goal.clone());
-        pathfinder.successors_fn = Some(event.successors_fn);
+        pathfinder.move_set = Some(event.move_set.clone());
+        pathfinder.opportunistic_goals = event.opportunistic_goals.clone();
+        pathfinder.nodes_since_detour_check = 0;
         pathfinder.is_calculating = true;
 
         let start = if pathfinder.path.is_empty() {
@@ -165,36 +622,94 @@ fn goto_listener(
             BlockPos::from(position)
         );
 
-        let successors_fn: moves::SuccessorsFn = event.successors_fn;
+        let move_set = event.move_set.clone();
 
         let world_lock = instance_container
             .get(instance_name)
             .expect("Entity tried to pathfind but the entity isn't in a valid world");
 
         let goal = event.goal.clone();
+        let opportunistic_goals = event.opportunistic_goals.clone();
         let entity = event.entity;
+        let health = pathfinder_health(health);
+        // the inventory is only cloned if we need it
+        let mining_inventory = if move_set.can_mine {
+            mining_inventory(inventory, game_mode).cloned()
+        } else {
+            None
+        };
 
         let task = thread_pool.spawn(async move {
             debug!("start: {start:?}");
 
             let successors = |pos: BlockPos| {
                 let world = world_lock.read();
-                successors_fn(&world, pos)
+                move_set.successors(
+                    &move_set.ctx(&world, health, mining_inventory.as_ref()),
+                    pos,
+                )
             };
 
+            // take a detour to the first opportunistic goal that's close enough,
+            // and we'll go back to the main goal once we get there
+            if !goal.success(start) {
+                for opportunistic_goal in &opportunistic_goals {
+                    let detour_goal = &opportunistic_goal.goal;
+                    if detour_goal.success(start) {
+                        continue;
+                    }
+                    let detour = a_star(
+                        start,
+                        |n| detour_goal.heuristic(n),
+                        successors,
+                        |n| detour_goal.success(n),
+                        SearchOpts {
+                            timeout: DETOUR_SEARCH_TIMEOUT,
+                            heuristic_weight: 1.,
+                            max_nodes: move_set.max_nodes,
+                            max_cost: Some(opportunistic_goal.max_detour_cost),
+                        },
+                    );
+                    if !detour.partial {
+                        debug!("taking a detour to an opportunistic goal");
+                        return Some(PathFoundEvent {
+                            entity,
+                            start,
+                            path: Ok(detour.movements.into_iter().collect()),
+                            // partial so we keep going to the main goal after
+                            is_partial: true,
+                            frontier: None,
+                            move_set,
+                            detour: Some(detour_goal.clone()),
+                        });
+                    }
+                }
+            }
+
             let mut attempt_number = 0;
 
             let mut path;
             let mut is_partial: bool;
+            let mut timed_out: bool;
+            let mut frontier: Option<Frontier>;
 
             'calculate: loop {
                 let start_time = std::time::Instant::now();
-                let astar::Path { movements, partial } = a_star(
+                let astar::Path {
+                    movements,
+                    partial,
+                    timed_out: search_timed_out,
+                } = a_star(
                     start,
                     |n| goal.heuristic(n),
                     successors,
                     |n| goal.success(n),
-                    Duration::from_secs(if attempt_number == 0 { 1 } else { 5 }),
+                    SearchOpts {
+                        timeout: Duration::from_secs(if attempt_number == 0 { 1 } else { 5 }),
+                        heuristic_weight: move_set.heuristic_weight,
+                        max_nodes: move_set.max_nodes,
+                        max_cost: None,
+                    },
                 );
                 let end_time = std::time::Instant::now();
                 debug!("partial: {partial:?}");
@@ -207,15 +722,44 @@ fn goto_listener(
 
                 path = movements.into_iter().collect::<VecDeque<_>>();
                 is_partial = partial;
+                timed_out = search_timed_out;
 
-                if path.is_empty() && partial {
+                frontier = None;
+                if move_set.incremental && partial {
+                    let end = path.back().map_or(start, |movement| movement.target);
+                    let unloaded_chunks = unloaded_chunks_near(&world_lock.read(), end);
+                    if !unloaded_chunks.is_empty() {
+                        debug!("path ends at the edge of the loaded chunks at {end:?}");
+                        frontier = Some(Frontier {
+                            pos: end,
+                            heuristic: goal.heuristic(end),
+                            unloaded_chunks,
+                            reached_at: None,
+                        });
+                    }
+                }
+
+                if path.is_empty() && partial && frontier.is_none() {
                     if attempt_number == 0 {
                         debug!("this path is empty, retrying with a higher timeout");
                         attempt_number += 1;
                         continue 'calculate;
                     } else {
                         debug!("this path is empty, giving up");
-                        break 'calculate;
+                        let error = if timed_out {
+                            PathfindError::Timeout
+                        } else {
+                            PathfindError::NoPath
+                        };
+                        return Some(PathFoundEvent {
+                            entity,
+                            start,
+                            path: Err(error),
+                            is_partial,
+                            frontier,
+                            move_set,
+                            detour: None,
+                        });
                     }
                 }
                 break;
@@ -224,9 +768,11 @@ fn goto_listener(
             Some(PathFoundEvent {
                 entity,
                 start,
-                path: Some(path),
+                path: Ok(path),
                 is_partial,
-                successors_fn,
+                frontier,
+                move_set,
+                detour: None,
             })
         });
 
@@ -253,34 +799,92 @@ fn handle_tasks(
 }
 
 // set the path for the target entity when we get the PathFoundEvent
+#[allow(clippy::type_complexity)]
 fn path_found_listener(
     mut events: EventReader<PathFoundEvent>,
-    mut query: Query<(&mut Pathfinder, &InstanceName)>,
+    mut query: Query<(
+        &mut Pathfinder,
+        &InstanceName,
+        Option<&Health>,
+        Option<&InventoryComponent>,
+        Option<&LocalGameMode>,
+    )>,
     instance_container: Res<InstanceContainer>,
+    mut walk_events: EventWriter<StartWalkEvent>,
 ) {
     for event in events.iter() {
-        let (mut pathfinder, instance_name) = query
+        let (mut pathfinder, instance_name, health, inventory, game_mode) = query
             .get_mut(event.entity)
             .expect("Path found for an entity that doesn't have a pathfinder");
-        if let Some(path) = &event.path {
-            if pathfinder.path.is_empty() {
+        if pathfinder.goal.is_none() {
+            // we stopped pathfinding while the path was being calculated
+            pathfinder.is_calculating = false;
+            continue;
+        }
+        if let Ok(path) = &event.path {
+            let waiting_frontier = match &pathfinder.frontier {
+                Some(frontier) if pathfinder.path.is_empty() => Some(frontier),
+                _ => None,
+            };
+            if let Some(waiting_frontier) = waiting_frontier {
+                let is_improvement = match &event.frontier {
+                    Some(frontier) => {
+                        frontier.heuristic < waiting_frontier.heuristic - MIN_FRONTIER_IMPROVEMENT
+                    }
+                    // the path doesn't stop at the edge of the loaded chunks, so take it
+                    None => true,
+                };
+                if !is_improvement {
+                    // the unloaded chunks were updated when the search started, so if any of
+                    // them loaded while we were searching we'll search again
+                    debug!("new path doesn't get us closer to the goal, waiting for more chunks");
+                    pathfinder.is_calculating = false;
+                    continue;
+                }
+            }
+            pathfinder.frontier = event.frontier.clone();
+            if let Some(detour) = &event.detour {
+                // we only take one detour to each goal so we can't go back and forth
+                pathfinder
+                    .opportunistic_goals
+                    .retain(|opportunistic_goal| !Arc::ptr_eq(&opportunistic_goal.goal, detour));
+                pathfinder.detour_end = path.back().map(|movement| movement.target);
+            }
+
+            if path.is_empty() && !event.is_partial && pathfinder.path.is_empty() {
+                info!("already at the goal");
+                pathfinder.finish_goal(Ok(()));
+            } else if pathfinder.path.is_empty() {
                 pathfinder.path = path.to_owned();
                 debug!("set path to {:?}", path.iter().take(10).collect::<Vec<_>>());
                 pathfinder.last_reached_node = Some(event.start);
             } else {
                 let mut new_path = VecDeque::new();
 
-                // combine the old and new paths if the first node of the new path is a
-                // successor of the last node of the old path
-                if let Some(first_node) = path.front() {
+                // if the new path starts partway through the old one (like when we're
+                // taking a detour), keep the old path up to there
+                if let Some(start_index) = pathfinder
+                    .path
+                    .iter()
+                    .position(|movement| movement.target == event.start)
+                {
+                    new_path.extend(pathfinder.path.iter().take(start_index + 1).cloned());
+                }
+                // otherwise, combine the old and new paths if the first node of the new path
+                // is a successor of the last node of the old path
+                else if let Some(first_node) = path.front() {
                     if let Some(last_node) = pathfinder.path.back() {
                         let world_lock = instance_container.get(instance_name).expect(
                             "Entity tried to pathfind but the entity isn't in a valid world",
                         );
-                        let successors_fn: moves::SuccessorsFn = event.successors_fn;
                         let successors = |pos: BlockPos| {
                             let world = world_lock.read();
-                            successors_fn(&world, pos)
+                            let ctx = event.move_set.ctx(
+                                &world,
+                                pathfinder_health(health),
+                                mining_inventory(inventory, game_mode),
+                            );
+                            event.move_set.successors(&ctx, pos)
                         };
 
                         if successors(last_node.target)
@@ -304,40 +908,116 @@ fn path_found_listener(
                 pathfinder.queued_path = Some(new_path);
             }
             pathfinder.last_node_reached_at = Some(Instant::now());
-        } else {
-            error!("No path found");
+        } else if let Err(error) = event.path {
+            error!("No path found: {error}");
             pathfinder.path.clear();
             pathfinder.queued_path = None;
+            pathfinder.finish_goal(Err(error));
+            walk_events.send(StartWalkEvent {
+                entity: event.entity,
+                direction: WalkDirection::None,
+            });
         }
         pathfinder.is_calculating = false;
         pathfinder.is_path_partial = event.is_partial;
     }
 }
 
+/// The moves that [`PathfinderClientExt::goto`] and
+/// [`PathfinderClientExt::start_goto`] use, which are [`MoveSet::flying`] if
+/// the server lets us fly and [`MoveSet::default`] otherwise.
+fn default_move_set(client: &azalea_client::Client) -> MoveSet {
+    let can_fly = client
+        .get_component::<PlayerAbilities>()
+        .is_some_and(|abilities| abilities.can_fly);
+    if can_fly {
+        MoveSet::flying()
+    } else {
+        MoveSet::default()
+    }
+}
+
+/// The health that the pathfinder should assume we have when deciding whether
+/// a fall is safe. Entities without health are treated as if they had full
+/// health.
+fn pathfinder_health(health: Option<&Health>) -> f32 {
+    health.map_or(moves::MAX_HEALTH, |health| **health)
+}
+
+/// The inventory that moves should use to decide how long breaking blocks
+/// takes, or `None` if the server wouldn't let us break blocks.
+fn mining_inventory<'a>(
+    inventory: Option<&'a InventoryComponent>,
+    game_mode: Option<&LocalGameMode>,
+) -> Option<&'a Menu> {
+    if game_mode.is_some_and(|game_mode| {
+        matches!(game_mode.current, GameMode::Adventure | GameMode::Spectator)
+    }) {
+        return None;
+    }
+    inventory.map(|inventory| &inventory.inventory_menu)
+}
+
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 fn tick_execute_path(
-    mut query: Query<(Entity, &mut Pathfinder, &Position, &Physics, &InstanceName)>,
+    mut query: Query<(
+        Entity,
+        &mut Pathfinder,
+        &Position,
+        &Physics,
+        &InstanceName,
+        Option<&Health>,
+        Option<&InventoryComponent>,
+        Option<&LocalGameMode>,
+        Option<&Mining>,
+        Option<&Flying>,
+    )>,
     mut look_at_events: EventWriter<LookAtEvent>,
     mut sprint_events: EventWriter<StartSprintEvent>,
     mut walk_events: EventWriter<StartWalkEvent>,
     mut jump_events: EventWriter<JumpEvent>,
+    mut start_mining_events: EventWriter<StartMiningBlockEvent>,
+    mut set_selected_hotbar_slot_events: EventWriter<SetSelectedHotbarSlotEvent>,
+    mut open_door_events: EventWriter<OpenDoorEvent>,
+    mut fly_events: EventWriter<FlyEvent>,
+    mut path_progress_events: EventWriter<PathProgressEvent>,
+    mut path_stuck_events: EventWriter<PathStuckEvent>,
     mut goto_events: EventWriter<GotoEvent>,
     instance_container: Res<InstanceContainer>,
 ) {
-    for (entity, mut pathfinder, position, physics, instance_name) in &mut query {
+    for (
+        entity,
+        mut pathfinder,
+        position,
+        physics,
+        instance_name,
+        health,
+        inventory,
+        game_mode,
+        mining,
+        flying,
+    ) in &mut query
+    {
         if pathfinder.goal.is_none() {
             // no goal, no pathfinding
             continue;
         }
 
-        let successors_fn: moves::SuccessorsFn = pathfinder
-            .successors_fn
-            .expect("pathfinder.successors_fn should be Some if the goal is Some");
+        let move_set = pathfinder
+            .move_set
+            .clone()
+            .expect("pathfinder.move_set should be Some if the goal is Some");
 
         let world_lock = instance_container
             .get(instance_name)
             .expect("Entity tried to pathfind but the entity isn't in a valid world");
 
-        if !pathfinder.is_calculating {
+        let mining_inventory = mining_inventory(inventory, game_mode);
+
+        // while we're executing a move, the stuck detector decides whether we're
+        // stuck, since a timeout can't tell slow moves (like climbing a long ladder
+        // or walking through cobwebs) apart from ones that aren't going anywhere
+        if !pathfinder.is_calculating && pathfinder.path.is_empty() {
             // timeout check
             if let Some(last_node_reached_at) = pathfinder.last_node_reached_at {
                 if last_node_reached_at.elapsed() > Duration::from_secs(2) {
@@ -349,17 +1029,28 @@ fn tick_execute_path(
             }
         }
 
+        let nodes_reached_before = pathfinder.nodes_reached;
         'skip: loop {
             // we check if the goal was reached *before* actually executing the movement so
             // we don't unnecessarily execute a movement when it wasn't necessary
 
-            // see if we already reached any future nodes and can skip ahead
+            // see if we already reached any future nodes and can skip ahead, but not
+            // past the end of a detour since we'd skip the detour
+            let max_skip = pathfinder
+                .detour_end
+                .and_then(|detour_end| {
+                    pathfinder
+                        .path
+                        .iter()
+                        .position(|movement| movement.target == detour_end)
+                })
+                .map_or(10, |detour_end_index| (detour_end_index + 1).min(10));
             for (i, movement) in pathfinder
                 .path
                 .clone()
                 .into_iter()
                 .enumerate()
-                .take(10)
+                .take(max_skip)
                 .rev()
             {
                 let is_reached_ctx = IsReachedCtx {
@@ -370,15 +1061,21 @@ fn tick_execute_path(
                     position: **position,
                     physics,
                 };
+                // we don't land at the end of the path if we're flying
                 let on_ground_if_last = if i == pathfinder.path.len() - 1 {
-                    physics.on_ground
+                    physics.on_ground || flying.is_some()
                 } else {
                     true
                 };
                 if (movement.data.is_reached)(is_reached_ctx) && on_ground_if_last {
+                    pathfinder.nodes_since_detour_check += i + 1;
+                    pathfinder.nodes_reached += i + 1;
                     pathfinder.path = pathfinder.path.split_off(i + 1);
                     pathfinder.last_reached_node = Some(movement.target);
                     pathfinder.last_node_reached_at = Some(Instant::now());
+                    if pathfinder.detour_end == Some(movement.target) {
+                        pathfinder.detour_end = None;
+                    }
 
                     if let Some(new_path) = pathfinder.queued_path.take() {
                         debug!(
@@ -409,8 +1106,10 @@ fn tick_execute_path(
                         if let Some(goal) = pathfinder.goal.clone() {
                             if goal.success(movement.target) {
                                 info!("goal was reached!");
-                                pathfinder.goal = None;
-                                pathfinder.successors_fn = None;
+                                pathfinder.finish_goal(Ok(()));
+                            } else if let Some(frontier) = &mut pathfinder.frontier {
+                                info!("reached the edge of the loaded chunks, waiting for more");
+                                frontier.reached_at = Some(Instant::now());
                             }
                         }
                     }
@@ -418,8 +1117,58 @@ fn tick_execute_path(
             }
             break;
         }
+        if pathfinder.nodes_reached != nodes_reached_before {
+            path_progress_events.send(PathProgressEvent {
+                entity,
+                progress: pathfinder.progress(),
+            });
+        }
+
+        {
+            // stuck check (we're executing a move but we aren't getting any closer to it)
+            let target = pathfinder.path.front().map(|movement| movement.target);
+            let recovery = match target {
+                // breaking a block that's in the way is slow on purpose
+                Some(target) if mining.is_none() => {
+                    pathfinder.stuck_detector.tick(**position, target)
+                }
+                _ => {
+                    pathfinder.stuck_detector.reset();
+                    None
+                }
+            };
+            if let (Some(recovery), Some(target)) = (recovery, target) {
+                warn!(
+                    "pathfinder is stuck going to {target:?}, trying to recover with {recovery:?}"
+                );
+                path_stuck_events.send(PathStuckEvent {
+                    entity,
+                    position: **position,
+                    target,
+                    recovery,
+                });
+                match recovery {
+                    StuckRecovery::Jump => jump_events.send(JumpEvent { entity }),
+                    // we start walking backwards below
+                    StuckRecovery::BackUp => {}
+                    StuckRecovery::Repath => {
+                        pathfinder.path.clear();
+                        pathfinder.queued_path = None;
+                        // set partial to true to make sure that the recalculation happens
+                        pathfinder.is_path_partial = true;
+                    }
+                }
+            }
+        }
 
-        if let Some(movement) = pathfinder.path.front() {
+        if pathfinder.stuck_detector.back_up_ticks_left > 0 {
+            pathfinder.stuck_detector.back_up_ticks_left -= 1;
+            walk_events.send(StartWalkEvent {
+                entity,
+                direction: WalkDirection::Backward,
+            });
+        } else if let Some(movement) = pathfinder.path.front() {
+            let world = world_lock.read();
             let ctx = ExecuteCtx {
                 entity,
                 target: movement.target,
@@ -427,11 +1176,19 @@ fn tick_execute_path(
                 start: pathfinder.last_reached_node.expect(
                     "pathfinder.last_reached_node should always be present if there's a path",
                 ),
+                next_target: pathfinder.path.get(1).map(|movement| movement.target),
                 physics,
+                world: &world,
+                braking_distance: move_set.braking_distance,
+                mining_inventory: mining_inventory.filter(|_| move_set.can_mine),
                 look_at_events: &mut look_at_events,
                 sprint_events: &mut sprint_events,
                 walk_events: &mut walk_events,
                 jump_events: &mut jump_events,
+                start_mining_events: &mut start_mining_events,
+                set_selected_hotbar_slot_events: &mut set_selected_hotbar_slot_events,
+                open_door_events: &mut open_door_events,
+                fly_events: &mut fly_events,
             };
             trace!("executing move");
             (movement.data.execute)(ctx);
@@ -439,9 +1196,10 @@ fn tick_execute_path(
 
         {
             // obstruction check (the path we're executing isn't possible anymore)
+            let health = pathfinder_health(health);
             let successors = |pos: BlockPos| {
                 let world = world_lock.read();
-                successors_fn(&world, pos)
+                move_set.successors(&move_set.ctx(&world, health, mining_inventory), pos)
             };
 
             if let Some(last_reached_node) = pathfinder.last_reached_node {
@@ -454,6 +1212,48 @@ fn tick_execute_path(
             }
         }
 
+        if pathfinder.path.is_empty() && !pathfinder.is_calculating {
+            if let Some(frontier) = &mut pathfinder.frontier {
+                let reached_at = *frontier.reached_at.get_or_insert_with(Instant::now);
+                if reached_at.elapsed() > FRONTIER_TIMEOUT {
+                    warn!("no new chunks loaded near {:?}, giving up", frontier.pos);
+                    pathfinder.finish_goal(Err(PathfindError::NoPath));
+                    continue;
+                }
+                let world = world_lock.read();
+                if !frontier
+                    .unloaded_chunks
+                    .iter()
+                    .any(|chunk_pos| world.chunks.get(chunk_pos).is_some())
+                {
+                    // don't search again until more chunks load
+                    continue;
+                }
+                // the search that we're about to start knows about the chunks that are
+                // loaded now, so only wait for the ones that aren't
+                frontier.unloaded_chunks = unloaded_chunks_near(&world, frontier.pos);
+            }
+        }
+
+        if !pathfinder.opportunistic_goals.is_empty()
+            && pathfinder.nodes_since_detour_check >= DETOUR_CHECK_INTERVAL
+            && pathfinder.path.len() > 5
+            && !pathfinder.is_calculating
+        {
+            // search again every so often so we notice opportunistic goals that we're
+            // walking past
+            if let Some(goal) = pathfinder.goal.clone() {
+                debug!("Looking for detours to opportunistic goals");
+                pathfinder.nodes_since_detour_check = 0;
+                goto_events.send(GotoEvent {
+                    entity,
+                    goal,
+                    move_set: move_set.clone(),
+                    opportunistic_goals: pathfinder.opportunistic_goals.clone(),
+                });
+            }
+        }
+
         {
             // start recalculating if the path ends soon
             if pathfinder.path.len() < 5 && !pathfinder.is_calculating && pathfinder.is_path_partial
@@ -463,7 +1263,8 @@ fn tick_execute_path(
                     goto_events.send(GotoEvent {
                         entity,
                         goal,
-                        successors_fn,
+                        move_set: move_set.clone(),
+                        opportunistic_goals: pathfinder.opportunistic_goals.clone(),
                     });
 
                     if pathfinder.path.is_empty() {
@@ -508,6 +1309,32 @@ fn stop_pathfinding_on_instance_change(
     }
 }
 
+/// Stop trying to reach the current goal. This is sent by
+/// [`PathfinderClientExt::stop_pathfinding`].
+#[derive(Event)]
+pub struct StopPathfindingEvent {
+    pub entity: Entity,
+}
+
+fn handle_stop_pathfinding_event(
+    mut events: EventReader<StopPathfindingEvent>,
+    mut query: Query<&mut Pathfinder>,
+    mut walk_events: EventWriter<StartWalkEvent>,
+) {
+    for event in events.iter() {
+        let Ok(mut pathfinder) = query.get_mut(event.entity) else {
+            continue;
+        };
+        pathfinder.finish_goal(Err(PathfindError::Cancelled));
+        pathfinder.path.clear();
+        pathfinder.queued_path = None;
+        walk_events.send(StartWalkEvent {
+            entity: event.entity,
+            direction: WalkDirection::None,
+        });
+    }
+}
+
 pub trait Goal {
     fn heuristic(&self, n: BlockPos) -> f32;
     fn success(&self, n: BlockPos) -> bool;
@@ -547,14 +1374,15 @@ mod tests {
     use std::{collections::HashSet, sync::Arc};
 
     use azalea_core::position::{BlockPos, ChunkPos, Vec3};
-    use azalea_world::{Chunk, ChunkStorage, PartialChunkStorage};
+    use azalea_world::{Chunk, ChunkStorage, InstanceContainer, InstanceName, PartialChunkStorage};
     use log::info;
 
     use super::{
+        costs,
         goals::BlockPosGoal,
-        moves,
+        moves::{self, MoveSet},
         simulation::{SimulatedPlayerBundle, Simulation},
-        GotoEvent,
+        GotoEvent, OpportunisticGoal, PathfindError, Pathfinder,
     };
 
     fn setup_simulation(
@@ -562,9 +1390,41 @@ mod tests {
         start_pos: BlockPos,
         end_pos: BlockPos,
         solid_blocks: Vec<BlockPos>,
+    ) -> Simulation {
+        setup_simulation_with_ladders(partial_chunks, start_pos, end_pos, solid_blocks, vec![])
+    }
+
+    /// Like [`setup_simulation`], but also places south-facing ladders at the
+    /// given positions.
+    fn setup_simulation_with_ladders(
+        partial_chunks: &mut PartialChunkStorage,
+        start_pos: BlockPos,
+        end_pos: BlockPos,
+        solid_blocks: Vec<BlockPos>,
+        ladders: Vec<BlockPos>,
+    ) -> Simulation {
+        let mut simulation = setup_world(partial_chunks, start_pos, solid_blocks, ladders);
+        simulation.app.world.send_event(GotoEvent {
+            entity: simulation.entity,
+            goal: Arc::new(BlockPosGoal(end_pos)),
+            move_set: MoveSet::new(vec![])
+                .with(moves::basic::basic_move)
+                .with(moves::climb::ladder_move),
+            opportunistic_goals: vec![],
+        });
+        simulation
+    }
+
+    /// Create a simulation with the given blocks without starting to
+    /// pathfind anywhere.
+    fn setup_world(
+        partial_chunks: &mut PartialChunkStorage,
+        start_pos: BlockPos,
+        solid_blocks: Vec<BlockPos>,
+        ladders: Vec<BlockPos>,
     ) -> Simulation {
         let mut chunk_positions = HashSet::new();
-        for block_pos in &solid_blocks {
+        for block_pos in solid_blocks.iter().chain(&ladders) {
             chunk_positions.insert(ChunkPos::from(block_pos));
         }
 
@@ -575,23 +1435,26 @@ mod tests {
         for block_pos in solid_blocks {
             chunks.set_block_state(&block_pos, azalea_registry::Block::Stone.into());
         }
+        for block_pos in ladders {
+            chunks.set_block_state(
+                &block_pos,
+                azalea_block::blocks::Ladder {
+                    facing: azalea_block::properties::FacingCardinal::South,
+                    waterlogged: false,
+                }
+                .into(),
+            );
+        }
         let player = SimulatedPlayerBundle::new(Vec3::new(
             start_pos.x as f64 + 0.5,
             start_pos.y as f64,
             start_pos.z as f64 + 0.5,
         ));
-        let mut simulation = Simulation::new(chunks, player);
         // simulation.app.add_plugins(bevy_log::LogPlugin {
         //     level: bevy_log::Level::TRACE,
         //     filter: "".to_string(),
         // });
-
-        simulation.app.world.send_event(GotoEvent {
-            entity: simulation.entity,
-            goal: Arc::new(BlockPosGoal(end_pos)),
-            successors_fn: moves::basic::basic_move,
-        });
-        simulation
+        Simulation::new(chunks, player)
     }
 
     #[test]
@@ -612,6 +1475,66 @@ mod tests {
         );
     }
 
+    fn finished_goal_result(simulation: &Simulation) -> Option<Result<(), PathfindError>> {
+        let pathfinder = simulation.app.world.get::<Pathfinder>(simulation.entity)?;
+        pathfinder.finished_goal.as_ref().map(|(_, result)| *result)
+    }
+
+    #[test]
+    fn test_finished_goal_is_reached() {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let mut simulation = setup_simulation(
+            &mut partial_chunks,
+            BlockPos::new(0, 71, 0),
+            BlockPos::new(0, 71, 2),
+            vec![
+                BlockPos::new(0, 70, 0),
+                BlockPos::new(0, 70, 1),
+                BlockPos::new(0, 70, 2),
+            ],
+        );
+        for _ in 0..40 {
+            simulation.tick();
+        }
+        assert_eq!(finished_goal_result(&simulation), Some(Ok(())));
+
+        let pathfinder = simulation
+            .app
+            .world
+            .get::<Pathfinder>(simulation.entity)
+            .unwrap();
+        let progress = pathfinder.progress();
+        assert_eq!(progress.nodes_reached, 2);
+        assert_eq!(progress.nodes_left(), 0);
+        assert_eq!(progress.fraction(), 1.);
+    }
+
+    #[test]
+    fn test_no_path() {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let mut simulation = setup_simulation(
+            &mut partial_chunks,
+            BlockPos::new(0, 71, 0),
+            BlockPos::new(0, 71, 10),
+            // there's nowhere to go from this block
+            vec![BlockPos::new(0, 70, 0)],
+        );
+        for _ in 0..100 {
+            simulation.tick();
+            if finished_goal_result(&simulation).is_some() {
+                break;
+            }
+        }
+        assert_eq!(
+            finished_goal_result(&simulation),
+            Some(Err(PathfindError::NoPath))
+        );
+        assert_eq!(
+            BlockPos::from(simulation.position()),
+            BlockPos::new(0, 71, 0)
+        );
+    }
+
     #[test]
     fn test_double_diagonal_with_walls() {
         let mut partial_chunks = PartialChunkStorage::default();
@@ -665,4 +1588,232 @@ mod tests {
             BlockPos::new(5, 76, 0)
         );
     }
+
+    #[test]
+    fn test_climb_ladder_up() {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let mut simulation = setup_simulation_with_ladders(
+            &mut partial_chunks,
+            BlockPos::new(0, 71, 0),
+            BlockPos::new(0, 74, -1),
+            vec![
+                BlockPos::new(0, 70, 0),
+                BlockPos::new(0, 71, -1),
+                BlockPos::new(0, 72, -1),
+                BlockPos::new(0, 73, -1),
+            ],
+            vec![
+                BlockPos::new(0, 71, 0),
+                BlockPos::new(0, 72, 0),
+                BlockPos::new(0, 73, 0),
+            ],
+        );
+        for i in 0..60 {
+            simulation.tick();
+            info!("-- tick #{i} --")
+        }
+        assert_eq!(
+            BlockPos::from(simulation.position()),
+            BlockPos::new(0, 74, -1)
+        );
+    }
+
+    #[test]
+    fn test_climb_ladder_down() {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let mut simulation = setup_simulation_with_ladders(
+            &mut partial_chunks,
+            BlockPos::new(0, 74, -1),
+            BlockPos::new(0, 71, 0),
+            vec![
+                BlockPos::new(0, 70, 0),
+                BlockPos::new(0, 71, -1),
+                BlockPos::new(0, 72, -1),
+                BlockPos::new(0, 73, -1),
+            ],
+            vec![
+                BlockPos::new(0, 71, 0),
+                BlockPos::new(0, 72, 0),
+                BlockPos::new(0, 73, 0),
+            ],
+        );
+        for i in 0..60 {
+            simulation.tick();
+            info!("-- tick #{i} --")
+        }
+        assert_eq!(
+            BlockPos::from(simulation.position()),
+            BlockPos::new(0, 71, 0)
+        );
+    }
+
+    fn setup_opportunistic_simulation(
+        partial_chunks: &mut PartialChunkStorage,
+        detour_pos: BlockPos,
+        max_detour_cost: f32,
+    ) -> Simulation {
+        // a straight line to the goal, and a branch going off to the side
+        let mut solid_blocks = (0..=6).map(|z| BlockPos::new(0, 70, z)).collect::<Vec<_>>();
+        solid_blocks.extend((1..=detour_pos.x).map(|x| BlockPos::new(x, 70, detour_pos.z)));
+        let mut simulation = setup_world(
+            partial_chunks,
+            BlockPos::new(0, 71, 0),
+            solid_blocks,
+            vec![],
+        );
+        simulation.app.world.send_event(GotoEvent {
+            entity: simulation.entity,
+            goal: Arc::new(BlockPosGoal(BlockPos::new(0, 71, 6))),
+            move_set: MoveSet::new(vec![]).with(moves::basic::basic_move),
+            opportunistic_goals: vec![OpportunisticGoal::new(
+                BlockPosGoal(detour_pos),
+                max_detour_cost,
+            )],
+        });
+        simulation
+    }
+
+    #[test]
+    fn test_opportunistic_goal_detour() {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let detour_pos = BlockPos::new(2, 71, 3);
+        let mut simulation = setup_opportunistic_simulation(
+            &mut partial_chunks,
+            detour_pos,
+            costs::WALK_ONE_BLOCK_COST * 10.,
+        );
+        let mut visited_detour = false;
+        for _ in 0..150 {
+            simulation.tick();
+            visited_detour |= BlockPos::from(simulation.position()) == detour_pos;
+        }
+        assert!(visited_detour);
+        assert_eq!(finished_goal_result(&simulation), Some(Ok(())));
+    }
+
+    #[test]
+    fn test_opportunistic_goal_too_far() {
+        let mut partial_chunks = PartialChunkStorage::default();
+        let detour_pos = BlockPos::new(2, 71, 3);
+        let mut simulation = setup_opportunistic_simulation(
+            &mut partial_chunks,
+            detour_pos,
+            costs::WALK_ONE_BLOCK_COST,
+        );
+        for _ in 0..150 {
+            simulation.tick();
+            assert_ne!(BlockPos::from(simulation.position()), detour_pos);
+        }
+        assert_eq!(finished_goal_result(&simulation), Some(Ok(())));
+    }
+
+    #[test]
+    fn test_incremental_path_resumes_at_frontier() {
+        let mut partial_chunks = PartialChunkStorage::default();
+        // only the part of the walkway that's in the first chunk is loaded
+        let mut simulation = setup_world(
+            &mut partial_chunks,
+            BlockPos::new(0, 71, 0),
+            (0..16).map(|z| BlockPos::new(0, 70, z)).collect(),
+            vec![],
+        );
+        let goal_pos = BlockPos::new(0, 71, 24);
+        simulation.app.world.send_event(GotoEvent {
+            entity: simulation.entity,
+            goal: Arc::new(BlockPosGoal(goal_pos)),
+            move_set: MoveSet::new(vec![])
+                .with(moves::basic::basic_move)
+                .incremental(),
+            opportunistic_goals: vec![],
+        });
+
+        let mut frontier_pos = None;
+        for _ in 0..200 {
+            simulation.tick();
+            let pathfinder = simulation
+                .app
+                .world
+                .get::<Pathfinder>(simulation.entity)
+                .unwrap();
+            if let Some(frontier) = &pathfinder.frontier {
+                if frontier.reached_at.is_some() && pathfinder.path.is_empty() {
+                    frontier_pos = Some(frontier.pos);
+                    break;
+                }
+            }
+        }
+        let frontier_pos = frontier_pos.expect("we should be waiting at the edge of the chunk");
+        assert_eq!(BlockPos::from(simulation.position()), frontier_pos);
+        assert_eq!(finished_goal_result(&simulation), None);
+
+        // load the rest of the walkway
+        {
+            let instance_name = simulation
+                .app
+                .world
+                .get::<InstanceName>(simulation.entity)
+                .unwrap()
+                .clone();
+            let instance = simulation
+                .app
+                .world
+                .resource::<InstanceContainer>()
+                .get(&instance_name)
+                .unwrap();
+            let mut instance = instance.write();
+            partial_chunks.set(
+                &ChunkPos::new(0, 1),
+                Some(Chunk::default()),
+                &mut instance.chunks,
+            );
+            for z in 16..=24 {
+                instance.chunks.set_block_state(
+                    &BlockPos::new(0, 70, z),
+                    azalea_registry::Block::Stone.into(),
+                );
+            }
+        }
+
+        let mut resumed_path = None;
+        for _ in 0..50 {
+            simulation.tick();
+            let pathfinder = simulation
+                .app
+                .world
+                .get::<Pathfinder>(simulation.entity)
+                .unwrap();
+            if !pathfinder.path.is_empty() {
+                resumed_path = Some(
+                    pathfinder
+                        .path
+                        .iter()
+                        .map(|movement| movement.target)
+                        .collect::<Vec<_>>(),
+                );
+                break;
+            }
+        }
+        let resumed_path = resumed_path.expect("we should find a path once the chunk loads");
+        // the new path starts where we were waiting and doesn't skip any blocks
+        let mut previous = frontier_pos;
+        for target in resumed_path {
+            assert!(
+                (target.x - previous.x).abs() <= 1
+                    && (target.z - previous.z).abs() <= 1
+                    && target.y == previous.y,
+                "the path jumps from {previous:?} to {target:?}"
+            );
+            previous = target;
+        }
+        assert_eq!(previous, goal_pos);
+
+        for _ in 0..100 {
+            simulation.tick();
+            if finished_goal_result(&simulation).is_some() {
+                break;
+            }
+        }
+        assert_eq!(finished_goal_result(&simulation), Some(Ok(())));
+        assert_eq!(BlockPos::from(simulation.position()), goal_pos);
+    }
 }
@@ -1,43 +1,46 @@
 use std::f32::consts::SQRT_2;
 
-use azalea_client::{SprintDirection, StartSprintEvent, StartWalkEvent, WalkDirection};
+use azalea_client::{
+    inventory::SetSelectedHotbarSlotEvent, mining::StartMiningBlockEvent, SprintDirection,
+    StartSprintEvent, StartWalkEvent, WalkDirection,
+};
 use azalea_core::{direction::CardinalDirection, position::BlockPos};
-use azalea_world::Instance;
 
-use crate::{
-    pathfinder::{astar, costs::*},
-    JumpEvent, LookAtEvent,
-};
+use crate::{auto_tool::best_tool_in_hotbar_for_block, pathfinder::astar, JumpEvent, LookAtEvent};
 
 use super::{
-    default_is_reached, fall_distance, is_block_passable, is_passable, is_standable, Edge,
-    ExecuteCtx, IsReachedCtx, MoveData,
+    default_is_reached, fall_damage, fall_distance, is_block_climbable, is_block_passable,
+    is_block_solid, is_fall_damage_safe, is_passable, is_standable, is_water_landing, mine_cost,
+    should_brake, Edge, ExecuteCtx, IsReachedCtx, MoveData, MoveKind, PathfinderCtx,
 };
 
-pub fn basic_move(world: &Instance, node: BlockPos) -> Vec<Edge> {
+pub fn basic_move(ctx: &PathfinderCtx, node: BlockPos) -> Vec<Edge> {
     let mut edges = Vec::new();
-    edges.extend(forward_move(world, node));
-    edges.extend(ascend_move(world, node));
-    edges.extend(descend_move(world, node));
-    edges.extend(diagonal_move(world, node));
+    edges.extend(forward_move(ctx, node));
+    edges.extend(ascend_move(ctx, node));
+    edges.extend(descend_move(ctx, node));
+    edges.extend(diagonal_move(ctx, node));
     edges
 }
 
-fn forward_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
+fn forward_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
     let mut edges = Vec::new();
     for dir in CardinalDirection::iter() {
         let offset = BlockPos::new(dir.x(), 0, dir.z());
 
         if !is_standable(&(pos + offset), world) {
+            edges.extend(mine_forward_move(ctx, pos + offset));
             continue;
         }
 
-        let cost = SPRINT_ONE_BLOCK_COST;
+        let cost = ctx.costs.sprint_one_block;
 
         edges.push(Edge {
             movement: astar::Movement {
                 target: pos + offset,
                 data: MoveData {
+                    kind: MoveKind::Forward,
                     execute: &execute_forward_move,
                     is_reached: &default_is_reached,
                 },
@@ -49,19 +52,107 @@ fn forward_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
     edges
 }
 
-fn execute_forward_move(
+fn execute_forward_move(ctx: ExecuteCtx) {
+    let brake = should_brake(&ctx);
+    let ExecuteCtx {
+        entity,
+        target,
+        look_at_events,
+        sprint_events,
+        walk_events,
+        ..
+    } = ctx;
+
+    let center = target.center();
+    look_at_events.send(LookAtEvent {
+        entity,
+        position: center,
+    });
+    if brake {
+        walk_events.send(StartWalkEvent {
+            entity,
+            direction: WalkDirection::Forward,
+        });
+    } else {
+        sprint_events.send(StartSprintEvent {
+            entity,
+            direction: SprintDirection::Forward,
+        });
+    }
+}
+
+/// Break the one or two blocks that are in the way and then walk forward. This
+/// only happens if mining is allowed.
+fn mine_forward_move(ctx: &PathfinderCtx, target: BlockPos) -> Option<Edge> {
+    ctx.mining_inventory?;
+    if !is_block_solid(&target.down(1), ctx.world) {
+        return None;
+    }
+    let cost =
+        ctx.costs.sprint_one_block + mine_cost(&target, ctx)? + mine_cost(&target.up(1), ctx)?;
+
+    Some(Edge {
+        movement: astar::Movement {
+            target,
+            data: MoveData {
+                kind: MoveKind::MineForward,
+                execute: &execute_mine_forward_move,
+                is_reached: &default_is_reached,
+            },
+        },
+        cost,
+    })
+}
+
+fn execute_mine_forward_move(
     ExecuteCtx {
         entity,
         target,
+        world,
+        mining_inventory,
         look_at_events,
         sprint_events,
+        walk_events,
+        start_mining_events,
+        set_selected_hotbar_slot_events,
         ..
     }: ExecuteCtx,
 ) {
-    let center = target.center();
+    // the top block is broken first, the same way players usually do it
+    for block_pos in [target.up(1), target] {
+        if is_block_passable(&block_pos, world) {
+            continue;
+        }
+        let Some(block) = world.chunks.get_block_state(&block_pos) else {
+            continue;
+        };
+
+        // we'd keep pushing into the block if we didn't stop
+        walk_events.send(StartWalkEvent {
+            entity,
+            direction: WalkDirection::None,
+        });
+        look_at_events.send(LookAtEvent {
+            entity,
+            position: block_pos.center(),
+        });
+        if let Some(inventory) = mining_inventory {
+            set_selected_hotbar_slot_events.send(SetSelectedHotbarSlotEvent {
+                entity,
+                slot: best_tool_in_hotbar_for_block(block, inventory).index as u8,
+            });
+        }
+        // this does nothing if we're already mining the block
+        start_mining_events.send(StartMiningBlockEvent {
+            entity,
+            position: block_pos,
+        });
+        return;
+    }
+
     look_at_events.send(LookAtEvent {
         entity,
-        position: center,
+        position: target.center(),
     });
     sprint_events.send(StartSprintEvent {
         entity,
@@ -69,8 +160,13 @@ fn execute_forward_move(
     });
 }
 
-fn ascend_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
+fn ascend_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
     let mut edges = Vec::new();
+    // getting off of ladders is handled by the climb moves
+    if is_block_climbable(&pos, world) {
+        return edges;
+    }
     for dir in CardinalDirection::iter() {
         let offset = BlockPos::new(dir.x(), 1, dir.z());
 
@@ -81,12 +177,13 @@ fn ascend_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
             continue;
         }
 
-        let cost = SPRINT_ONE_BLOCK_COST + *JUMP_ONE_BLOCK_COST;
+        let cost = ctx.costs.sprint_one_block + ctx.costs.jump_one_block;
 
         edges.push(Edge {
             movement: astar::Movement {
                 target: pos + offset,
                 data: MoveData {
+                    kind: MoveKind::Ascend,
                     execute: &execute_ascend_move,
                     is_reached: &ascend_is_reached,
                 },
@@ -151,31 +248,52 @@ pub fn ascend_is_reached(
     BlockPos::from(position) == target || BlockPos::from(position) == target.down(1)
 }
 
-fn descend_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
+fn descend_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
     let mut edges = Vec::new();
     for dir in CardinalDirection::iter() {
         let new_horizontal_position = pos + BlockPos::new(dir.x(), 0, dir.z());
         let fall_distance = fall_distance(&new_horizontal_position, world);
-        if fall_distance == 0 || fall_distance > 3 {
+        if fall_distance == 0 || fall_distance == u32::MAX {
+            continue;
+        }
+        let mut new_position = new_horizontal_position.down(fall_distance as i32);
+        // getting onto ladders is handled by the climb moves
+        if is_block_climbable(&new_position, world) {
             continue;
         }
-        let new_position = new_horizontal_position.down(fall_distance as i32);
 
         // check whether 3 blocks vertically forward are passable
-        if !is_passable(&new_horizontal_position, world) {
+        if !is_passable(&new_horizontal_position, world) || ctx.is_avoided(new_horizontal_position)
+        {
             continue;
         }
-        // check whether we can stand on the target position
+        // check whether we can stand on the target position, or if not then whether
+        // we'll land in water
+        let mut fall_distance = fall_distance;
         if !is_standable(&new_position, world) {
+            let water_position = new_position.down(1);
+            if !is_water_landing(&water_position, world) {
+                continue;
+            }
+            new_position = water_position;
+            fall_distance += 1;
+        }
+
+        let damage = fall_damage(fall_distance, &new_position, world);
+        if !is_fall_damage_safe(damage, ctx.health) {
             continue;
         }
 
-        let cost = SPRINT_ONE_BLOCK_COST + FALL_ONE_BLOCK_COST * fall_distance as f32;
+        let cost = ctx.costs.sprint_one_block
+            + ctx.costs.fall_one_block * fall_distance as f32
+            + ctx.costs.fall_damage * damage;
 
         edges.push(Edge {
             movement: astar::Movement {
                 target: new_position,
                 data: MoveData {
+                    kind: MoveKind::Descend,
                     execute: &execute_descend_move,
                     is_reached: &descend_is_reached,
                 },
@@ -250,31 +368,33 @@ pub fn descend_is_reached(
         && (position.y - target.y as f64) < 0.5
 }
 
-fn diagonal_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
+fn diagonal_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
     let mut edges = Vec::new();
     for dir in CardinalDirection::iter() {
         let right = dir.right();
         let offset = BlockPos::new(dir.x() + right.x(), 0, dir.z() + right.z());
+        let left_side = BlockPos::new(pos.x + dir.x(), pos.y, pos.z + dir.z());
+        let right_side = BlockPos::new(pos.x + right.x(), pos.y, pos.z + right.z());
 
-        if !is_passable(
-            &BlockPos::new(pos.x + dir.x(), pos.y, pos.z + dir.z()),
-            world,
-        ) && !is_passable(
-            &BlockPos::new(pos.x + dir.right().x(), pos.y, pos.z + dir.right().z()),
-            world,
-        ) {
+        if !is_passable(&left_side, world) && !is_passable(&right_side, world) {
+            continue;
+        }
+        // we might clip the corners while moving diagonally
+        if ctx.is_avoided(left_side) || ctx.is_avoided(right_side) {
             continue;
         }
         if !is_standable(&(pos + offset), world) {
             continue;
         }
         // +0.001 so it doesn't unnecessarily go diagonal sometimes
-        let cost = SPRINT_ONE_BLOCK_COST * SQRT_2 + 0.001;
+        let cost = ctx.costs.sprint_one_block * SQRT_2 + ctx.costs.diagonal_penalty;
 
         edges.push(Edge {
             movement: astar::Movement {
                 target: pos + offset,
                 data: MoveData {
+                    kind: MoveKind::Diagonal,
                     execute: &execute_diagonal_move,
                     is_reached: &default_is_reached,
                 },
@@ -284,22 +404,31 @@ fn diagonal_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
     }
     edges
 }
-fn execute_diagonal_move(
-    ExecuteCtx {
+fn execute_diagonal_move(ctx: ExecuteCtx) {
+    let brake = should_brake(&ctx);
+    let ExecuteCtx {
         entity,
         target,
         look_at_events,
         sprint_events,
+        walk_events,
         ..
-    }: ExecuteCtx,
-) {
+    } = ctx;
+
     let center = target.center();
     look_at_events.send(LookAtEvent {
         entity,
         position: center,
     });
-    sprint_events.send(StartSprintEvent {
-        entity,
-        direction: SprintDirection::Forward,
-    });
+    if brake {
+        walk_events.send(StartWalkEvent {
+            entity,
+            direction: WalkDirection::Forward,
+        });
+    } else {
+        sprint_events.send(StartSprintEvent {
+            entity,
+            direction: SprintDirection::Forward,
+        });
+    }
 }
@@ -0,0 +1,247 @@
+use azalea_client::{StartWalkEvent, WalkDirection};
+use azalea_core::{
+    direction::CardinalDirection,
+    position::{BlockPos, Vec3},
+};
+use bevy_ecs::{entity::Entity, event::EventWriter};
+
+use crate::{pathfinder::astar, JumpEvent, LookAtEvent};
+
+use super::{
+    default_is_reached, is_block_climbable, is_block_passable, is_block_solid, is_passable,
+    is_standable, Edge, ExecuteCtx, IsReachedCtx, MoveData, MoveKind, PathfinderCtx,
+};
+
+pub fn ladder_move(ctx: &PathfinderCtx, node: BlockPos) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    edges.extend(climb_up_move(ctx, node));
+    edges.extend(climb_down_move(ctx, node));
+    edges.extend(climb_on_move(ctx, node));
+    edges.extend(climb_off_move(ctx, node));
+    edges
+}
+
+fn climb_up_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
+    let mut edges = Vec::new();
+
+    let target = pos.up(1);
+    // we have to be holding onto the ladder the whole way up
+    if !is_block_climbable(&pos, world) || !is_block_climbable(&target, world) {
+        return edges;
+    }
+    if !is_block_passable(&target.up(1), world) {
+        return edges;
+    }
+
+    edges.push(Edge {
+        movement: astar::Movement {
+            target,
+            data: MoveData {
+                kind: MoveKind::ClimbUp,
+                execute: &execute_climb_up_move,
+                is_reached: &climb_is_reached,
+            },
+        },
+        cost: ctx.costs.climb_one_block,
+    });
+    edges
+}
+fn execute_climb_up_move(
+    ExecuteCtx {
+        entity,
+        target,
+        position,
+        look_at_events,
+        walk_events,
+        jump_events,
+        ..
+    }: ExecuteCtx,
+) {
+    stay_on_ladder(entity, target, position, look_at_events, walk_events);
+    // holding jump while we're in a ladder makes us go up, the same as walking
+    // into it does
+    jump_events.send(JumpEvent { entity });
+}
+
+fn climb_down_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
+    let mut edges = Vec::new();
+
+    let target = pos.down(1);
+    if !is_block_climbable(&target, world) {
+        return edges;
+    }
+
+    edges.push(Edge {
+        movement: astar::Movement {
+            target,
+            data: MoveData {
+                kind: MoveKind::ClimbDown,
+                execute: &execute_climb_down_move,
+                is_reached: &climb_is_reached,
+            },
+        },
+        cost: ctx.costs.climb_one_block,
+    });
+    edges
+}
+fn execute_climb_down_move(
+    ExecuteCtx {
+        entity,
+        target,
+        position,
+        look_at_events,
+        walk_events,
+        ..
+    }: ExecuteCtx,
+) {
+    // we slide down ladders on our own as long as we're not walking into them
+    stay_on_ladder(entity, target, position, look_at_events, walk_events);
+}
+
+#[must_use]
+pub fn climb_is_reached(
+    IsReachedCtx {
+        position, target, ..
+    }: IsReachedCtx,
+) -> bool {
+    BlockPos::from(position) == target
+}
+
+/// Walk towards the middle of the ladder if we're too far from it, and
+/// otherwise stop walking so we don't push ourselves off.
+fn stay_on_ladder(
+    entity: Entity,
+    target: BlockPos,
+    position: Vec3,
+    look_at_events: &mut EventWriter<LookAtEvent>,
+    walk_events: &mut EventWriter<StartWalkEvent>,
+) {
+    let center = target.center();
+    let horizontal_distance_from_center = (center - position).horizontal_distance_sqr().sqrt();
+
+    if horizontal_distance_from_center > 0.2 {
+        look_at_events.send(LookAtEvent {
+            entity,
+            position: Vec3 {
+                x: center.x,
+                y: position.y,
+                z: center.z,
+            },
+        });
+        walk_events.send(StartWalkEvent {
+            entity,
+            direction: WalkDirection::Forward,
+        });
+    } else {
+        walk_events.send(StartWalkEvent {
+            entity,
+            direction: WalkDirection::None,
+        });
+    }
+}
+
+/// Step off of a block and onto the top of a ladder that's next to it.
+fn climb_on_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
+    let mut edges = Vec::new();
+    for dir in CardinalDirection::iter() {
+        let new_horizontal_position = pos + BlockPos::new(dir.x(), 0, dir.z());
+        let target = new_horizontal_position.down(1);
+
+        if !is_block_climbable(&target, world) {
+            continue;
+        }
+        if !is_passable(&new_horizontal_position, world) {
+            continue;
+        }
+
+        edges.push(Edge {
+            movement: astar::Movement {
+                target,
+                data: MoveData {
+                    kind: MoveKind::ClimbOn,
+                    execute: &execute_climb_on_move,
+                    is_reached: &climb_is_reached,
+                },
+            },
+            cost: ctx.costs.walk_one_block + ctx.costs.climb_one_block,
+        });
+    }
+    edges
+}
+fn execute_climb_on_move(
+    ExecuteCtx {
+        entity,
+        target,
+        position,
+        look_at_events,
+        walk_events,
+        ..
+    }: ExecuteCtx,
+) {
+    // we have to be careful not to overshoot here, since there's usually
+    // nothing to land on past the ladder
+    stay_on_ladder(entity, target, position, look_at_events, walk_events);
+}
+
+/// Get off the top of a ladder onto the block next to it.
+fn climb_off_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
+    let mut edges = Vec::new();
+    if !is_block_climbable(&pos, world) {
+        return edges;
+    }
+    // make sure we don't hit our head while we're climbing
+    if !is_block_passable(&pos.up(2), world) {
+        return edges;
+    }
+
+    for dir in CardinalDirection::iter() {
+        let target = pos + BlockPos::new(dir.x(), 1, dir.z());
+
+        // moving from one ladder to another is done by the other moves
+        if !is_block_solid(&target.down(1), world) {
+            continue;
+        }
+        if !is_standable(&target, world) {
+            continue;
+        }
+
+        edges.push(Edge {
+            movement: astar::Movement {
+                target,
+                data: MoveData {
+                    kind: MoveKind::ClimbOff,
+                    execute: &execute_climb_off_move,
+                    is_reached: &default_is_reached,
+                },
+            },
+            cost: ctx.costs.climb_one_block + ctx.costs.walk_one_block,
+        });
+    }
+    edges
+}
+fn execute_climb_off_move(
+    ExecuteCtx {
+        entity,
+        target,
+        look_at_events,
+        walk_events,
+        jump_events,
+        ..
+    }: ExecuteCtx,
+) {
+    // walking into the block we're getting onto makes us climb up until we can
+    // step onto it
+    look_at_events.send(LookAtEvent {
+        entity,
+        position: target.center(),
+    });
+    walk_events.send(StartWalkEvent {
+        entity,
+        direction: WalkDirection::Forward,
+    });
+    jump_events.send(JumpEvent { entity });
+}
@@ -0,0 +1,337 @@
+//! Walking through doors, trapdoors, and fence gates, and right-clicking them
+//! to open them if they're closed.
+
+use azalea_block::{
+    blocks,
+    properties::{FacingCardinal, Half, TopBottom},
+    Block, BlockState,
+};
+use std::time::{Duration, Instant};
+
+use azalea_client::{interact::BlockInteractEvent, StartWalkEvent, WalkDirection};
+use azalea_core::{direction::CardinalDirection, position::BlockPos};
+use azalea_world::{InstanceContainer, InstanceName};
+use bevy_ecs::prelude::*;
+
+use crate::{
+    pathfinder::{astar, Pathfinder},
+    LookAtEvent,
+};
+
+use super::{
+    default_is_reached, is_block_passable, is_block_solid, Edge, ExecuteCtx, MoveData, MoveKind,
+    PathfinderCtx,
+};
+
+/// The kinds of blocks that can be opened and closed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpenableKind {
+    Door,
+    Trapdoor,
+    FenceGate,
+}
+
+/// The state of a door, trapdoor, or fence gate.
+#[derive(Clone, Copy, Debug)]
+pub struct Openable {
+    pub kind: OpenableKind,
+    pub facing: FacingCardinal,
+    pub open: bool,
+    /// Whether this is the upper half of a door, or a trapdoor that's in the
+    /// top half of its block. This is always false for fence gates.
+    pub top: bool,
+    /// Whether this can only be opened with redstone, like iron doors and
+    /// iron trapdoors.
+    pub redstone_only: bool,
+    /// The block state that we'd get by opening or closing this block.
+    pub toggled: BlockState,
+}
+
+impl Openable {
+    /// Whether we could walk through this block in the given direction if it
+    /// was open.
+    pub fn is_passable_when_open(&self, direction: CardinalDirection) -> bool {
+        let facing_x = matches!(self.facing, FacingCardinal::East | FacingCardinal::West);
+        let moving_x = direction.x() != 0;
+        match self.kind {
+            // open doors are pushed against the side of the block that's parallel
+            // to their facing direction
+            OpenableKind::Door => facing_x == moving_x,
+            // but open trapdoors are against the side that they're facing away from
+            OpenableKind::Trapdoor => facing_x != moving_x,
+            // open fence gates don't have a hitbox at all
+            OpenableKind::FenceGate => true,
+        }
+    }
+
+    /// Whether we can stand on top of this block, which is true for closed
+    /// trapdoors that are in the top half of their block.
+    pub fn is_ledge(&self) -> bool {
+        self.kind == OpenableKind::Trapdoor && self.top && !self.open
+    }
+}
+
+macro_rules! openable_from {
+    ($block:expr, $kind:expr, $redstone_only:expr, [$($ty:ident),*], |$b:ident| $top:expr) => {
+        $(
+            if let Some($b) = $block.downcast_ref::<blocks::$ty>() {
+                return Some(Openable {
+                    kind: $kind,
+                    facing: $b.facing,
+                    open: $b.open,
+                    top: $top,
+                    redstone_only: $redstone_only,
+                    toggled: BlockState::from(blocks::$ty {
+                        open: !$b.open,
+                        ..*$b
+                    }),
+                });
+            }
+        )*
+    };
+}
+
+/// Get the state of the door, trapdoor, or fence gate, or `None` if the block
+/// isn't one of those.
+pub fn openable(block: BlockState) -> Option<Openable> {
+    let block = Box::<dyn Block>::from(block);
+    openable_from!(
+        block,
+        OpenableKind::Door,
+        false,
+        [
+            OakDoor,
+            SpruceDoor,
+            BirchDoor,
+            JungleDoor,
+            AcaciaDoor,
+            CherryDoor,
+            DarkOakDoor,
+            MangroveDoor,
+            BambooDoor,
+            CrimsonDoor,
+            WarpedDoor
+        ],
+        |door| matches!(door.half, Half::Upper)
+    );
+    openable_from!(
+        block,
+        OpenableKind::Trapdoor,
+        false,
+        [
+            OakTrapdoor,
+            SpruceTrapdoor,
+            BirchTrapdoor,
+            JungleTrapdoor,
+            AcaciaTrapdoor,
+            CherryTrapdoor,
+            DarkOakTrapdoor,
+            MangroveTrapdoor,
+            BambooTrapdoor,
+            CrimsonTrapdoor,
+            WarpedTrapdoor
+        ],
+        |trapdoor| matches!(trapdoor.half, TopBottom::Top)
+    );
+    openable_from!(
+        block,
+        OpenableKind::FenceGate,
+        false,
+        [
+            OakFenceGate,
+            SpruceFenceGate,
+            BirchFenceGate,
+            JungleFenceGate,
+            AcaciaFenceGate,
+            CherryFenceGate,
+            DarkOakFenceGate,
+            MangroveFenceGate,
+            BambooFenceGate,
+            CrimsonFenceGate,
+            WarpedFenceGate
+        ],
+        |_gate| false
+    );
+    // these can only be opened with redstone, but we can still walk through them
+    // if they're already open
+    openable_from!(
+        block,
+        OpenableKind::Door,
+        true,
+        [IronDoor],
+        |door| matches!(door.half, Half::Upper)
+    );
+    openable_from!(
+        block,
+        OpenableKind::Trapdoor,
+        true,
+        [IronTrapdoor],
+        |trapdoor| matches!(trapdoor.half, TopBottom::Top)
+    );
+    None
+}
+
+/// Walk forward through a door, trapdoor, or fence gate, opening it first if
+/// it's closed.
+///
+/// Iron doors and trapdoors are only walked through if something else already
+/// opened them, since we can't open them by right-clicking.
+pub fn door_move(ctx: &PathfinderCtx, node: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
+    let mut edges = Vec::new();
+    'dir: for dir in CardinalDirection::iter() {
+        let target = node + BlockPos::new(dir.x(), 0, dir.z());
+        if !world.world_border.is_within_bounds(&target) || !is_block_solid(&target.down(1), world)
+        {
+            continue;
+        }
+
+        let mut cost = ctx.costs.walk_one_block;
+        let mut has_openable = false;
+        for block_pos in [target, target.up(1)] {
+            if is_block_passable(&block_pos, world) {
+                continue;
+            }
+            let Some(openable) = world.chunks.get_block_state(&block_pos).and_then(openable) else {
+                continue 'dir;
+            };
+            if !openable.is_passable_when_open(dir) || (openable.redstone_only && !openable.open) {
+                continue 'dir;
+            }
+            has_openable = true;
+            // both halves of a door open together, so we only have to click the bottom one
+            let is_door_top = openable.kind == OpenableKind::Door && openable.top;
+            if !openable.open && !is_door_top {
+                cost += ctx.costs.open_door;
+            }
+        }
+        if !has_openable {
+            // normal moves already handle this
+            continue;
+        }
+
+        edges.push(Edge {
+            movement: astar::Movement {
+                target,
+                data: MoveData {
+                    kind: MoveKind::Door,
+                    execute: &execute_door_move,
+                    is_reached: &default_is_reached,
+                },
+            },
+            cost,
+        });
+    }
+    edges
+}
+
+fn execute_door_move(
+    ExecuteCtx {
+        entity,
+        target,
+        world,
+        look_at_events,
+        walk_events,
+        open_door_events,
+        ..
+    }: ExecuteCtx,
+) {
+    for block_pos in [target, target.up(1)] {
+        let Some(openable) = world.chunks.get_block_state(&block_pos).and_then(openable) else {
+            continue;
+        };
+        if openable.open || openable.redstone_only {
+            continue;
+        }
+
+        // we'd keep pushing into it if we didn't stop
+        walk_events.send(StartWalkEvent {
+            entity,
+            direction: WalkDirection::None,
+        });
+        look_at_events.send(LookAtEvent {
+            entity,
+            position: block_pos.center(),
+        });
+        open_door_events.send(OpenDoorEvent {
+            entity,
+            position: block_pos,
+        });
+        return;
+    }
+
+    // doorways are narrow, so we walk instead of sprinting
+    look_at_events.send(LookAtEvent {
+        entity,
+        position: target.center(),
+    });
+    walk_events.send(StartWalkEvent {
+        entity,
+        direction: WalkDirection::Forward,
+    });
+}
+
+/// Right-click a door, trapdoor, or fence gate to open it. This is sent by the
+/// pathfinder when there's a closed one in the way.
+#[derive(Event)]
+pub struct OpenDoorEvent {
+    pub entity: Entity,
+    pub position: BlockPos,
+}
+
+/// How long we wait for the server to open a door that we right-clicked
+/// before we try clicking it again.
+pub const DOOR_CLICK_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Right-click the door unless we already did recently, so we don't click it
+/// again (and close it) while we wait for the server to tell us that it
+/// opened.
+///
+/// The clicks are tracked in [`Pathfinder::pending_door_clicks`] instead of
+/// changing the block in the instance, since other clients might be sharing
+/// it.
+pub fn handle_open_door_event(
+    mut events: EventReader<OpenDoorEvent>,
+    mut query: Query<(&InstanceName, &mut Pathfinder)>,
+    instance_container: Res<InstanceContainer>,
+    mut block_interact_events: EventWriter<BlockInteractEvent>,
+) {
+    for event in events.iter() {
+        let Ok((instance_name, mut pathfinder)) = query.get_mut(event.entity) else {
+            continue;
+        };
+        let Some(instance_lock) = instance_container.get(instance_name) else {
+            continue;
+        };
+        let instance = instance_lock.read();
+        let now = Instant::now();
+        // the server opened the doors that we're not waiting for anymore, or it didn't
+        // let us and we'll have to try again
+        pathfinder
+            .pending_door_clicks
+            .retain(|position, clicked_at| {
+                now.duration_since(*clicked_at) < DOOR_CLICK_TIMEOUT
+                    && instance
+                        .get_block_state(position)
+                        .and_then(openable)
+                        .is_some_and(|door| !door.open)
+            });
+
+        let Some(door) = instance.get_block_state(&event.position).and_then(openable) else {
+            continue;
+        };
+        if door.open || door.redstone_only {
+            continue;
+        }
+        if pathfinder.pending_door_clicks.contains_key(&event.position) {
+            // we already clicked it and the server hasn't opened it yet
+            continue;
+        }
+
+        block_interact_events.send(BlockInteractEvent {
+            entity: event.entity,
+            position: event.position,
+        });
+        pathfinder.pending_door_clicks.insert(event.position, now);
+    }
+}
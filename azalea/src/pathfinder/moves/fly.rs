@@ -0,0 +1,176 @@
+//! Flying in any direction, for bots that are in creative or spectator mode.
+//!
+//! Flying ignores gravity, so these moves don't care whether there's anything
+//! to stand on and only check that we'd fit in the blocks we move through.
+
+use azalea_client::{flying::SetFlyingEvent, PlayerAbilities, StartWalkEvent, WalkDirection};
+use azalea_core::position::{BlockPos, Vec3};
+use azalea_entity::metadata::ShiftKeyDown;
+use bevy_ecs::prelude::*;
+
+use crate::{pathfinder::astar, JumpEvent, LookAtEvent};
+
+use super::{is_passable, Edge, ExecuteCtx, IsReachedCtx, MoveData, MoveKind, PathfinderCtx};
+
+/// Fly to any of the 26 blocks around us, including straight up and down and
+/// diagonally in all three dimensions.
+pub fn fly_move(ctx: &PathfinderCtx, node: BlockPos) -> Vec<Edge> {
+    let mut edges = Vec::new();
+    for dx in -1..=1 {
+        for dy in -1..=1 {
+            for dz in -1..=1 {
+                if dx == 0 && dy == 0 && dz == 0 {
+                    continue;
+                }
+                let offset = BlockPos::new(dx, dy, dz);
+                if !can_fly_through(ctx, node, offset) {
+                    continue;
+                }
+
+                let distance = ((dx * dx + dy * dy + dz * dz) as f32).sqrt();
+                edges.push(Edge {
+                    movement: astar::Movement {
+                        target: node + offset,
+                        data: MoveData {
+                            kind: MoveKind::Fly,
+                            execute: &execute_fly_move,
+                            is_reached: &fly_is_reached,
+                        },
+                    },
+                    cost: ctx.costs.fly_one_block * distance,
+                });
+            }
+        }
+    }
+    edges
+}
+
+/// Whether we'd fit in every block that we could touch while flying from
+/// `node` to `node + offset`.
+///
+/// For diagonal moves this includes the blocks that are only offset on some of
+/// the axes, so we don't cut corners and get stuck on them.
+fn can_fly_through(ctx: &PathfinderCtx, node: BlockPos, offset: BlockPos) -> bool {
+    let axis = |d: i32| if d == 0 { vec![0] } else { vec![0, d] };
+    for x in axis(offset.x) {
+        for y in axis(offset.y) {
+            for z in axis(offset.z) {
+                if x == 0 && y == 0 && z == 0 {
+                    continue;
+                }
+                let pos = node + BlockPos::new(x, y, z);
+                if !is_passable(&pos, ctx.world) || ctx.is_avoided(pos) {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
+
+fn execute_fly_move(
+    ExecuteCtx {
+        entity,
+        target,
+        position,
+        look_at_events,
+        walk_events,
+        jump_events,
+        fly_events,
+        ..
+    }: ExecuteCtx,
+) {
+    let center = target.center();
+    let horizontal_distance_from_center = (center - position).horizontal_distance_sqr().sqrt();
+
+    if horizontal_distance_from_center > 0.2 {
+        // look straight ahead so we don't have to turn our head up and down
+        look_at_events.send(LookAtEvent {
+            entity,
+            position: Vec3 {
+                x: center.x,
+                y: position.y,
+                z: center.z,
+            },
+        });
+        walk_events.send(StartWalkEvent {
+            entity,
+            direction: WalkDirection::Forward,
+        });
+    } else {
+        walk_events.send(StartWalkEvent {
+            entity,
+            direction: WalkDirection::None,
+        });
+    }
+
+    // aim for a little above the bottom of the block so we don't clip into the
+    // one below it
+    let target_y = target.y as f64 + 0.1;
+    let down = position.y > target_y + 0.4;
+    if position.y < target_y {
+        jump_events.send(JumpEvent { entity });
+    }
+    fly_events.send(FlyEvent { entity, down });
+}
+
+/// Returns whether our feet are in the target block. Unlike the default, this
+/// doesn't care whether we're in the middle of the block, since we'd have to
+/// slow down in the air to get there.
+#[must_use]
+pub fn fly_is_reached(
+    IsReachedCtx {
+        position, target, ..
+    }: IsReachedCtx,
+) -> bool {
+    BlockPos::from(position) == target
+}
+
+/// Start flying if we aren't already, and fly down for a tick if `down` is
+/// true. Flying up is done with a [`JumpEvent`].
+#[derive(Event)]
+pub struct FlyEvent {
+    pub entity: Entity,
+    pub down: bool,
+}
+
+/// A component that's present on entities that are sneaking because a
+/// [`FlyEvent`] told them to fly down, so we know to stop sneaking after the
+/// tick.
+#[derive(Component)]
+pub struct FlyingDown;
+
+pub fn handle_fly_event(
+    mut commands: Commands,
+    mut events: EventReader<FlyEvent>,
+    mut query: Query<(&PlayerAbilities, &mut ShiftKeyDown)>,
+    mut set_flying_events: EventWriter<SetFlyingEvent>,
+) {
+    for event in events.iter() {
+        let Ok((abilities, mut shift_key_down)) = query.get_mut(event.entity) else {
+            continue;
+        };
+        if !abilities.flying {
+            set_flying_events.send(SetFlyingEvent {
+                entity: event.entity,
+                flying: true,
+            });
+        }
+        if event.down {
+            **shift_key_down = true;
+            commands.entity(event.entity).insert(FlyingDown);
+        }
+    }
+}
+
+/// Stop sneaking after the physics ran, like [`JumpEvent`]s only make us jump
+/// for one tick.
+pub fn stop_flying_down(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ShiftKeyDown), With<FlyingDown>>,
+) {
+    for (entity, mut shift_key_down) in &mut query {
+        **shift_key_down = false;
+        commands.entity(entity).remove::<FlyingDown>();
+    }
+}
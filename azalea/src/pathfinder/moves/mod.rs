@@ -1,24 +1,94 @@
 pub mod basic;
+pub mod climb;
+pub mod door;
+pub mod fly;
 pub mod parkour;
 
-use std::fmt::Debug;
+use std::{
+    fmt::{Debug, Display},
+    sync::Arc,
+};
 
-use crate::{JumpEvent, LookAtEvent};
+use crate::{auto_tool::best_tool_in_hotbar_for_block, JumpEvent, LookAtEvent};
 
-use super::astar;
-use azalea_client::{StartSprintEvent, StartWalkEvent};
+use super::{astar, costs::PathfinderCosts};
+use azalea_block::{Block, BlockState, FluidState};
+use azalea_client::{
+    inventory::SetSelectedHotbarSlotEvent, mining::StartMiningBlockEvent, StartSprintEvent,
+    StartWalkEvent,
+};
 use azalea_core::position::{BlockPos, Vec3};
+use azalea_inventory::Menu;
 use azalea_physics::collision::{self, BlockWithShape};
+use azalea_registry::tags;
 use azalea_world::Instance;
 use bevy_ecs::{entity::Entity, event::EventWriter};
 
-type Edge = astar::Edge<BlockPos, MoveData>;
+/// An edge in the pathfinder graph. The `target` of the movement is the node
+/// the bot will be at after executing it, and the `cost` is roughly how many
+/// ticks it takes (see [`costs`]).
+///
+/// [`costs`]: crate::pathfinder::costs
+pub type Edge = astar::Edge<BlockPos, MoveData>;
 
-pub type SuccessorsFn =
-    fn(&azalea_world::Instance, BlockPos) -> Vec<astar::Edge<BlockPos, MoveData>>;
+/// A function that returns every move that can be made from the given node.
+/// You can make your own and add it to a [`MoveSet`] to teach the pathfinder
+/// new moves.
+pub type SuccessorsFn = fn(&PathfinderCtx, BlockPos) -> Vec<astar::Edge<BlockPos, MoveData>>;
 
+/// A function that returns whether the pathfinder should never move through
+/// the given position. See [`MoveSet::avoid`].
+pub type AvoidFn = dyn Fn(BlockPos, &Instance) -> bool + Send + Sync;
+
+/// The world and the state of the bot that's pathfinding, which move
+/// generators use to decide which moves are possible.
+pub struct PathfinderCtx<'a> {
+    pub world: &'a Instance,
+    /// The bot's current health. This is used to avoid falls that would do too
+    /// much damage.
+    pub health: f32,
+    /// The positions that moves shouldn't go through, from
+    /// [`MoveSet::avoid`].
+    pub avoid: Option<&'a AvoidFn>,
+    /// Our inventory, which is used to estimate how long it'd take to break
+    /// blocks. This is `None` if moves shouldn't break blocks.
+    pub mining_inventory: Option<&'a Menu>,
+    /// How much each kind of move costs, from [`MoveSet::costs`].
+    pub costs: PathfinderCosts,
+}
+impl<'a> PathfinderCtx<'a> {
+    /// Make a context for a bot with full health that doesn't avoid anything.
+    pub fn new(world: &'a Instance) -> Self {
+        Self {
+            world,
+            health: MAX_HEALTH,
+            avoid: None,
+            mining_inventory: None,
+            costs: PathfinderCosts::default(),
+        }
+    }
+
+    /// Whether moves should treat this position as impassable because it
+    /// matches the [`MoveSet::avoid`] predicate.
+    pub fn is_avoided(&self, pos: BlockPos) -> bool {
+        self.avoid.is_some_and(|avoid| avoid(pos, self.world))
+    }
+}
+
+/// The health that players have when they spawn.
+pub const MAX_HEALTH: f32 = 20.;
+
+/// The functions that are used to execute a move and check whether it's done.
+///
+/// Both functions are called every tick while the move is the first one in the
+/// path. `execute` should only send events (like [`LookAtEvent`] and
+/// [`StartWalkEvent`]) and not change anything itself, and `is_reached` should
+/// return true once the bot is at the target node so the pathfinder can move
+/// on to the next one.
 #[derive(Clone)]
 pub struct MoveData {
+    /// What kind of move this is. This is only used for debugging.
+    pub kind: MoveKind,
     /// Use the context to determine what events should be sent to complete this
     /// movement.
     pub execute: &'static (dyn Fn(ExecuteCtx) + Send + Sync),
@@ -28,14 +98,126 @@ pub struct MoveData {
 impl Debug for MoveData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("MoveData")
-            // .field("move_kind", &self.move_kind)
+            .field("kind", &self.kind)
             .finish()
     }
 }
 
+/// The type of a move, so paths can be printed in a readable way.
+///
+/// If you're adding your own moves to a [`MoveSet`], use
+/// [`MoveKind::Custom`] with a name for them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MoveKind {
+    Forward,
+    Ascend,
+    Descend,
+    Diagonal,
+    Parkour,
+    HeadHitterParkour,
+    ClimbUp,
+    ClimbDown,
+    ClimbOn,
+    ClimbOff,
+    /// Break the blocks in front of us and then walk into the space.
+    MineForward,
+    /// Walk through a door, trapdoor, or fence gate, opening it first if it's
+    /// closed.
+    Door,
+    /// Fly to one of the blocks around us, which can be in any direction.
+    Fly,
+    Custom(&'static str),
+}
+impl Display for MoveKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MoveKind::Forward => "Forward",
+            MoveKind::Ascend => "Ascend",
+            MoveKind::Descend => "Descend",
+            MoveKind::Diagonal => "Diagonal",
+            MoveKind::Parkour => "Parkour",
+            MoveKind::HeadHitterParkour => "Headhitter parkour",
+            MoveKind::ClimbUp => "Climb up",
+            MoveKind::ClimbDown => "Climb down",
+            MoveKind::ClimbOn => "Climb on",
+            MoveKind::ClimbOff => "Climb off",
+            MoveKind::MineForward => "Mine forward",
+            MoveKind::Door => "Door",
+            MoveKind::Fly => "Fly",
+            MoveKind::Custom(name) => name,
+        };
+        f.write_str(name)
+    }
+}
+
+/// One move in a path, which can be displayed like `Descend 2 SW` (the kind
+/// of move, how many blocks it goes if that's more than one, and the direction
+/// it goes in).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathStep {
+    pub kind: MoveKind,
+    pub start: BlockPos,
+    pub target: BlockPos,
+}
+impl Display for PathStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let delta = self.target - self.start;
+        write!(f, "{}", self.kind)?;
+
+        // the horizontal distance for jumps and the vertical distance for everything
+        // else
+        let distance = match self.kind {
+            MoveKind::Parkour | MoveKind::HeadHitterParkour => delta.x.abs().max(delta.z.abs()),
+            _ => delta.y.abs(),
+        };
+        if distance > 1 {
+            write!(f, " {distance}")?;
+        }
+
+        let north_south = match delta.z.signum() {
+            -1 => "N",
+            1 => "S",
+            _ => "",
+        };
+        let east_west = match delta.x.signum() {
+            1 => "E",
+            -1 => "W",
+            _ => "",
+        };
+        if !north_south.is_empty() || !east_west.is_empty() {
+            write!(f, " {north_south}{east_west}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Describe every move in a path. `start` is the position the path starts
+/// from.
+pub fn describe_path<'a>(
+    start: BlockPos,
+    path: impl IntoIterator<Item = &'a astar::Movement<BlockPos, MoveData>>,
+) -> Vec<PathStep> {
+    let mut start = start;
+    path.into_iter()
+        .map(|movement| {
+            let step = PathStep {
+                kind: movement.data.kind,
+                start,
+                target: movement.target,
+            };
+            start = movement.target;
+            step
+        })
+        .collect()
+}
+
 /// whether this block is passable
-fn is_block_passable(pos: &BlockPos, world: &Instance) -> bool {
+pub fn is_block_passable(pos: &BlockPos, world: &Instance) -> bool {
     if let Some(block) = world.chunks.get_block_state(pos) {
+        // ladders have a hitbox but it's thin enough that we can stand in them
+        if is_climbable(block) {
+            return !block.waterlogged();
+        }
         if block.shape() != &collision::empty_shape() {
             return false;
         }
@@ -58,32 +240,177 @@ fn is_block_passable(pos: &BlockPos, world: &Instance) -> bool {
 }
 
 /// whether this block has a solid hitbox (i.e. we can stand on it)
-fn is_block_solid(pos: &BlockPos, world: &Instance) -> bool {
+pub fn is_block_solid(pos: &BlockPos, world: &Instance) -> bool {
     if let Some(block) = world.chunks.get_block_state(pos) {
         block.shape() == &collision::block_shape()
+            // closed trapdoors in the top half of a block are just as flat
+            || door::openable(block).is_some_and(|openable| openable.is_ledge())
     } else {
         false
     }
 }
 
-/// Whether this block and the block above are passable
-fn is_passable(pos: &BlockPos, world: &Instance) -> bool {
-    is_block_passable(pos, world) && is_block_passable(&pos.up(1), world)
+fn is_climbable(block: BlockState) -> bool {
+    let block = Box::<dyn Block>::from(block).as_registry_block();
+    // scaffolding is technically climbable but you can also stand on it, so it's
+    // not handled here
+    block != azalea_registry::Block::Scaffolding && tags::blocks::CLIMBABLE.contains(&block)
 }
 
-/// Whether we can stand in this position. Checks if the block below is solid,
-/// and that the two blocks above that are passable.
+/// Whether this block is something we can climb, like a ladder or vines.
+pub fn is_block_climbable(pos: &BlockPos, world: &Instance) -> bool {
+    if let Some(block) = world.chunks.get_block_state(pos) {
+        is_climbable(block)
+    } else {
+        false
+    }
+}
+
+/// Whether this block and the block above are passable, and inside of the
+/// world border.
+pub fn is_passable(pos: &BlockPos, world: &Instance) -> bool {
+    world.world_border.is_within_bounds(pos)
+        && is_block_passable(pos, world)
+        && is_block_passable(&pos.up(1), world)
+}
+
+/// Whether we can stand in this position. Checks if the block below is solid
+/// (or that we're holding onto a ladder), and that the two blocks above that
+/// are passable.
+pub fn is_standable(pos: &BlockPos, world: &Instance) -> bool {
+    (is_block_solid(&pos.down(1), world) || is_block_climbable(pos, world))
+        && is_passable(pos, world)
+}
+
+fn is_water(block: BlockState) -> bool {
+    block.waterlogged()
+        || Box::<dyn Block>::from(block).as_registry_block() == azalea_registry::Block::Water
+}
+
+/// Whether we can land in this position after falling and take no damage
+/// because of the water.
+///
+/// The water has to be exactly one block deep, since we don't simulate
+/// swimming yet.
+pub fn is_water_landing(pos: &BlockPos, world: &Instance) -> bool {
+    world.world_border.is_within_bounds(pos)
+        && world.chunks.get_block_state(pos).is_some_and(is_water)
+        && is_block_solid(&pos.down(1), world)
+        && is_block_passable(&pos.up(1), world)
+}
+
+fn is_falling_block(block: BlockState) -> bool {
+    let block = Box::<dyn Block>::from(block).as_registry_block();
+    matches!(
+        block,
+        azalea_registry::Block::Sand
+            | azalea_registry::Block::RedSand
+            | azalea_registry::Block::SuspiciousSand
+            | azalea_registry::Block::Gravel
+            | azalea_registry::Block::SuspiciousGravel
+            | azalea_registry::Block::DragonEgg
+            | azalea_registry::Block::PointedDripstone
+            | azalea_registry::Block::Scaffolding
+    ) || tags::blocks::CONCRETE_POWDER.contains(&block)
+        || tags::blocks::ANVIL.contains(&block)
+}
+
+/// How many ticks it'd take to break the block at this position with the best
+/// tool in our hotbar, or `None` if moves shouldn't break it. Passable blocks
+/// don't have to be broken, so they cost nothing.
+///
+/// This is always `None` if mining isn't allowed. Blocks that can't be broken,
+/// blocks that are next to fluids, and blocks that are holding up sand or
+/// gravel are never broken, so we don't flood the tunnel or get buried.
+pub fn mine_cost(pos: &BlockPos, ctx: &PathfinderCtx) -> Option<f32> {
+    let world = ctx.world;
+    if is_block_passable(pos, world) {
+        return Some(0.);
+    }
+    let inventory = ctx.mining_inventory?;
+    if !world.world_border.is_within_bounds(pos) || ctx.is_avoided(*pos) {
+        return None;
+    }
+    let block = world.chunks.get_block_state(pos)?;
+    let is_fluid = |pos: &BlockPos| {
+        world
+            .chunks
+            .get_block_state(pos)
+            .is_some_and(|block| FluidState::from(block).fluid != azalea_registry::Fluid::Empty)
+    };
+    if is_fluid(pos) {
+        return None;
+    }
+    let neighbors = [
+        pos.up(1),
+        *pos + BlockPos::new(0, 0, -1),
+        *pos + BlockPos::new(0, 0, 1),
+        *pos + BlockPos::new(1, 0, 0),
+        *pos + BlockPos::new(-1, 0, 0),
+    ];
+    if neighbors.iter().any(is_fluid) {
+        return None;
+    }
+    if world
+        .chunks
+        .get_block_state(&pos.up(1))
+        .is_some_and(is_falling_block)
+    {
+        return None;
+    }
 
-fn is_standable(pos: &BlockPos, world: &Instance) -> bool {
-    is_block_solid(&pos.down(1), world) && is_passable(pos, world)
+    let percentage_per_tick = best_tool_in_hotbar_for_block(block, inventory).percentage_per_tick;
+    if percentage_per_tick <= 0. {
+        return None;
+    }
+    // costs are in ticks, so this can be added directly
+    Some((1. / percentage_per_tick).ceil())
 }
 
-/// Get the amount of air blocks until the next solid block below this one.
-fn fall_distance(pos: &BlockPos, world: &Instance) -> u32 {
+/// How many blocks an entity can fall without taking damage.
+pub const SAFE_FALL_DISTANCE: u32 = 3;
+
+/// Predict how much damage we'd take from falling `fall_distance` blocks and
+/// landing at the given position, accounting for blocks like water and hay
+/// bales that negate or reduce fall damage.
+pub fn fall_damage(fall_distance: u32, landing: &BlockPos, world: &Instance) -> f32 {
+    if fall_distance <= SAFE_FALL_DISTANCE
+        || is_block_climbable(landing, world)
+        || world.chunks.get_block_state(landing).is_some_and(is_water)
+    {
+        return 0.;
+    }
+
+    let block_below = world
+        .chunks
+        .get_block_state(&landing.down(1))
+        .map(|block| Box::<dyn Block>::from(block).as_registry_block());
+    let multiplier = match block_below {
+        Some(azalea_registry::Block::SlimeBlock) => 0.,
+        Some(azalea_registry::Block::HayBlock | azalea_registry::Block::HoneyBlock) => 0.2,
+        Some(block) if tags::blocks::BEDS.contains(&block) => 0.5,
+        _ => 1.,
+    };
+
+    ((fall_distance - SAFE_FALL_DISTANCE) as f32 * multiplier).ceil()
+}
+
+/// Whether we'd survive taking this much fall damage with some health to spare.
+pub fn is_fall_damage_safe(damage: f32, health: f32) -> bool {
+    // leave at least one heart so we don't die from something else right after
+    damage <= 0. || health - damage >= 2.
+}
+
+/// Get the amount of air blocks until the next solid block below this one, or
+/// until we'd grab onto a ladder.
+pub fn fall_distance(pos: &BlockPos, world: &Instance) -> u32 {
     let mut distance = 0;
     let mut current_pos = pos.down(1);
     while is_block_passable(&current_pos, world) {
         distance += 1;
+        if is_block_climbable(&current_pos, world) {
+            break;
+        }
         current_pos = current_pos.down(1);
 
         if current_pos.y < world.chunks.min_y {
@@ -92,19 +419,33 @@ fn fall_distance(pos: &BlockPos, world: &Instance) -> u32 {
     }
     distance
 }
-pub struct ExecuteCtx<'w1, 'w2, 'w3, 'w4, 'a> {
+pub struct ExecuteCtx<'w1, 'w2, 'w3, 'w4, 'w5, 'w6, 'w7, 'w8, 'a> {
     pub entity: Entity,
     /// The node that we're trying to reach.
     pub target: BlockPos,
     /// The last node that we reached.
     pub start: BlockPos,
+    /// The node that comes after `target` in the path, or `None` if `target`
+    /// is the last one we know about.
+    pub next_target: Option<BlockPos>,
     pub position: Vec3,
     pub physics: &'a azalea_entity::Physics,
+    pub world: &'a Instance,
+    /// How close we have to be to the target before we stop sprinting if the
+    /// path turns or drops after it. See [`MoveSet::braking_distance`].
+    pub braking_distance: f64,
+    /// Our inventory if moves are allowed to break blocks, which is used to
+    /// pick the best tool.
+    pub mining_inventory: Option<&'a Menu>,
 
     pub look_at_events: &'a mut EventWriter<'w1, LookAtEvent>,
     pub sprint_events: &'a mut EventWriter<'w2, StartSprintEvent>,
     pub walk_events: &'a mut EventWriter<'w3, StartWalkEvent>,
     pub jump_events: &'a mut EventWriter<'w4, JumpEvent>,
+    pub start_mining_events: &'a mut EventWriter<'w5, StartMiningBlockEvent>,
+    pub set_selected_hotbar_slot_events: &'a mut EventWriter<'w6, SetSelectedHotbarSlotEvent>,
+    pub open_door_events: &'a mut EventWriter<'w7, door::OpenDoorEvent>,
+    pub fly_events: &'a mut EventWriter<'w8, fly::FlyEvent>,
 }
 pub struct IsReachedCtx<'a> {
     /// The node that we're trying to reach.
@@ -115,11 +456,291 @@ pub struct IsReachedCtx<'a> {
     pub physics: &'a azalea_entity::Physics,
 }
 
-pub fn default_move(world: &Instance, node: BlockPos) -> Vec<Edge> {
-    let mut edges = Vec::new();
-    edges.extend(basic::basic_move(world, node));
-    edges.extend(parkour::parkour_move(world, node));
-    edges
+pub fn default_move(ctx: &PathfinderCtx, node: BlockPos) -> Vec<Edge> {
+    MoveSet::default().successors(ctx, node)
+}
+
+/// A list of [`SuccessorsFn`]s whose edges are combined to get every move the
+/// pathfinder can make from a node.
+///
+/// ```
+/// # use azalea::pathfinder::moves::{Edge, MoveSet, PathfinderCtx};
+/// # use azalea::BlockPos;
+/// fn boat_move(ctx: &PathfinderCtx, node: BlockPos) -> Vec<Edge> {
+///     // ...
+/// #   vec![]
+/// }
+///
+/// let move_set = MoveSet::default().with(boat_move);
+/// ```
+#[derive(Clone)]
+pub struct MoveSet {
+    pub moves: Vec<SuccessorsFn>,
+    /// Positions that the pathfinder should never move through.
+    pub avoid: Option<Arc<AvoidFn>>,
+    /// Whether moves are allowed to break blocks that are in the way. See
+    /// [`MoveSet::allow_mining`].
+    pub can_mine: bool,
+    /// How many blocks away from the end of a straight section of the path we
+    /// switch from sprinting to walking. See [`MoveSet::braking_distance`].
+    pub braking_distance: f64,
+    /// How much the pathfinder favors nodes that look closer to the goal. See
+    /// [`MoveSet::heuristic_weight`].
+    pub heuristic_weight: f32,
+    /// The most nodes the pathfinder will look at before giving up and using
+    /// the best partial path. See [`MoveSet::max_nodes`].
+    pub max_nodes: Option<usize>,
+    /// How much each kind of move costs. See [`MoveSet::costs`].
+    pub costs: PathfinderCosts,
+    /// Whether we should walk to the edge of the loaded chunks and wait for
+    /// more to load instead of giving up. See [`MoveSet::incremental`].
+    pub incremental: bool,
+}
+impl MoveSet {
+    pub fn new(moves: Vec<SuccessorsFn>) -> Self {
+        Self {
+            moves,
+            avoid: None,
+            can_mine: false,
+            braking_distance: DEFAULT_BRAKING_DISTANCE,
+            heuristic_weight: 1.,
+            max_nodes: None,
+            incremental: false,
+            costs: PathfinderCosts::default(),
+        }
+    }
+
+    /// The moves for bots that can fly, like in creative or spectator mode.
+    ///
+    /// This only has [`fly::fly_move`], which flies in any direction and
+    /// ignores gravity. We start flying automatically when we execute one of
+    /// its moves.
+    ///
+    /// ```
+    /// # use azalea::pathfinder::moves::MoveSet;
+    /// let move_set = MoveSet::flying();
+    /// ```
+    pub fn flying() -> Self {
+        Self::new(vec![fly::fly_move])
+    }
+
+    /// Add another move generator to this set.
+    #[must_use]
+    pub fn with(mut self, successors_fn: SuccessorsFn) -> Self {
+        self.moves.push(successors_fn);
+        self
+    }
+
+    /// Make the pathfinder treat every position that matches the predicate as
+    /// impassable, in addition to the normal passability checks.
+    ///
+    /// The predicate is called several times for every node that the
+    /// pathfinder looks at, so it should be cheap.
+    ///
+    /// ```
+    /// # use azalea::pathfinder::moves::MoveSet;
+    /// // don't walk on magma blocks
+    /// let move_set = MoveSet::default().avoid(|pos, world| {
+    ///     world.chunks.get_block_state(&pos.down(1))
+    ///         == Some(azalea::registry::Block::MagmaBlock.into())
+    /// });
+    /// ```
+    #[must_use]
+    pub fn avoid(
+        mut self,
+        avoid: impl Fn(BlockPos, &Instance) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.avoid = Some(Arc::new(avoid));
+        self
+    }
+
+    /// Let the pathfinder break blocks to get to the goal, like for tunneling
+    /// or strip mining. How long it takes to break a block (with the best tool
+    /// in our hotbar) is added to the cost of the move, so existing paths are
+    /// still preferred if they're not much longer.
+    ///
+    /// Blocks are never broken in adventure or spectator mode, since the
+    /// server wouldn't let us.
+    ///
+    /// ```
+    /// # use azalea::pathfinder::moves::MoveSet;
+    /// let move_set = MoveSet::default().allow_mining();
+    /// ```
+    #[must_use]
+    pub fn allow_mining(mut self) -> Self {
+        self.can_mine = true;
+        self
+    }
+
+    /// Set how close (in blocks) we have to be to a node before we stop
+    /// sprinting, if the path turns or drops down right after it. This stops
+    /// us from overshooting corners and falling off edges.
+    ///
+    /// Higher values are safer but make the bot slower on twisty paths, and
+    /// `0.` disables braking entirely. The default is
+    /// [`DEFAULT_BRAKING_DISTANCE`].
+    ///
+    /// ```
+    /// # use azalea::pathfinder::moves::MoveSet;
+    /// let move_set = MoveSet::default().braking_distance(1.5);
+    /// ```
+    #[must_use]
+    pub fn braking_distance(mut self, braking_distance: f64) -> Self {
+        self.braking_distance = braking_distance;
+        self
+    }
+
+    /// Make the pathfinder find paths faster by trusting the goal's heuristic
+    /// more, at the cost of the paths not always being the shortest.
+    ///
+    /// A weight of `1.` (the default) always finds the best path, and
+    /// something like `2.` or `3.` is much faster for long distances. Values
+    /// less than `1.` are treated as `1.`.
+    ///
+    /// ```
+    /// # use azalea::pathfinder::moves::MoveSet;
+    /// let move_set = MoveSet::default().heuristic_weight(2.5);
+    /// ```
+    #[must_use]
+    pub fn heuristic_weight(mut self, heuristic_weight: f32) -> Self {
+        self.heuristic_weight = heuristic_weight.max(1.);
+        self
+    }
+
+    /// Stop searching after looking at this many nodes and use the best
+    /// partial path instead. The rest of the path is calculated once we get
+    /// to the end of the partial one.
+    ///
+    /// This is useful for very long paths, since it lets us start moving
+    /// before the whole path has been found.
+    ///
+    /// ```
+    /// # use azalea::pathfinder::moves::MoveSet;
+    /// let move_set = MoveSet::default().max_nodes(50_000);
+    /// ```
+    #[must_use]
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// Make the pathfinder work for goals that are outside of the loaded
+    /// chunks.
+    ///
+    /// Instead of failing when the goal isn't loaded, we walk to the edge of
+    /// the loaded chunks that's closest to the goal, wait there for more
+    /// chunks to load, and then keep going. If no new chunks load for a while,
+    /// the pathfinder gives up with [`PathfindError::NoPath`].
+    ///
+    /// [`PathfindError::NoPath`]: super::PathfindError::NoPath
+    ///
+    /// ```
+    /// # use azalea::pathfinder::moves::MoveSet;
+    /// let move_set = MoveSet::default().incremental();
+    /// ```
+    #[must_use]
+    pub fn incremental(mut self) -> Self {
+        self.incremental = true;
+        self
+    }
+
+    /// Change how much the pathfinder thinks each kind of move costs, which
+    /// changes which paths it prefers. See [`PathfinderCosts`].
+    #[must_use]
+    pub fn costs(mut self, costs: PathfinderCosts) -> Self {
+        self.costs = costs;
+        self
+    }
+
+    /// Make a [`PathfinderCtx`] that uses the options from this set.
+    ///
+    /// `mining_inventory` is ignored if mining isn't allowed.
+    pub fn ctx<'a>(
+        &'a self,
+        world: &'a Instance,
+        health: f32,
+        mining_inventory: Option<&'a Menu>,
+    ) -> PathfinderCtx<'a> {
+        PathfinderCtx {
+            world,
+            health,
+            avoid: self.avoid.as_deref(),
+            mining_inventory: mining_inventory.filter(|_| self.can_mine),
+            costs: self.costs,
+        }
+    }
+
+    /// Get all the edges from the given node for every move in this set.
+    pub fn successors(&self, ctx: &PathfinderCtx, node: BlockPos) -> Vec<Edge> {
+        let mut edges = Vec::new();
+        for successors_fn in &self.moves {
+            edges.extend(
+                successors_fn(ctx, node)
+                    .into_iter()
+                    // this catches every move, including ones that don't check for avoided
+                    // positions themselves
+                    .filter(|edge| !ctx.is_avoided(edge.movement.target)),
+            );
+        }
+        edges
+    }
+}
+impl Default for MoveSet {
+    fn default() -> Self {
+        Self::new(vec![
+            basic::basic_move,
+            parkour::parkour_move,
+            climb::ladder_move,
+            door::door_move,
+        ])
+    }
+}
+
+/// The default for [`MoveSet::braking_distance`].
+pub const DEFAULT_BRAKING_DISTANCE: f64 = 1.;
+
+/// Whether a move that would normally sprint should walk instead, because
+/// we're close to the target and the path turns or drops right after it.
+///
+/// Sprinting into a turn makes us slide past the corner, and sprinting up to a
+/// drop can carry us past the block we're supposed to land on.
+pub fn should_brake(ctx: &ExecuteCtx) -> bool {
+    should_brake_at(
+        ctx.position,
+        ctx.start,
+        ctx.target,
+        ctx.next_target,
+        ctx.braking_distance,
+    )
+}
+
+/// [`should_brake`], for a move from `start` to `target` that we're at
+/// `position` in, where `next_target` is the node after it.
+fn should_brake_at(
+    position: Vec3,
+    start: BlockPos,
+    target: BlockPos,
+    next_target: Option<BlockPos>,
+    braking_distance: f64,
+) -> bool {
+    let center = target.center();
+    let horizontal_distance_to_target = (center - position).horizontal_distance_sqr().sqrt();
+    if horizontal_distance_to_target > braking_distance {
+        return false;
+    }
+
+    let Some(next_target) = next_target else {
+        // the path ends here (or we haven't calculated the rest yet)
+        return true;
+    };
+    if next_target.y < target.y {
+        return true;
+    }
+
+    let direction = target - start;
+    let next_direction = next_target - target;
+    (direction.x.signum(), direction.z.signum())
+        != (next_direction.x.signum(), next_direction.z.signum())
 }
 
 /// Returns whether the entity is at the node and should start going to the
@@ -136,6 +757,9 @@ pub fn default_is_reached(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::pathfinder::costs::{
+        FLY_ONE_BLOCK_COST, OPEN_DOOR_COST, SPRINT_ONE_BLOCK_COST, WALK_ONE_BLOCK_COST,
+    };
     use azalea_block::BlockState;
     use azalea_core::position::ChunkPos;
     use azalea_world::{Chunk, ChunkStorage, PartialInstance};
@@ -226,4 +850,460 @@ mod tests {
         assert!(!is_standable(&BlockPos::new(0, 0, 0), &world));
         assert!(!is_standable(&BlockPos::new(0, 2, 0), &world));
     }
+
+    #[test]
+    fn test_not_standable_outside_world_border() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        for x in 0..4 {
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(x, 0, 0),
+                azalea_registry::Block::Stone.into(),
+                &chunk_storage,
+            );
+        }
+
+        let mut world: Instance = chunk_storage.into();
+        world.world_border.center_x = 1.;
+        world.world_border.center_z = 0.5;
+        world.world_border.set_size(4.);
+        assert!(is_standable(&BlockPos::new(2, 1, 0), &world));
+        assert!(!is_standable(&BlockPos::new(3, 1, 0), &world));
+    }
+
+    #[test]
+    fn test_fall_damage() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        let floors = [
+            (0, azalea_registry::Block::Stone),
+            (1, azalea_registry::Block::HayBlock),
+            (2, azalea_registry::Block::SlimeBlock),
+            (3, azalea_registry::Block::Stone),
+        ];
+        for (x, block) in floors {
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(x, 0, 0),
+                block.into(),
+                &chunk_storage,
+            );
+        }
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(3, 1, 0),
+            azalea_registry::Block::Water.into(),
+            &chunk_storage,
+        );
+
+        let world = chunk_storage.into();
+        assert_eq!(fall_damage(3, &BlockPos::new(0, 1, 0), &world), 0.);
+        assert_eq!(fall_damage(10, &BlockPos::new(0, 1, 0), &world), 7.);
+        assert_eq!(fall_damage(10, &BlockPos::new(1, 1, 0), &world), 2.);
+        assert_eq!(fall_damage(100, &BlockPos::new(2, 1, 0), &world), 0.);
+        assert!(is_water_landing(&BlockPos::new(3, 1, 0), &world));
+        assert_eq!(fall_damage(100, &BlockPos::new(3, 1, 0), &world), 0.);
+
+        assert!(is_fall_damage_safe(7., 20.));
+        assert!(!is_fall_damage_safe(3., 4.));
+        assert!(is_fall_damage_safe(0., 1.));
+    }
+
+    #[test]
+    fn test_avoid() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        for x in 0..3 {
+            for z in 0..3 {
+                partial_world.chunks.set_block_state(
+                    &BlockPos::new(x, 0, z),
+                    azalea_registry::Block::Stone.into(),
+                    &chunk_storage,
+                );
+            }
+        }
+        let world = chunk_storage.into();
+
+        let targets = |move_set: &MoveSet| {
+            move_set
+                .successors(
+                    &move_set.ctx(&world, MAX_HEALTH, None),
+                    BlockPos::new(1, 1, 1),
+                )
+                .into_iter()
+                .map(|edge| edge.movement.target)
+                .collect::<Vec<_>>()
+        };
+
+        let move_set = MoveSet::new(vec![basic::basic_move]);
+        assert!(targets(&move_set).contains(&BlockPos::new(2, 1, 1)));
+        assert!(targets(&move_set).contains(&BlockPos::new(2, 1, 2)));
+
+        let move_set = move_set.avoid(|pos, _| pos == BlockPos::new(2, 1, 1));
+        let targets = targets(&move_set);
+        assert!(!targets.contains(&BlockPos::new(2, 1, 1)));
+        // the diagonal moves next to it are avoided too
+        assert!(!targets.contains(&BlockPos::new(2, 1, 2)));
+        assert!(!targets.contains(&BlockPos::new(2, 1, 0)));
+        assert!(targets.contains(&BlockPos::new(0, 1, 0)));
+    }
+
+    #[test]
+    fn test_mine_forward() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        for x in 0..4 {
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(x, 0, 0),
+                azalea_registry::Block::Stone.into(),
+                &chunk_storage,
+            );
+        }
+        // a stone wall in front of us, a bedrock wall after that, and water above
+        // the bedrock
+        for y in 1..3 {
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(1, y, 0),
+                azalea_registry::Block::Stone.into(),
+                &chunk_storage,
+            );
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(2, y, 0),
+                azalea_registry::Block::Bedrock.into(),
+                &chunk_storage,
+            );
+        }
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(3, 1, 0),
+            azalea_registry::Block::Stone.into(),
+            &chunk_storage,
+        );
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(3, 2, 0),
+            azalea_registry::Block::Water.into(),
+            &chunk_storage,
+        );
+        let world = chunk_storage.into();
+        let inventory = Menu::Player(azalea_inventory::Player::default());
+
+        let move_set = MoveSet::new(vec![basic::basic_move]);
+        let ctx = move_set.ctx(&world, MAX_HEALTH, Some(&inventory));
+        // mining isn't allowed, so the inventory is ignored
+        assert!(ctx.mining_inventory.is_none());
+        assert!(!move_set
+            .successors(&ctx, BlockPos::new(0, 1, 0))
+            .iter()
+            .any(|edge| edge.movement.target == BlockPos::new(1, 1, 0)));
+
+        let move_set = move_set.allow_mining();
+        let ctx = move_set.ctx(&world, MAX_HEALTH, Some(&inventory));
+        let edges = move_set.successors(&ctx, BlockPos::new(0, 1, 0));
+        let edge = edges
+            .iter()
+            .find(|edge| edge.movement.target == BlockPos::new(1, 1, 0))
+            .unwrap();
+        assert_eq!(edge.movement.data.kind, MoveKind::MineForward);
+        // stone takes 150 ticks to break by hand
+        assert_eq!(edge.cost, SPRINT_ONE_BLOCK_COST + 150. * 2.);
+
+        // bedrock can't be broken
+        assert_eq!(mine_cost(&BlockPos::new(2, 1, 0), &ctx), None);
+        // and we don't break blocks next to water
+        assert_eq!(mine_cost(&BlockPos::new(3, 1, 0), &ctx), None);
+        assert_eq!(mine_cost(&BlockPos::new(0, 1, 0), &ctx), Some(0.));
+    }
+
+    #[test]
+    fn test_door_move() {
+        use azalea_block::{
+            blocks,
+            properties::{FacingCardinal, Half, Hinge, TopBottom},
+        };
+
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        for x in 0..3 {
+            for z in 0..3 {
+                partial_world.chunks.set_block_state(
+                    &BlockPos::new(x, 0, z),
+                    azalea_registry::Block::Stone.into(),
+                    &chunk_storage,
+                );
+            }
+        }
+        // a closed oak door east of us and a closed iron door south of us
+        for half in [Half::Lower, Half::Upper] {
+            let y = if matches!(half, Half::Lower) { 1 } else { 2 };
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(2, y, 1),
+                blocks::OakDoor {
+                    facing: FacingCardinal::East,
+                    half,
+                    hinge: Hinge::Left,
+                    open: false,
+                    powered: false,
+                }
+                .into(),
+                &chunk_storage,
+            );
+            partial_world.chunks.set_block_state(
+                &BlockPos::new(1, y, 2),
+                blocks::IronDoor {
+                    facing: FacingCardinal::South,
+                    half,
+                    hinge: Hinge::Left,
+                    open: false,
+                    powered: false,
+                }
+                .into(),
+                &chunk_storage,
+            );
+        }
+        // and a closed trapdoor that we can stand on, which would still be in our way
+        // if we opened it
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(0, 1, 1),
+            blocks::OakTrapdoor {
+                facing: FacingCardinal::East,
+                half: TopBottom::Top,
+                open: false,
+                powered: false,
+                waterlogged: false,
+            }
+            .into(),
+            &chunk_storage,
+        );
+        let world = chunk_storage.into();
+
+        let ctx = PathfinderCtx::new(&world);
+        let edges = door::door_move(&ctx, BlockPos::new(1, 1, 1));
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].movement.target, BlockPos::new(2, 1, 1));
+        assert_eq!(edges[0].movement.data.kind, MoveKind::Door);
+        // the top half opens with the bottom half, so it doesn't cost anything
+        assert_eq!(edges[0].cost, WALK_ONE_BLOCK_COST + OPEN_DOOR_COST);
+
+        assert!(is_block_solid(&BlockPos::new(0, 1, 1), &world));
+        assert!(is_standable(&BlockPos::new(0, 2, 1), &world));
+    }
+
+    #[test]
+    fn test_fly_move() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        for x in 0..3 {
+            for z in 0..3 {
+                partial_world.chunks.set_block_state(
+                    &BlockPos::new(x, 0, z),
+                    azalea_registry::Block::Stone.into(),
+                    &chunk_storage,
+                );
+            }
+        }
+        // a floating block that's at head height if we go east
+        partial_world.chunks.set_block_state(
+            &BlockPos::new(2, 2, 1),
+            azalea_registry::Block::Stone.into(),
+            &chunk_storage,
+        );
+        let world = chunk_storage.into();
+
+        let move_set = MoveSet::flying();
+        let edges = move_set.successors(
+            &move_set.ctx(&world, MAX_HEALTH, None),
+            BlockPos::new(1, 1, 1),
+        );
+        let cost_to = |target: BlockPos| {
+            edges
+                .iter()
+                .find(|edge| edge.movement.target == target)
+                .map(|edge| edge.cost)
+        };
+
+        assert!(edges
+            .iter()
+            .all(|edge| edge.movement.data.kind == MoveKind::Fly));
+        // we can fly straight up without anything to stand on
+        assert_eq!(cost_to(BlockPos::new(1, 2, 1)), Some(FLY_ONE_BLOCK_COST));
+        assert_eq!(
+            cost_to(BlockPos::new(0, 2, 0)),
+            Some(FLY_ONE_BLOCK_COST * 3f32.sqrt())
+        );
+        // but not into the floor
+        assert_eq!(cost_to(BlockPos::new(1, 0, 1)), None);
+        // or into the floating block
+        assert_eq!(cost_to(BlockPos::new(2, 1, 1)), None);
+        // and we don't cut the corner around it
+        assert_eq!(cost_to(BlockPos::new(2, 1, 2)), None);
+        assert_eq!(cost_to(BlockPos::new(2, 2, 2)), None);
+        assert_eq!(cost_to(BlockPos::new(1, 1, 2)), Some(FLY_ONE_BLOCK_COST));
+    }
+
+    #[test]
+    fn test_path_step_display() {
+        let step = |kind, target| PathStep {
+            kind,
+            start: BlockPos::new(0, 64, 0),
+            target,
+        };
+        assert_eq!(
+            step(MoveKind::Ascend, BlockPos::new(0, 65, -1)).to_string(),
+            "Ascend N"
+        );
+        assert_eq!(
+            step(MoveKind::Descend, BlockPos::new(-1, 62, 1)).to_string(),
+            "Descend 2 SW"
+        );
+        assert_eq!(
+            step(MoveKind::Diagonal, BlockPos::new(1, 64, -1)).to_string(),
+            "Diagonal NE"
+        );
+        assert_eq!(
+            step(MoveKind::Parkour, BlockPos::new(3, 64, 0)).to_string(),
+            "Parkour 3 E"
+        );
+        assert_eq!(
+            step(MoveKind::ClimbUp, BlockPos::new(0, 65, 0)).to_string(),
+            "Climb up"
+        );
+    }
+
+    #[test]
+    fn test_custom_costs() {
+        let mut partial_world = PartialInstance::default();
+        let mut chunk_storage = ChunkStorage::default();
+        partial_world.chunks.set(
+            &ChunkPos { x: 0, z: 0 },
+            Some(Chunk::default()),
+            &mut chunk_storage,
+        );
+        for x in 0..3 {
+            for z in 0..3 {
+                partial_world.chunks.set_block_state(
+                    &BlockPos::new(x, 0, z),
+                    azalea_registry::Block::Stone.into(),
+                    &chunk_storage,
+                );
+            }
+        }
+        let world = chunk_storage.into();
+
+        let cost_to = |ctx: &PathfinderCtx, target: BlockPos| {
+            basic::basic_move(ctx, BlockPos::new(1, 1, 1))
+                .into_iter()
+                .find(|edge| edge.movement.target == target)
+                .map(|edge| edge.cost)
+        };
+
+        let ctx = PathfinderCtx::new(&world);
+        assert_eq!(
+            cost_to(&ctx, BlockPos::new(2, 1, 1)),
+            Some(SPRINT_ONE_BLOCK_COST)
+        );
+
+        let move_set = MoveSet::default().costs(PathfinderCosts {
+            sprint_one_block: 1.,
+            diagonal_penalty: 0.5,
+            ..Default::default()
+        });
+        let ctx = move_set.ctx(&world, MAX_HEALTH, None);
+        assert_eq!(cost_to(&ctx, BlockPos::new(2, 1, 1)), Some(1.));
+        assert_eq!(
+            cost_to(&ctx, BlockPos::new(2, 1, 2)),
+            Some(std::f32::consts::SQRT_2 + 0.5)
+        );
+    }
+
+    #[test]
+    fn test_should_brake_straight_line() {
+        // almost at (1, 0, 0) and the path keeps going the same way
+        let position = Vec3::new(1.2, 0., 0.5);
+        assert!(!should_brake_at(
+            position,
+            BlockPos::new(0, 0, 0),
+            BlockPos::new(1, 0, 0),
+            Some(BlockPos::new(2, 0, 0)),
+            DEFAULT_BRAKING_DISTANCE,
+        ));
+        // going up a block isn't a turn either
+        assert!(!should_brake_at(
+            position,
+            BlockPos::new(0, 0, 0),
+            BlockPos::new(1, 0, 0),
+            Some(BlockPos::new(2, 1, 0)),
+            DEFAULT_BRAKING_DISTANCE,
+        ));
+    }
+
+    #[test]
+    fn test_should_brake_before_turn() {
+        let start = BlockPos::new(0, 0, 0);
+        let target = BlockPos::new(1, 0, 0);
+        let next_target = Some(BlockPos::new(1, 0, 1));
+        assert!(should_brake_at(
+            Vec3::new(1.2, 0., 0.5),
+            start,
+            target,
+            next_target,
+            DEFAULT_BRAKING_DISTANCE,
+        ));
+        // we're still too far away from the corner to slow down
+        assert!(!should_brake_at(
+            Vec3::new(0.2, 0., 0.5),
+            start,
+            target,
+            next_target,
+            DEFAULT_BRAKING_DISTANCE,
+        ));
+    }
+
+    #[test]
+    fn test_should_brake_before_drop() {
+        assert!(should_brake_at(
+            Vec3::new(1.2, 0., 0.5),
+            BlockPos::new(0, 0, 0),
+            BlockPos::new(1, 0, 0),
+            Some(BlockPos::new(2, -2, 0)),
+            DEFAULT_BRAKING_DISTANCE,
+        ));
+    }
+
+    #[test]
+    fn test_should_brake_at_end_of_path() {
+        assert!(should_brake_at(
+            Vec3::new(1.2, 0., 0.5),
+            BlockPos::new(0, 0, 0),
+            BlockPos::new(1, 0, 0),
+            None,
+            DEFAULT_BRAKING_DISTANCE,
+        ));
+    }
 }
@@ -1,26 +1,28 @@
 use azalea_client::{SprintDirection, StartSprintEvent, StartWalkEvent, WalkDirection};
 use azalea_core::{direction::CardinalDirection, position::BlockPos};
-use azalea_world::Instance;
 
-use crate::{
-    pathfinder::{astar, costs::*},
-    JumpEvent, LookAtEvent,
-};
+use crate::{pathfinder::astar, JumpEvent, LookAtEvent};
 
 use super::{
     default_is_reached, is_block_passable, is_block_solid, is_passable, is_standable, Edge,
-    ExecuteCtx, IsReachedCtx, MoveData,
+    ExecuteCtx, IsReachedCtx, MoveData, MoveKind, PathfinderCtx,
 };
 
-pub fn parkour_move(world: &Instance, node: BlockPos) -> Vec<Edge> {
+pub fn parkour_move(ctx: &PathfinderCtx, node: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
     let mut edges = Vec::new();
-    edges.extend(parkour_forward_1_move(world, node));
-    edges.extend(parkour_headhitter_forward_1_move(world, node));
-    edges.extend(parkour_forward_2_move(world, node));
+    // we can't jump off of a ladder
+    if !is_block_solid(&node.down(1), world) {
+        return edges;
+    }
+    edges.extend(parkour_forward_1_move(ctx, node));
+    edges.extend(parkour_headhitter_forward_1_move(ctx, node));
+    edges.extend(parkour_forward_2_move(ctx, node));
     edges
 }
 
-fn parkour_forward_1_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
+fn parkour_forward_1_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
     let mut edges = Vec::new();
     for dir in CardinalDirection::iter() {
         let gap_offset = BlockPos::new(dir.x(), 0, dir.z());
@@ -44,12 +46,14 @@ fn parkour_forward_1_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
             continue;
         }
 
-        let cost = *JUMP_ONE_BLOCK_COST + SPRINT_ONE_BLOCK_COST + SPRINT_ONE_BLOCK_COST;
+        let cost =
+            ctx.costs.jump_one_block + ctx.costs.sprint_one_block + ctx.costs.sprint_one_block;
 
         edges.push(Edge {
             movement: astar::Movement {
                 target: pos + offset,
                 data: MoveData {
+                    kind: MoveKind::Parkour,
                     execute: &execute_parkour_move,
                     is_reached: &parkour_is_reached,
                 },
@@ -61,7 +65,8 @@ fn parkour_forward_1_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
     edges
 }
 
-fn parkour_forward_2_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
+fn parkour_forward_2_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
     let mut edges = Vec::new();
     for dir in CardinalDirection::iter() {
         let gap_1_offset = BlockPos::new(dir.x(), 0, dir.z());
@@ -92,15 +97,16 @@ fn parkour_forward_2_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
             continue;
         }
 
-        let cost = *JUMP_ONE_BLOCK_COST
-            + SPRINT_ONE_BLOCK_COST
-            + SPRINT_ONE_BLOCK_COST
-            + SPRINT_ONE_BLOCK_COST;
+        let cost = ctx.costs.jump_one_block
+            + ctx.costs.sprint_one_block
+            + ctx.costs.sprint_one_block
+            + ctx.costs.sprint_one_block;
 
         edges.push(Edge {
             movement: astar::Movement {
                 target: pos + offset,
                 data: MoveData {
+                    kind: MoveKind::Parkour,
                     execute: &execute_parkour_move,
                     is_reached: &default_is_reached,
                 },
@@ -112,7 +118,8 @@ fn parkour_forward_2_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
     edges
 }
 
-fn parkour_headhitter_forward_1_move(world: &Instance, pos: BlockPos) -> Vec<Edge> {
+fn parkour_headhitter_forward_1_move(ctx: &PathfinderCtx, pos: BlockPos) -> Vec<Edge> {
+    let world = ctx.world;
     let mut edges = Vec::new();
     for dir in CardinalDirection::iter() {
         let gap_offset = BlockPos::new(dir.x(), 0, dir.z());
@@ -136,12 +143,13 @@ fn parkour_headhitter_forward_1_move(world: &Instance, pos: BlockPos) -> Vec<Edg
             continue;
         }
 
-        let cost = *JUMP_ONE_BLOCK_COST + WALK_ONE_BLOCK_COST + WALK_ONE_BLOCK_COST;
+        let cost = ctx.costs.jump_one_block + ctx.costs.walk_one_block + ctx.costs.walk_one_block;
 
         edges.push(Edge {
             movement: astar::Movement {
                 target: pos + offset,
                 data: MoveData {
+                    kind: MoveKind::HeadHitterParkour,
                     execute: &execute_headhitter_parkour_move,
                     is_reached: &default_is_reached,
                 },
@@ -0,0 +1,155 @@
+//! Noticing when we stop making progress along a path, and trying to get
+//! unstuck.
+//!
+//! We only count being stuck as not getting any closer to the node that we're
+//! going to, so moves that are slow but still moving (like climbing a ladder or
+//! walking through cobwebs) aren't mistaken for being stuck.
+
+use azalea_core::position::{BlockPos, Vec3};
+use bevy_ecs::{entity::Entity, event::Event};
+
+/// How many ticks we can go without getting closer to the node that we're
+/// going to before we're considered stuck.
+pub const STUCK_TICKS: u32 = 20;
+/// How much closer to the node we have to get (in blocks) for it to count as
+/// progress. This is small enough that walking through cobwebs still counts.
+pub const MIN_PROGRESS: f64 = 0.01;
+/// How many ticks we walk backwards for when we're backing up.
+pub const BACK_UP_TICKS: u32 = 5;
+
+/// What we do to try to get unstuck. Each time we get stuck going to the same
+/// node we try the next one of these, starting with [`Self::Jump`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StuckRecovery {
+    /// Jump while we keep trying the move, in case we're caught on the edge of
+    /// a block.
+    Jump,
+    /// Walk backwards for [`BACK_UP_TICKS`] ticks and then try the move again.
+    BackUp,
+    /// Throw away the path and find a new one from where we are.
+    Repath,
+}
+
+/// Sent when the pathfinder stops making progress along its path, right before
+/// it tries to recover.
+#[derive(Event, Debug, Clone)]
+pub struct PathStuckEvent {
+    pub entity: Entity,
+    /// Where we were when we got stuck.
+    pub position: Vec3,
+    /// The node that we were trying to get to.
+    pub target: BlockPos,
+    pub recovery: StuckRecovery,
+}
+
+/// Keeps track of how close we've gotten to the node that we're going to. This
+/// is stored in the [`Pathfinder`](super::Pathfinder).
+#[derive(Debug, Clone, Default)]
+pub struct StuckDetector {
+    target: Option<BlockPos>,
+    closest_distance: f64,
+    ticks_without_progress: u32,
+    recovery_attempts: u32,
+    /// How many more ticks we're going to walk backwards for, if we're backing
+    /// up. The move isn't executed while this is more than 0.
+    pub back_up_ticks_left: u32,
+}
+
+impl StuckDetector {
+    /// Forget about the node we were going to, like when we're not executing a
+    /// path or we're doing something slow on purpose (like mining).
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Update the detector with where we are now and the node that we're
+    /// going to. Returns how we should try to get unstuck if we haven't gotten
+    /// any closer for [`STUCK_TICKS`] ticks.
+    pub fn tick(&mut self, position: Vec3, target: BlockPos) -> Option<StuckRecovery> {
+        let distance = position.distance_to(&target.center());
+        if self.target != Some(target) {
+            self.reset();
+            self.target = Some(target);
+            self.closest_distance = distance;
+            return None;
+        }
+
+        if distance < self.closest_distance - MIN_PROGRESS {
+            self.closest_distance = distance;
+            self.ticks_without_progress = 0;
+            return None;
+        }
+
+        self.ticks_without_progress += 1;
+        if self.ticks_without_progress < STUCK_TICKS {
+            return None;
+        }
+
+        let recovery = match self.recovery_attempts % 3 {
+            0 => StuckRecovery::Jump,
+            1 => StuckRecovery::BackUp,
+            _ => StuckRecovery::Repath,
+        };
+        self.recovery_attempts += 1;
+        self.ticks_without_progress = 0;
+        // backing up moves us away, so we have to measure progress from wherever we
+        // end up
+        self.closest_distance = distance;
+        if recovery == StuckRecovery::BackUp {
+            self.back_up_ticks_left = BACK_UP_TICKS;
+        }
+        Some(recovery)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standing_still_is_stuck() {
+        let mut detector = StuckDetector::default();
+        let target = BlockPos::new(1, 0, 0);
+        let position = Vec3::new(0.5, 0., 0.5);
+
+        for _ in 0..STUCK_TICKS {
+            assert_eq!(detector.tick(position, target), None);
+        }
+        assert_eq!(detector.tick(position, target), Some(StuckRecovery::Jump));
+
+        for _ in 0..STUCK_TICKS - 1 {
+            assert_eq!(detector.tick(position, target), None);
+        }
+        assert_eq!(detector.tick(position, target), Some(StuckRecovery::BackUp));
+        assert_eq!(detector.back_up_ticks_left, BACK_UP_TICKS);
+
+        for _ in 0..STUCK_TICKS - 1 {
+            assert_eq!(detector.tick(position, target), None);
+        }
+        assert_eq!(detector.tick(position, target), Some(StuckRecovery::Repath));
+    }
+
+    #[test]
+    fn test_slow_progress_is_not_stuck() {
+        let mut detector = StuckDetector::default();
+        let target = BlockPos::new(0, 10, 0);
+        // about as fast as climbing a ladder
+        for i in 0..STUCK_TICKS * 5 {
+            let position = Vec3::new(0.5, i as f64 * 0.1, 0.5);
+            assert_eq!(detector.tick(position, target), None);
+        }
+    }
+
+    #[test]
+    fn test_new_target_resets() {
+        let mut detector = StuckDetector::default();
+        let position = Vec3::new(0.5, 0., 0.5);
+
+        for _ in 0..STUCK_TICKS - 1 {
+            detector.tick(position, BlockPos::new(1, 0, 0));
+        }
+        for _ in 0..STUCK_TICKS {
+            assert_eq!(detector.tick(position, BlockPos::new(0, 0, 1)), None);
+        }
+    }
+}
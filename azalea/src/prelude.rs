@@ -2,8 +2,8 @@
 //! re-exported here.
 
 pub use crate::{
-    bot::BotClientExt, container::ContainerClientExt, pathfinder::PathfinderClientExt,
-    ClientBuilder,
+    bot::BotClientExt, container::ContainerClientExt, eat::EatClientExt,
+    pathfinder::PathfinderClientExt, sleep::SleepClientExt, ClientBuilder,
 };
 pub use azalea_client::{Account, Client, Event};
 // this is necessary to make the macros that reference bevy_ecs work
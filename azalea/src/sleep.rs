@@ -0,0 +1,43 @@
+//! Sleeping in beds. See [`SleepClientExt::sleep`].
+
+pub use azalea_client::sleep::*;
+use azalea_client::Client;
+use azalea_core::position::BlockPos;
+
+use crate::bot::BotClientExt;
+
+pub trait SleepClientExt {
+    async fn sleep(&mut self, bed: BlockPos) -> Result<(), SleepError>;
+}
+
+impl SleepClientExt for Client {
+    /// Right-click a bed and wait until we're sleeping in it. If the server
+    /// doesn't let us sleep, this returns the reason why.
+    ///
+    /// Sleeping in a bed also sets our spawn point. Use [`Client::wake_up`] to
+    /// get out of the bed.
+    ///
+    /// ```
+    /// # use azalea::prelude::*;
+    /// # use azalea::BlockPos;
+    /// # async fn example(mut bot: azalea::Client, bed: BlockPos) {
+    /// if let Err(err) = bot.sleep(bed).await {
+    ///     println!("Couldn't sleep: {err}");
+    /// }
+    /// # }
+    /// ```
+    async fn sleep(&mut self, bed: BlockPos) -> Result<(), SleepError> {
+        let mut receiver = self.get_tick_broadcaster();
+        self.start_sleeping(bed);
+
+        while receiver.recv().await.is_ok() {
+            if self.get_component::<Sleeping>().is_some() {
+                return Ok(());
+            }
+            if let Some(LastSleepError(err)) = self.get_component::<LastSleepError>() {
+                return Err(err);
+            }
+        }
+        Err(SleepError::NoResponse)
+    }
+}
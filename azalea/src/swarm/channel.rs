@@ -0,0 +1,204 @@
+//! Send messages between the bots in a swarm without going through the server.
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+};
+
+use azalea_client::Client;
+use bevy_app::{App, FixedUpdate, Plugin};
+use bevy_ecs::{
+    entity::Entity,
+    system::{ResMut, Resource},
+};
+use log::warn;
+use thiserror::Error;
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+
+use super::Swarm;
+
+/// How many messages of each type can be waiting to be received before the
+/// oldest ones are dropped.
+const CHANNEL_CAPACITY: usize = 256;
+
+pub struct SwarmChannelPlugin;
+impl Plugin for SwarmChannelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SwarmChannel>()
+            .add_systems(FixedUpdate, flush_swarm_channel);
+    }
+}
+
+/// A resource that lets bots in a swarm publish typed messages to each other.
+///
+/// There's a separate channel for every message type. Messages are delivered
+/// at the start of the next tick, so every subscribed bot sees them at the same
+/// time.
+#[derive(Resource, Default)]
+pub struct SwarmChannel {
+    senders: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+    pending: Vec<Box<dyn FnOnce() + Send + Sync>>,
+}
+
+/// The [`SwarmChannel`] resource doesn't exist, which means that the
+/// [`SwarmChannelPlugin`] was disabled.
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("The SwarmChannelPlugin isn't enabled")]
+pub struct NoSwarmChannelError;
+
+/// A message that was sent to the [`SwarmChannel`].
+#[derive(Clone, Debug)]
+pub struct SwarmMessage<T> {
+    /// The bot that sent the message.
+    pub sender: Entity,
+    pub message: T,
+}
+
+impl SwarmChannel {
+    fn sender<T: Clone + Send + Sync + 'static>(&mut self) -> &broadcast::Sender<SwarmMessage<T>> {
+        self.senders
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(broadcast::channel::<SwarmMessage<T>>(CHANNEL_CAPACITY).0))
+            .downcast_ref()
+            .expect("senders are always stored with the TypeId of their message")
+    }
+
+    /// Queue a message to be sent to every subscriber of its type on the next
+    /// tick.
+    pub fn publish<T: Clone + Send + Sync + 'static>(&mut self, sender: Entity, message: T) {
+        let channel_sender = self.sender::<T>().clone();
+        self.pending.push(Box::new(move || {
+            // this only errors if there's no subscribers, which is fine
+            let _ = channel_sender.send(SwarmMessage { sender, message });
+        }));
+    }
+
+    /// Start receiving messages of the given type. If `entity` is present,
+    /// messages that were sent by that entity will be skipped.
+    pub fn subscribe<T: Clone + Send + Sync + 'static>(
+        &mut self,
+        entity: Option<Entity>,
+    ) -> SwarmReceiver<T> {
+        SwarmReceiver {
+            receiver: self.sender::<T>().subscribe(),
+            entity,
+        }
+    }
+}
+
+fn flush_swarm_channel(mut channel: ResMut<SwarmChannel>) {
+    for send in channel.pending.drain(..) {
+        send();
+    }
+}
+
+/// Receives messages of one type from the [`SwarmChannel`].
+pub struct SwarmReceiver<T> {
+    receiver: broadcast::Receiver<SwarmMessage<T>>,
+    /// The entity that subscribed, so it doesn't receive its own messages.
+    entity: Option<Entity>,
+}
+
+impl<T: Clone> SwarmReceiver<T> {
+    /// Wait for the next message. This only returns `None` if the swarm was
+    /// dropped.
+    pub async fn recv(&mut self) -> Option<SwarmMessage<T>> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(message) if Some(message.sender) == self.entity => continue,
+                Ok(message) => return Some(message),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Swarm channel receiver lagged behind and skipped {skipped} messages");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Get the next message if one has already been received. This is useful
+    /// for checking for messages in your handler every tick.
+    pub fn try_recv(&mut self) -> Option<SwarmMessage<T>> {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(message) if Some(message.sender) == self.entity => continue,
+                Ok(message) => return Some(message),
+                Err(TryRecvError::Lagged(skipped)) => {
+                    warn!("Swarm channel receiver lagged behind and skipped {skipped} messages");
+                }
+                Err(TryRecvError::Empty | TryRecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+pub trait SwarmClientExt {
+    /// Send a message to every other bot in the swarm that's subscribed to
+    /// messages of this type. They'll receive it on the next tick.
+    ///
+    /// This returns an error if the [`SwarmChannelPlugin`] is disabled.
+    ///
+    /// ```
+    /// # use azalea::{prelude::*, swarm::prelude::*};
+    /// # use azalea::swarm::NoSwarmChannelError;
+    /// #[derive(Clone)]
+    /// struct FoundDiamonds(azalea::BlockPos);
+    ///
+    /// # fn example(bot: azalea::Client, pos: azalea::BlockPos) -> Result<(), NoSwarmChannelError> {
+    /// bot.swarm_broadcast(FoundDiamonds(pos))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn swarm_broadcast<T: Clone + Send + Sync + 'static>(
+        &self,
+        message: T,
+    ) -> Result<(), NoSwarmChannelError>;
+    /// Receive the messages of the given type that other bots in the swarm
+    /// send.
+    ///
+    /// This returns an error if the [`SwarmChannelPlugin`] is disabled.
+    fn swarm_subscribe<T: Clone + Send + Sync + 'static>(
+        &self,
+    ) -> Result<SwarmReceiver<T>, NoSwarmChannelError>;
+}
+
+impl SwarmClientExt for Client {
+    fn swarm_broadcast<T: Clone + Send + Sync + 'static>(
+        &self,
+        message: T,
+    ) -> Result<(), NoSwarmChannelError> {
+        self.ecs
+            .lock()
+            .get_resource_mut::<SwarmChannel>()
+            .ok_or(NoSwarmChannelError)?
+            .publish(self.entity, message);
+        Ok(())
+    }
+
+    fn swarm_subscribe<T: Clone + Send + Sync + 'static>(
+        &self,
+    ) -> Result<SwarmReceiver<T>, NoSwarmChannelError> {
+        Ok(self
+            .ecs
+            .lock()
+            .get_resource_mut::<SwarmChannel>()
+            .ok_or(NoSwarmChannelError)?
+            .subscribe(Some(self.entity)))
+    }
+}
+
+impl Swarm {
+    /// Receive every message of the given type that the bots in this swarm
+    /// send to each other.
+    ///
+    /// This returns an error if the [`SwarmChannelPlugin`] is disabled.
+    pub fn subscribe<T: Clone + Send + Sync + 'static>(
+        &self,
+    ) -> Result<SwarmReceiver<T>, NoSwarmChannelError> {
+        Ok(self
+            .ecs_lock
+            .lock()
+            .get_resource_mut::<SwarmChannel>()
+            .ok_or(NoSwarmChannelError)?
+            .subscribe(None))
+    }
+}
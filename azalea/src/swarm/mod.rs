@@ -1,12 +1,18 @@
 //! Swarms are a way to conveniently control many bots.
 
+mod channel;
 mod chat;
 mod events;
 pub mod prelude;
 
 use azalea_client::{
-    chat::ChatPacket, start_ecs_runner, Account, Client, DefaultPlugins, Event, JoinError,
+    chat::ChatPacket,
+    login_query::{LoginQuery, LoginQueryHandlers},
+    start_ecs_runner, Account, Client, DefaultPlugins, Event, GameProfileComponent, JoinError,
+    JoinedClientBundle, LocalPlayerBundle,
 };
+use azalea_core::resource_location::ResourceLocation;
+use azalea_entity::{indexing::EntityUuidIndex, LoadedBy};
 use azalea_protocol::{
     connect::ConnectionError,
     resolver::{self, ResolverError},
@@ -24,6 +30,11 @@ use tokio::sync::mpsc;
 
 use crate::{BoxHandleFn, DefaultBotPlugins, HandleFn, NoState};
 
+pub use channel::{
+    NoSwarmChannelError, SwarmChannel, SwarmChannelPlugin, SwarmClientExt, SwarmMessage,
+    SwarmReceiver,
+};
+
 /// A swarm is a way to conveniently control many bots at once, while also
 /// being able to control bots at an individual level when desired.
 ///
@@ -278,6 +289,22 @@ where
         self
     }
 
+    /// Answer the login queries that the server sends on the given channel
+    /// with the handler. The handler is shared by every bot in the swarm, and
+    /// it can use [`LoginQuery::account`] to tell them apart.
+    #[must_use]
+    pub fn login_query_handler(
+        mut self,
+        identifier: ResourceLocation,
+        handler: impl Fn(&LoginQuery) -> Option<Vec<u8>> + Send + Sync + 'static,
+    ) -> Self {
+        self.app
+            .world
+            .get_resource_or_insert_with(LoginQueryHandlers::default)
+            .insert(identifier, handler);
+        self
+    }
+
     /// Set how long we should wait between each bot joining the server.
     ///
     /// By default, every bot will connect at the same time. If you set this
@@ -546,7 +573,11 @@ impl Swarm {
                     error!("Error sending event to swarm: {e}");
                 }
             }
-            cloned_bots.lock().remove(&bot.entity);
+            if cloned_bots.lock().remove(&bot.entity).is_none() {
+                // the bot was removed with Swarm::remove, so this isn't a disconnect that the
+                // swarm handler should know about
+                return;
+            }
             let account = cloned_bot
                 .get_component::<Account>()
                 .expect("bot is missing required Account component");
@@ -583,6 +614,73 @@ impl Swarm {
             }
         }
     }
+
+    /// Disconnect a bot and remove it from the swarm, so you can scale the
+    /// number of bots up and down while the swarm is running.
+    ///
+    /// This closes the connection and removes the bot's entity from the ECS,
+    /// along with any entities and instances that only this bot had loaded.
+    /// Unlike [`Client::disconnect`], it doesn't cause a
+    /// [`SwarmEvent::Disconnect`], so it won't trigger your reconnect logic.
+    ///
+    /// ```rust,no_run
+    /// # use azalea::{prelude::*, swarm::prelude::*};
+    /// # #[derive(Component, Clone, Default)]
+    /// # struct State;
+    /// # async fn example(mut swarm: Swarm) -> anyhow::Result<()> {
+    /// let bot = swarm.add(&Account::offline("bot"), State::default()).await?;
+    /// // ...
+    /// swarm.remove(&bot);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn remove(&mut self, bot: &Client) {
+        self.bots.lock().remove(&bot.entity);
+
+        let mut ecs = self.ecs_lock.lock();
+        let Some(mut entity) = ecs.get_entity_mut(bot.entity) else {
+            return;
+        };
+        let uuid = entity
+            .get::<GameProfileComponent>()
+            .map(|profile| profile.uuid);
+        // dropping the RawConnection closes the connection, and dropping the
+        // InstanceHolder lets the instance be freed if nothing else is using it
+        entity.remove::<(LocalPlayerBundle, JoinedClientBundle)>();
+
+        if entity.contains::<LoadedBy>() {
+            // entities (including our own) that aren't loaded by anything anymore get
+            // despawned and removed from the indexes in the next update
+            for mut loaded_by in ecs.query::<&mut LoadedBy>().iter_mut(&mut ecs) {
+                loaded_by.remove(&bot.entity);
+            }
+        } else {
+            // we never got past the configuration state, so nothing else refers to us
+            entity.despawn();
+            if let Some(uuid) = uuid {
+                ecs.resource_mut::<EntityUuidIndex>().remove(&uuid);
+            }
+        }
+
+        ecs.resource_mut::<InstanceContainer>()
+            .instances
+            .retain(|_, instance| instance.strong_count() > 0);
+
+        drop(ecs);
+        // run the schedule so the entities get despawned now instead of after the next
+        // packet
+        let _ = self.run_schedule_sender.send(());
+    }
+
+    /// The number of bots that are currently in the swarm.
+    pub fn len(&self) -> usize {
+        self.bots.lock().len()
+    }
+
+    /// Returns whether there are no bots in the swarm.
+    pub fn is_empty(&self) -> bool {
+        self.bots.lock().is_empty()
+    }
 }
 
 impl IntoIterator for Swarm {
@@ -627,6 +725,7 @@ impl PluginGroup for DefaultSwarmPlugins {
         PluginGroupBuilder::start::<Self>()
             .add(chat::SwarmChatPlugin)
             .add(events::SwarmPlugin)
+            .add(channel::SwarmChannelPlugin)
     }
 }
 